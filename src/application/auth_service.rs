@@ -1,29 +1,285 @@
-use crate::domain::error::DomainError;
-use crate::domain::repository::UserRepository;
-use crate::domain::user::{CreateUser, LoginRequest, User};
-use crate::infrastructure::security::{generate_token, hash_password, verify_password};
+use crate::data::api_key_repository::InMemoryApiKeyRepository;
+use crate::data::kyc_repository::InMemoryKycRepository;
+use crate::data::login_attempt_repository::InMemoryLoginAttemptRepository;
+use crate::data::oidc_repository::InMemoryOidcRepository;
+use crate::data::revocation_store::InMemoryRevocationStore;
+use crate::data::session_repository::InMemorySessionRepository;
+use crate::data::token_repository::{
+    InMemoryEmailVerificationTokenRepository, InMemoryPasswordResetTokenRepository,
+    InMemoryRefreshTokenRepository,
+};
+use crate::data::two_factor_repository::InMemoryTwoFactorRepository;
+use crate::domain::api_key::{
+    ApiKey, ApiKeyAuthenticator, ApiKeyIdentity, CreateApiKeyRequest, NewApiKey,
+};
+use crate::domain::error::{DomainError, FieldError};
+use crate::domain::login_attempt::LoginThrottlePolicy;
+use crate::domain::models::Amount;
+use crate::domain::notifier::{Notification, Notifier};
+use crate::domain::oidc::{
+    ExternalIdentity, IdentityProvider, OidcAuthorization, OidcAuthorizationStart, OidcIdentity,
+};
+use crate::domain::p2p::ClaimableTransfer;
+use crate::domain::repository::{
+    ApiKeyRepository, EmailVerificationTokenRepository, KycRepository, LoginAttemptRepository,
+    OidcRepository, PasswordResetTokenRepository, RefreshTokenRepository, RevocationStore,
+    SessionRepository, TwoFactorRepository, UserRepository,
+};
+use crate::domain::session::{ClientInfo, Session};
+use crate::domain::token::{
+    AuthTokens, ClientCredentials, EmailVerificationToken, LoginOutcome, PasswordResetToken,
+    RefreshToken, Scope,
+};
+use crate::domain::two_factor::{LoginChallenge, TwoFactorEnrollment, TwoFactorSettings};
+use crate::domain::user::{
+    ChangePasswordRequest, CreateUser, CustomerPolicy, DuplicateEmail, EmailMigrationReport,
+    KycStatus, KycTransition, LoginRequest, Profile, ResetPasswordRequest, Role,
+    UpdateKycStatusRequest, UpdateProfileRequest, User, UserExport, UserStatus,
+};
+use crate::domain::validation::{
+    PasswordPolicy, email_identity, normalize_email, validate_profile_update,
+};
+use crate::infrastructure::jwt_keys::JwtKeySet;
+use crate::infrastructure::notifier::LogNotifier;
+use crate::infrastructure::oidc::{code_challenge, generate_code_verifier};
+use crate::infrastructure::security::{
+    ACCESS_TOKEN_TTL_SECS, AccessTokenClaims, generate_opaque_token, hash_password, hash_token,
+    sign_access_token, sign_session_token, verify_access_token, verify_dummy_password,
+    verify_password,
+};
+use crate::infrastructure::totp::{generate_totp_secret, otpauth_uri, verify_totp};
 use anyhow::Result;
+use async_trait::async_trait;
+use chrono::{DateTime, Duration, Utc};
+use jsonwebtoken::jwk::JwkSet;
+use std::collections::{BTreeMap, HashMap};
 use std::sync::Arc;
 use tracing::{debug, error, info, instrument, trace, warn};
 use uuid::Uuid;
 
+// Absolute lifetime of a refresh token family, counted from login
+const REFRESH_TOKEN_TTL_DAYS: i64 = 30;
+const PASSWORD_RESET_TOKEN_TTL_MINUTES: i64 = 30;
+const EMAIL_VERIFICATION_TOKEN_TTL_HOURS: i64 = 24;
+// A verification email may be resent once a minute and at most 5 times an hour
+const EMAIL_VERIFICATION_RESEND_COOLDOWN_SECS: i64 = 60;
+const EMAIL_VERIFICATION_MAX_PER_HOUR: usize = 5;
+const LOGIN_CHALLENGE_TTL_SECS: u64 = 300;
+// Codes tried against one challenge before it is discarded
+const LOGIN_CHALLENGE_MAX_ATTEMPTS: u32 = 5;
+const RECOVERY_CODE_COUNT: usize = 10;
+const TOTP_ISSUER: &str = "Yandex Bank";
+// Transfers above this amount need a second factor unless configured otherwise
+const DEFAULT_STEP_UP_THRESHOLD: u64 = 1_000_000;
+const API_KEY_PREFIX: &str = "ybk_";
+// Characters of a key kept in clear so that users can tell their keys apart
+const API_KEY_DISPLAY_LEN: usize = 12;
+const API_KEY_NAME_MAX_LEN: usize = 100;
+const MAX_API_KEYS_PER_USER: usize = 20;
+// Time the user has to sign in at the identity provider
+const OIDC_AUTHORIZATION_TTL_SECS: u64 = 600;
+// Largest single withdrawal or transfer of a customer whose identity is not verified
+const DEFAULT_UNVERIFIED_TRANSACTION_LIMIT: u64 = 1_500_000;
+const KYC_REASON_MAX_LEN: usize = 500;
+
 pub struct AuthService<R: UserRepository> {
     user_repository: Arc<R>,
-    jwt_secret: String,
+    refresh_token_repository: Arc<dyn RefreshTokenRepository>,
+    revocation_store: Arc<dyn RevocationStore>,
+    reset_token_repository: Arc<dyn PasswordResetTokenRepository>,
+    verification_token_repository: Arc<dyn EmailVerificationTokenRepository>,
+    notifier: Arc<dyn Notifier>,
+    login_attempt_repository: Arc<dyn LoginAttemptRepository>,
+    login_throttle_policy: LoginThrottlePolicy,
+    two_factor_repository: Arc<dyn TwoFactorRepository>,
+    api_key_repository: Arc<dyn ApiKeyRepository>,
+    // `None` disables OIDC login
+    identity_provider: Option<Arc<dyn IdentityProvider>>,
+    oidc_repository: Arc<dyn OidcRepository>,
+    session_repository: Arc<dyn SessionRepository>,
+    kyc_repository: Arc<dyn KycRepository>,
+    step_up_threshold: Option<Amount>,
+    unverified_transaction_limit: Amount,
+    password_policy: PasswordPolicy,
+    // client_id -> SHA-256 hash of the client secret
+    token_clients: HashMap<String, String>,
+    jwt_keys: Arc<JwtKeySet>,
 }
 
 impl<R: UserRepository> AuthService<R> {
     pub fn new(user_repository: Arc<R>, jwt_secret: String) -> Self {
         Self {
             user_repository,
-            jwt_secret,
+            refresh_token_repository: Arc::new(InMemoryRefreshTokenRepository::new()),
+            revocation_store: Arc::new(InMemoryRevocationStore::new()),
+            reset_token_repository: Arc::new(InMemoryPasswordResetTokenRepository::new()),
+            verification_token_repository: Arc::new(InMemoryEmailVerificationTokenRepository::new()),
+            notifier: Arc::new(LogNotifier::new()),
+            login_attempt_repository: Arc::new(InMemoryLoginAttemptRepository::new()),
+            login_throttle_policy: LoginThrottlePolicy::default(),
+            two_factor_repository: Arc::new(InMemoryTwoFactorRepository::new()),
+            api_key_repository: Arc::new(InMemoryApiKeyRepository::new()),
+            identity_provider: None,
+            oidc_repository: Arc::new(InMemoryOidcRepository::new()),
+            session_repository: Arc::new(InMemorySessionRepository::new()),
+            kyc_repository: Arc::new(InMemoryKycRepository::new()),
+            step_up_threshold: Some(Amount::new(DEFAULT_STEP_UP_THRESHOLD)),
+            unverified_transaction_limit: Amount::new(DEFAULT_UNVERIFIED_TRANSACTION_LIMIT),
+            password_policy: PasswordPolicy::default(),
+            token_clients: HashMap::new(),
+            jwt_keys: Arc::new(JwtKeySet::from_secret(&jwt_secret)),
         }
     }
 
-    #[instrument(skip(self), fields(email = %req.email))]
+    // Replaces the key set built from the secret given to `new`. The same set must be given
+    // to `JwtAuthMiddleware`.
+    pub fn with_jwt_keys(mut self, jwt_keys: Arc<JwtKeySet>) -> Self {
+        self.jwt_keys = jwt_keys;
+        self
+    }
+
+    pub fn with_refresh_token_repository(
+        mut self,
+        refresh_token_repository: Arc<dyn RefreshTokenRepository>,
+    ) -> Self {
+        self.refresh_token_repository = refresh_token_repository;
+        self
+    }
+
+    // The same store must be given to `JwtAuthMiddleware` for revocations to take effect
+    pub fn with_revocation_store(mut self, revocation_store: Arc<dyn RevocationStore>) -> Self {
+        self.revocation_store = revocation_store;
+        self
+    }
+
+    pub fn with_password_reset_token_repository(
+        mut self,
+        reset_token_repository: Arc<dyn PasswordResetTokenRepository>,
+    ) -> Self {
+        self.reset_token_repository = reset_token_repository;
+        self
+    }
+
+    pub fn with_verification_token_repository(
+        mut self,
+        verification_token_repository: Arc<dyn EmailVerificationTokenRepository>,
+    ) -> Self {
+        self.verification_token_repository = verification_token_repository;
+        self
+    }
+
+    pub fn with_notifier(mut self, notifier: Arc<dyn Notifier>) -> Self {
+        self.notifier = notifier;
+        self
+    }
+
+    pub fn with_login_attempt_repository(
+        mut self,
+        login_attempt_repository: Arc<dyn LoginAttemptRepository>,
+    ) -> Self {
+        self.login_attempt_repository = login_attempt_repository;
+        self
+    }
+
+    pub fn with_login_throttle_policy(
+        mut self,
+        login_throttle_policy: LoginThrottlePolicy,
+    ) -> Self {
+        self.login_throttle_policy = login_throttle_policy;
+        self
+    }
+
+    pub fn with_two_factor_repository(
+        mut self,
+        two_factor_repository: Arc<dyn TwoFactorRepository>,
+    ) -> Self {
+        self.two_factor_repository = two_factor_repository;
+        self
+    }
+
+    pub fn with_api_key_repository(
+        mut self,
+        api_key_repository: Arc<dyn ApiKeyRepository>,
+    ) -> Self {
+        self.api_key_repository = api_key_repository;
+        self
+    }
+
+    pub fn with_identity_provider(mut self, identity_provider: Arc<dyn IdentityProvider>) -> Self {
+        self.identity_provider = Some(identity_provider);
+        self
+    }
+
+    pub fn with_oidc_repository(mut self, oidc_repository: Arc<dyn OidcRepository>) -> Self {
+        self.oidc_repository = oidc_repository;
+        self
+    }
+
+    pub fn with_session_repository(
+        mut self,
+        session_repository: Arc<dyn SessionRepository>,
+    ) -> Self {
+        self.session_repository = session_repository;
+        self
+    }
+
+    pub fn with_kyc_repository(mut self, kyc_repository: Arc<dyn KycRepository>) -> Self {
+        self.kyc_repository = kyc_repository;
+        self
+    }
+
+    // Transfers above the threshold require a second factor; `None` disables step-up
+    pub fn with_step_up_threshold(mut self, step_up_threshold: Option<Amount>) -> Self {
+        self.step_up_threshold = step_up_threshold;
+        self
+    }
+
+    // Withdrawals and transfers above the limit need a verified identity
+    pub fn with_unverified_transaction_limit(mut self, limit: Amount) -> Self {
+        self.unverified_transaction_limit = limit;
+        self
+    }
+
+    pub fn with_password_policy(mut self, password_policy: PasswordPolicy) -> Self {
+        self.password_policy = password_policy;
+        self
+    }
+
+    // Registers a backend client allowed to obtain tokens through `get_token`.
+    // Without any registered client token issuance by user id is disabled.
+    pub fn with_token_client(mut self, client_id: impl Into<String>, client_secret: &str) -> Self {
+        self.token_clients
+            .insert(client_id.into(), hash_token(client_secret));
+        self
+    }
+
     pub async fn register_user(&self, req: CreateUser) -> Result<User> {
+        self.register_user_with_role(req, Role::Customer).await
+    }
+
+    // Registration is public and always creates customers; other roles are granted by
+    // admins, or at startup for the first admin. The user stays pending until they follow
+    // the verification email.
+    #[instrument(skip(self), fields(email = %req.email))]
+    pub async fn register_user_with_role(&self, req: CreateUser, role: Role) -> Result<User> {
         trace!("Starting user registration");
 
+        // Report every broken rule at once rather than one per attempt
+        let mut errors = Vec::new();
+        let email = normalize_email(&req.email).unwrap_or_else(|e| {
+            errors.push(e);
+            req.email.clone()
+        });
+        errors.extend(self.password_policy.validate(&req.password, Some(&email)));
+        if !errors.is_empty() {
+            warn!(email = %req.email, failed_rules = errors.len(), "Registration rejected");
+            return Err(DomainError::InvalidFields(errors).into());
+        }
+        let req = CreateUser {
+            email,
+            password: req.password,
+        };
+
         // Check if user already exists
         if self
             .user_repository
@@ -48,14 +304,20 @@ impl<R: UserRepository> AuthService<R> {
             id: Uuid::new_v4().to_string(),
             email: req.email,
             password_hash,
+            role,
+            status: UserStatus::PendingVerification,
+            profile: Profile::default(),
+            kyc_status: KycStatus::Unverified,
         };
 
         debug!(user_id = %user.id, email = %user.email, "Saving user to repository");
         self.user_repository.save_user(user.clone()).await?;
+        self.send_verification_email(&user).await?;
 
         info!(
             user_id = %user.id,
             email = %user.email,
+            role = %user.role,
             "User registered successfully"
         );
 
@@ -63,34 +325,85 @@ impl<R: UserRepository> AuthService<R> {
     }
 
     #[instrument(skip(self), fields(email = %req.email))]
-    pub async fn login(&self, req: LoginRequest) -> Result<String> {
+    pub async fn login(&self, req: LoginRequest) -> Result<LoginOutcome> {
+        self.login_from(req, &ClientInfo::default()).await
+    }
+
+    // Logs in a user, throttling failed attempts per email and, when known, per client IP.
+    // The client is recorded on the session the login starts.
+    // Unknown emails get the same error, throttling and password hashing cost as wrong
+    // passwords, so responses do not reveal which emails are registered.
+    // Users with 2FA get a challenge to complete with `complete_two_factor_login`.
+    #[instrument(skip(self), fields(email = %req.email))]
+    pub async fn login_from(&self, req: LoginRequest, client: &ClientInfo) -> Result<LoginOutcome> {
         trace!("Starting login");
 
+        let now = Utc::now();
+        // Malformed emails cannot belong to anyone; let the lookup fail like for unknown users
+        let email = normalize_email(&req.email).unwrap_or_else(|_| req.email.clone());
+        let email_key = email_attempt_key(&email);
+        let ip_key = client.ip.as_deref().map(ip_attempt_key);
+        self.check_login_throttle(&email_key, ip_key.as_deref(), now)
+            .await?;
+
+        // Deleted users fail like unknown ones
         let user = self
             .user_repository
-            .find_user_by_email(&req.email)
+            .find_user_by_email(&email)
             .await?
-            .ok_or_else(|| {
-                warn!(email = %req.email, "User not found during login");
-                DomainError::Unauthorized("Invalid email or password".to_string())
-            })?;
+            .filter(|u| u.status != UserStatus::Deleted);
 
         // Verify password
-        let is_valid = verify_password(&req.password, &user.password_hash).map_err(|e| {
-            error!(error = %e, "Failed to verify password");
-            DomainError::Internal(format!("Failed to verify password: {}", e))
-        })?;
+        let is_valid = match &user {
+            Some(user) => verify_password(&req.password, &user.password_hash).map_err(|e| {
+                error!(error = %e, "Failed to verify password");
+                DomainError::Internal(format!("Failed to verify password: {}", e))
+            })?,
+            None => verify_dummy_password(&req.password),
+        };
 
-        if !is_valid {
-            warn!(user_id = %user.id, email = %user.email, "Invalid password during login");
-            return Err(DomainError::Unauthorized("Invalid email or password".to_string()).into());
+        let user = match user {
+            Some(user) if is_valid => user,
+            user => {
+                match &user {
+                    Some(user) => {
+                        warn!(user_id = %user.id, email = %user.email, "Invalid password during login")
+                    }
+                    None => warn!(email = %req.email, "User not found during login"),
+                }
+                self.record_login_failure(&email_key, ip_key.as_deref(), now)
+                    .await?;
+                return Err(
+                    DomainError::Unauthorized("Invalid email or password".to_string()).into(),
+                );
+            }
+        };
+
+        // Only the email's counter is reset: one valid login must not let an IP
+        // continue guessing other users' passwords
+        self.login_attempt_repository
+            .clear_failed_attempts(&email_key)
+            .await?;
+
+        if self.two_factor_enabled(&user.id).await? {
+            let challenge_token = generate_opaque_token();
+            self.two_factor_repository
+                .save_login_challenge(LoginChallenge {
+                    token_hash: hash_token(&challenge_token),
+                    user_id: user.id.clone(),
+                    expires_at: now + Duration::seconds(LOGIN_CHALLENGE_TTL_SECS as i64),
+                    failed_attempts: 0,
+                    client: client.clone(),
+                })
+                .await?;
+            info!(user_id = %user.id, "Password accepted, two-factor code required");
+            return Ok(LoginOutcome::TwoFactorRequired {
+                challenge_token,
+                expires_in: LOGIN_CHALLENGE_TTL_SECS,
+            });
         }
 
-        // Generate JWT token
-        let token = generate_token(&user.id, &self.jwt_secret).map_err(|e| {
-            error!(error = %e, "Failed to generate token");
-            DomainError::Internal(format!("Failed to generate token: {}", e))
-        })?;
+        let tokens = self.start_session(&user, client).await?;
 
         info!(
             user_id = %user.id,
@@ -98,303 +411,3675 @@ impl<R: UserRepository> AuthService<R> {
             "Login successful"
         );
 
-        Ok(token)
+        Ok(LoginOutcome::Authenticated(tokens))
     }
 
-    #[instrument(skip(self), fields(user_id = user_id))]
-    pub async fn get_token(&self, user_id: &str) -> Result<String> {
-        trace!("Generating token for user");
+    // Second step of a login with 2FA: exchanges the challenge and a TOTP or recovery code
+    // for tokens. A challenge is discarded after a few wrong codes.
+    #[instrument(skip(self, challenge_token, code))]
+    pub async fn complete_two_factor_login(
+        &self,
+        challenge_token: &str,
+        code: &str,
+    ) -> Result<AuthTokens> {
+        trace!("Starting two-factor login");
+
+        let token_hash = hash_token(challenge_token);
+        let mut challenge = self
+            .two_factor_repository
+            .find_login_challenge(&token_hash)
+            .await?
+            .filter(|c| c.expires_at > Utc::now())
+            .ok_or_else(|| {
+                warn!("Unknown or expired login challenge presented");
+                DomainError::Unauthorized("Invalid or expired login challenge".to_string())
+            })?;
 
-        // Verify user exists
+        if !self.verify_second_factor(&challenge.user_id, code).await? {
+            challenge.failed_attempts += 1;
+            if challenge.failed_attempts >= LOGIN_CHALLENGE_MAX_ATTEMPTS {
+                warn!(user_id = %challenge.user_id, "Login challenge discarded after failed codes");
+                self.two_factor_repository
+                    .delete_login_challenge(&token_hash)
+                    .await?;
+            } else {
+                self.two_factor_repository
+                    .save_login_challenge(challenge)
+                    .await?;
+            }
+            return Err(DomainError::Unauthorized("Invalid two-factor code".to_string()).into());
+        }
+
+        self.two_factor_repository
+            .delete_login_challenge(&token_hash)
+            .await?;
         let user = self
             .user_repository
-            .find_user_by_id(user_id)
+            .find_user_by_id(&challenge.user_id)
             .await?
             .ok_or_else(|| {
-                warn!(user_id = user_id, "User not found during token generation");
-                DomainError::NotFound(format!("User not found: {}", user_id))
+                DomainError::Unauthorized("Invalid or expired login challenge".to_string())
             })?;
+        let tokens = self.start_session(&user, &challenge.client).await?;
 
-        // Generate JWT token
-        let token = generate_token(&user.id, &self.jwt_secret).map_err(|e| {
-            error!(error = %e, "Failed to generate token");
-            DomainError::Internal(format!("Failed to generate token: {}", e))
-        })?;
+        info!(user_id = %challenge.user_id, "Two-factor login successful");
+        Ok(tokens)
+    }
 
-        info!(
-            user_id = %user.id,
-            email = %user.email,
-            "Token generated successfully"
-        );
+    // Starts (or restarts) 2FA enrollment with a new secret. 2FA stays off until
+    // `activate_two_factor` receives a code generated from it.
+    #[instrument(skip(self))]
+    pub async fn enroll_two_factor(&self, user_id: &str) -> Result<TwoFactorEnrollment> {
+        let user = self
+            .user_repository
+            .find_user_by_id(user_id)
+            .await?
+            .ok_or_else(|| DomainError::Unauthorized("Invalid token".to_string()))?;
+        if self.two_factor_enabled(user_id).await? {
+            return Err(DomainError::Validation(
+                "Two-factor authentication is already enabled".to_string(),
+            )
+            .into());
+        }
 
-        Ok(token)
+        let secret = generate_totp_secret();
+        self.two_factor_repository
+            .save_two_factor(TwoFactorSettings {
+                user_id: user.id.clone(),
+                secret: secret.clone(),
+                enabled: false,
+                recovery_code_hashes: Vec::new(),
+                last_used_step: None,
+                created_at: Utc::now(),
+            })
+            .await?;
+
+        info!(user_id = %user.id, "Two-factor enrollment started");
+        Ok(TwoFactorEnrollment {
+            otpauth_uri: otpauth_uri(TOTP_ISSUER, &user.email, &secret),
+            secret,
+        })
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use crate::data::user_repository::InMemoryUserRepository;
-    use crate::domain::user::{CreateUser, LoginRequest};
+    // Turns 2FA on once the user proves their authenticator works. Returns the recovery
+    // codes, which are not stored in clear and cannot be shown again.
+    #[instrument(skip(self, code))]
+    pub async fn activate_two_factor(&self, user_id: &str, code: &str) -> Result<Vec<String>> {
+        let mut settings = self
+            .two_factor_repository
+            .find_two_factor(user_id)
+            .await?
+            .ok_or_else(|| {
+                DomainError::Validation("Two-factor enrollment has not been started".to_string())
+            })?;
+        if settings.enabled {
+            return Err(DomainError::Validation(
+                "Two-factor authentication is already enabled".to_string(),
+            )
+            .into());
+        }
 
-    #[tokio::test]
-    async fn test_register_user_registers_new_user_successfully() {
-        let repo = Arc::new(InMemoryUserRepository::new());
-        let service = AuthService::new(repo, "test_secret".to_string());
+        let step =
+            verify_totp(&settings.secret, code, Utc::now().timestamp()).ok_or_else(|| {
+                warn!(
+                    user_id = user_id,
+                    "Invalid code during two-factor activation"
+                );
+                DomainError::Unauthorized("Invalid two-factor code".to_string())
+            })?;
 
-        let req = CreateUser {
-            email: "newuser@example.com".to_string(),
-            password: "password123".to_string(),
+        let recovery_codes: Vec<String> = (0..RECOVERY_CODE_COUNT)
+            .map(|_| generate_recovery_code())
+            .collect();
+        settings.enabled = true;
+        settings.last_used_step = Some(step);
+        settings.recovery_code_hashes =
+            recovery_codes.iter().map(|code| hash_token(code)).collect();
+        self.two_factor_repository.save_two_factor(settings).await?;
+
+        info!(user_id = user_id, "Two-factor authentication enabled");
+        Ok(recovery_codes)
+    }
+
+    // Step-up check for transfers: above the configured threshold the user must have 2FA
+    // enabled and present a valid code with the request
+    #[instrument(skip(self, code), fields(amount = amount.inner()))]
+    pub async fn authorize_transfer(
+        &self,
+        user_id: &str,
+        amount: Amount,
+        code: Option<&str>,
+    ) -> Result<()> {
+        let Some(threshold) = self.step_up_threshold.filter(|t| amount > *t) else {
+            return Ok(());
         };
 
-        let user = service.register_user(req).await.unwrap();
+        if !self.two_factor_enabled(user_id).await? {
+            warn!(
+                user_id = user_id,
+                "Large transfer without two-factor authentication"
+            );
+            return Err(DomainError::Forbidden(format!(
+                "Transfers above {} require two-factor authentication",
+                threshold.inner()
+            ))
+            .into());
+        }
+        let Some(code) = code else {
+            return Err(DomainError::Forbidden(format!(
+                "Two-factor code required for transfers above {}",
+                threshold.inner()
+            ))
+            .into());
+        };
+        if !self.verify_second_factor(user_id, code).await? {
+            return Err(DomainError::Forbidden("Invalid two-factor code".to_string()).into());
+        }
 
-        assert_eq!(user.email, "newuser@example.com");
-        assert!(!user.id.is_empty());
-        assert!(!user.password_hash.is_empty());
-        // Password should be hashed, not stored plain
-        assert_ne!(user.password_hash, "password123");
+        info!(user_id = user_id, "Transfer step-up verified");
+        Ok(())
     }
 
-    #[tokio::test]
-    async fn test_register_user_returns_error_for_duplicate_email() {
-        let repo = Arc::new(InMemoryUserRepository::new());
-        let service = AuthService::new(repo, "test_secret".to_string());
+    async fn two_factor_enabled(&self, user_id: &str) -> Result<bool> {
+        Ok(self
+            .two_factor_repository
+            .find_two_factor(user_id)
+            .await?
+            .is_some_and(|s| s.enabled))
+    }
 
-        let req1 = CreateUser {
-            email: "duplicate@example.com".to_string(),
-            password: "password1".to_string(),
+    // Checks a TOTP or recovery code. Failures are throttled per user like password
+    // failures per email, which bounds guessing of 6-digit codes.
+    async fn verify_second_factor(&self, user_id: &str, code: &str) -> Result<bool> {
+        let now = Utc::now();
+        let key = two_factor_attempt_key(user_id);
+        self.check_login_throttle(&key, None, now).await?;
+
+        let Some(settings) = self
+            .two_factor_repository
+            .find_two_factor(user_id)
+            .await?
+            .filter(|s| s.enabled)
+        else {
+            return Ok(false);
         };
-        let req2 = CreateUser {
-            email: "duplicate@example.com".to_string(),
-            password: "password2".to_string(),
+
+        let valid = match verify_totp(&settings.secret, code, now.timestamp()) {
+            Some(step) => {
+                self.two_factor_repository
+                    .advance_totp_step(user_id, step)
+                    .await?
+            }
+            None => {
+                self.two_factor_repository
+                    .consume_recovery_code(user_id, &hash_token(&code.trim().to_lowercase()))
+                    .await?
+            }
         };
 
-        service.register_user(req1).await.unwrap();
-        let result = service.register_user(req2).await;
+        if valid {
+            self.login_attempt_repository
+                .clear_failed_attempts(&key)
+                .await?;
+        } else {
+            warn!(user_id = user_id, "Invalid two-factor code");
+            self.record_login_failure(&key, None, now).await?;
+        }
+        Ok(valid)
+    }
 
-        assert!(result.is_err());
-        let error = result.unwrap_err();
-        if let Ok(domain_error) = error.downcast::<DomainError>() {
-            match domain_error {
-                DomainError::Validation(msg) => {
-                    assert!(msg.contains("already exists"));
-                }
-                _ => panic!("Expected Validation error"),
+    // Lifts the lockout and backoff of an email, e.g. after the owner proved their identity
+    // to support. Registered as an admin route.
+    #[instrument(skip(self))]
+    pub async fn unlock_login(&self, email: &str) -> Result<()> {
+        let email = normalize_email(email).unwrap_or_else(|_| email.to_string());
+        self.login_attempt_repository
+            .clear_failed_attempts(&email_attempt_key(&email))
+            .await?;
+
+        info!(email = %email, "Login unlocked");
+        Ok(())
+    }
+
+    // Rejects the attempt while the email or the client IP is backing off or locked out
+    async fn check_login_throttle(
+        &self,
+        email_key: &str,
+        ip_key: Option<&str>,
+        now: DateTime<Utc>,
+    ) -> Result<()> {
+        let policy = &self.login_throttle_policy;
+        let mut blocked_until = None;
+
+        if let Some(attempts) = self
+            .login_attempt_repository
+            .find_failed_attempts(email_key)
+            .await?
+        {
+            blocked_until = policy.email_blocked_until(&attempts, now);
+        }
+        if let Some(ip_key) = ip_key
+            && let Some(attempts) = self
+                .login_attempt_repository
+                .find_failed_attempts(ip_key)
+                .await?
+        {
+            blocked_until = blocked_until.max(policy.ip_blocked_until(&attempts, now));
+        }
+
+        match blocked_until {
+            Some(until) => {
+                // Round up so clients retrying after `Retry-After` are not refused again
+                let retry_after = ((until - now).num_milliseconds() as u64).div_ceil(1000);
+                warn!(
+                    key = email_key,
+                    ip = ip_key,
+                    retry_after = retry_after,
+                    "Login throttled"
+                );
+                Err(DomainError::TooManyAttempts(retry_after).into())
             }
+            None => Ok(()),
         }
     }
 
-    #[tokio::test]
-    async fn test_register_user_hashes_password() {
-        let repo = Arc::new(InMemoryUserRepository::new());
-        let service = AuthService::new(repo, "test_secret".to_string());
+    async fn record_login_failure(
+        &self,
+        email_key: &str,
+        ip_key: Option<&str>,
+        now: DateTime<Utc>,
+    ) -> Result<()> {
+        let window = self.login_throttle_policy.attempt_window;
+        for key in std::iter::once(email_key).chain(ip_key) {
+            let attempts = self
+                .login_attempt_repository
+                .record_failed_attempt(key, now, window)
+                .await?;
+            if attempts.count == self.login_throttle_policy.lockout_threshold {
+                warn!(key = key, "Login locked out after repeated failures");
+            }
+        }
+        Ok(())
+    }
 
-        let req = CreateUser {
-            email: "hashtest@example.com".to_string(),
-            password: "plain_password".to_string(),
-        };
+    // Exchanges a refresh token for a new token pair. The presented refresh token is
+    // consumed; presenting it again revokes every token of its family.
+    #[instrument(skip(self, refresh_token))]
+    pub async fn refresh(&self, refresh_token: &str) -> Result<AuthTokens> {
+        trace!("Starting token refresh");
 
-        let user = service.register_user(req).await.unwrap();
+        let token = self
+            .refresh_token_repository
+            .mark_refresh_token_used(&hash_token(refresh_token))
+            .await?
+            .ok_or_else(|| {
+                warn!("Unknown refresh token presented");
+                DomainError::Unauthorized("Invalid refresh token".to_string())
+            })?;
 
-        // Password hash should not be the plain password
-        assert_ne!(user.password_hash, "plain_password");
-        // Hash should be verifiable
-        let is_valid = verify_password("plain_password", &user.password_hash).unwrap();
-        assert!(is_valid);
-    }
+        if token.revoked {
+            warn!(
+                user_id = %token.user_id,
+                family_id = %token.family_id,
+                "Revoked refresh token presented"
+            );
+            return Err(DomainError::Unauthorized("Invalid refresh token".to_string()).into());
+        }
 
-    #[tokio::test]
-    async fn test_login_logs_in_with_correct_credentials() {
-        let repo = Arc::new(InMemoryUserRepository::new());
-        let service = AuthService::new(repo, "test_secret".to_string());
+        if token.used {
+            warn!(
+                user_id = %token.user_id,
+                family_id = %token.family_id,
+                "Refresh token reuse detected, revoking token family"
+            );
+            self.refresh_token_repository
+                .revoke_refresh_token_family(&token.family_id)
+                .await?;
+            return Err(
+                DomainError::Unauthorized("Refresh token reuse detected".to_string()).into(),
+            );
+        }
 
-        // Register user first
-        let register_req = CreateUser {
-            email: "login@example.com".to_string(),
-            password: "correct_password".to_string(),
-        };
-        service.register_user(register_req).await.unwrap();
+        if token.expires_at <= Utc::now() {
+            warn!(user_id = %token.user_id, "Expired refresh token presented");
+            return Err(DomainError::Unauthorized("Refresh token has expired".to_string()).into());
+        }
 
-        // Login
-        let login_req = LoginRequest {
-            email: "login@example.com".to_string(),
-            password: "correct_password".to_string(),
-        };
+        // The user may have been removed since the family was issued
+        let user = self
+            .user_repository
+            .find_user_by_id(&token.user_id)
+            .await?
+            .ok_or_else(|| {
+                warn!(user_id = %token.user_id, "Refresh token of unknown user presented");
+                DomainError::Unauthorized("Invalid refresh token".to_string())
+            })?;
 
-        let token = service.login(login_req).await.unwrap();
-        assert!(!token.is_empty());
+        // The access token carries the user's current role
+        let tokens = self
+            .issue_tokens(&user, &token.family_id, token.expires_at)
+            .await?;
+        if let Some(mut session) = self
+            .session_repository
+            .find_session(&token.family_id)
+            .await?
+        {
+            session.last_seen_at = Utc::now();
+            self.session_repository.save_session(session).await?;
+        }
+
+        info!(
+            user_id = %token.user_id,
+            family_id = %token.family_id,
+            "Tokens refreshed"
+        );
+
+        Ok(tokens)
     }
 
-    #[tokio::test]
-    async fn test_login_returns_error_for_wrong_password() {
-        let repo = Arc::new(InMemoryUserRepository::new());
-        let service = AuthService::new(repo, "test_secret".to_string());
+    // Public keys verifying our access tokens, for `/.well-known/jwks.json`
+    pub fn jwks(&self) -> JwkSet {
+        self.jwt_keys.jwks()
+    }
 
-        // Register user
-        let register_req = CreateUser {
-            email: "wrongpass@example.com".to_string(),
-            password: "correct_password".to_string(),
-        };
-        service.register_user(register_req).await.unwrap();
+    // Validates an access token and checks it has not been revoked
+    #[instrument(skip(self, access_token))]
+    pub async fn authenticate(&self, access_token: &str) -> Result<AccessTokenClaims> {
+        let claims = verify_access_token(access_token, &self.jwt_keys).map_err(|e| {
+            warn!(error = %e, "Invalid access token");
+            DomainError::Unauthorized("Invalid token".to_string())
+        })?;
 
-        // Try to login with wrong password
-        let login_req = LoginRequest {
-            email: "wrongpass@example.com".to_string(),
-            password: "wrong_password".to_string(),
-        };
+        if self
+            .revocation_store
+            .is_access_token_revoked(
+                &claims.user_id,
+                &claims.jti,
+                claims.session_id.as_deref(),
+                claims.issued_at,
+            )
+            .await?
+        {
+            warn!(user_id = %claims.user_id, jti = %claims.jti, "Revoked access token presented");
+            return Err(DomainError::Unauthorized("Token has been revoked".to_string()).into());
+        }
 
-        let result = service.login(login_req).await;
-        assert!(result.is_err());
+        Ok(claims)
+    }
 
-        let error = result.unwrap_err();
-        if let Ok(domain_error) = error.downcast::<DomainError>() {
-            match domain_error {
-                DomainError::Unauthorized(msg) => {
-                    assert!(msg.contains("Invalid email or password"));
+    // Revokes the given access token and, if presented, the refresh token family it belongs to.
+    // Tokens of a login session end the whole session.
+    #[instrument(skip(self, claims, refresh_token), fields(user_id = %claims.user_id))]
+    pub async fn logout(
+        &self,
+        claims: &AccessTokenClaims,
+        refresh_token: Option<&str>,
+    ) -> Result<()> {
+        trace!("Starting logout");
+
+        self.revocation_store
+            .revoke_token(&claims.jti, claims.expires_at)
+            .await?;
+
+        if let Some(refresh_token) = refresh_token {
+            let token = self
+                .refresh_token_repository
+                .find_refresh_token(&hash_token(refresh_token))
+                .await?;
+            // Ignore refresh tokens of other users rather than letting them be revoked
+            match token {
+                Some(token) if token.user_id == claims.user_id => {
+                    self.refresh_token_repository
+                        .revoke_refresh_token_family(&token.family_id)
+                        .await?;
                 }
-                _ => panic!("Expected Unauthorized error"),
+                _ => warn!("Unknown refresh token presented on logout"),
             }
         }
-    }
 
-    #[tokio::test]
-    async fn test_login_returns_error_for_nonexistent_user() {
-        let repo = Arc::new(InMemoryUserRepository::new());
-        let service = AuthService::new(repo, "test_secret".to_string());
-
-        let login_req = LoginRequest {
-            email: "nonexistent@example.com".to_string(),
-            password: "password".to_string(),
-        };
+        if let Some(session_id) = &claims.session_id {
+            self.end_session(&claims.user_id, session_id).await?;
+        }
 
-        let result = service.login(login_req).await;
-        assert!(result.is_err());
+        info!(user_id = %claims.user_id, jti = %claims.jti, "Logout successful");
+        Ok(())
+    }
 
-        let error = result.unwrap_err();
-        if let Ok(domain_error) = error.downcast::<DomainError>() {
-            match domain_error {
-                DomainError::Unauthorized(msg) => {
-                    assert!(msg.contains("Invalid email or password"));
-                }
-                _ => panic!("Expected Unauthorized error"),
+    // Invalidates every access and refresh token issued to the user so far
+    #[instrument(skip(self))]
+    pub async fn logout_all_sessions(&self, user_id: &str) -> Result<()> {
+        trace!("Revoking all sessions");
+
+        self.revocation_store
+            .revoke_user_tokens_before(user_id, Utc::now())
+            .await?;
+        self.refresh_token_repository
+            .revoke_user_refresh_tokens(user_id)
+            .await?;
+        let now = Utc::now();
+        for mut session in self.session_repository.list_user_sessions(user_id).await? {
+            if session.is_active(now) {
+                session.revoked_at = Some(now);
+                self.session_repository.save_session(session).await?;
             }
         }
+
+        info!(user_id = user_id, "All sessions revoked");
+        Ok(())
     }
 
-    #[tokio::test]
-    async fn test_login_returns_valid_jwt_token() {
-        let repo = Arc::new(InMemoryUserRepository::new());
-        let jwt_secret = "test_secret_key".to_string();
+    // Active sessions of the user, oldest first
+    #[instrument(skip(self))]
+    pub async fn list_sessions(&self, user_id: &str) -> Result<Vec<Session>> {
+        let now = Utc::now();
+        let sessions = self.session_repository.list_user_sessions(user_id).await?;
+        Ok(sessions.into_iter().filter(|s| s.is_active(now)).collect())
+    }
+
+    // Signs the user out on one device: the session's refresh tokens stop working and its
+    // access tokens are rejected from now on
+    #[instrument(skip(self))]
+    pub async fn revoke_session(&self, user_id: &str, session_id: &str) -> Result<()> {
+        if !self.end_session(user_id, session_id).await? {
+            return Err(DomainError::NotFound(format!("Session not found: {}", session_id)).into());
+        }
+        info!(
+            user_id = user_id,
+            session_id = session_id,
+            "Session revoked"
+        );
+        Ok(())
+    }
+
+    // False if the user has no active session with this id
+    async fn end_session(&self, user_id: &str, session_id: &str) -> Result<bool> {
+        let now = Utc::now();
+        let Some(mut session) = self
+            .session_repository
+            .find_session(session_id)
+            .await?
+            .filter(|s| s.user_id == user_id && s.is_active(now))
+        else {
+            return Ok(false);
+        };
+
+        self.refresh_token_repository
+            .revoke_refresh_token_family(session_id)
+            .await?;
+        // Access tokens of the session outlive it by at most their lifetime
+        self.revocation_store
+            .revoke_session(
+                session_id,
+                now + Duration::seconds(ACCESS_TOKEN_TTL_SECS as i64),
+            )
+            .await?;
+        session.revoked_at = Some(now);
+        self.session_repository.save_session(session).await?;
+        Ok(true)
+    }
+
+    #[instrument(skip(self, req))]
+    pub async fn change_password(&self, user_id: &str, req: ChangePasswordRequest) -> Result<()> {
+        trace!("Starting password change");
+
+        let user = self
+            .user_repository
+            .find_user_by_id(user_id)
+            .await?
+            .ok_or_else(|| {
+                warn!(user_id = user_id, "User not found during password change");
+                DomainError::Unauthorized("Invalid token".to_string())
+            })?;
+
+        if !self
+            .verify_current_password(&user, &req.current_password)
+            .await?
+        {
+            warn!(user_id = %user.id, "Invalid current password during password change");
+            return Err(DomainError::Unauthorized("Invalid current password".to_string()).into());
+        }
+
+        self.check_password_policy(&req.new_password, &user.email)?;
+
+        self.set_password(user, &req.new_password).await?;
+
+        info!(user_id = user_id, "Password changed");
+        Ok(())
+    }
+
+    // Sends a reset token to the user. Succeeds for unknown emails as well, so the
+    // endpoint cannot be used to find out which emails are registered.
+    #[instrument(skip(self))]
+    pub async fn request_password_reset(&self, email: &str) -> Result<()> {
+        trace!("Starting password reset request");
+
+        let user = self
+            .user_repository
+            .find_user_by_email(email)
+            .await?
+            .filter(|u| u.status != UserStatus::Deleted);
+        let Some(user) = user else {
+            warn!(email = email, "Password reset requested for unknown email");
+            return Ok(());
+        };
+
+        let token = generate_opaque_token();
+        let now = Utc::now();
+        let expires_at = now + Duration::minutes(PASSWORD_RESET_TOKEN_TTL_MINUTES);
+        self.reset_token_repository
+            .save_reset_token(PasswordResetToken {
+                token_hash: hash_token(&token),
+                user_id: user.id.clone(),
+                created_at: now,
+                expires_at,
+            })
+            .await?;
+
+        self.notifier
+            .notify(Notification::PasswordReset {
+                email: user.email.clone(),
+                token,
+                expires_at,
+            })
+            .await?;
+
+        info!(user_id = %user.id, "Password reset token sent");
+        Ok(())
+    }
+
+    #[instrument(skip(self, req))]
+    pub async fn reset_password(&self, req: ResetPasswordRequest) -> Result<()> {
+        trace!("Starting password reset");
+
+        let invalid_token =
+            || DomainError::Unauthorized("Invalid or expired reset token".to_string());
+
+        // Check the rules that do not depend on the user first, so a rejected password
+        // does not burn the single-use token
+        let errors = self.password_policy.validate(&req.new_password, None);
+        if !errors.is_empty() {
+            warn!(
+                failed_rules = errors.len(),
+                "Password reset rejected by policy"
+            );
+            return Err(DomainError::InvalidFields(errors).into());
+        }
+
+        let token = self
+            .reset_token_repository
+            .take_reset_token(&hash_token(&req.token))
+            .await?
+            .ok_or_else(|| {
+                warn!("Unknown password reset token presented");
+                invalid_token()
+            })?;
+
+        if token.expires_at <= Utc::now() {
+            warn!(user_id = %token.user_id, "Expired password reset token presented");
+            return Err(invalid_token().into());
+        }
+
+        let user = self
+            .user_repository
+            .find_user_by_id(&token.user_id)
+            .await?
+            .ok_or_else(|| {
+                warn!(user_id = %token.user_id, "Password reset token of unknown user");
+                invalid_token()
+            })?;
+
+        self.check_password_policy(&req.new_password, &user.email)?;
+
+        let user_id = user.id.clone();
+        self.set_password(user, &req.new_password).await?;
+
+        info!(user_id = %user_id, "Password reset");
+        Ok(())
+    }
+
+    // Activates the user the verification token was sent to
+    #[instrument(skip(self, token))]
+    pub async fn verify_email(&self, token: &str) -> Result<User> {
+        trace!("Starting email verification");
+
+        let invalid_token =
+            || DomainError::Unauthorized("Invalid or expired verification token".to_string());
+
+        let token = self
+            .verification_token_repository
+            .take_verification_token(&hash_token(token))
+            .await?
+            .filter(|t| t.expires_at > Utc::now())
+            .ok_or_else(|| {
+                warn!("Unknown or expired email verification token presented");
+                invalid_token()
+            })?;
+
+        let mut user = self
+            .user_repository
+            .find_user_by_id(&token.user_id)
+            .await?
+            .ok_or_else(|| {
+                warn!(user_id = %token.user_id, "Verification token of unknown user");
+                invalid_token()
+            })?;
+
+        user.status = UserStatus::Active;
+        self.user_repository.update_user(user.clone()).await?;
+        self.verification_token_repository
+            .delete_user_verification_tokens(&user.id)
+            .await?;
+
+        info!(user_id = %user.id, "Email verified");
+        Ok(user)
+    }
+
+    // Sends a new verification email; earlier ones stay valid until they expire
+    #[instrument(skip(self))]
+    pub async fn resend_verification_email(&self, user_id: &str) -> Result<()> {
+        let user = self
+            .user_repository
+            .find_user_by_id(user_id)
+            .await?
+            .ok_or_else(|| DomainError::Unauthorized("Invalid token".to_string()))?;
+        if user.status == UserStatus::Active {
+            return Err(
+                DomainError::Validation("Email address is already verified".to_string()).into(),
+            );
+        }
+
+        let now = Utc::now();
+        let recent: Vec<DateTime<Utc>> = self
+            .verification_token_repository
+            .list_user_verification_tokens(user_id)
+            .await?
+            .into_iter()
+            .map(|t| t.created_at)
+            .filter(|created_at| *created_at > now - Duration::hours(1))
+            .collect();
+        // Until the last one is a minute old, or the oldest of the hour's quota an hour old
+        let retry_at = recent
+            .iter()
+            .max()
+            .map(|last| *last + Duration::seconds(EMAIL_VERIFICATION_RESEND_COOLDOWN_SECS))
+            .filter(|retry_at| *retry_at > now)
+            .or_else(|| {
+                (recent.len() >= EMAIL_VERIFICATION_MAX_PER_HOUR)
+                    .then(|| recent.iter().min().map(|first| *first + Duration::hours(1)))
+                    .flatten()
+            });
+        if let Some(retry_at) = retry_at {
+            warn!(user_id = user_id, "Verification email resend throttled");
+            let retry_after = (retry_at - now).num_seconds().max(1) as u64;
+            return Err(DomainError::TooManyAttempts(retry_after).into());
+        }
+
+        self.send_verification_email(&user).await?;
+        info!(user_id = user_id, "Verification email resent");
+        Ok(())
+    }
+
+    async fn send_verification_email(&self, user: &User) -> Result<()> {
+        let token = generate_opaque_token();
+        let now = Utc::now();
+        let expires_at = now + Duration::hours(EMAIL_VERIFICATION_TOKEN_TTL_HOURS);
+        self.verification_token_repository
+            .save_verification_token(EmailVerificationToken {
+                token_hash: hash_token(&token),
+                user_id: user.id.clone(),
+                created_at: now,
+                expires_at,
+            })
+            .await?;
+
+        self.notifier
+            .notify(Notification::EmailVerification {
+                email: user.email.clone(),
+                token,
+                expires_at,
+            })
+            .await?;
+
+        debug!(user_id = %user.id, "Verification email sent");
+        Ok(())
+    }
+
+    // Applies the fields present in the request. Name and date of birth are what the identity
+    // check looked at, so they are locked while it is in progress or passed.
+    #[instrument(skip(self, req))]
+    pub async fn update_profile(&self, user_id: &str, req: UpdateProfileRequest) -> Result<User> {
+        let mut user = self.get_user(user_id).await?;
+
+        let mut errors = validate_profile_update(&req, Utc::now().date_naive());
+        if matches!(user.kyc_status, KycStatus::Pending | KycStatus::Verified) {
+            let locked = |field: &str| {
+                FieldError::new(
+                    field,
+                    "locked",
+                    format!(
+                        "{} cannot be changed while identity verification is {}",
+                        field, user.kyc_status
+                    ),
+                )
+            };
+            if req
+                .full_name
+                .as_ref()
+                .is_some_and(|name| user.profile.full_name.as_ref() != Some(name))
+            {
+                errors.push(locked("full_name"));
+            }
+            if req
+                .date_of_birth
+                .is_some_and(|date| user.profile.date_of_birth != Some(date))
+            {
+                errors.push(locked("date_of_birth"));
+            }
+        }
+        if !errors.is_empty() {
+            warn!(failed_rules = errors.len(), "Profile update rejected");
+            return Err(DomainError::InvalidFields(errors).into());
+        }
+
+        let profile = &mut user.profile;
+        if let Some(full_name) = req.full_name {
+            profile.full_name = Some(full_name.trim().to_string());
+        }
+        if req.date_of_birth.is_some() {
+            profile.date_of_birth = req.date_of_birth;
+        }
+        if req.phone.is_some() {
+            profile.phone = req.phone;
+        }
+        if req.address.is_some() {
+            profile.address = req.address;
+        }
+        self.user_repository.update_user(user.clone()).await?;
+
+        info!(user_id = user_id, "Profile updated");
+        Ok(user)
+    }
+
+    // Moves the user's identity verification along and records who did it and why
+    #[instrument(skip(self, req), fields(status = %req.status))]
+    pub async fn set_kyc_status(
+        &self,
+        user_id: &str,
+        operator_id: &str,
+        req: UpdateKycStatusRequest,
+    ) -> Result<User> {
+        let reason = req.reason.trim();
+        if reason.is_empty() || reason.chars().count() > KYC_REASON_MAX_LEN {
+            return Err(DomainError::InvalidFields(vec![FieldError::new(
+                "reason",
+                "length",
+                format!("Reason must be 1 to {} characters long", KYC_REASON_MAX_LEN),
+            )])
+            .into());
+        }
+
+        let mut user = self.get_user(user_id).await?;
+        let from = user.kyc_status;
+        if !from.can_transition_to(req.status) {
+            warn!(user_id = user_id, from = %from, to = %req.status, "KYC transition refused");
+            return Err(DomainError::Validation(format!(
+                "KYC status cannot change from {} to {}",
+                from, req.status
+            ))
+            .into());
+        }
+        if matches!(req.status, KycStatus::Pending | KycStatus::Verified)
+            && !user.profile.is_complete()
+        {
+            return Err(DomainError::Validation(
+                "Full name, date of birth and address are required for identity verification"
+                    .to_string(),
+            )
+            .into());
+        }
+
+        user.kyc_status = req.status;
+        self.user_repository.update_user(user.clone()).await?;
+        self.kyc_repository
+            .record_kyc_transition(KycTransition {
+                user_id: user_id.to_string(),
+                from,
+                to: req.status,
+                reason: reason.to_string(),
+                operator_id: operator_id.to_string(),
+                at: Utc::now(),
+            })
+            .await?;
+
+        info!(
+            user_id = user_id,
+            operator_id = operator_id,
+            from = %from,
+            to = %req.status,
+            "KYC status changed"
+        );
+        Ok(user)
+    }
+
+    #[instrument(skip(self))]
+    pub async fn kyc_history(&self, user_id: &str) -> Result<Vec<KycTransition>> {
+        self.get_user(user_id).await?;
+        self.kyc_repository.list_kyc_transitions(user_id).await
+    }
+
+    // What we store about the user themselves; their accounts come from `BankService`
+    #[instrument(skip(self))]
+    pub async fn export_user_data(&self, user_id: &str) -> Result<UserExport> {
+        let user = self.get_user(user_id).await?;
+        let kyc_history = self.kyc_repository.list_kyc_transitions(user_id).await?;
+
+        info!(user_id = user_id, "User data exported");
+        Ok(UserExport {
+            id: user.id,
+            email: user.email,
+            role: user.role,
+            status: user.status,
+            kyc_status: user.kyc_status,
+            profile: user.profile,
+            kyc_history,
+        })
+    }
+
+    // Re-authenticates a signed-in user before an irreversible action such as deletion
+    #[instrument(skip(self, password))]
+    pub async fn confirm_password(&self, user_id: &str, password: &str) -> Result<()> {
+        let user = self.get_user(user_id).await?;
+        if !self.verify_current_password(&user, password).await? {
+            warn!(user_id = user_id, "Invalid password confirmation");
+            return Err(DomainError::Unauthorized("Invalid password".to_string()).into());
+        }
+        Ok(())
+    }
+
+    // Checks the password of a signed-in user. Failures count against the same email
+    // throttle as logins, so a stolen session cannot be used to guess the password.
+    async fn verify_current_password(&self, user: &User, password: &str) -> Result<bool> {
+        let now = Utc::now();
+        let key = email_attempt_key(&user.email);
+        self.check_login_throttle(&key, None, now).await?;
+
+        let is_valid = verify_password(password, &user.password_hash).map_err(|e| {
+            error!(error = %e, "Failed to verify password");
+            DomainError::Internal(format!("Failed to verify password: {}", e))
+        })?;
+        if is_valid {
+            self.login_attempt_repository
+                .clear_failed_attempts(&key)
+                .await?;
+        } else {
+            self.record_login_failure(&key, None, now).await?;
+        }
+        Ok(is_valid)
+    }
+
+    // Erases the user's personal data and ends every way of signing in as them. The user
+    // record stays, anonymized, as accounts, transactions and the KYC audit trail refer to it.
+    // The caller confirms the password first, see `confirm_password`.
+    #[instrument(skip(self))]
+    pub async fn delete_user(&self, user_id: &str) -> Result<()> {
+        self.user_repository
+            .anonymize_user(user_id)
+            .await?
+            .ok_or_else(|| DomainError::NotFound(format!("User not found: {}", user_id)))?;
+        self.logout_all_sessions(user_id).await?;
+        for api_key in self.api_key_repository.list_user_api_keys(user_id).await? {
+            self.api_key_repository
+                .delete_api_key(user_id, &api_key.id)
+                .await?;
+        }
+        self.verification_token_repository
+            .delete_user_verification_tokens(user_id)
+            .await?;
+        self.reset_token_repository
+            .delete_user_reset_tokens(user_id)
+            .await?;
+        self.two_factor_repository
+            .delete_two_factor(user_id)
+            .await?;
+        // A still-linked identity would sign in as the anonymized user
+        self.oidc_repository.delete_user_identities(user_id).await?;
+
+        info!(user_id = user_id, "User deleted and anonymized");
+        Ok(())
+    }
+
+    fn check_password_policy(&self, password: &str, email: &str) -> Result<()> {
+        let errors: Vec<FieldError> = self.password_policy.validate(password, Some(email));
+        if !errors.is_empty() {
+            warn!(failed_rules = errors.len(), "Password rejected by policy");
+            return Err(DomainError::InvalidFields(errors).into());
+        }
+        Ok(())
+    }
+
+    // Stores the new password and invalidates everything issued under the old one:
+    // sessions, refresh tokens and outstanding reset tokens
+    async fn set_password(&self, mut user: User, new_password: &str) -> Result<()> {
+        user.password_hash = hash_password(new_password).map_err(|e| {
+            error!(error = %e, "Failed to hash password");
+            DomainError::Internal(format!("Failed to hash password: {}", e))
+        })?;
+
+        debug!(user_id = %user.id, "Saving new password hash");
+        self.user_repository.update_user(user.clone()).await?;
+        self.reset_token_repository
+            .delete_user_reset_tokens(&user.id)
+            .await?;
+        self.logout_all_sessions(&user.id).await?;
+
+        self.notifier
+            .notify(Notification::PasswordChanged { email: user.email })
+            .await?;
+        Ok(())
+    }
+
+    #[instrument(skip(self))]
+    pub async fn get_user(&self, user_id: &str) -> Result<User> {
+        self.user_repository
+            .find_user_by_id(user_id)
+            .await?
+            .ok_or_else(|| DomainError::NotFound(format!("User not found: {}", user_id)).into())
+    }
+
+    #[instrument(skip(self))]
+    pub async fn get_user_by_email(&self, email: &str) -> Result<User> {
+        let email = normalize_email(email).unwrap_or_else(|_| email.to_string());
+        self.user_repository
+            .find_user_by_email(&email)
+            .await?
+            .ok_or_else(|| DomainError::NotFound(format!("User not found: {}", email)).into())
+    }
+
+    // The user a payment by email goes to, if the email belongs to one
+    #[instrument(skip(self, email))]
+    pub async fn find_payee(&self, email: &str) -> Result<Option<User>> {
+        let email = normalize_email(email).map_err(|e| DomainError::InvalidFields(vec![e]))?;
+        Ok(self
+            .user_repository
+            .find_user_by_email(&email)
+            .await?
+            .filter(|u| u.status != UserStatus::Deleted))
+    }
+
+    // The user's email if they proved they own it; only then may they claim money sent to it
+    #[instrument(skip(self))]
+    pub async fn verified_email(&self, user_id: &str) -> Result<Option<String>> {
+        let user = self.get_user(user_id).await?;
+        Ok((user.status == UserStatus::Active).then_some(user.email))
+    }
+
+    // Tells the recipient of a claimable transfer how to get the money
+    #[instrument(skip(self, transfer), fields(transfer_id = %transfer.id))]
+    pub async fn notify_claimable_transfer(&self, transfer: &ClaimableTransfer) -> Result<()> {
+        self.notifier
+            .notify(Notification::TransferToClaim {
+                email: transfer.recipient_email.clone(),
+                transfer_id: transfer.id.clone(),
+                amount: transfer.amount,
+                expires_at: transfer.expires_at,
+            })
+            .await
+    }
+
+    // Changes the role of a user. Their tokens carry the old role, so every session is
+    // revoked and the new role applies from the next login.
+    #[instrument(skip(self))]
+    pub async fn set_user_role(&self, user_id: &str, role: Role) -> Result<User> {
+        let mut user = self.get_user(user_id).await?;
+        if user.role == role {
+            return Ok(user);
+        }
+
+        let previous = user.role;
+        user.role = role;
+        self.user_repository.update_user(user.clone()).await?;
+        self.logout_all_sessions(user_id).await?;
+
+        info!(
+            user_id = user_id,
+            previous_role = %previous,
+            role = %role,
+            "User role changed"
+        );
+        Ok(user)
+    }
+
+    // Creates a personal API key. The returned key is the only copy of the secret.
+    #[instrument(skip(self, req), fields(name = %req.name))]
+    pub async fn create_api_key(
+        &self,
+        user_id: &str,
+        req: CreateApiKeyRequest,
+    ) -> Result<NewApiKey> {
+        trace!("Creating API key");
+
+        let name = req.name.trim();
+        if name.is_empty() || name.chars().count() > API_KEY_NAME_MAX_LEN {
+            return Err(DomainError::Validation(format!(
+                "API key name must be 1 to {} characters",
+                API_KEY_NAME_MAX_LEN
+            ))
+            .into());
+        }
+        let now = Utc::now();
+        if req.expires_at.is_some_and(|expires_at| expires_at <= now) {
+            return Err(DomainError::Validation(
+                "API key expiry must be in the future".to_string(),
+            )
+            .into());
+        }
+        if req.scopes.as_ref().is_some_and(|scopes| scopes.is_empty()) {
+            return Err(
+                DomainError::Validation("At least one scope is required".to_string()).into(),
+            );
+        }
+
+        let user = self
+            .user_repository
+            .find_user_by_id(user_id)
+            .await?
+            .ok_or_else(|| DomainError::Unauthorized("Invalid token".to_string()))?;
+        if self
+            .api_key_repository
+            .list_user_api_keys(&user.id)
+            .await?
+            .len()
+            >= MAX_API_KEYS_PER_USER
+        {
+            warn!(user_id = %user.id, "API key limit reached");
+            return Err(DomainError::Validation(format!(
+                "At most {} API keys are allowed per user",
+                MAX_API_KEYS_PER_USER
+            ))
+            .into());
+        }
+
+        let key = format!("{}{}", API_KEY_PREFIX, generate_opaque_token());
+        let api_key = ApiKey {
+            id: Uuid::new_v4().to_string(),
+            user_id: user.id.clone(),
+            name: name.to_string(),
+            key_hash: hash_token(&key),
+            prefix: key.chars().take(API_KEY_DISPLAY_LEN).collect(),
+            scopes: req.scopes,
+            created_at: now,
+            expires_at: req.expires_at,
+        };
+        self.api_key_repository
+            .save_api_key(api_key.clone())
+            .await?;
+
+        info!(user_id = %user.id, key_id = %api_key.id, scopes = ?api_key.scopes, "API key created");
+        Ok(NewApiKey { api_key, key })
+    }
+
+    #[instrument(skip(self))]
+    pub async fn list_api_keys(&self, user_id: &str) -> Result<Vec<ApiKey>> {
+        self.api_key_repository.list_user_api_keys(user_id).await
+    }
+
+    #[instrument(skip(self))]
+    pub async fn revoke_api_key(&self, user_id: &str, key_id: &str) -> Result<()> {
+        if !self
+            .api_key_repository
+            .delete_api_key(user_id, key_id)
+            .await?
+        {
+            return Err(DomainError::NotFound(format!("API key not found: {}", key_id)).into());
+        }
+
+        info!(user_id = user_id, key_id = key_id, "API key revoked");
+        Ok(())
+    }
+
+    // Starts a sign-in at the identity provider. With `link_user_id`, the external identity
+    // is linked to that (signed-in) user instead of being looked up. The returned binding
+    // must come back with the callback, from the same browser.
+    #[instrument(skip(self))]
+    pub async fn start_oidc_login(
+        &self,
+        link_user_id: Option<&str>,
+    ) -> Result<OidcAuthorizationStart> {
+        let provider = self.identity_provider()?;
+
+        let state = generate_opaque_token();
+        let binding = generate_opaque_token();
+        let nonce = generate_opaque_token();
+        let code_verifier = generate_code_verifier();
+        let authorization_url =
+            provider.authorization_url(&state, &nonce, &code_challenge(&code_verifier));
+        self.oidc_repository
+            .save_authorization(OidcAuthorization {
+                state_hash: hash_token(&state),
+                code_verifier,
+                nonce,
+                link_user_id: link_user_id.map(str::to_string),
+                binding_hash: hash_token(&binding),
+                expires_at: Utc::now() + Duration::seconds(OIDC_AUTHORIZATION_TTL_SECS as i64),
+            })
+            .await?;
+
+        info!(
+            issuer = provider.issuer(),
+            linking = link_user_id.is_some(),
+            "OIDC login started"
+        );
+        Ok(OidcAuthorizationStart {
+            authorization_url,
+            state,
+            expires_in: OIDC_AUTHORIZATION_TTL_SECS,
+            binding,
+        })
+    }
+
+    // Completes a sign-in with the code the provider redirected back with. The provider is
+    // trusted to have authenticated the user, second factor included; it only signs in users
+    // it created or whose owner linked it.
+    #[instrument(skip(self, code, state, binding, client))]
+    pub async fn complete_oidc_login(
+        &self,
+        code: &str,
+        state: &str,
+        binding: Option<&str>,
+        client: &ClientInfo,
+    ) -> Result<AuthTokens> {
+        let provider = self.identity_provider()?;
+
+        let authorization = self
+            .oidc_repository
+            .take_authorization(&hash_token(state))
+            .await?
+            .filter(|a| a.expires_at > Utc::now())
+            .ok_or_else(|| {
+                warn!("Unknown or expired OIDC state");
+                DomainError::Unauthorized("Invalid or expired login state".to_string())
+            })?;
+        // The state is consumed either way, so a leaked one cannot be retried
+        if binding.map(hash_token).as_deref() != Some(authorization.binding_hash.as_str()) {
+            warn!("OIDC callback from another browser than the one that started the login");
+            return Err(DomainError::Unauthorized(
+                "Login was started in another browser".to_string(),
+            )
+            .into());
+        }
+
+        let identity = provider
+            .exchange_code(code, &authorization.code_verifier, &authorization.nonce)
+            .await
+            .map_err(|e| {
+                warn!(error = %e, "OIDC code exchange failed");
+                DomainError::Unauthorized("Identity provider rejected the login".to_string())
+            })?;
+
+        let user = self
+            .user_for_identity(&identity, authorization.link_user_id.as_deref())
+            .await?;
+        let tokens = self.start_session(&user, client).await?;
+
+        info!(user_id = %user.id, issuer = %identity.issuer, "OIDC login successful");
+        Ok(tokens)
+    }
+
+    fn identity_provider(&self) -> Result<&Arc<dyn IdentityProvider>> {
+        self.identity_provider
+            .as_ref()
+            .ok_or_else(|| DomainError::NotFound("OIDC login is not configured".to_string()).into())
+    }
+
+    // The local user behind an external identity: the linked one, the one being linked, or
+    // else a new customer. An existing user is never linked by email alone, as that would
+    // skip their password and second factor.
+    async fn user_for_identity(
+        &self,
+        identity: &OidcIdentity,
+        link_user_id: Option<&str>,
+    ) -> Result<User> {
+        let linked = self
+            .oidc_repository
+            .find_identity(&identity.issuer, &identity.subject)
+            .await?;
+        if let Some(linked) = linked {
+            if link_user_id.is_some_and(|id| id != linked.user_id) {
+                warn!(subject = %identity.subject, "External identity linked to another user");
+                return Err(DomainError::Validation(
+                    "This identity is already linked to another user".to_string(),
+                )
+                .into());
+            }
+            return self.get_user(&linked.user_id).await;
+        }
+
+        let user = match link_user_id {
+            Some(user_id) => self
+                .user_repository
+                .find_user_by_id(user_id)
+                .await?
+                .ok_or_else(|| DomainError::Unauthorized("Invalid token".to_string()))?,
+            None => {
+                let email = identity
+                    .email
+                    .as_deref()
+                    .ok_or_else(|| {
+                        DomainError::Validation(
+                            "The identity provider did not share an email".to_string(),
+                        )
+                    })
+                    .and_then(|email| {
+                        normalize_email(email).map_err(|e| {
+                            DomainError::Validation(format!(
+                                "Invalid email from identity provider: {}",
+                                e.message
+                            ))
+                        })
+                    })?;
+                match self.user_repository.find_user_by_email(&email).await? {
+                    // Only the owner links it, verified email or not
+                    Some(_) => {
+                        warn!(subject = %identity.subject, "Email of an existing user");
+                        return Err(DomainError::Forbidden(
+                            "Sign in with your password and link this identity to your account"
+                                .to_string(),
+                        )
+                        .into());
+                    }
+                    None => {
+                        self.create_external_user(email, identity.email_verified)
+                            .await?
+                    }
+                }
+            }
+        };
+
+        self.oidc_repository
+            .link_identity(ExternalIdentity {
+                issuer: identity.issuer.clone(),
+                subject: identity.subject.clone(),
+                user_id: user.id.clone(),
+                linked_at: Utc::now(),
+            })
+            .await?;
+        info!(user_id = %user.id, issuer = %identity.issuer, "External identity linked");
+        Ok(user)
+    }
+
+    // Users created by an OIDC login have no password until they reset one. Emails the
+    // provider has not verified are verified like on registration.
+    async fn create_external_user(&self, email: String, email_verified: bool) -> Result<User> {
+        let password_hash = hash_password(&generate_opaque_token()).map_err(|e| {
+            error!(error = %e, "Failed to hash password");
+            DomainError::Internal(format!("Failed to hash password: {}", e))
+        })?;
+        let user = User {
+            id: Uuid::new_v4().to_string(),
+            email,
+            password_hash,
+            role: Role::Customer,
+            status: if email_verified {
+                UserStatus::Active
+            } else {
+                UserStatus::PendingVerification
+            },
+            profile: Profile::default(),
+            kyc_status: KycStatus::Unverified,
+        };
+        self.user_repository.save_user(user.clone()).await?;
+        if !email_verified {
+            self.send_verification_email(&user).await?;
+        }
+
+        info!(user_id = %user.id, email = %user.email, "User created from external identity");
+        Ok(user)
+    }
+
+    // Rewrites stored emails to their normalized form. Users whose emails collapse to the same
+    // identity are not touched, since merging them means merging their accounts; they are
+    // reported so that an operator can resolve them.
+    #[instrument(skip(self))]
+    pub async fn migrate_email_identities(&self) -> Result<EmailMigrationReport> {
+        trace!("Starting email identity migration");
+
+        let mut groups: BTreeMap<String, Vec<User>> = BTreeMap::new();
+        for user in self.user_repository.list_users().await? {
+            groups
+                .entry(email_identity(&user.email))
+                .or_default()
+                .push(user);
+        }
+
+        let mut report = EmailMigrationReport::default();
+        for (identity, mut users) in groups {
+            if users.len() > 1 {
+                let mut user_ids: Vec<String> = users.into_iter().map(|u| u.id).collect();
+                user_ids.sort();
+                warn!(email = %identity, users = ?user_ids, "Duplicate email identity");
+                report.duplicates.push(DuplicateEmail {
+                    email: identity,
+                    user_ids,
+                });
+                continue;
+            }
+
+            let mut user = users.remove(0);
+            match normalize_email(&user.email) {
+                Ok(normalized) if normalized != user.email => {
+                    debug!(user_id = %user.id, from = %user.email, to = %normalized, "Normalizing email");
+                    user.email = normalized;
+                    report.normalized.push(user.id.clone());
+                    self.user_repository.update_user(user).await?;
+                }
+                Ok(_) => {}
+                Err(e) => {
+                    warn!(user_id = %user.id, error = %e.message, "Stored email is invalid");
+                    report.invalid.push(user.id);
+                }
+            }
+        }
+
+        info!(
+            normalized = report.normalized.len(),
+            duplicates = report.duplicates.len(),
+            invalid = report.invalid.len(),
+            "Email identity migration finished"
+        );
+        Ok(report)
+    }
+
+    // Records a new session for a login and issues its first tokens
+    async fn start_session(&self, user: &User, client: &ClientInfo) -> Result<AuthTokens> {
+        if user.status == UserStatus::Deleted {
+            warn!(user_id = %user.id, "Sign-in of a deleted user refused");
+            return Err(DomainError::Unauthorized("User has been deleted".to_string()).into());
+        }
+        let now = Utc::now();
+        let session = Session {
+            id: Uuid::new_v4().to_string(),
+            user_id: user.id.clone(),
+            user_agent: client.user_agent.clone(),
+            ip: client.ip.clone(),
+            created_at: now,
+            last_seen_at: now,
+            expires_at: now + Duration::days(REFRESH_TOKEN_TTL_DAYS),
+            revoked_at: None,
+        };
+        let tokens = self
+            .issue_tokens(user, &session.id, session.expires_at)
+            .await?;
+        debug!(user_id = %user.id, session_id = %session.id, "Session started");
+        self.session_repository.save_session(session).await?;
+        Ok(tokens)
+    }
+
+    // Issues an access token and a refresh token of a session. The session id doubles as
+    // the refresh token family, whose expiry the new refresh token keeps.
+    async fn issue_tokens(
+        &self,
+        user: &User,
+        session_id: &str,
+        expires_at: DateTime<Utc>,
+    ) -> Result<AuthTokens> {
+        let user_id = user.id.as_str();
+        let access_token = sign_session_token(&self.jwt_keys, user_id, user.role, session_id)
+            .map_err(|e| {
+                error!(error = %e, "Failed to generate token");
+                DomainError::Internal(format!("Failed to generate token: {}", e))
+            })?;
+
+        let now = Utc::now();
+        let family_id = session_id.to_string();
+        let refresh_token = generate_opaque_token();
+        debug!(user_id = user_id, family_id = %family_id, "Saving refresh token");
+        self.refresh_token_repository
+            .save_refresh_token(RefreshToken {
+                token_hash: hash_token(&refresh_token),
+                family_id,
+                user_id: user_id.to_string(),
+                created_at: now,
+                expires_at,
+                used: false,
+                revoked: false,
+            })
+            .await?;
+
+        Ok(AuthTokens {
+            access_token,
+            refresh_token,
+            expires_in: ACCESS_TOKEN_TTL_SECS,
+        })
+    }
+
+    // Issues an access token on behalf of a user. With `scopes`, the token may only call
+    // routes requiring one of them.
+    #[instrument(skip(self, credentials), fields(client_id = %credentials.client_id, user_id = user_id))]
+    pub async fn get_token(
+        &self,
+        credentials: &ClientCredentials,
+        user_id: &str,
+        scopes: Option<&[Scope]>,
+    ) -> Result<String> {
+        trace!("Generating token for user");
+
+        self.authenticate_client(credentials)?;
+
+        if scopes.is_some_and(|scopes| scopes.is_empty()) {
+            return Err(
+                DomainError::Validation("At least one scope is required".to_string()).into(),
+            );
+        }
+
+        // Verify user exists
+        let user = self
+            .user_repository
+            .find_user_by_id(user_id)
+            .await?
+            .filter(|u| u.status != UserStatus::Deleted)
+            .ok_or_else(|| {
+                warn!(user_id = user_id, "User not found during token generation");
+                DomainError::NotFound(format!("User not found: {}", user_id))
+            })?;
+
+        // A leaked client secret must not open the admin routes, so staff get customer
+        // tokens here and sign in themselves for their privileged work
+        if user.role != Role::Customer {
+            warn!(user_id = %user.id, role = %user.role, "Client token capped to the customer role");
+        }
+
+        // Generate JWT token
+        let token =
+            sign_access_token(&self.jwt_keys, &user.id, Role::Customer, scopes).map_err(|e| {
+                error!(error = %e, "Failed to generate token");
+                DomainError::Internal(format!("Failed to generate token: {}", e))
+            })?;
+
+        info!(
+            client_id = %credentials.client_id,
+            user_id = %user.id,
+            email = %user.email,
+            scopes = ?scopes,
+            "Token generated successfully"
+        );
+
+        Ok(token)
+    }
+
+    fn authenticate_client(&self, credentials: &ClientCredentials) -> Result<()> {
+        // Hash the presented secret even for unknown clients so both cases cost the same
+        let presented_hash = hash_token(&credentials.client_secret);
+        let authenticated = self
+            .token_clients
+            .get(&credentials.client_id)
+            .is_some_and(|expected| *expected == presented_hash);
+        if !authenticated {
+            warn!(
+                client_id = %credentials.client_id,
+                "Request with invalid client credentials"
+            );
+            return Err(DomainError::Unauthorized("Invalid client credentials".to_string()).into());
+        }
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl<R: UserRepository> ApiKeyAuthenticator for AuthService<R> {
+    #[instrument(skip(self, key))]
+    async fn authenticate_api_key(&self, key: &str) -> Result<Option<ApiKeyIdentity>> {
+        if !key.starts_with(API_KEY_PREFIX) {
+            return Ok(None);
+        }
+        let Some(api_key) = self
+            .api_key_repository
+            .find_api_key_by_hash(&hash_token(key))
+            .await?
+        else {
+            return Ok(None);
+        };
+        if api_key.is_expired(Utc::now()) {
+            debug!(key_id = %api_key.id, "Expired API key presented");
+            return Ok(None);
+        }
+        let Some(user) = self
+            .user_repository
+            .find_user_by_id(&api_key.user_id)
+            .await?
+        else {
+            warn!(key_id = %api_key.id, "API key of unknown user presented");
+            return Ok(None);
+        };
+
+        Ok(Some(ApiKeyIdentity {
+            key_id: api_key.id,
+            user_id: user.id,
+            role: user.role,
+            scopes: api_key.scopes,
+        }))
+    }
+}
+
+#[async_trait]
+impl<R: UserRepository> CustomerPolicy for AuthService<R> {
+    #[instrument(skip(self))]
+    async fn check_can_open_account(&self, user_id: &str) -> Result<()> {
+        let user = self.get_user(user_id).await?;
+        if user.status == UserStatus::PendingVerification {
+            warn!(
+                user_id = user_id,
+                "Account opening refused, email not verified"
+            );
+            return Err(DomainError::Forbidden(
+                "Verify your email address before opening an account".to_string(),
+            )
+            .into());
+        }
+        if user.kyc_status == KycStatus::Rejected {
+            warn!(user_id = user_id, "Account opening refused, KYC rejected");
+            return Err(DomainError::Forbidden(
+                "Identity verification was rejected, contact support".to_string(),
+            )
+            .into());
+        }
+        Ok(())
+    }
+
+    #[instrument(skip(self))]
+    async fn transaction_limit(&self, user_id: &str) -> Result<Option<Amount>> {
+        let user = self.get_user(user_id).await?;
+        Ok((user.kyc_status != KycStatus::Verified).then_some(self.unverified_transaction_limit))
+    }
+}
+
+fn email_attempt_key(email: &str) -> String {
+    format!("email:{}", email_identity(email))
+}
+
+fn ip_attempt_key(ip: &str) -> String {
+    format!("ip:{}", ip)
+}
+
+fn two_factor_attempt_key(user_id: &str) -> String {
+    format!("2fa:{}", user_id)
+}
+
+// Four groups of four hex digits, e.g. "3f9a-0c2e-77b1-d405"
+fn generate_recovery_code() -> String {
+    let token = generate_opaque_token();
+    token.as_bytes()[..16]
+        .chunks(4)
+        .map(|group| std::str::from_utf8(group).unwrap_or_default())
+        .collect::<Vec<_>>()
+        .join("-")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::user_repository::InMemoryUserRepository;
+    use crate::domain::user::{CreateUser, LoginRequest};
+    use crate::domain::validation::PasswordPolicy;
+    use crate::infrastructure::notifier::InMemoryNotifier;
+
+    #[tokio::test]
+    async fn test_register_user_registers_new_user_successfully() {
+        let repo = Arc::new(InMemoryUserRepository::new());
+        let service = AuthService::new(repo, "test_secret".to_string());
+
+        let req = CreateUser {
+            email: "newuser@example.com".to_string(),
+            password: "Passw0rd-123-Full".to_string(),
+        };
+
+        let user = service.register_user(req).await.unwrap();
+
+        assert_eq!(user.email, "newuser@example.com");
+        assert!(!user.id.is_empty());
+        assert!(!user.password_hash.is_empty());
+        // Password should be hashed, not stored plain
+        assert_ne!(user.password_hash, "Passw0rd-123-Full");
+    }
+
+    #[tokio::test]
+    async fn test_register_user_returns_error_for_duplicate_email() {
+        let repo = Arc::new(InMemoryUserRepository::new());
+        let service = AuthService::new(repo, "test_secret".to_string());
+
+        let req1 = CreateUser {
+            email: "duplicate@example.com".to_string(),
+            password: "Passw0rd-One-1".to_string(),
+        };
+        let req2 = CreateUser {
+            email: "duplicate@example.com".to_string(),
+            password: "Passw0rd-Two-2".to_string(),
+        };
+
+        service.register_user(req1).await.unwrap();
+        let result = service.register_user(req2).await;
+
+        assert!(result.is_err());
+        let error = result.unwrap_err();
+        if let Ok(domain_error) = error.downcast::<DomainError>() {
+            match domain_error {
+                DomainError::Validation(msg) => {
+                    assert!(msg.contains("already exists"));
+                }
+                _ => panic!("Expected Validation error"),
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_register_user_hashes_password() {
+        let repo = Arc::new(InMemoryUserRepository::new());
+        let service = AuthService::new(repo, "test_secret".to_string());
+
+        let req = CreateUser {
+            email: "hashtest@example.com".to_string(),
+            password: "Plain-Passw0rd-7".to_string(),
+        };
+
+        let user = service.register_user(req).await.unwrap();
+
+        // Password hash should not be the plain password
+        assert_ne!(user.password_hash, "Plain-Passw0rd-7");
+        // Hash should be verifiable
+        let is_valid = verify_password("Plain-Passw0rd-7", &user.password_hash).unwrap();
+        assert!(is_valid);
+    }
+
+    #[tokio::test]
+    async fn test_login_logs_in_with_correct_credentials() {
+        let repo = Arc::new(InMemoryUserRepository::new());
+        let service = AuthService::new(repo, "test_secret".to_string());
+
+        // Register user first
+        let register_req = CreateUser {
+            email: "login@example.com".to_string(),
+            password: "Correct-Passw0rd-2".to_string(),
+        };
+        service.register_user(register_req).await.unwrap();
+
+        // Login
+        let login_req = LoginRequest {
+            email: "login@example.com".to_string(),
+            password: "Correct-Passw0rd-2".to_string(),
+        };
+
+        let tokens = service
+            .login(login_req)
+            .await
+            .unwrap()
+            .into_tokens()
+            .unwrap();
+        assert!(!tokens.access_token.is_empty());
+        assert!(!tokens.refresh_token.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_login_returns_error_for_wrong_password() {
+        let repo = Arc::new(InMemoryUserRepository::new());
+        let service = AuthService::new(repo, "test_secret".to_string());
+
+        // Register user
+        let register_req = CreateUser {
+            email: "wrongpass@example.com".to_string(),
+            password: "Correct-Passw0rd-2".to_string(),
+        };
+        service.register_user(register_req).await.unwrap();
+
+        // Try to login with wrong password
+        let login_req = LoginRequest {
+            email: "wrongpass@example.com".to_string(),
+            password: "wrong_password".to_string(),
+        };
+
+        let result = service.login(login_req).await;
+        assert!(result.is_err());
+
+        let error = result.unwrap_err();
+        if let Ok(domain_error) = error.downcast::<DomainError>() {
+            match domain_error {
+                DomainError::Unauthorized(msg) => {
+                    assert!(msg.contains("Invalid email or password"));
+                }
+                _ => panic!("Expected Unauthorized error"),
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_login_returns_error_for_nonexistent_user() {
+        let repo = Arc::new(InMemoryUserRepository::new());
+        let service = AuthService::new(repo, "test_secret".to_string());
+
+        let login_req = LoginRequest {
+            email: "nonexistent@example.com".to_string(),
+            password: "Passw0rd-Strong".to_string(),
+        };
+
+        let result = service.login(login_req).await;
+        assert!(result.is_err());
+
+        let error = result.unwrap_err();
+        if let Ok(domain_error) = error.downcast::<DomainError>() {
+            match domain_error {
+                DomainError::Unauthorized(msg) => {
+                    assert!(msg.contains("Invalid email or password"));
+                }
+                _ => panic!("Expected Unauthorized error"),
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_login_returns_valid_jwt_token() {
+        let repo = Arc::new(InMemoryUserRepository::new());
+        let jwt_secret = "test_secret_key".to_string();
+        let service = AuthService::new(repo, jwt_secret.clone());
+
+        // Register user
+        let register_req = CreateUser {
+            email: "token@example.com".to_string(),
+            password: "Passw0rd-Strong".to_string(),
+        };
+        let user = service.register_user(register_req).await.unwrap();
+
+        // Login
+        let login_req = LoginRequest {
+            email: "token@example.com".to_string(),
+            password: "Passw0rd-Strong".to_string(),
+        };
+        let tokens = service
+            .login(login_req)
+            .await
+            .unwrap()
+            .into_tokens()
+            .unwrap();
+
+        // Validate token
+        let extracted_user_id =
+            crate::infrastructure::security::validate_token(&tokens.access_token, &jwt_secret)
+                .unwrap();
+        assert_eq!(extracted_user_id, user.id);
+    }
+
+    fn client_credentials(client_id: &str, client_secret: &str) -> ClientCredentials {
+        ClientCredentials {
+            client_id: client_id.to_string(),
+            client_secret: client_secret.to_string(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_get_token_generates_token_for_existing_user() {
+        let repo = Arc::new(InMemoryUserRepository::new());
+        let jwt_secret = "test_secret".to_string();
+        let service = AuthService::new(repo, jwt_secret.clone())
+            .with_token_client("backoffice", "client_secret");
+
+        // Register user
+        let register_req = CreateUser {
+            email: "gettoken@example.com".to_string(),
+            password: "Passw0rd-Strong".to_string(),
+        };
+        let user = service.register_user(register_req).await.unwrap();
+
+        // Get token
+        let token = service
+            .get_token(
+                &client_credentials("backoffice", "client_secret"),
+                &user.id,
+                None,
+            )
+            .await
+            .unwrap();
+        assert!(!token.is_empty());
+
+        // Validate token
+        let extracted_user_id =
+            crate::infrastructure::security::validate_token(&token, &jwt_secret).unwrap();
+        assert_eq!(extracted_user_id, user.id);
+    }
+
+    #[tokio::test]
+    async fn test_get_token_issues_scoped_token() {
+        let repo = Arc::new(InMemoryUserRepository::new());
+        let service = AuthService::new(repo, "test_secret".to_string())
+            .with_token_client("backoffice", "client_secret");
+        let user = service
+            .register_user(CreateUser {
+                email: "scoped@example.com".to_string(),
+                password: "Passw0rd-Strong".to_string(),
+            })
+            .await
+            .unwrap();
+        let credentials = client_credentials("backoffice", "client_secret");
+
+        let token = service
+            .get_token(&credentials, &user.id, Some(&[Scope::AccountsRead]))
+            .await
+            .unwrap();
+        let claims = service.authenticate(&token).await.unwrap();
+        assert_eq!(claims.scopes, Some(vec![Scope::AccountsRead]));
+
+        let error = service
+            .get_token(&credentials, &user.id, Some(&[]))
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            error.downcast_ref::<DomainError>(),
+            Some(DomainError::Validation(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_get_token_never_issues_privileged_roles() {
+        let repo = Arc::new(InMemoryUserRepository::new());
+        let service = AuthService::new(repo, "test_secret".to_string())
+            .with_token_client("backoffice", "client_secret");
+        let admin = service
+            .register_user_with_role(
+                CreateUser {
+                    email: "staff@example.com".to_string(),
+                    password: "Passw0rd-Strong".to_string(),
+                },
+                Role::Admin,
+            )
+            .await
+            .unwrap();
+
+        let token = service
+            .get_token(
+                &client_credentials("backoffice", "client_secret"),
+                &admin.id,
+                None,
+            )
+            .await
+            .unwrap();
+        let claims = service.authenticate(&token).await.unwrap();
+        assert_eq!(claims.user_id, admin.id);
+        assert_eq!(claims.role, Role::Customer);
+    }
+
+    #[tokio::test]
+    async fn test_get_token_rejects_invalid_client_credentials() {
+        let repo = Arc::new(InMemoryUserRepository::new());
+        let service = AuthService::new(repo, "test_secret".to_string())
+            .with_token_client("backoffice", "client_secret");
+        let user = service
+            .register_user(CreateUser {
+                email: "client@example.com".to_string(),
+                password: "Passw0rd-Strong".to_string(),
+            })
+            .await
+            .unwrap();
+
+        for credentials in [
+            client_credentials("backoffice", "wrong_secret"),
+            client_credentials("unknown", "client_secret"),
+        ] {
+            let result = service.get_token(&credentials, &user.id, None).await;
+            match result.unwrap_err().downcast::<DomainError>() {
+                Ok(DomainError::Unauthorized(msg)) => {
+                    assert!(msg.contains("Invalid client credentials"))
+                }
+                other => panic!("Expected Unauthorized error, got {:?}", other),
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_get_token_disabled_without_registered_clients() {
+        let repo = Arc::new(InMemoryUserRepository::new());
+        let service = AuthService::new(repo, "test_secret".to_string());
+        let user = service
+            .register_user(CreateUser {
+                email: "noclient@example.com".to_string(),
+                password: "Passw0rd-Strong".to_string(),
+            })
+            .await
+            .unwrap();
+
+        let result = service
+            .get_token(&client_credentials("", ""), &user.id, None)
+            .await;
+        assert!(result.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_get_token_returns_error_for_nonexistent_user() {
+        let repo = Arc::new(InMemoryUserRepository::new());
+        let service = AuthService::new(repo, "test_secret".to_string())
+            .with_token_client("backoffice", "client_secret");
+
+        let result = service
+            .get_token(
+                &client_credentials("backoffice", "client_secret"),
+                "nonexistent-user-id",
+                None,
+            )
+            .await;
+        assert!(result.is_err());
+
+        let error = result.unwrap_err();
+        if let Ok(domain_error) = error.downcast::<DomainError>() {
+            match domain_error {
+                DomainError::NotFound(msg) => {
+                    assert!(msg.contains("User not found"));
+                }
+                _ => panic!("Expected NotFound error"),
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_multiple_users_can_register() {
+        let repo = Arc::new(InMemoryUserRepository::new());
+        let service = AuthService::new(repo, "test_secret".to_string());
+
+        let req1 = CreateUser {
+            email: "user1@example.com".to_string(),
+            password: "First-Passw0rd-1".to_string(),
+        };
+        let req2 = CreateUser {
+            email: "user2@example.com".to_string(),
+            password: "Second-Passw0rd-2".to_string(),
+        };
+
+        let user1 = service.register_user(req1).await.unwrap();
+        let user2 = service.register_user(req2).await.unwrap();
+
+        assert_ne!(user1.id, user2.id);
+        assert_ne!(user1.email, user2.email);
+    }
+
+    #[tokio::test]
+    async fn test_login_with_different_passwords() {
+        let repo = Arc::new(InMemoryUserRepository::new());
+        let service = AuthService::new(repo, "test_secret".to_string());
+
+        // Register with password1
+        let register_req = CreateUser {
+            email: "multipass@example.com".to_string(),
+            password: "Passw0rd-One-1".to_string(),
+        };
+        service.register_user(register_req).await.unwrap();
+
+        // Login with correct password should work
+        let login_req1 = LoginRequest {
+            email: "multipass@example.com".to_string(),
+            password: "Passw0rd-One-1".to_string(),
+        };
+        assert!(service.login(login_req1).await.is_ok());
+
+        // Login with wrong password should fail
+        let login_req2 = LoginRequest {
+            email: "multipass@example.com".to_string(),
+            password: "Passw0rd-Two-2".to_string(),
+        };
+        assert!(service.login(login_req2).await.is_err());
+    }
+
+    async fn register_and_login(service: &AuthService<InMemoryUserRepository>) -> AuthTokens {
+        service
+            .register_user(CreateUser {
+                email: "refresh@example.com".to_string(),
+                password: "Passw0rd-Strong".to_string(),
+            })
+            .await
+            .unwrap();
+        service
+            .login(LoginRequest {
+                email: "refresh@example.com".to_string(),
+                password: "Passw0rd-Strong".to_string(),
+            })
+            .await
+            .unwrap()
+            .into_tokens()
+            .unwrap()
+    }
+
+    fn assert_unauthorized<T: std::fmt::Debug>(result: Result<T>, expected: &str) {
+        match result.unwrap_err().downcast::<DomainError>() {
+            Ok(DomainError::Unauthorized(msg)) => assert!(msg.contains(expected)),
+            other => panic!("Expected Unauthorized error, got {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_refresh_rotates_refresh_token() {
+        let repo = Arc::new(InMemoryUserRepository::new());
+        let jwt_secret = "test_secret".to_string();
         let service = AuthService::new(repo, jwt_secret.clone());
+        let tokens = register_and_login(&service).await;
+
+        let refreshed = service.refresh(&tokens.refresh_token).await.unwrap();
+
+        assert_ne!(refreshed.refresh_token, tokens.refresh_token);
+        assert!(
+            crate::infrastructure::security::validate_token(&refreshed.access_token, &jwt_secret)
+                .is_ok()
+        );
+        // The rotated token can be used in turn
+        assert!(service.refresh(&refreshed.refresh_token).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_refresh_reuse_revokes_token_family() {
+        let repo = Arc::new(InMemoryUserRepository::new());
+        let service = AuthService::new(repo, "test_secret".to_string());
+        let tokens = register_and_login(&service).await;
+
+        let rotated = service.refresh(&tokens.refresh_token).await.unwrap();
+
+        // Replaying the consumed token is detected...
+        assert_unauthorized(
+            service.refresh(&tokens.refresh_token).await,
+            "reuse detected",
+        );
+        // ...and the legitimate successor is revoked with the whole family
+        assert_unauthorized(
+            service.refresh(&rotated.refresh_token).await,
+            "Invalid refresh token",
+        );
+    }
+
+    #[tokio::test]
+    async fn test_refresh_reuse_does_not_affect_other_families() {
+        let repo = Arc::new(InMemoryUserRepository::new());
+        let service = AuthService::new(repo, "test_secret".to_string());
+        let first = register_and_login(&service).await;
+        let second = service
+            .login(LoginRequest {
+                email: "refresh@example.com".to_string(),
+                password: "Passw0rd-Strong".to_string(),
+            })
+            .await
+            .unwrap()
+            .into_tokens()
+            .unwrap();
+
+        service.refresh(&first.refresh_token).await.unwrap();
+        assert!(service.refresh(&first.refresh_token).await.is_err());
+
+        assert!(service.refresh(&second.refresh_token).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_refresh_rejects_unknown_token() {
+        let repo = Arc::new(InMemoryUserRepository::new());
+        let service = AuthService::new(repo, "test_secret".to_string());
+
+        assert_unauthorized(
+            service.refresh("not-a-refresh-token").await,
+            "Invalid refresh token",
+        );
+    }
+
+    #[tokio::test]
+    async fn test_refresh_rejects_expired_token() {
+        let repo = Arc::new(InMemoryUserRepository::new());
+        let refresh_repo = Arc::new(InMemoryRefreshTokenRepository::new());
+        let service = AuthService::new(repo, "test_secret".to_string())
+            .with_refresh_token_repository(refresh_repo.clone());
+        register_and_login(&service).await;
+
+        let now = Utc::now();
+        refresh_repo
+            .save_refresh_token(RefreshToken {
+                token_hash: hash_token("expired-token"),
+                family_id: "family".to_string(),
+                user_id: "user".to_string(),
+                created_at: now - Duration::days(31),
+                expires_at: now - Duration::seconds(1),
+                used: false,
+                revoked: false,
+            })
+            .await
+            .unwrap();
+
+        // Expired tokens are purged from storage on the next save
+        assert!(service.refresh("expired-token").await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_logout_revokes_access_and_refresh_token() {
+        let repo = Arc::new(InMemoryUserRepository::new());
+        let service = AuthService::new(repo, "test_secret".to_string());
+        let tokens = register_and_login(&service).await;
+        let other = service
+            .login(LoginRequest {
+                email: "refresh@example.com".to_string(),
+                password: "Passw0rd-Strong".to_string(),
+            })
+            .await
+            .unwrap()
+            .into_tokens()
+            .unwrap();
+
+        let claims = service.authenticate(&tokens.access_token).await.unwrap();
+        service
+            .logout(&claims, Some(&tokens.refresh_token))
+            .await
+            .unwrap();
+
+        assert!(service.authenticate(&tokens.access_token).await.is_err());
+        assert!(service.refresh(&tokens.refresh_token).await.is_err());
+        // Other sessions of the same user are untouched
+        assert!(service.authenticate(&other.access_token).await.is_ok());
+        assert!(service.refresh(&other.refresh_token).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_logout_all_sessions_revokes_every_token() {
+        let repo = Arc::new(InMemoryUserRepository::new());
+        let service = AuthService::new(repo, "test_secret".to_string());
+        let first = register_and_login(&service).await;
+        let second = service
+            .login(LoginRequest {
+                email: "refresh@example.com".to_string(),
+                password: "Passw0rd-Strong".to_string(),
+            })
+            .await
+            .unwrap()
+            .into_tokens()
+            .unwrap();
+        let user_id = service
+            .authenticate(&first.access_token)
+            .await
+            .unwrap()
+            .user_id;
+
+        service.logout_all_sessions(&user_id).await.unwrap();
+
+        for tokens in [&first, &second] {
+            assert!(service.authenticate(&tokens.access_token).await.is_err());
+            assert!(service.refresh(&tokens.refresh_token).await.is_err());
+        }
+        assert!(service.list_sessions(&user_id).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_revoke_session_ends_only_that_session() {
+        let repo = Arc::new(InMemoryUserRepository::new());
+        let service = AuthService::new(repo, "test_secret".to_string());
+        let first = register_and_login(&service).await;
+        let second = service
+            .login_from(
+                login_as("refresh@example.com", "Passw0rd-Strong"),
+                &client_at("10.0.0.2"),
+            )
+            .await
+            .unwrap()
+            .into_tokens()
+            .unwrap();
+        let claims = service.authenticate(&first.access_token).await.unwrap();
+        let session_id = claims.session_id.clone().unwrap();
+        // Tokens from a refresh belong to the same session
+        let refreshed = service.refresh(&first.refresh_token).await.unwrap();
+
+        let sessions = service.list_sessions(&claims.user_id).await.unwrap();
+        assert_eq!(sessions.len(), 2);
+        assert_eq!(sessions[1].ip.as_deref(), Some("10.0.0.2"));
+
+        service
+            .revoke_session(&claims.user_id, &session_id)
+            .await
+            .unwrap();
+
+        assert!(service.authenticate(&first.access_token).await.is_err());
+        assert!(service.authenticate(&refreshed.access_token).await.is_err());
+        assert!(service.refresh(&refreshed.refresh_token).await.is_err());
+        assert!(service.authenticate(&second.access_token).await.is_ok());
+        assert_eq!(
+            service.list_sessions(&claims.user_id).await.unwrap().len(),
+            1
+        );
+
+        // Already revoked, and never visible to other users
+        assert!(
+            service
+                .revoke_session(&claims.user_id, &session_id)
+                .await
+                .is_err()
+        );
+        let second_id = service
+            .authenticate(&second.access_token)
+            .await
+            .unwrap()
+            .session_id
+            .unwrap();
+        assert!(
+            service
+                .revoke_session("someone-else", &second_id)
+                .await
+                .is_err()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_registered_users_are_customers() {
+        let repo = Arc::new(InMemoryUserRepository::new());
+        let service = AuthService::new(repo, "test_secret".to_string());
+        let tokens = register_and_login(&service).await;
+
+        let claims = service.authenticate(&tokens.access_token).await.unwrap();
+
+        assert_eq!(claims.role, Role::Customer);
+        assert_eq!(
+            service.get_user(&claims.user_id).await.unwrap().role,
+            Role::Customer
+        );
+    }
+
+    #[tokio::test]
+    async fn test_register_user_with_role_puts_role_in_tokens() {
+        let repo = Arc::new(InMemoryUserRepository::new());
+        let service = AuthService::new(repo, "test_secret".to_string());
+        let user = service
+            .register_user_with_role(
+                CreateUser {
+                    email: "root@example.com".to_string(),
+                    password: "Passw0rd-Strong".to_string(),
+                },
+                Role::Admin,
+            )
+            .await
+            .unwrap();
+        assert_eq!(user.role, Role::Admin);
+
+        let tokens = service
+            .login(LoginRequest {
+                email: "root@example.com".to_string(),
+                password: "Passw0rd-Strong".to_string(),
+            })
+            .await
+            .unwrap()
+            .into_tokens()
+            .unwrap();
+
+        let claims = service.authenticate(&tokens.access_token).await.unwrap();
+        assert_eq!(claims.role, Role::Admin);
+    }
+
+    #[tokio::test]
+    async fn test_set_user_role_revokes_sessions_with_old_role() {
+        let repo = Arc::new(InMemoryUserRepository::new());
+        let service = AuthService::new(repo, "test_secret".to_string());
+        let tokens = register_and_login(&service).await;
+        let user_id = service
+            .authenticate(&tokens.access_token)
+            .await
+            .unwrap()
+            .user_id;
+
+        let user = service
+            .set_user_role(&user_id, Role::Operator)
+            .await
+            .unwrap();
+
+        assert_eq!(user.role, Role::Operator);
+        assert!(service.authenticate(&tokens.access_token).await.is_err());
+        assert!(service.refresh(&tokens.refresh_token).await.is_err());
+
+        let tokens = service
+            .login(LoginRequest {
+                email: "refresh@example.com".to_string(),
+                password: "Passw0rd-Strong".to_string(),
+            })
+            .await
+            .unwrap()
+            .into_tokens()
+            .unwrap();
+        // Decoded directly: revocation has second precision, so a token issued in the same
+        // second as the role change is rejected until the next second
+        let claims = crate::infrastructure::security::decode_access_token(
+            &tokens.access_token,
+            "test_secret",
+        )
+        .unwrap();
+        assert_eq!(claims.role, Role::Operator);
+    }
+
+    #[tokio::test]
+    async fn test_set_user_role_rejects_unknown_user() {
+        let repo = Arc::new(InMemoryUserRepository::new());
+        let service = AuthService::new(repo, "test_secret".to_string());
+
+        let error = service
+            .set_user_role("missing", Role::Admin)
+            .await
+            .unwrap_err();
+
+        assert!(matches!(
+            error.downcast_ref::<DomainError>(),
+            Some(DomainError::NotFound(_))
+        ));
+    }
+
+    fn api_key_request(name: &str) -> CreateApiKeyRequest {
+        CreateApiKeyRequest {
+            name: name.to_string(),
+            expires_at: None,
+            scopes: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_api_key_authenticates_as_its_user_with_current_role() {
+        let repo = Arc::new(InMemoryUserRepository::new());
+        let service = AuthService::new(repo, "test_secret".to_string());
+        let tokens = register_and_login(&service).await;
+        let user_id = service
+            .authenticate(&tokens.access_token)
+            .await
+            .unwrap()
+            .user_id;
+
+        let created = service
+            .create_api_key(
+                &user_id,
+                CreateApiKeyRequest {
+                    scopes: Some(vec![Scope::AccountsRead]),
+                    ..api_key_request("reporting")
+                },
+            )
+            .await
+            .unwrap();
+        assert!(created.key.starts_with(created.api_key.prefix.as_str()));
+        assert_eq!(created.api_key.key_hash, hash_token(&created.key));
+
+        service
+            .set_user_role(&user_id, Role::Operator)
+            .await
+            .unwrap();
+        let identity = service
+            .authenticate_api_key(&created.key)
+            .await
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(identity.user_id, user_id);
+        assert_eq!(identity.key_id, created.api_key.id);
+        assert_eq!(identity.role, Role::Operator);
+        assert_eq!(identity.scopes, Some(vec![Scope::AccountsRead]));
+        assert!(
+            service
+                .authenticate_api_key("ybk_unknown")
+                .await
+                .unwrap()
+                .is_none()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_revoked_and_expired_api_keys_are_rejected() {
+        let repo = Arc::new(InMemoryUserRepository::new());
+        let api_key_repository = Arc::new(InMemoryApiKeyRepository::new());
+        let service = AuthService::new(repo, "test_secret".to_string())
+            .with_api_key_repository(api_key_repository.clone());
+        let tokens = register_and_login(&service).await;
+        let user_id = service
+            .authenticate(&tokens.access_token)
+            .await
+            .unwrap()
+            .user_id;
+
+        let revoked = service
+            .create_api_key(&user_id, api_key_request("old"))
+            .await
+            .unwrap();
+        service
+            .revoke_api_key(&user_id, &revoked.api_key.id)
+            .await
+            .unwrap();
+        assert!(
+            service
+                .authenticate_api_key(&revoked.key)
+                .await
+                .unwrap()
+                .is_none()
+        );
+
+        // Expiry in the past cannot be requested, so let the stored key expire instead
+        let expiring = service
+            .create_api_key(&user_id, api_key_request("expiring"))
+            .await
+            .unwrap();
+        let mut stored = expiring.api_key.clone();
+        stored.expires_at = Some(Utc::now() - Duration::seconds(1));
+        api_key_repository.save_api_key(stored).await.unwrap();
+        assert!(
+            service
+                .authenticate_api_key(&expiring.key)
+                .await
+                .unwrap()
+                .is_none()
+        );
+
+        let listed = service.list_api_keys(&user_id).await.unwrap();
+        assert_eq!(listed.len(), 1);
+        assert_eq!(listed[0].name, "expiring");
+    }
+
+    #[tokio::test]
+    async fn test_create_api_key_validates_request() {
+        let repo = Arc::new(InMemoryUserRepository::new());
+        let service = AuthService::new(repo, "test_secret".to_string());
+        let tokens = register_and_login(&service).await;
+        let user_id = service
+            .authenticate(&tokens.access_token)
+            .await
+            .unwrap()
+            .user_id;
+
+        let invalid = [
+            api_key_request("  "),
+            api_key_request(&"x".repeat(API_KEY_NAME_MAX_LEN + 1)),
+            CreateApiKeyRequest {
+                expires_at: Some(Utc::now() - Duration::minutes(1)),
+                ..api_key_request("expired")
+            },
+            CreateApiKeyRequest {
+                scopes: Some(vec![]),
+                ..api_key_request("no scopes")
+            },
+        ];
+        for req in invalid {
+            let error = service.create_api_key(&user_id, req).await.unwrap_err();
+            assert!(matches!(
+                error.downcast_ref::<DomainError>(),
+                Some(DomainError::Validation(_))
+            ));
+        }
+
+        let error = service
+            .revoke_api_key(&user_id, "missing")
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            error.downcast_ref::<DomainError>(),
+            Some(DomainError::NotFound(_))
+        ));
+    }
+
+    fn service_with_notifier() -> (AuthService<InMemoryUserRepository>, InMemoryNotifier) {
+        let notifier = InMemoryNotifier::new();
+        let service = AuthService::new(
+            Arc::new(InMemoryUserRepository::new()),
+            "test_secret".to_string(),
+        )
+        .with_notifier(Arc::new(notifier.clone()));
+        (service, notifier)
+    }
+
+    async fn reset_token_sent_to(notifier: &InMemoryNotifier, email: &str) -> String {
+        notifier
+            .sent_to(email)
+            .await
+            .into_iter()
+            .rev()
+            .find_map(|n| match n {
+                Notification::PasswordReset { token, .. } => Some(token),
+                _ => None,
+            })
+            .expect("No password reset notification sent")
+    }
+
+    fn login_request(password: &str) -> LoginRequest {
+        LoginRequest {
+            email: "refresh@example.com".to_string(),
+            password: password.to_string(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_change_password_requires_current_password() {
+        let (service, _) = service_with_notifier();
+        let tokens = register_and_login(&service).await;
+        let user_id = service
+            .authenticate(&tokens.access_token)
+            .await
+            .unwrap()
+            .user_id;
+
+        let result = service
+            .change_password(
+                &user_id,
+                ChangePasswordRequest {
+                    current_password: "wrong".to_string(),
+                    new_password: "New-Passw0rd-1".to_string(),
+                },
+            )
+            .await;
+
+        assert!(result.is_err());
+        assert!(
+            service
+                .login(login_request("Passw0rd-Strong"))
+                .await
+                .is_ok()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_change_password_updates_password_and_revokes_sessions() {
+        let (service, notifier) = service_with_notifier();
+        let tokens = register_and_login(&service).await;
+        let user_id = service
+            .authenticate(&tokens.access_token)
+            .await
+            .unwrap()
+            .user_id;
+
+        service
+            .change_password(
+                &user_id,
+                ChangePasswordRequest {
+                    current_password: "Passw0rd-Strong".to_string(),
+                    new_password: "New-Passw0rd-1".to_string(),
+                },
+            )
+            .await
+            .unwrap();
+
+        assert!(service.authenticate(&tokens.access_token).await.is_err());
+        assert!(service.refresh(&tokens.refresh_token).await.is_err());
+        assert!(
+            service
+                .login(login_request("Passw0rd-Strong"))
+                .await
+                .is_err()
+        );
+        assert!(service.login(login_request("New-Passw0rd-1")).await.is_ok());
+        assert!(notifier.sent_to("refresh@example.com").await.contains(
+            &Notification::PasswordChanged {
+                email: "refresh@example.com".to_string()
+            }
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_reset_password_with_emailed_token() {
+        let (service, notifier) = service_with_notifier();
+        let tokens = register_and_login(&service).await;
+
+        service
+            .request_password_reset("refresh@example.com")
+            .await
+            .unwrap();
+        let token = reset_token_sent_to(&notifier, "refresh@example.com").await;
+
+        service
+            .reset_password(ResetPasswordRequest {
+                token: token.clone(),
+                new_password: "Reset-Passw0rd-4".to_string(),
+            })
+            .await
+            .unwrap();
+
+        assert!(
+            service
+                .login(login_request("Reset-Passw0rd-4"))
+                .await
+                .is_ok()
+        );
+        assert!(service.refresh(&tokens.refresh_token).await.is_err());
+
+        // Reset tokens are single use
+        let reused = service
+            .reset_password(ResetPasswordRequest {
+                token,
+                new_password: "Another-Passw0rd-2".to_string(),
+            })
+            .await;
+        assert!(reused.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_reset_password_invalidates_older_reset_tokens() {
+        let (service, notifier) = service_with_notifier();
+        register_and_login(&service).await;
+
+        service
+            .request_password_reset("refresh@example.com")
+            .await
+            .unwrap();
+        let first = reset_token_sent_to(&notifier, "refresh@example.com").await;
+        service
+            .request_password_reset("refresh@example.com")
+            .await
+            .unwrap();
+        let second = reset_token_sent_to(&notifier, "refresh@example.com").await;
+
+        service
+            .reset_password(ResetPasswordRequest {
+                token: second,
+                new_password: "Reset-Passw0rd-4".to_string(),
+            })
+            .await
+            .unwrap();
+
+        let result = service
+            .reset_password(ResetPasswordRequest {
+                token: first,
+                new_password: "Attacker-Passw0rd-3".to_string(),
+            })
+            .await;
+        assert!(result.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_reset_password_rejects_expired_token() {
+        let reset_tokens = Arc::new(InMemoryPasswordResetTokenRepository::new());
+        let (service, _) = service_with_notifier();
+        let service = service.with_password_reset_token_repository(reset_tokens.clone());
+        register_and_login(&service).await;
+
+        reset_tokens
+            .save_reset_token(PasswordResetToken {
+                token_hash: hash_token("expired"),
+                user_id: "user".to_string(),
+                created_at: Utc::now() - Duration::hours(1),
+                expires_at: Utc::now() - Duration::seconds(1),
+            })
+            .await
+            .unwrap();
+
+        let result = service
+            .reset_password(ResetPasswordRequest {
+                token: "expired".to_string(),
+                new_password: "New-Passw0rd-1".to_string(),
+            })
+            .await;
+        assert!(result.is_err());
+    }
 
-        // Register user
-        let register_req = CreateUser {
-            email: "token@example.com".to_string(),
-            password: "password".to_string(),
-        };
-        let user = service.register_user(register_req).await.unwrap();
+    #[tokio::test]
+    async fn test_request_password_reset_for_unknown_email_is_silent() {
+        let (service, notifier) = service_with_notifier();
+
+        assert!(
+            service
+                .request_password_reset("nobody@example.com")
+                .await
+                .is_ok()
+        );
+        assert!(notifier.sent().await.is_empty());
+    }
 
-        // Login
-        let login_req = LoginRequest {
-            email: "token@example.com".to_string(),
-            password: "password".to_string(),
+    async fn verification_tokens_sent_to(notifier: &InMemoryNotifier, email: &str) -> Vec<String> {
+        notifier
+            .sent_to(email)
+            .await
+            .into_iter()
+            .filter_map(|n| match n {
+                Notification::EmailVerification { token, .. } => Some(token),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_registered_user_is_pending_until_email_is_verified() {
+        let (service, notifier) = service_with_notifier();
+        let user = service
+            .register_user(CreateUser {
+                email: "pending@example.com".to_string(),
+                password: "Passw0rd-Strong".to_string(),
+            })
+            .await
+            .unwrap();
+        assert_eq!(user.status, UserStatus::PendingVerification);
+        assert!(service.check_can_open_account(&user.id).await.is_err());
+
+        let tokens = verification_tokens_sent_to(&notifier, "pending@example.com").await;
+        assert_eq!(tokens.len(), 1);
+        assert_unauthorized(
+            service.verify_email("not-a-token").await,
+            "Invalid or expired verification token",
+        );
+
+        let verified = service.verify_email(&tokens[0]).await.unwrap();
+        assert_eq!(verified.status, UserStatus::Active);
+        assert!(service.check_can_open_account(&user.id).await.is_ok());
+        // Single use
+        assert!(service.verify_email(&tokens[0]).await.is_err());
+        assert!(service.resend_verification_email(&user.id).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_resend_verification_email_is_rate_limited() {
+        let (service, notifier) = service_with_notifier();
+        let user = service
+            .register_user(CreateUser {
+                email: "pending@example.com".to_string(),
+                password: "Passw0rd-Strong".to_string(),
+            })
+            .await
+            .unwrap();
+
+        // Registration has just sent one
+        match service
+            .resend_verification_email(&user.id)
+            .await
+            .unwrap_err()
+            .downcast::<DomainError>()
+        {
+            Ok(DomainError::TooManyAttempts(retry_after)) => {
+                assert!(retry_after > 0 && retry_after <= 60)
+            }
+            other => panic!("Expected TooManyAttempts error, got {:?}", other),
+        }
+        assert_eq!(
+            verification_tokens_sent_to(&notifier, "pending@example.com")
+                .await
+                .len(),
+            1
+        );
+    }
+
+    fn complete_profile() -> UpdateProfileRequest {
+        UpdateProfileRequest {
+            full_name: Some("Alice Smith".to_string()),
+            date_of_birth: chrono::NaiveDate::from_ymd_opt(1990, 5, 17),
+            phone: Some("+79161234567".to_string()),
+            address: Some(crate::domain::user::Address {
+                line1: "Tverskaya 1".to_string(),
+                line2: None,
+                city: "Moscow".to_string(),
+                postal_code: "125009".to_string(),
+                country: "RU".to_string(),
+            }),
+        }
+    }
+
+    fn kyc_request(status: KycStatus) -> UpdateKycStatusRequest {
+        UpdateKycStatusRequest {
+            status,
+            reason: "Passport checked".to_string(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_kyc_review_is_audited_and_locks_identity_fields() {
+        let (service, _) = service_with_notifier();
+        let user = service
+            .register_user(CreateUser {
+                email: "alice@example.com".to_string(),
+                password: "Passw0rd-Strong".to_string(),
+            })
+            .await
+            .unwrap();
+
+        // Nothing to check without a complete profile
+        assert!(
+            service
+                .set_kyc_status(&user.id, "operator-1", kyc_request(KycStatus::Pending))
+                .await
+                .is_err()
+        );
+        service
+            .update_profile(&user.id, complete_profile())
+            .await
+            .unwrap();
+        // Decisions are only taken on a review
+        assert!(
+            service
+                .set_kyc_status(&user.id, "operator-1", kyc_request(KycStatus::Verified))
+                .await
+                .is_err()
+        );
+        service
+            .set_kyc_status(&user.id, "operator-1", kyc_request(KycStatus::Pending))
+            .await
+            .unwrap();
+
+        let renamed = UpdateProfileRequest {
+            full_name: Some("Eve Smith".to_string()),
+            ..Default::default()
         };
-        let token = service.login(login_req).await.unwrap();
+        assert_eq!(
+            failed_rules(service.update_profile(&user.id, renamed).await),
+            vec![("full_name".to_string(), "locked".to_string())]
+        );
+        let new_phone = UpdateProfileRequest {
+            phone: Some("+79990000000".to_string()),
+            ..Default::default()
+        };
+        assert!(service.update_profile(&user.id, new_phone).await.is_ok());
+
+        let user = service
+            .set_kyc_status(&user.id, "operator-2", kyc_request(KycStatus::Verified))
+            .await
+            .unwrap();
+        assert_eq!(user.kyc_status, KycStatus::Verified);
+        let history = service.kyc_history(&user.id).await.unwrap();
+        let audit: Vec<_> = history
+            .iter()
+            .map(|t| (t.from, t.to, t.operator_id.as_str()))
+            .collect();
+        assert_eq!(
+            audit,
+            vec![
+                (KycStatus::Unverified, KycStatus::Pending, "operator-1"),
+                (KycStatus::Pending, KycStatus::Verified, "operator-2"),
+            ]
+        );
+    }
 
-        // Validate token
-        let extracted_user_id =
-            crate::infrastructure::security::validate_token(&token, &jwt_secret).unwrap();
-        assert_eq!(extracted_user_id, user.id);
+    #[tokio::test]
+    async fn test_customer_policy_follows_kyc_status() {
+        let (service, notifier) = service_with_notifier();
+        let service = service.with_unverified_transaction_limit(Amount::new(5000));
+        let user = service
+            .register_user(CreateUser {
+                email: "alice@example.com".to_string(),
+                password: "Passw0rd-Strong".to_string(),
+            })
+            .await
+            .unwrap();
+        let token = verification_tokens_sent_to(&notifier, "alice@example.com")
+            .await
+            .remove(0);
+        service.verify_email(&token).await.unwrap();
+        service
+            .update_profile(&user.id, complete_profile())
+            .await
+            .unwrap();
+
+        assert!(service.check_can_open_account(&user.id).await.is_ok());
+        assert_eq!(
+            service.transaction_limit(&user.id).await.unwrap(),
+            Some(Amount::new(5000))
+        );
+
+        service
+            .set_kyc_status(&user.id, "operator-1", kyc_request(KycStatus::Pending))
+            .await
+            .unwrap();
+        service
+            .set_kyc_status(&user.id, "operator-1", kyc_request(KycStatus::Verified))
+            .await
+            .unwrap();
+        assert_eq!(service.transaction_limit(&user.id).await.unwrap(), None);
+
+        service
+            .set_kyc_status(&user.id, "operator-1", kyc_request(KycStatus::Rejected))
+            .await
+            .unwrap();
+        assert!(matches!(
+            service
+                .check_can_open_account(&user.id)
+                .await
+                .unwrap_err()
+                .downcast::<DomainError>(),
+            Ok(DomainError::Forbidden(_))
+        ));
+    }
+
+    fn failed_rules(result: Result<User>) -> Vec<(String, String)> {
+        match result.unwrap_err().downcast::<DomainError>() {
+            Ok(DomainError::InvalidFields(errors)) => {
+                errors.into_iter().map(|e| (e.field, e.rule)).collect()
+            }
+            other => panic!("Expected InvalidFields error, got {:?}", other),
+        }
     }
 
     #[tokio::test]
-    async fn test_get_token_generates_token_for_existing_user() {
+    async fn test_register_user_reports_all_failed_rules() {
         let repo = Arc::new(InMemoryUserRepository::new());
-        let jwt_secret = "test_secret".to_string();
-        let service = AuthService::new(repo, jwt_secret.clone());
+        let service = AuthService::new(repo, "test_secret".to_string());
 
-        // Register user
-        let register_req = CreateUser {
-            email: "gettoken@example.com".to_string(),
-            password: "password".to_string(),
-        };
-        let user = service.register_user(register_req).await.unwrap();
+        let result = service
+            .register_user(CreateUser {
+                email: "not-an-email".to_string(),
+                password: "short".to_string(),
+            })
+            .await;
+
+        let rules = failed_rules(result);
+        assert!(rules.contains(&("email".to_string(), "format".to_string())));
+        assert!(rules.contains(&("password".to_string(), "min_length".to_string())));
+        assert!(rules.contains(&("password".to_string(), "uppercase".to_string())));
+        assert!(rules.contains(&("password".to_string(), "digit".to_string())));
+    }
 
-        // Get token
-        let token = service.get_token(&user.id).await.unwrap();
-        assert!(!token.is_empty());
+    #[tokio::test]
+    async fn test_register_user_rejects_breached_password() {
+        let repo = Arc::new(InMemoryUserRepository::new());
+        let service = AuthService::new(repo, "test_secret".to_string());
 
-        // Validate token
-        let extracted_user_id =
-            crate::infrastructure::security::validate_token(&token, &jwt_secret).unwrap();
-        assert_eq!(extracted_user_id, user.id);
+        let result = service
+            .register_user(CreateUser {
+                email: "breached@example.com".to_string(),
+                password: "Password123".to_string(),
+            })
+            .await;
+
+        assert_eq!(
+            failed_rules(result),
+            vec![("password".to_string(), "common".to_string())]
+        );
     }
 
     #[tokio::test]
-    async fn test_get_token_returns_error_for_nonexistent_user() {
+    async fn test_register_user_trims_email() {
+        let repo = Arc::new(InMemoryUserRepository::new());
+        let service = AuthService::new(repo, "test_secret".to_string());
+
+        let user = service
+            .register_user(CreateUser {
+                email: "  trimmed@example.com ".to_string(),
+                password: "Passw0rd-Strong".to_string(),
+            })
+            .await
+            .unwrap();
+
+        assert_eq!(user.email, "trimmed@example.com");
+    }
+
+    #[tokio::test]
+    async fn test_register_user_uses_configured_password_policy() {
+        let repo = Arc::new(InMemoryUserRepository::new());
+        let service = AuthService::new(repo, "test_secret".to_string()).with_password_policy(
+            PasswordPolicy {
+                min_length: 20,
+                ..PasswordPolicy::default()
+            },
+        );
+
+        let result = service
+            .register_user(CreateUser {
+                email: "policy@example.com".to_string(),
+                password: "Passw0rd-Strong".to_string(),
+            })
+            .await;
+
+        assert_eq!(
+            failed_rules(result),
+            vec![("password".to_string(), "min_length".to_string())]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_change_password_enforces_policy() {
+        let (service, _) = service_with_notifier();
+        let tokens = register_and_login(&service).await;
+        let user_id = service
+            .authenticate(&tokens.access_token)
+            .await
+            .unwrap()
+            .user_id;
+
+        let result = service
+            .change_password(
+                &user_id,
+                ChangePasswordRequest {
+                    current_password: "Passw0rd-Strong".to_string(),
+                    new_password: "weak".to_string(),
+                },
+            )
+            .await;
+
+        assert!(matches!(
+            result.unwrap_err().downcast::<DomainError>(),
+            Ok(DomainError::InvalidFields(_))
+        ));
+        // The session survives a rejected change
+        assert!(service.authenticate(&tokens.access_token).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_reset_password_with_weak_password_keeps_token() {
+        let (service, notifier) = service_with_notifier();
+        register_and_login(&service).await;
+        service
+            .request_password_reset("refresh@example.com")
+            .await
+            .unwrap();
+        let token = reset_token_sent_to(&notifier, "refresh@example.com").await;
+
+        let weak = service
+            .reset_password(ResetPasswordRequest {
+                token: token.clone(),
+                new_password: "weak".to_string(),
+            })
+            .await;
+        assert!(weak.is_err());
+
+        let strong = service
+            .reset_password(ResetPasswordRequest {
+                token,
+                new_password: "Reset-Passw0rd-9".to_string(),
+            })
+            .await;
+        assert!(strong.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_register_user_rejects_email_differing_only_in_case() {
         let repo = Arc::new(InMemoryUserRepository::new());
         let service = AuthService::new(repo, "test_secret".to_string());
+        service
+            .register_user(CreateUser {
+                email: "alice@x.com".to_string(),
+                password: "Passw0rd-Strong".to_string(),
+            })
+            .await
+            .unwrap();
+
+        let result = service
+            .register_user(CreateUser {
+                email: " Alice@X.COM".to_string(),
+                password: "Passw0rd-Strong".to_string(),
+            })
+            .await;
 
-        let result = service.get_token("nonexistent-user-id").await;
         assert!(result.is_err());
+    }
 
-        let error = result.unwrap_err();
-        if let Ok(domain_error) = error.downcast::<DomainError>() {
-            match domain_error {
-                DomainError::NotFound(msg) => {
-                    assert!(msg.contains("User not found"));
-                }
-                _ => panic!("Expected NotFound error"),
+    #[tokio::test]
+    async fn test_login_ignores_email_case_and_unicode_form() {
+        let repo = Arc::new(InMemoryUserRepository::new());
+        let service = AuthService::new(repo, "test_secret".to_string());
+        let user = service
+            .register_user(CreateUser {
+                email: "Alice@Example.com".to_string(),
+                password: "Passw0rd-Strong".to_string(),
+            })
+            .await
+            .unwrap();
+        assert_eq!(user.email, "Alice@example.com");
+
+        for email in [
+            "alice@example.com",
+            "ALICE@EXAMPLE.COM ",
+            "ａｌｉｃｅ@example.com",
+        ] {
+            let result = service
+                .login(LoginRequest {
+                    email: email.to_string(),
+                    password: "Passw0rd-Strong".to_string(),
+                })
+                .await;
+            assert!(result.is_ok(), "login as {} should succeed", email);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_migrate_email_identities_normalizes_and_reports_duplicates() {
+        let legacy = |id: &str, email: &str| User {
+            id: id.to_string(),
+            email: email.to_string(),
+            password_hash: "hash".to_string(),
+            role: Role::Customer,
+            status: UserStatus::Active,
+            profile: Profile::default(),
+            kyc_status: KycStatus::Unverified,
+        };
+        let repo = Arc::new(InMemoryUserRepository::with_users(vec![
+            legacy("user-1", "alice@x.com"),
+            legacy("user-2", "Alice@X.com"),
+            legacy("user-3", " Bob@Example.COM"),
+            legacy("user-4", "carol@example.com"),
+            legacy("user-5", "not-an-email"),
+        ]));
+        let service = AuthService::new(repo.clone(), "test_secret".to_string());
+
+        let report = service.migrate_email_identities().await.unwrap();
+
+        assert_eq!(report.normalized, vec!["user-3".to_string()]);
+        assert_eq!(
+            report.duplicates,
+            vec![DuplicateEmail {
+                email: "alice@x.com".to_string(),
+                user_ids: vec!["user-1".to_string(), "user-2".to_string()],
+            }]
+        );
+        assert_eq!(report.invalid, vec!["user-5".to_string()]);
+
+        let bob = repo.find_user_by_id("user-3").await.unwrap().unwrap();
+        assert_eq!(bob.email, "Bob@example.com");
+        // Duplicates are left as they were
+        let alice = repo.find_user_by_id("user-2").await.unwrap().unwrap();
+        assert_eq!(alice.email, "Alice@X.com");
+    }
+
+    fn throttled_service(policy: LoginThrottlePolicy) -> AuthService<InMemoryUserRepository> {
+        let repo = Arc::new(InMemoryUserRepository::new());
+        AuthService::new(repo, "test_secret".to_string()).with_login_throttle_policy(policy)
+    }
+
+    fn login_as(email: &str, password: &str) -> LoginRequest {
+        LoginRequest {
+            email: email.to_string(),
+            password: password.to_string(),
+        }
+    }
+
+    fn client_at(ip: &str) -> ClientInfo {
+        ClientInfo {
+            ip: Some(ip.to_string()),
+            user_agent: None,
+        }
+    }
+
+    fn assert_throttled<T: std::fmt::Debug>(result: Result<T>) -> u64 {
+        match result.unwrap_err().downcast::<DomainError>() {
+            Ok(DomainError::TooManyAttempts(retry_after)) => retry_after,
+            other => panic!("Expected TooManyAttempts error, got {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_login_backs_off_after_failed_attempts() {
+        let service = throttled_service(LoginThrottlePolicy {
+            free_attempts: 2,
+            base_delay: Duration::seconds(60),
+            ..LoginThrottlePolicy::default()
+        });
+        register_and_login(&service).await;
+
+        for _ in 0..2 {
+            assert_unauthorized(
+                service.login(login_request("Wrong-Passw0rd-1")).await,
+                "Invalid email or password",
+            );
+        }
+
+        // Refused even with the right password until the delay has passed
+        let retry_after = assert_throttled(service.login(login_request("Passw0rd-Strong")).await);
+        assert!(retry_after > 0 && retry_after <= 60);
+    }
+
+    #[tokio::test]
+    async fn test_login_locks_out_unknown_email_like_registered_one() {
+        let service = throttled_service(LoginThrottlePolicy {
+            free_attempts: 5,
+            lockout_threshold: 2,
+            ..LoginThrottlePolicy::default()
+        });
+        register_and_login(&service).await;
+
+        for email in ["refresh@example.com", "ghost@example.com"] {
+            for _ in 0..2 {
+                assert_unauthorized(
+                    service.login(login_as(email, "Wrong-Passw0rd-1")).await,
+                    "Invalid email or password",
+                );
             }
+            let retry_after =
+                assert_throttled(service.login(login_as(email, "Passw0rd-Strong")).await);
+            assert_eq!(retry_after, 15 * 60);
         }
     }
 
     #[tokio::test]
-    async fn test_multiple_users_can_register() {
+    async fn test_login_throttles_client_ip_across_emails() {
+        let service = throttled_service(LoginThrottlePolicy {
+            ip_free_attempts: 2,
+            base_delay: Duration::seconds(60),
+            ..LoginThrottlePolicy::default()
+        });
+        register_and_login(&service).await;
+
+        for email in ["a@example.com", "b@example.com"] {
+            assert!(
+                service
+                    .login_from(login_as(email, "Wrong-Passw0rd-1"), &client_at("10.0.0.1"))
+                    .await
+                    .is_err()
+            );
+        }
+
+        assert_throttled(
+            service
+                .login_from(login_request("Passw0rd-Strong"), &client_at("10.0.0.1"))
+                .await,
+        );
+        assert!(
+            service
+                .login_from(login_request("Passw0rd-Strong"), &client_at("10.0.0.2"))
+                .await
+                .is_ok()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_successful_login_resets_email_failures() {
+        let service = throttled_service(LoginThrottlePolicy {
+            free_attempts: 2,
+            base_delay: Duration::seconds(60),
+            ..LoginThrottlePolicy::default()
+        });
+        register_and_login(&service).await;
+
+        assert!(
+            service
+                .login(login_request("Wrong-Passw0rd-1"))
+                .await
+                .is_err()
+        );
+        assert!(
+            service
+                .login(login_request("Passw0rd-Strong"))
+                .await
+                .is_ok()
+        );
+        assert!(
+            service
+                .login(login_request("Wrong-Passw0rd-1"))
+                .await
+                .is_err()
+        );
+
+        assert!(
+            service
+                .login(login_request("Passw0rd-Strong"))
+                .await
+                .is_ok()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_unlock_login_lifts_lockout() {
+        let service = throttled_service(LoginThrottlePolicy {
+            lockout_threshold: 1,
+            ..LoginThrottlePolicy::default()
+        });
+        register_and_login(&service).await;
+        assert!(
+            service
+                .login(login_request("Wrong-Passw0rd-1"))
+                .await
+                .is_err()
+        );
+        assert_throttled(service.login(login_request("Passw0rd-Strong")).await);
+
+        service.unlock_login("Refresh@Example.com").await.unwrap();
+        assert!(
+            service
+                .login(login_request("Passw0rd-Strong"))
+                .await
+                .is_ok()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_change_password_shares_login_throttle() {
+        let service = throttled_service(LoginThrottlePolicy {
+            free_attempts: 2,
+            base_delay: Duration::seconds(60),
+            ..LoginThrottlePolicy::default()
+        });
+        let tokens = register_and_login(&service).await;
+        let user_id = service
+            .authenticate(&tokens.access_token)
+            .await
+            .unwrap()
+            .user_id;
+        let change = |current: &str| ChangePasswordRequest {
+            current_password: current.to_string(),
+            new_password: "New-Passw0rd-1".to_string(),
+        };
+
+        for _ in 0..2 {
+            assert_unauthorized(
+                service
+                    .change_password(&user_id, change("Wrong-Passw0rd-1"))
+                    .await,
+                "Invalid current password",
+            );
+        }
+
+        // The session cannot be used to keep guessing, nor can the login form
+        assert_throttled(
+            service
+                .change_password(&user_id, change("Passw0rd-Strong"))
+                .await,
+        );
+        assert_throttled(service.login(login_request("Passw0rd-Strong")).await);
+    }
+
+    async fn enable_two_factor(
+        service: &AuthService<InMemoryUserRepository>,
+    ) -> (String, String, Vec<String>) {
+        let user = service
+            .user_repository
+            .find_user_by_email("refresh@example.com")
+            .await
+            .unwrap()
+            .unwrap();
+        let enrollment = service.enroll_two_factor(&user.id).await.unwrap();
+        let code = current_totp_code(&enrollment.secret, 0);
+        let recovery_codes = service.activate_two_factor(&user.id, &code).await.unwrap();
+        (enrollment.secret, code, recovery_codes)
+    }
+
+    // Code of the step `offset` steps from now; tests use later steps for later codes
+    // because a step is accepted only once
+    fn current_totp_code(secret: &str, offset: i64) -> String {
+        let step = crate::infrastructure::totp::totp_step(Utc::now().timestamp()) + offset;
+        crate::infrastructure::totp::totp_code(secret, step).unwrap()
+    }
+
+    fn challenge_token(outcome: LoginOutcome) -> String {
+        match outcome {
+            LoginOutcome::TwoFactorRequired {
+                challenge_token, ..
+            } => challenge_token,
+            LoginOutcome::Authenticated(_) => panic!("Expected a two-factor challenge"),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_enroll_two_factor_returns_otpauth_uri() {
         let repo = Arc::new(InMemoryUserRepository::new());
         let service = AuthService::new(repo, "test_secret".to_string());
+        register_and_login(&service).await;
+        let user = service
+            .user_repository
+            .find_user_by_email("refresh@example.com")
+            .await
+            .unwrap()
+            .unwrap();
 
-        let req1 = CreateUser {
-            email: "user1@example.com".to_string(),
-            password: "pass1".to_string(),
-        };
-        let req2 = CreateUser {
-            email: "user2@example.com".to_string(),
-            password: "pass2".to_string(),
-        };
+        let enrollment = service.enroll_two_factor(&user.id).await.unwrap();
 
-        let user1 = service.register_user(req1).await.unwrap();
-        let user2 = service.register_user(req2).await.unwrap();
+        assert!(
+            enrollment
+                .otpauth_uri
+                .starts_with("otpauth://totp/Yandex%20Bank:refresh@example.com?secret=")
+        );
+        assert!(enrollment.otpauth_uri.contains(&enrollment.secret));
+        // Not active before a code is verified
+        assert!(
+            service
+                .login(login_request("Passw0rd-Strong"))
+                .await
+                .unwrap()
+                .into_tokens()
+                .is_some()
+        );
+    }
 
-        assert_ne!(user1.id, user2.id);
-        assert_ne!(user1.email, user2.email);
+    #[tokio::test]
+    async fn test_activate_two_factor_rejects_wrong_code() {
+        let repo = Arc::new(InMemoryUserRepository::new());
+        let service = AuthService::new(repo, "test_secret".to_string());
+        register_and_login(&service).await;
+        let user_id = service
+            .user_repository
+            .find_user_by_email("refresh@example.com")
+            .await
+            .unwrap()
+            .unwrap()
+            .id;
+        service.enroll_two_factor(&user_id).await.unwrap();
+
+        assert_unauthorized(
+            service.activate_two_factor(&user_id, "000000x").await,
+            "Invalid two-factor code",
+        );
+        assert!(!service.two_factor_enabled(&user_id).await.unwrap());
     }
 
     #[tokio::test]
-    async fn test_login_with_different_passwords() {
+    async fn test_login_with_two_factor_requires_code() {
         let repo = Arc::new(InMemoryUserRepository::new());
         let service = AuthService::new(repo, "test_secret".to_string());
+        register_and_login(&service).await;
+        let (secret, activation_code, recovery_codes) = enable_two_factor(&service).await;
+        assert_eq!(recovery_codes.len(), RECOVERY_CODE_COUNT);
+
+        let outcome = service
+            .login(login_request("Passw0rd-Strong"))
+            .await
+            .unwrap();
+        let challenge = challenge_token(outcome);
+
+        assert_unauthorized(
+            service.complete_two_factor_login(&challenge, "123").await,
+            "Invalid two-factor code",
+        );
+        // The code used for activation cannot be replayed
+        assert_unauthorized(
+            service
+                .complete_two_factor_login(&challenge, &activation_code)
+                .await,
+            "Invalid two-factor code",
+        );
 
-        // Register with password1
-        let register_req = CreateUser {
-            email: "multipass@example.com".to_string(),
-            password: "password1".to_string(),
-        };
-        service.register_user(register_req).await.unwrap();
+        let tokens = service
+            .complete_two_factor_login(&challenge, &current_totp_code(&secret, 1))
+            .await
+            .unwrap();
+        assert!(service.authenticate(&tokens.access_token).await.is_ok());
+
+        // A challenge is single use
+        assert_unauthorized(
+            service
+                .complete_two_factor_login(&challenge, &recovery_codes[0])
+                .await,
+            "Invalid or expired login challenge",
+        );
+    }
 
-        // Login with correct password should work
-        let login_req1 = LoginRequest {
-            email: "multipass@example.com".to_string(),
-            password: "password1".to_string(),
-        };
-        assert!(service.login(login_req1).await.is_ok());
+    #[tokio::test]
+    async fn test_recovery_codes_are_single_use() {
+        let repo = Arc::new(InMemoryUserRepository::new());
+        let service = AuthService::new(repo, "test_secret".to_string());
+        register_and_login(&service).await;
+        let (_, _, recovery_codes) = enable_two_factor(&service).await;
+
+        let challenge = challenge_token(
+            service
+                .login(login_request("Passw0rd-Strong"))
+                .await
+                .unwrap(),
+        );
+        // Recovery codes are accepted regardless of case and surrounding whitespace
+        let code = format!(" {} ", recovery_codes[0].to_uppercase());
+        assert!(
+            service
+                .complete_two_factor_login(&challenge, &code)
+                .await
+                .is_ok()
+        );
 
-        // Login with wrong password should fail
-        let login_req2 = LoginRequest {
-            email: "multipass@example.com".to_string(),
-            password: "password2".to_string(),
+        let challenge = challenge_token(
+            service
+                .login(login_request("Passw0rd-Strong"))
+                .await
+                .unwrap(),
+        );
+        assert_unauthorized(
+            service
+                .complete_two_factor_login(&challenge, &recovery_codes[0])
+                .await,
+            "Invalid two-factor code",
+        );
+        assert!(
+            service
+                .complete_two_factor_login(&challenge, &recovery_codes[1])
+                .await
+                .is_ok()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_login_challenge_discarded_after_failed_codes() {
+        let repo = Arc::new(InMemoryUserRepository::new());
+        let service = AuthService::new(repo, "test_secret".to_string()).with_login_throttle_policy(
+            LoginThrottlePolicy {
+                free_attempts: 100,
+                lockout_threshold: 100,
+                ..LoginThrottlePolicy::default()
+            },
+        );
+        register_and_login(&service).await;
+        let (secret, _, _) = enable_two_factor(&service).await;
+        let challenge = challenge_token(
+            service
+                .login(login_request("Passw0rd-Strong"))
+                .await
+                .unwrap(),
+        );
+
+        for _ in 0..LOGIN_CHALLENGE_MAX_ATTEMPTS {
+            assert!(
+                service
+                    .complete_two_factor_login(&challenge, "bad-code")
+                    .await
+                    .is_err()
+            );
+        }
+
+        assert_unauthorized(
+            service
+                .complete_two_factor_login(&challenge, &current_totp_code(&secret, 1))
+                .await,
+            "Invalid or expired login challenge",
+        );
+    }
+
+    #[tokio::test]
+    async fn test_authorize_transfer_requires_step_up_above_threshold() {
+        let repo = Arc::new(InMemoryUserRepository::new());
+        let service = AuthService::new(repo, "test_secret".to_string())
+            .with_step_up_threshold(Some(Amount::new(1000)));
+        register_and_login(&service).await;
+        let user_id = service
+            .user_repository
+            .find_user_by_email("refresh@example.com")
+            .await
+            .unwrap()
+            .unwrap()
+            .id;
+        let assert_forbidden = |result: Result<()>, expected: &str| match result
+            .unwrap_err()
+            .downcast::<DomainError>()
+        {
+            Ok(DomainError::Forbidden(msg)) => assert!(msg.contains(expected), "{}", msg),
+            other => panic!("Expected Forbidden error, got {:?}", other),
         };
-        assert!(service.login(login_req2).await.is_err());
+
+        // Up to the threshold no second factor is needed
+        assert!(
+            service
+                .authorize_transfer(&user_id, Amount::new(1000), None)
+                .await
+                .is_ok()
+        );
+        assert_forbidden(
+            service
+                .authorize_transfer(&user_id, Amount::new(1001), None)
+                .await,
+            "require two-factor authentication",
+        );
+
+        let (secret, _, _) = enable_two_factor(&service).await;
+        assert_forbidden(
+            service
+                .authorize_transfer(&user_id, Amount::new(1001), None)
+                .await,
+            "Two-factor code required",
+        );
+        assert_forbidden(
+            service
+                .authorize_transfer(&user_id, Amount::new(1001), Some("000000"))
+                .await,
+            "Invalid two-factor code",
+        );
+        assert!(
+            service
+                .authorize_transfer(
+                    &user_id,
+                    Amount::new(1001),
+                    Some(&current_totp_code(&secret, 1))
+                )
+                .await
+                .is_ok()
+        );
+    }
+
+    async fn registered_user_id(service: &AuthService<InMemoryUserRepository>) -> String {
+        let tokens = register_and_login(service).await;
+        service
+            .authenticate(&tokens.access_token)
+            .await
+            .unwrap()
+            .user_id
+    }
+
+    #[tokio::test]
+    async fn test_delete_user_drops_reset_tokens() {
+        let (service, notifier) = service_with_notifier();
+        let user_id = registered_user_id(&service).await;
+        service
+            .request_password_reset("refresh@example.com")
+            .await
+            .unwrap();
+        let token = reset_token_sent_to(&notifier, "refresh@example.com").await;
+
+        service.delete_user(&user_id).await.unwrap();
+
+        assert!(
+            service
+                .reset_token_repository
+                .take_reset_token(&hash_token(&token))
+                .await
+                .unwrap()
+                .is_none()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_delete_user_erases_two_factor_secret() {
+        let (service, _) = service_with_notifier();
+        let user_id = registered_user_id(&service).await;
+        enable_two_factor(&service).await;
+
+        service.delete_user(&user_id).await.unwrap();
+
+        assert!(
+            service
+                .two_factor_repository
+                .find_two_factor(&user_id)
+                .await
+                .unwrap()
+                .is_none()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_delete_user_unlinks_external_identities() {
+        let (service, _) = service_with_notifier();
+        let user_id = registered_user_id(&service).await;
+        service
+            .oidc_repository
+            .link_identity(ExternalIdentity {
+                issuer: "https://idp.example.com".to_string(),
+                subject: "employee-1".to_string(),
+                user_id: user_id.clone(),
+                linked_at: Utc::now(),
+            })
+            .await
+            .unwrap();
+
+        service.delete_user(&user_id).await.unwrap();
+
+        assert!(
+            service
+                .oidc_repository
+                .find_identity("https://idp.example.com", "employee-1")
+                .await
+                .unwrap()
+                .is_none()
+        );
     }
 }
@@ -1,28 +1,148 @@
-use crate::domain::error::DomainError;
-use crate::domain::models::{Account, Amount, CreateAccount, Transfer};
-use crate::domain::repository::AccountRepository;
+use crate::data::beneficiary_repository::InMemoryBeneficiaryRepository;
+use crate::data::p2p_repository::InMemoryClaimableTransferRepository;
+use crate::data::payment_request_repository::InMemoryPaymentRequestRepository;
+use crate::data::reversal_repository::InMemoryTransferReversalRepository;
+use crate::domain::beneficiary::{Beneficiary, CreateBeneficiary, UpdateBeneficiary};
+use crate::domain::error::{DomainError, FieldError};
+use crate::domain::models::{
+    Account, AccountExport, AccountHolder, AccountStatement, Amount, BatchMode, BatchRowResult,
+    BatchRowStatus, BatchTransferReport, BatchTransferRow, CreateAccount, HolderPermission,
+    Transaction, TransactionDetails, TransactionKind, Transfer, TransferReceipt,
+};
+use crate::domain::p2p::{ClaimStatus, ClaimableTransfer, P2pPayment, PayByEmail};
+use crate::domain::payment_request::{CreatePaymentRequest, PaymentRequest, PaymentRequestStatus};
+use crate::domain::repository::{
+    AccountRepository, BeneficiaryRepository, ClaimableTransferRepository,
+    PaymentRequestRepository, TransferReversalRepository,
+};
+use crate::domain::reversal::{
+    REVERSAL_OF_METADATA_KEY, ReverseTransfer, ShortfallPolicy, TransferReversal,
+};
+use crate::domain::user::CustomerPolicy;
+use crate::domain::validation::{
+    email_identity, normalize_email, validate_beneficiary, validate_payment_request,
+    validate_transaction_details,
+};
 use anyhow::Result;
+use chrono::{DateTime, Duration, Utc};
+use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
-use tracing::{debug, info, instrument, trace, warn};
+use tracing::{debug, error, info, instrument, trace, warn};
+use uuid::Uuid;
+
+// Upper bound on the number of rows accepted in a single batch
+pub const MAX_BATCH_SIZE: usize = 1000;
+// Time the recipient of a payment by email has to claim it before it is refunded
+const DEFAULT_CLAIM_TTL_DAYS: i64 = 14;
+// Transfers to a beneficiary above the threshold wait this long after it was saved
+const DEFAULT_PAYEE_COOLING_OFF_HOURS: i64 = 24;
+const DEFAULT_PAYEE_COOLING_OFF_THRESHOLD: u64 = 500_000;
 
 pub struct BankService<R: AccountRepository> {
     repository: Arc<R>,
+    // `None` lets every user open accounts and move any amount
+    customer_policy: Option<Arc<dyn CustomerPolicy>>,
+    claimable_transfer_repository: Arc<dyn ClaimableTransferRepository>,
+    claim_ttl: Duration,
+    beneficiary_repository: Arc<dyn BeneficiaryRepository>,
+    payee_cooling_off: Duration,
+    payee_cooling_off_threshold: Amount,
+    payment_request_repository: Arc<dyn PaymentRequestRepository>,
+    reversal_repository: Arc<dyn TransferReversalRepository>,
+    shortfall_policy: ShortfallPolicy,
 }
 
 impl<R: AccountRepository> BankService<R> {
     pub fn new(repository: Arc<R>) -> Self {
-        Self { repository }
+        Self {
+            repository,
+            customer_policy: None,
+            claimable_transfer_repository: Arc::new(InMemoryClaimableTransferRepository::new()),
+            claim_ttl: Duration::days(DEFAULT_CLAIM_TTL_DAYS),
+            beneficiary_repository: Arc::new(InMemoryBeneficiaryRepository::new()),
+            payee_cooling_off: Duration::hours(DEFAULT_PAYEE_COOLING_OFF_HOURS),
+            payee_cooling_off_threshold: Amount::new(DEFAULT_PAYEE_COOLING_OFF_THRESHOLD),
+            payment_request_repository: Arc::new(InMemoryPaymentRequestRepository::new()),
+            reversal_repository: Arc::new(InMemoryTransferReversalRepository::new()),
+            shortfall_policy: ShortfallPolicy::default(),
+        }
+    }
+
+    pub fn with_customer_policy(mut self, policy: Arc<dyn CustomerPolicy>) -> Self {
+        self.customer_policy = Some(policy);
+        self
+    }
+
+    pub fn with_claimable_transfer_repository(
+        mut self,
+        claimable_transfer_repository: Arc<dyn ClaimableTransferRepository>,
+    ) -> Self {
+        self.claimable_transfer_repository = claimable_transfer_repository;
+        self
+    }
+
+    pub fn with_claim_ttl(mut self, claim_ttl: Duration) -> Self {
+        self.claim_ttl = claim_ttl;
+        self
+    }
+
+    pub fn with_beneficiary_repository(
+        mut self,
+        beneficiary_repository: Arc<dyn BeneficiaryRepository>,
+    ) -> Self {
+        self.beneficiary_repository = beneficiary_repository;
+        self
+    }
+
+    // Transfers above the threshold to a beneficiary saved less than `period` ago are refused
+    pub fn with_payee_cooling_off(mut self, period: Duration) -> Self {
+        self.payee_cooling_off = period;
+        self
+    }
+
+    pub fn with_payee_cooling_off_threshold(mut self, threshold: Amount) -> Self {
+        self.payee_cooling_off_threshold = threshold;
+        self
+    }
+
+    pub fn with_payment_request_repository(
+        mut self,
+        payment_request_repository: Arc<dyn PaymentRequestRepository>,
+    ) -> Self {
+        self.payment_request_repository = payment_request_repository;
+        self
+    }
+
+    pub fn with_reversal_repository(
+        mut self,
+        reversal_repository: Arc<dyn TransferReversalRepository>,
+    ) -> Self {
+        self.reversal_repository = reversal_repository;
+        self
+    }
+
+    // What a reversal does when the recipient no longer has the money
+    pub fn with_shortfall_policy(mut self, policy: ShortfallPolicy) -> Self {
+        self.shortfall_policy = policy;
+        self
     }
 
     #[instrument(skip(self), fields(name = %req.name))]
-    pub async fn create_account(&self, req: CreateAccount) -> Result<Account> {
+    pub async fn create_account(&self, user_id: &str, req: CreateAccount) -> Result<Account> {
         trace!("Starting account creation");
-        let id = fastrand::u32(..); // Simple ID generation
+        if let Some(policy) = &self.customer_policy {
+            policy.check_can_open_account(user_id).await?;
+        }
+        // Simple ID generation; 0 is left out, it stands for "no account" in `Transfer`
+        let id = fastrand::u32(1..);
         debug!(account_id = id, "Generated account ID");
         let account = Account {
             id,
             name: req.name,
             balance: Amount::new(0),
+            owner_id: user_id.to_string(),
+            holders: Vec::new(),
+            closed: false,
         };
         trace!(account_id = account.id, "Saving account to repository");
         self.repository.save(account.clone()).await?;
@@ -36,7 +156,48 @@ impl<R: AccountRepository> BankService<R> {
     }
 
     #[instrument(skip(self), fields(account_id = id))]
-    pub async fn get_account(&self, id: u32) -> Result<Account> {
+    pub async fn get_account(&self, user_id: &str, id: u32) -> Result<Account> {
+        self.account_for(user_id, id, |_| true).await
+    }
+
+    // Any account, whoever holds it; for the back office
+    #[instrument(skip(self), fields(account_id = id))]
+    pub async fn get_any_account(&self, id: u32) -> Result<Account> {
+        self.find_account(id).await
+    }
+
+    // The account, if the user's permission on it passes `allowed`. Users without any access
+    // get `AccountNotFound`, so that accounts of others cannot be probed.
+    async fn account_for(
+        &self,
+        user_id: &str,
+        id: u32,
+        allowed: impl Fn(&HolderPermission) -> bool,
+    ) -> Result<Account> {
+        let account = self.find_account(id).await?;
+        match account.permission_of(user_id) {
+            None => {
+                warn!(
+                    account_id = id,
+                    user_id = user_id,
+                    "Account accessed by a non-holder"
+                );
+                Err(DomainError::AccountNotFound.into())
+            }
+            Some(permission) if !allowed(&permission) => {
+                warn!(
+                    account_id = id,
+                    user_id = user_id,
+                    permission = ?permission,
+                    "Operation not allowed by holder permission"
+                );
+                Err(operation_not_allowed().into())
+            }
+            Some(_) => Ok(account),
+        }
+    }
+
+    async fn find_account(&self, id: u32) -> Result<Account> {
         trace!("Fetching account from repository");
         match self.repository.find_by_id(id).await? {
             Some(account) => {
@@ -55,9 +216,19 @@ impl<R: AccountRepository> BankService<R> {
     }
 
     #[instrument(skip(self), fields(account_id = id, amount = amount.inner()))]
-    pub async fn deposit(&self, id: u32, amount: Amount) -> Result<Account> {
+    pub async fn deposit(
+        &self,
+        user_id: &str,
+        id: u32,
+        amount: Amount,
+        details: TransactionDetails,
+    ) -> Result<Account> {
         trace!("Starting deposit operation");
-        let mut account = self.get_account(id).await?;
+        check_details(&details)?;
+        let mut account = self
+            .account_for(user_id, id, HolderPermission::can_deposit)
+            .await?;
+        check_open(&account)?;
         let old_balance = account.balance.inner();
         let deposit_amount = amount.inner();
         debug!(
@@ -74,6 +245,15 @@ impl<R: AccountRepository> BankService<R> {
             "Updating account"
         );
         self.repository.update(account.clone()).await?;
+        self.record_transaction(
+            &account,
+            TransactionKind::Deposit,
+            amount,
+            None,
+            None,
+            &details,
+        )
+        .await?;
         info!(
             account_id = account.id,
             old_balance = old_balance,
@@ -85,9 +265,20 @@ impl<R: AccountRepository> BankService<R> {
     }
 
     #[instrument(skip(self), fields(account_id = id, amount = amount.inner()))]
-    pub async fn withdraw(&self, id: u32, amount: Amount) -> Result<Account> {
+    pub async fn withdraw(
+        &self,
+        user_id: &str,
+        id: u32,
+        amount: Amount,
+        details: TransactionDetails,
+    ) -> Result<Account> {
         trace!("Starting withdrawal operation");
-        let mut account = self.get_account(id).await?;
+        check_details(&details)?;
+        self.check_transaction_limit(user_id, amount).await?;
+        let mut account = self
+            .account_for(user_id, id, |p| p.can_debit(amount))
+            .await?;
+        check_open(&account)?;
         let current_balance = account.balance.inner();
         let withdrawal_amount = amount.inner();
         debug!(
@@ -113,6 +304,15 @@ impl<R: AccountRepository> BankService<R> {
             "Updating account"
         );
         self.repository.update(account.clone()).await?;
+        self.record_transaction(
+            &account,
+            TransactionKind::Withdrawal,
+            amount,
+            None,
+            None,
+            &details,
+        )
+        .await?;
         info!(
             account_id = account.id,
             old_balance = current_balance,
@@ -128,7 +328,33 @@ impl<R: AccountRepository> BankService<R> {
         to_account_id = req.to_account_id,
         amount = req.amount.inner()
     ))]
-    pub async fn transfer(&self, req: Transfer) -> Result<()> {
+    pub async fn transfer(&self, user_id: &str, req: Transfer) -> Result<TransferReceipt> {
+        check_details(&req.details)?;
+        let req = self.resolve_beneficiary(user_id, req).await?;
+        self.check_transaction_limit(user_id, req.amount).await?;
+        self.account_for(user_id, req.from_account_id, |p| p.can_debit(req.amount))
+            .await?;
+        let mut receipt = self.execute_transfer(req).await?;
+        // The recipient's balance is shown to holders of the receiving account only
+        let to_account = self.find_account(receipt.to_account_id).await?;
+        if to_account.permission_of(user_id).is_none() {
+            receipt.to_balance = None;
+        }
+        Ok(receipt)
+    }
+
+    // Moves the money without consulting the customer policy. The receipt carries both
+    // resulting balances.
+    async fn execute_transfer(&self, req: Transfer) -> Result<TransferReceipt> {
+        self.execute_transfer_as(Uuid::new_v4().to_string(), req)
+            .await
+    }
+
+    async fn execute_transfer_as(
+        &self,
+        transfer_id: String,
+        req: Transfer,
+    ) -> Result<TransferReceipt> {
         trace!("Starting transfer operation");
         if req.from_account_id == req.to_account_id {
             warn!(
@@ -147,12 +373,14 @@ impl<R: AccountRepository> BankService<R> {
             from_account_id = req.from_account_id,
             "Fetching source account"
         );
-        let mut from_account = self.get_account(req.from_account_id).await?;
+        let mut from_account = self.find_account(req.from_account_id).await?;
         debug!(
             to_account_id = req.to_account_id,
             "Fetching destination account"
         );
-        let mut to_account = self.get_account(req.to_account_id).await?;
+        let mut to_account = self.find_account(req.to_account_id).await?;
+        check_open(&from_account)?;
+        check_open(&to_account)?;
 
         let transfer_amount = req.amount.inner();
         let from_balance = from_account.balance.inner();
@@ -175,40 +403,1375 @@ impl<R: AccountRepository> BankService<R> {
             return Err(DomainError::InsufficientFunds.into());
         }
 
-        let new_from_balance = from_balance - transfer_amount;
-        from_account.balance = Amount::new(new_from_balance);
+        let new_from_balance = from_balance - transfer_amount;
+        from_account.balance = Amount::new(new_from_balance);
+
+        let new_to_balance = to_balance + transfer_amount;
+        to_account.balance = Amount::new(new_to_balance);
+
+        trace!(
+            from_account_id = from_account.id,
+            new_from_balance = new_from_balance,
+            "Updating source account"
+        );
+        self.repository.update(from_account.clone()).await?;
+        trace!(
+            to_account_id = to_account.id,
+            new_to_balance = new_to_balance,
+            "Updating destination account"
+        );
+        self.repository.update(to_account.clone()).await?;
+
+        self.record_transaction(
+            &from_account,
+            TransactionKind::TransferOut,
+            req.amount,
+            Some(to_account.id),
+            Some(&transfer_id),
+            &req.details,
+        )
+        .await?;
+        self.record_transaction(
+            &to_account,
+            TransactionKind::TransferIn,
+            req.amount,
+            Some(from_account.id),
+            Some(&transfer_id),
+            &req.details,
+        )
+        .await?;
+
+        info!(
+            transfer_id = %transfer_id,
+            from_account_id = req.from_account_id,
+            to_account_id = req.to_account_id,
+            transfer_amount = transfer_amount,
+            "Transfer completed successfully"
+        );
+        Ok(TransferReceipt {
+            transfer_id,
+            from_account_id: from_account.id,
+            to_account_id: to_account.id,
+            amount: req.amount,
+            details: req.details,
+            created_at: Utc::now(),
+            from_balance: Some(from_account.balance),
+            to_balance: Some(to_account.balance),
+        })
+    }
+
+    // Points a transfer naming a beneficiary at the beneficiary's account, once the
+    // cooling-off period allows the amount
+    async fn resolve_beneficiary(&self, user_id: &str, mut req: Transfer) -> Result<Transfer> {
+        let Some(beneficiary_id) = &req.beneficiary_id else {
+            return Ok(req);
+        };
+        let beneficiary = self.get_beneficiary(user_id, beneficiary_id).await?;
+        if req.to_account_id != 0 && req.to_account_id != beneficiary.account_id {
+            return Err(DomainError::Validation(
+                "to_account_id does not match the beneficiary's account".to_string(),
+            )
+            .into());
+        }
+
+        let cooling_off_ends = beneficiary.created_at + self.payee_cooling_off;
+        if req.amount > self.payee_cooling_off_threshold && Utc::now() < cooling_off_ends {
+            warn!(
+                beneficiary_id = %beneficiary.id,
+                cooling_off_ends = %cooling_off_ends,
+                "Large transfer to a new beneficiary refused"
+            );
+            return Err(DomainError::Forbidden(format!(
+                "Transfers above {} to a newly added beneficiary are possible from {}",
+                self.payee_cooling_off_threshold.inner(),
+                cooling_off_ends.to_rfc3339()
+            ))
+            .into());
+        }
+        req.to_account_id = beneficiary.account_id;
+        Ok(req)
+    }
+
+    #[instrument(skip(self, req), fields(account_id = req.account_id))]
+    pub async fn add_beneficiary(
+        &self,
+        user_id: &str,
+        req: CreateBeneficiary,
+    ) -> Result<Beneficiary> {
+        let nickname = req.nickname.filter(|n| !n.trim().is_empty());
+        let errors = validate_beneficiary(Some(&req.name), nickname.as_deref());
+        if !errors.is_empty() {
+            return Err(DomainError::InvalidFields(errors).into());
+        }
+        self.find_account(req.account_id).await?;
+        if let Some(saved) = self
+            .list_beneficiaries(user_id)
+            .await?
+            .into_iter()
+            .find(|b| b.account_id == req.account_id)
+        {
+            return Err(DomainError::Validation(format!(
+                "The account is already saved as beneficiary {}",
+                saved.id
+            ))
+            .into());
+        }
+
+        let beneficiary = Beneficiary {
+            id: Uuid::new_v4().to_string(),
+            user_id: user_id.to_string(),
+            name: req.name.trim().to_string(),
+            account_id: req.account_id,
+            nickname: nickname.map(|n| n.trim().to_string()),
+            created_at: Utc::now(),
+        };
+        self.beneficiary_repository
+            .save_beneficiary(beneficiary.clone())
+            .await?;
+        info!(beneficiary_id = %beneficiary.id, "Beneficiary added");
+        Ok(beneficiary)
+    }
+
+    #[instrument(skip(self))]
+    pub async fn list_beneficiaries(&self, user_id: &str) -> Result<Vec<Beneficiary>> {
+        self.beneficiary_repository
+            .list_beneficiaries(user_id)
+            .await
+    }
+
+    #[instrument(skip(self))]
+    pub async fn get_beneficiary(&self, user_id: &str, id: &str) -> Result<Beneficiary> {
+        self.beneficiary_repository
+            .find_beneficiary(user_id, id)
+            .await?
+            .ok_or_else(|| DomainError::NotFound(format!("Beneficiary not found: {}", id)).into())
+    }
+
+    #[instrument(skip(self, req))]
+    pub async fn update_beneficiary(
+        &self,
+        user_id: &str,
+        id: &str,
+        req: UpdateBeneficiary,
+    ) -> Result<Beneficiary> {
+        let errors = validate_beneficiary(req.name.as_deref(), req.nickname.as_deref());
+        if !errors.is_empty() {
+            return Err(DomainError::InvalidFields(errors).into());
+        }
+        let mut beneficiary = self.get_beneficiary(user_id, id).await?;
+        if let Some(name) = req.name {
+            beneficiary.name = name.trim().to_string();
+        }
+        if let Some(nickname) = req.nickname {
+            let nickname = nickname.trim();
+            beneficiary.nickname = (!nickname.is_empty()).then(|| nickname.to_string());
+        }
+        self.beneficiary_repository
+            .save_beneficiary(beneficiary.clone())
+            .await?;
+        info!(beneficiary_id = id, "Beneficiary updated");
+        Ok(beneficiary)
+    }
+
+    #[instrument(skip(self))]
+    pub async fn delete_beneficiary(&self, user_id: &str, id: &str) -> Result<()> {
+        if !self
+            .beneficiary_repository
+            .delete_beneficiary(user_id, id)
+            .await?
+        {
+            return Err(DomainError::NotFound(format!("Beneficiary not found: {}", id)).into());
+        }
+        info!(beneficiary_id = id, "Beneficiary deleted");
+        Ok(())
+    }
+
+    // Pays the owner of `req.email`, `payee_id` if they are a user. The money goes to the
+    // payee's default account, or is held as a claimable transfer if they have none.
+    #[instrument(skip(self, req), fields(
+        from_account_id = req.from_account_id,
+        amount = req.amount.inner()
+    ))]
+    pub async fn pay_by_email(
+        &self,
+        user_id: &str,
+        req: PayByEmail,
+        payee_id: Option<&str>,
+    ) -> Result<P2pPayment> {
+        let email = normalize_email(&req.email).map_err(|e| DomainError::InvalidFields(vec![e]))?;
+        if req.amount.inner() == 0 {
+            return Err(DomainError::InvalidAmount.into());
+        }
+        if payee_id == Some(user_id) {
+            return Err(DomainError::Validation("Cannot pay yourself by email".to_string()).into());
+        }
+        self.check_transaction_limit(user_id, req.amount).await?;
+        let mut from_account = self
+            .account_for(user_id, req.from_account_id, |p| p.can_debit(req.amount))
+            .await?;
+        check_open(&from_account)?;
+
+        if let Some(payee_id) = payee_id
+            && let Some(to_account) = self.default_account(payee_id).await?
+        {
+            self.execute_transfer(Transfer {
+                from_account_id: from_account.id,
+                to_account_id: to_account.id,
+                amount: req.amount,
+                beneficiary_id: None,
+                details: Default::default(),
+            })
+            .await?;
+            info!(payee_id = payee_id, "Payment by email credited");
+            return Ok(P2pPayment::Completed {
+                email,
+                amount: req.amount,
+            });
+        }
+
+        if from_account.balance < req.amount {
+            warn!(
+                from_account_id = from_account.id,
+                balance = from_account.balance.inner(),
+                "Insufficient funds for payment by email"
+            );
+            return Err(DomainError::InsufficientFunds.into());
+        }
+        from_account.balance = Amount::new(from_account.balance.inner() - req.amount.inner());
+        self.repository.update(from_account.clone()).await?;
+        self.record_transaction(
+            &from_account,
+            TransactionKind::TransferOut,
+            req.amount,
+            None,
+            None,
+            &TransactionDetails::default(),
+        )
+        .await?;
+
+        let now = Utc::now();
+        let transfer = ClaimableTransfer {
+            id: Uuid::new_v4().to_string(),
+            sender_id: user_id.to_string(),
+            from_account_id: from_account.id,
+            recipient_email: email,
+            amount: req.amount,
+            status: ClaimStatus::Pending,
+            created_at: now,
+            expires_at: now + self.claim_ttl,
+            settled_at: None,
+        };
+        self.claimable_transfer_repository
+            .save_claimable_transfer(transfer.clone())
+            .await?;
+        info!(
+            claimable_transfer_id = %transfer.id,
+            expires_at = %transfer.expires_at,
+            "Payment by email held until claimed"
+        );
+        Ok(P2pPayment::Pending { transfer })
+    }
+
+    // Credits a transfer sent to `email`, which must be the user's verified email, to
+    // `account_id` or the user's default account
+    #[instrument(skip(self, email))]
+    pub async fn claim_transfer(
+        &self,
+        user_id: &str,
+        email: &str,
+        id: &str,
+        account_id: Option<u32>,
+    ) -> Result<ClaimableTransfer> {
+        let transfer = self
+            .claimable_transfer_repository
+            .find_claimable_transfer(id)
+            .await?
+            .filter(|t| email_identity(&t.recipient_email) == email_identity(email))
+            .ok_or_else(|| {
+                DomainError::NotFound(format!("Claimable transfer not found: {}", id))
+            })?;
+        if transfer.status != ClaimStatus::Pending {
+            return Err(
+                DomainError::Validation("Transfer is no longer pending".to_string()).into(),
+            );
+        }
+        let now = Utc::now();
+        if transfer.is_expired(now) {
+            self.refund_claimable_transfer(id, now).await?;
+            return Err(DomainError::Validation(
+                "Transfer expired and was refunded to the sender".to_string(),
+            )
+            .into());
+        }
+
+        let to_account_id = match account_id {
+            Some(account_id) => {
+                self.account_for(user_id, account_id, HolderPermission::can_deposit)
+                    .await?
+                    .id
+            }
+            None => {
+                self.default_account(user_id)
+                    .await?
+                    .ok_or_else(|| {
+                        DomainError::Validation("Open an account to claim the transfer".to_string())
+                    })?
+                    .id
+            }
+        };
+        // Checked before settling, as a settled transfer can no longer be claimed elsewhere
+        check_open(&self.find_account(to_account_id).await?)?;
+        let Some(transfer) = self
+            .claimable_transfer_repository
+            .settle_claimable_transfer(id, ClaimStatus::Claimed, now)
+            .await?
+        else {
+            return Err(
+                DomainError::Validation("Transfer is no longer pending".to_string()).into(),
+            );
+        };
+        let to_account = self.find_account(to_account_id).await?;
+        self.credit(to_account, transfer.amount, Some(transfer.from_account_id))
+            .await?;
+        info!(
+            claimable_transfer_id = id,
+            to_account_id = to_account_id,
+            "Claimable transfer claimed"
+        );
+        Ok(transfer)
+    }
+
+    // Transfers the user sent by email and, with their verified email, those sent to them
+    #[instrument(skip(self, email))]
+    pub async fn list_claimable_transfers(
+        &self,
+        user_id: &str,
+        email: Option<&str>,
+    ) -> Result<Vec<ClaimableTransfer>> {
+        self.claimable_transfer_repository
+            .list_claimable_transfers(user_id, email)
+            .await
+    }
+
+    // Gives the money of every transfer unclaimed at `now` back to the senders; returns the
+    // number of refunds
+    #[instrument(skip(self))]
+    pub async fn refund_expired_transfers(&self, now: DateTime<Utc>) -> Result<usize> {
+        let expired = self
+            .claimable_transfer_repository
+            .list_expired_claimable_transfers(now)
+            .await?;
+        let mut refunded = 0;
+        for transfer in expired {
+            if self.refund_claimable_transfer(&transfer.id, now).await? {
+                refunded += 1;
+            }
+        }
+        if refunded > 0 {
+            info!(refunded = refunded, "Expired claimable transfers refunded");
+        }
+        Ok(refunded)
+    }
+
+    // False if the transfer was settled in the meantime, or if the sender's account has been
+    // closed. Such a transfer stays pending, and its money held, until an operator settles it.
+    async fn refund_claimable_transfer(&self, id: &str, now: DateTime<Utc>) -> Result<bool> {
+        if let Some(transfer) = self
+            .claimable_transfer_repository
+            .find_claimable_transfer(id)
+            .await?
+            && self.find_account(transfer.from_account_id).await?.closed
+        {
+            error!(
+                claimable_transfer_id = id,
+                from_account_id = transfer.from_account_id,
+                "Cannot refund a claimable transfer to a closed account"
+            );
+            return Ok(false);
+        }
+        let Some(transfer) = self
+            .claimable_transfer_repository
+            .settle_claimable_transfer(id, ClaimStatus::Refunded, now)
+            .await?
+        else {
+            return Ok(false);
+        };
+        let from_account = self.find_account(transfer.from_account_id).await?;
+        self.credit(from_account, transfer.amount, None).await?;
+        info!(
+            claimable_transfer_id = id,
+            from_account_id = transfer.from_account_id,
+            "Claimable transfer refunded"
+        );
+        Ok(true)
+    }
+
+    // Where payments by email to the user go: their own account with the lowest id
+    async fn default_account(&self, user_id: &str) -> Result<Option<Account>> {
+        Ok(self
+            .repository
+            .find_by_owner(user_id)
+            .await?
+            .into_iter()
+            .next())
+    }
+
+    // Books incoming money that was held outside of any account
+    async fn credit(
+        &self,
+        mut account: Account,
+        amount: Amount,
+        counterparty_account_id: Option<u32>,
+    ) -> Result<()> {
+        check_open(&account)?;
+        account.balance = Amount::new(account.balance.inner() + amount.inner());
+        self.repository.update(account.clone()).await?;
+        self.record_transaction(
+            &account,
+            TransactionKind::TransferIn,
+            amount,
+            counterparty_account_id,
+            None,
+            &TransactionDetails::default(),
+        )
+        .await
+    }
+
+    // Asks `payer_id` to pay `req.amount` into one of the requester's accounts
+    #[instrument(skip(self, req), fields(
+        to_account_id = req.to_account_id,
+        amount = req.amount.inner()
+    ))]
+    pub async fn create_payment_request(
+        &self,
+        requester_id: &str,
+        payer_id: &str,
+        req: CreatePaymentRequest,
+    ) -> Result<PaymentRequest> {
+        let now = Utc::now();
+        let errors = validate_payment_request(&req, now.date_naive());
+        if !errors.is_empty() {
+            return Err(DomainError::InvalidFields(errors).into());
+        }
+        if payer_id == requester_id {
+            return Err(DomainError::Validation(
+                "Cannot request a payment from yourself".to_string(),
+            )
+            .into());
+        }
+        self.account_for(
+            requester_id,
+            req.to_account_id,
+            HolderPermission::can_deposit,
+        )
+        .await?;
+
+        let request = PaymentRequest {
+            id: Uuid::new_v4().to_string(),
+            requester_id: requester_id.to_string(),
+            payer_id: payer_id.to_string(),
+            to_account_id: req.to_account_id,
+            amount: req.amount,
+            description: req.description.trim().to_string(),
+            due_date: req.due_date,
+            status: PaymentRequestStatus::Pending,
+            created_at: now,
+            settled_at: None,
+            from_account_id: None,
+        };
+        self.payment_request_repository
+            .save_payment_request(request.clone())
+            .await?;
+        info!(payment_request_id = %request.id, "Payment request created");
+        Ok(request)
+    }
+
+    // Requests the user sent or received, newest first
+    #[instrument(skip(self))]
+    pub async fn list_payment_requests(&self, user_id: &str) -> Result<Vec<PaymentRequest>> {
+        let requests = self
+            .payment_request_repository
+            .list_user_payment_requests(user_id)
+            .await?;
+        let mut listed = Vec::with_capacity(requests.len());
+        for request in requests {
+            listed.push(self.expire_if_overdue(request).await?);
+        }
+        Ok(listed)
+    }
+
+    // A request the user sent or received; others get `NotFound`
+    #[instrument(skip(self))]
+    pub async fn get_payment_request(&self, user_id: &str, id: &str) -> Result<PaymentRequest> {
+        let request = self
+            .payment_request_repository
+            .find_payment_request(id)
+            .await?
+            .filter(|r| r.requester_id == user_id || r.payer_id == user_id)
+            .ok_or_else(|| DomainError::NotFound(format!("Payment request not found: {}", id)))?;
+        self.expire_if_overdue(request).await
+    }
+
+    // Pays a request addressed to the user from `from_account_id`
+    #[instrument(skip(self))]
+    pub async fn pay_payment_request(
+        &self,
+        user_id: &str,
+        id: &str,
+        from_account_id: u32,
+    ) -> Result<PaymentRequest> {
+        let request = self.payable_request(user_id, id).await?;
+        // Settled before the transfer so that two concurrent payments cannot both go through
+        let Some(mut paid) = self
+            .payment_request_repository
+            .settle_payment_request(id, PaymentRequestStatus::Paid, Utc::now())
+            .await?
+        else {
+            return Err(not_pending().into());
+        };
+        let transfer = Transfer {
+            from_account_id,
+            to_account_id: request.to_account_id,
+            amount: request.amount,
+            beneficiary_id: None,
+            details: TransactionDetails {
+                description: Some(request.description.clone()),
+                reference: None,
+                metadata: [("payment_request_id".to_string(), id.to_string())].into(),
+            },
+        };
+        if let Err(e) = self.transfer(user_id, transfer).await {
+            warn!(payment_request_id = id, error = %e, "Payment request transfer failed");
+            self.payment_request_repository
+                .save_payment_request(request)
+                .await?;
+            return Err(e);
+        }
+        paid.from_account_id = Some(from_account_id);
+        self.payment_request_repository
+            .save_payment_request(paid.clone())
+            .await?;
+        info!(
+            payment_request_id = id,
+            from_account_id = from_account_id,
+            "Payment request paid"
+        );
+        Ok(paid)
+    }
+
+    #[instrument(skip(self))]
+    pub async fn decline_payment_request(&self, user_id: &str, id: &str) -> Result<PaymentRequest> {
+        self.payable_request(user_id, id).await?;
+        let declined = self
+            .payment_request_repository
+            .settle_payment_request(id, PaymentRequestStatus::Declined, Utc::now())
+            .await?
+            .ok_or_else(not_pending)?;
+        info!(payment_request_id = id, "Payment request declined");
+        Ok(declined)
+    }
+
+    // Cancels the pending requests the user sent or received, so that nobody pays into the
+    // accounts of a deleted user. Returns them as they were, for `restore_payment_requests`.
+    #[instrument(skip(self))]
+    pub async fn cancel_payment_requests(&self, user_id: &str) -> Result<Vec<PaymentRequest>> {
+        let now = Utc::now();
+        let mut cancelled = Vec::new();
+        for request in self
+            .payment_request_repository
+            .list_user_payment_requests(user_id)
+            .await?
+            .into_iter()
+            .filter(|r| r.status == PaymentRequestStatus::Pending)
+        {
+            if self
+                .payment_request_repository
+                .settle_payment_request(&request.id, PaymentRequestStatus::Cancelled, now)
+                .await?
+                .is_some()
+            {
+                cancelled.push(request);
+            }
+        }
+        if !cancelled.is_empty() {
+            info!(
+                cancelled = cancelled.len(),
+                "Pending payment requests cancelled"
+            );
+        }
+        Ok(cancelled)
+    }
+
+    // Undoes `cancel_payment_requests` when the deletion does not go through. Cancelled
+    // requests cannot change meanwhile, so they are put back as they were.
+    #[instrument(skip(self, requests))]
+    pub async fn restore_payment_requests(&self, requests: Vec<PaymentRequest>) -> Result<()> {
+        for request in requests {
+            self.payment_request_repository
+                .save_payment_request(request)
+                .await?;
+        }
+        Ok(())
+    }
+
+    // A pending request addressed to the user. The requester gets `Forbidden`, as they may
+    // see the request but not settle it.
+    async fn payable_request(&self, user_id: &str, id: &str) -> Result<PaymentRequest> {
+        let request = self.get_payment_request(user_id, id).await?;
+        if request.payer_id != user_id {
+            return Err(DomainError::Forbidden(
+                "Only the payer can pay or decline a payment request".to_string(),
+            )
+            .into());
+        }
+        if request.status != PaymentRequestStatus::Pending {
+            return Err(not_pending().into());
+        }
+        Ok(request)
+    }
+
+    // Requests are not swept; one past its due date is marked expired when next read
+    async fn expire_if_overdue(&self, request: PaymentRequest) -> Result<PaymentRequest> {
+        let now = Utc::now();
+        if request.status != PaymentRequestStatus::Pending || !request.is_overdue(now.date_naive())
+        {
+            return Ok(request);
+        }
+        debug!(payment_request_id = %request.id, "Payment request expired");
+        Ok(self
+            .payment_request_repository
+            .settle_payment_request(&request.id, PaymentRequestStatus::Expired, now)
+            .await?
+            .unwrap_or(request))
+    }
+
+    // Holders of the account, the owner first
+    #[instrument(skip(self), fields(account_id = id))]
+    pub async fn list_holders(&self, user_id: &str, id: u32) -> Result<Vec<AccountHolder>> {
+        let account = self.get_account(user_id, id).await?;
+        let mut holders = vec![AccountHolder {
+            user_id: account.owner_id,
+            permission: HolderPermission::Owner,
+        }];
+        holders.extend(account.holders);
+        Ok(holders)
+    }
+
+    // Gives a user access to the account, or changes the access they have. Only the owner
+    // manages holders.
+    #[instrument(skip(self, holder), fields(account_id = id, holder_id = %holder.user_id))]
+    pub async fn add_holder(
+        &self,
+        user_id: &str,
+        id: u32,
+        holder: AccountHolder,
+    ) -> Result<Vec<AccountHolder>> {
+        match holder.permission {
+            HolderPermission::Owner => {
+                return Err(DomainError::Validation(
+                    "An account has a single owner; add a co-owner instead".to_string(),
+                )
+                .into());
+            }
+            HolderPermission::TransferUpToLimit { transfer_limit }
+                if transfer_limit.inner() == 0 =>
+            {
+                return Err(DomainError::Validation(
+                    "Transfer limit must be positive; add a viewer instead".to_string(),
+                )
+                .into());
+            }
+            _ => {}
+        }
+        let mut account = self
+            .account_for(user_id, id, HolderPermission::can_manage_holders)
+            .await?;
+        if holder.user_id == account.owner_id {
+            return Err(
+                DomainError::Validation("The owner already has full access".to_string()).into(),
+            );
+        }
+
+        match account
+            .holders
+            .iter_mut()
+            .find(|h| h.user_id == holder.user_id)
+        {
+            Some(existing) => existing.permission = holder.permission,
+            None => account.holders.push(holder.clone()),
+        }
+        self.repository.update(account).await?;
+
+        info!(
+            account_id = id,
+            holder_id = %holder.user_id,
+            permission = ?holder.permission,
+            "Account holder added"
+        );
+        self.list_holders(user_id, id).await
+    }
+
+    // The owner removes any holder; other holders may only remove themselves
+    #[instrument(skip(self), fields(account_id = id))]
+    pub async fn remove_holder(&self, user_id: &str, id: u32, holder_id: &str) -> Result<()> {
+        let mut account = self
+            .account_for(user_id, id, |p| {
+                holder_id == user_id || p.can_manage_holders()
+            })
+            .await?;
+        if holder_id == account.owner_id {
+            return Err(DomainError::Validation("The owner cannot be removed".to_string()).into());
+        }
+
+        let before = account.holders.len();
+        account.holders.retain(|h| h.user_id != holder_id);
+        if account.holders.len() == before {
+            return Err(DomainError::NotFound(format!(
+                "User {} is not a holder of account {}",
+                holder_id, id
+            ))
+            .into());
+        }
+        self.repository.update(account).await?;
+
+        info!(
+            account_id = id,
+            holder_id = holder_id,
+            "Account holder removed"
+        );
+        Ok(())
+    }
+
+    // Accounts the user opened, each with all of its transactions
+    #[instrument(skip(self))]
+    pub async fn export_accounts(&self, user_id: &str) -> Result<Vec<AccountExport>> {
+        let mut exports = Vec::new();
+        for account in self.repository.find_by_owner(user_id).await? {
+            let transactions = self
+                .repository
+                .find_transactions_by_account(account.id)
+                .await?;
+            exports.push(AccountExport {
+                account,
+                transactions,
+            });
+        }
+        debug!(accounts = exports.len(), "Accounts exported");
+        Ok(exports)
+    }
+
+    // A user can only be deleted once nothing is left on their accounts. The accounts and
+    // their transactions are kept for audit afterwards.
+    #[instrument(skip(self))]
+    pub async fn check_accounts_settled(&self, user_id: &str) -> Result<()> {
+        let funded: Vec<String> = self
+            .repository
+            .find_by_owner(user_id)
+            .await?
+            .iter()
+            .filter(|a| a.balance.inner() > 0)
+            .map(|a| a.id.to_string())
+            .collect();
+        if !funded.is_empty() {
+            warn!(accounts = ?funded, "Accounts still hold money");
+            return Err(DomainError::Validation(format!(
+                "Withdraw or transfer the money left on accounts {} first",
+                funded.join(", ")
+            ))
+            .into());
+        }
+        Ok(())
+    }
+
+    // Closes the user's accounts when they are deleted, so that no money reaches them
+    // afterwards. Payments by email they sent that are still unclaimed would be refunded
+    // into the closed accounts once they expire, so they are refunded first. Refuses,
+    // leaving the accounts open, while any of them holds money. Returns the ids of the
+    // accounts it closed.
+    #[instrument(skip(self))]
+    pub async fn close_accounts(&self, user_id: &str) -> Result<Vec<u32>> {
+        let now = Utc::now();
+        for transfer in self.pending_claimable_transfers(user_id).await? {
+            self.refund_claimable_transfer(&transfer.id, now).await?;
+        }
+
+        let mut closed = Vec::new();
+        for account in self.repository.find_by_owner(user_id).await? {
+            if !account.closed {
+                self.repository.set_closed(account.id, true).await?;
+                closed.push(account.id);
+            }
+        }
+        // Checked once closed, so that nothing arrives between the check and the closing
+        if let Err(e) = self.check_accounts_settled(user_id).await {
+            self.reopen_accounts(&closed).await?;
+            return Err(e);
+        }
+        if !self.pending_claimable_transfers(user_id).await?.is_empty() {
+            warn!("Payment by email sent while the accounts were being closed");
+            self.reopen_accounts(&closed).await?;
+            return Err(DomainError::Validation(
+                "Payments by email are still waiting to be claimed, try again".to_string(),
+            )
+            .into());
+        }
+        info!(accounts = ?closed, "Accounts closed");
+        Ok(closed)
+    }
+
+    async fn pending_claimable_transfers(&self, user_id: &str) -> Result<Vec<ClaimableTransfer>> {
+        Ok(self
+            .claimable_transfer_repository
+            .list_claimable_transfers(user_id, None)
+            .await?
+            .into_iter()
+            .filter(|t| t.status == ClaimStatus::Pending)
+            .collect())
+    }
+
+    // Undoes `close_accounts` when the deletion does not go through
+    #[instrument(skip(self))]
+    pub async fn reopen_accounts(&self, ids: &[u32]) -> Result<()> {
+        for &id in ids {
+            self.repository.set_closed(id, false).await?;
+        }
+        debug!(accounts = ?ids, "Accounts reopened");
+        Ok(())
+    }
+
+    async fn transaction_limit(&self, user_id: &str) -> Result<Option<Amount>> {
+        match &self.customer_policy {
+            Some(policy) => policy.transaction_limit(user_id).await,
+            None => Ok(None),
+        }
+    }
+
+    async fn check_transaction_limit(&self, user_id: &str, amount: Amount) -> Result<()> {
+        if let Some(limit) = self.transaction_limit(user_id).await?
+            && amount.inner() > limit.inner()
+        {
+            warn!(
+                user_id = user_id,
+                amount = amount.inner(),
+                limit = limit.inner(),
+                "Transaction limit exceeded"
+            );
+            return Err(limit_exceeded(limit).into());
+        }
+        Ok(())
+    }
+
+    // Builds a statement of the account for the given period. The opening balance is
+    // the balance right before the first transaction of the period.
+    #[instrument(skip(self), fields(account_id = id))]
+    pub async fn get_statement(
+        &self,
+        user_id: &str,
+        id: u32,
+        from: Option<DateTime<Utc>>,
+        to: Option<DateTime<Utc>>,
+    ) -> Result<AccountStatement> {
+        trace!("Building account statement");
+        if let (Some(from), Some(to)) = (from, to)
+            && from > to
+        {
+            return Err(DomainError::Validation(
+                "Statement period start must not be after its end".to_string(),
+            )
+            .into());
+        }
+
+        let account = self.get_account(user_id, id).await?;
+        let history = self.repository.find_transactions_by_account(id).await?;
+
+        // Balance at the start of the period: after the last transaction before it
+        let opening_balance = history
+            .iter()
+            .take_while(|t| from.is_some_and(|from| t.created_at < from))
+            .last()
+            .map(|t| t.balance_after)
+            .unwrap_or(Amount::new(0));
+        let transactions: Vec<Transaction> = history
+            .into_iter()
+            .filter(|t| from.is_none_or(|from| t.created_at >= from))
+            .filter(|t| to.is_none_or(|to| t.created_at <= to))
+            .collect();
+        let closing_balance = transactions
+            .last()
+            .map(|t| t.balance_after)
+            .unwrap_or(opening_balance);
+
+        debug!(
+            account_id = id,
+            transactions = transactions.len(),
+            opening_balance = opening_balance.inner(),
+            closing_balance = closing_balance.inner(),
+            "Account statement built"
+        );
+        Ok(AccountStatement {
+            account_id: account.id,
+            account_name: account.name,
+            from,
+            to,
+            opening_balance,
+            closing_balance,
+            transactions,
+        })
+    }
+
+    async fn record_transaction(
+        &self,
+        account: &Account,
+        kind: TransactionKind,
+        amount: Amount,
+        counterparty_account_id: Option<u32>,
+        transfer_id: Option<&str>,
+        details: &TransactionDetails,
+    ) -> Result<()> {
+        let transaction = Transaction {
+            id: Uuid::new_v4().to_string(),
+            account_id: account.id,
+            kind,
+            amount,
+            balance_after: account.balance,
+            counterparty_account_id,
+            transfer_id: transfer_id.map(str::to_string),
+            details: details.clone(),
+            created_at: Utc::now(),
+        };
+        trace!(
+            transaction_id = %transaction.id,
+            account_id = account.id,
+            kind = ?kind,
+            "Recording transaction"
+        );
+        self.repository.save_transaction(transaction).await
+    }
+
+    // Gives back all or part of a transfer with a compensating transfer from the recipient
+    // to the sender, on behalf of an operator. Reversals of a transfer never add up to more
+    // than it; what happens when the recipient is short of funds is `shortfall_policy`.
+    #[instrument(skip(self, req))]
+    pub async fn reverse_transfer(
+        &self,
+        operator_id: &str,
+        transfer_id: &str,
+        req: ReverseTransfer,
+    ) -> Result<TransferReversal> {
+        let reason = req.reason.trim();
+        if reason.is_empty() {
+            return Err(DomainError::InvalidFields(vec![FieldError::new(
+                "reason",
+                "required",
+                "reason is required".to_string(),
+            )])
+            .into());
+        }
+        let original = self
+            .repository
+            .find_transactions_by_transfer(transfer_id)
+            .await?
+            .into_iter()
+            .find(|t| t.kind == TransactionKind::TransferOut)
+            .ok_or_else(|| DomainError::NotFound(format!("Transfer not found: {}", transfer_id)))?;
+        if original
+            .details
+            .metadata
+            .contains_key(REVERSAL_OF_METADATA_KEY)
+        {
+            return Err(
+                DomainError::Validation("A reversal cannot be reversed".to_string()).into(),
+            );
+        }
+        let (sender_account_id, recipient_account_id) = match original.counterparty_account_id {
+            Some(recipient_account_id) => (original.account_id, recipient_account_id),
+            None => {
+                return Err(
+                    DomainError::NotFound(format!("Transfer not found: {}", transfer_id)).into(),
+                );
+            }
+        };
+
+        let reversed: u64 = self
+            .reversal_repository
+            .list_reversals(transfer_id)
+            .await?
+            .iter()
+            .map(|r| r.amount.inner())
+            .sum();
+        let remaining = original.amount.inner().saturating_sub(reversed);
+        if remaining == 0 {
+            return Err(
+                DomainError::Validation("Transfer has already been reversed".to_string()).into(),
+            );
+        }
+        let requested = req.amount.unwrap_or(Amount::new(remaining));
+        if requested.inner() == 0 {
+            return Err(DomainError::InvalidAmount.into());
+        }
+        if requested.inner() > remaining {
+            return Err(DomainError::Validation(format!(
+                "At most {} of the transfer is left to reverse",
+                remaining
+            ))
+            .into());
+        }
+
+        let recipient = self.find_account(recipient_account_id).await?;
+        let amount = if recipient.balance >= requested {
+            requested
+        } else {
+            warn!(
+                recipient_account_id = recipient_account_id,
+                balance = recipient.balance.inner(),
+                requested = requested.inner(),
+                policy = ?self.shortfall_policy,
+                "Recipient is short of funds for the reversal"
+            );
+            match self.shortfall_policy {
+                ShortfallPolicy::Partial if recipient.balance.inner() > 0 => recipient.balance,
+                _ => return Err(DomainError::InsufficientFunds.into()),
+            }
+        };
+
+        let reversal = TransferReversal {
+            id: Uuid::new_v4().to_string(),
+            transfer_id: transfer_id.to_string(),
+            reversal_transfer_id: Uuid::new_v4().to_string(),
+            from_account_id: recipient_account_id,
+            to_account_id: sender_account_id,
+            amount,
+            requested_amount: requested,
+            reason: reason.to_string(),
+            operator_id: operator_id.to_string(),
+            created_at: Utc::now(),
+        };
+        if !self
+            .reversal_repository
+            .add_reversal(reversal.clone(), original.amount)
+            .await?
+        {
+            return Err(DomainError::Validation(
+                "Transfer was reversed in the meantime".to_string(),
+            )
+            .into());
+        }
+        let compensation = Transfer {
+            from_account_id: recipient_account_id,
+            to_account_id: sender_account_id,
+            amount,
+            beneficiary_id: None,
+            details: TransactionDetails {
+                description: Some(reversal.reason.clone()),
+                reference: original.details.reference.clone(),
+                metadata: [(
+                    REVERSAL_OF_METADATA_KEY.to_string(),
+                    transfer_id.to_string(),
+                )]
+                .into(),
+            },
+        };
+        if let Err(e) = self
+            .execute_transfer_as(reversal.reversal_transfer_id.clone(), compensation)
+            .await
+        {
+            warn!(transfer_id = transfer_id, error = %e, "Compensating transfer failed");
+            self.reversal_repository
+                .remove_reversal(transfer_id, &reversal.id)
+                .await?;
+            return Err(e);
+        }
+        info!(
+            transfer_id = transfer_id,
+            reversal_id = %reversal.id,
+            operator_id = operator_id,
+            amount = amount.inner(),
+            "Transfer reversed"
+        );
+        Ok(reversal)
+    }
+
+    #[instrument(skip(self, rows), fields(rows = rows.len(), mode = ?mode))]
+    pub async fn batch_transfer(
+        &self,
+        user_id: &str,
+        mut rows: Vec<BatchTransferRow>,
+        mode: BatchMode,
+    ) -> Result<BatchTransferReport> {
+        trace!("Starting batch transfer");
+        if rows.is_empty() {
+            return Err(DomainError::Validation(
+                "Batch must contain at least one transfer".to_string(),
+            )
+            .into());
+        }
+        if rows.len() > MAX_BATCH_SIZE {
+            return Err(DomainError::Validation(format!(
+                "Batch must not contain more than {} transfers",
+                MAX_BATCH_SIZE
+            ))
+            .into());
+        }
+
+        // Validate all rows up front against a snapshot of the balances
+        let limit = self.transaction_limit(user_id).await?;
+        let validation = self.validate_batch(user_id, &mut rows, limit).await?;
+        let invalid_rows = validation.iter().filter(|v| v.is_some()).count();
+        debug!(invalid_rows = invalid_rows, "Batch validated");
+
+        let mut results: Vec<BatchRowResult> = rows
+            .iter()
+            .zip(validation.iter())
+            .map(|(row, error)| BatchRowResult {
+                line: row.line,
+                status: if error.is_some() {
+                    BatchRowStatus::Failed
+                } else {
+                    BatchRowStatus::Rejected
+                },
+                reason: error.as_ref().map(|e| e.to_string()),
+            })
+            .collect();
+
+        if mode == BatchMode::AllOrNothing && invalid_rows > 0 {
+            warn!(
+                invalid_rows = invalid_rows,
+                "Batch rejected because some rows failed validation"
+            );
+            for result in results.iter_mut() {
+                if result.status == BatchRowStatus::Rejected {
+                    result.reason =
+                        Some("Batch rejected: other rows failed validation".to_string());
+                }
+            }
+            return Ok(Self::batch_report(mode, results));
+        }
+
+        let mut executed: Vec<usize> = Vec::new();
+        for (index, row) in rows.iter().enumerate() {
+            if validation[index].is_some() {
+                continue;
+            }
+            match self.execute_transfer(row.transfer.clone()).await {
+                Ok(_) => {
+                    results[index].status = BatchRowStatus::Completed;
+                    executed.push(index);
+                }
+                Err(e) => {
+                    warn!(line = row.line, error = %e, "Batch row failed during execution");
+                    results[index].status = BatchRowStatus::Failed;
+                    results[index].reason = Some(e.to_string());
+                    if mode == BatchMode::AllOrNothing {
+                        let not_undone = self.rollback_batch(&rows, &executed).await;
+                        for (i, result) in results.iter_mut().enumerate() {
+                            if not_undone.contains(&i) {
+                                // The money moved and stays moved; say so
+                                result.reason = Some(format!(
+                                    "Batch rollback failed: line {} failed but this transfer could not be undone",
+                                    row.line
+                                ));
+                            } else if result.status != BatchRowStatus::Failed {
+                                result.status = BatchRowStatus::Rejected;
+                                result.reason =
+                                    Some(format!("Batch rolled back: line {} failed", row.line));
+                            }
+                        }
+                        return Ok(Self::batch_report(mode, results));
+                    }
+                }
+            }
+        }
+
+        let report = Self::batch_report(mode, results);
+        info!(
+            total = report.total,
+            succeeded = report.succeeded,
+            failed = report.failed,
+            "Batch transfer completed"
+        );
+        Ok(report)
+    }
+
+    // Returns the validation error of every row (None for valid rows), applying the
+    // effects of valid rows to a local copy of the balances in order. A row passes the same
+    // checks as `transfer` would apply to it alone. Rows paying a saved beneficiary are
+    // resolved in place, with its cooling-off.
+    async fn validate_batch(
+        &self,
+        user_id: &str,
+        rows: &mut [BatchTransferRow],
+        limit: Option<Amount>,
+    ) -> Result<Vec<Option<DomainError>>> {
+        let mut resolution_errors = Vec::with_capacity(rows.len());
+        for row in rows.iter_mut() {
+            let error = match self
+                .resolve_beneficiary(user_id, row.transfer.clone())
+                .await
+            {
+                Ok(transfer) => {
+                    row.transfer = transfer;
+                    None
+                }
+                Err(e) => Some(e.downcast::<DomainError>()?),
+            };
+            resolution_errors.push(error);
+        }
+
+        let mut balances: HashMap<u32, Option<u64>> = HashMap::new();
+        let mut permissions: HashMap<u32, Option<HolderPermission>> = HashMap::new();
+        let mut closed: HashSet<u32> = HashSet::new();
+        for row in rows.iter() {
+            for id in [row.transfer.from_account_id, row.transfer.to_account_id] {
+                if let std::collections::hash_map::Entry::Vacant(entry) = balances.entry(id) {
+                    let account = self.repository.find_by_id(id).await?;
+                    permissions.insert(id, account.as_ref().and_then(|a| a.permission_of(user_id)));
+                    if account.as_ref().is_some_and(|a| a.closed) {
+                        closed.insert(id);
+                    }
+                    entry.insert(account.map(|a| a.balance.inner()));
+                }
+            }
+        }
+
+        let mut errors = Vec::with_capacity(rows.len());
+        for (row, resolution_error) in rows.iter().zip(resolution_errors) {
+            let transfer = &row.transfer;
+            let amount = transfer.amount.inner();
+            let error = if resolution_error.is_some() {
+                resolution_error
+            } else if transfer.from_account_id == transfer.to_account_id || amount == 0 {
+                Some(DomainError::InvalidAmount)
+            } else if let Err(e) = check_details(&transfer.details) {
+                Some(e)
+            } else if let Some(limit) = limit.filter(|limit| amount > limit.inner()) {
+                Some(limit_exceeded(limit))
+            } else {
+                match (
+                    balances[&transfer.from_account_id],
+                    balances[&transfer.to_account_id],
+                ) {
+                    (Some(from_balance), Some(to_balance)) => {
+                        if let Some(error) =
+                            debit_error(permissions[&transfer.from_account_id], transfer.amount)
+                        {
+                            Some(error)
+                        } else if let Some(&id) = [transfer.from_account_id, transfer.to_account_id]
+                            .iter()
+                            .find(|id| closed.contains(id))
+                        {
+                            Some(account_closed(id))
+                        } else if from_balance < amount {
+                            Some(DomainError::InsufficientFunds)
+                        } else {
+                            balances.insert(transfer.from_account_id, Some(from_balance - amount));
+                            balances.insert(transfer.to_account_id, Some(to_balance + amount));
+                            None
+                        }
+                    }
+                    _ => Some(DomainError::AccountNotFound),
+                }
+            };
+            if let Some(e) = &error {
+                debug!(line = row.line, error = %e, "Batch row failed validation");
+            }
+            errors.push(error);
+        }
+        Ok(errors)
+    }
+
+    // Compensates already executed rows in reverse order. Returns the rows that could not be
+    // undone, e.g. because the recipient already spent the money.
+    async fn rollback_batch(&self, rows: &[BatchTransferRow], executed: &[usize]) -> Vec<usize> {
+        let mut not_undone = Vec::new();
+        for &index in executed.iter().rev() {
+            let original = &rows[index].transfer;
+            let compensation = Transfer {
+                from_account_id: original.to_account_id,
+                to_account_id: original.from_account_id,
+                amount: original.amount,
+                beneficiary_id: None,
+                details: Default::default(),
+            };
+            if let Err(e) = self.execute_transfer(compensation).await {
+                error!(
+                    line = rows[index].line,
+                    error = %e,
+                    "Failed to roll back batch row"
+                );
+                not_undone.push(index);
+            }
+        }
+        not_undone
+    }
+
+    fn batch_report(mode: BatchMode, results: Vec<BatchRowResult>) -> BatchTransferReport {
+        let succeeded = results
+            .iter()
+            .filter(|r| r.status == BatchRowStatus::Completed)
+            .count();
+        BatchTransferReport {
+            mode,
+            total: results.len(),
+            succeeded,
+            failed: results.len() - succeeded,
+            results,
+        }
+    }
+}
+
+fn operation_not_allowed() -> DomainError {
+    DomainError::Forbidden("Your access to this account does not allow this operation".to_string())
+}
+
+fn check_open(account: &Account) -> Result<()> {
+    if account.closed {
+        warn!(account_id = account.id, "Account is closed");
+        return Err(account_closed(account.id).into());
+    }
+    Ok(())
+}
+
+fn account_closed(id: u32) -> DomainError {
+    DomainError::Validation(format!("Account {} is closed", id))
+}
 
-        let new_to_balance = to_balance + transfer_amount;
-        to_account.balance = Amount::new(new_to_balance);
+fn not_pending() -> DomainError {
+    DomainError::Validation("Payment request is no longer pending".to_string())
+}
 
-        trace!(
-            from_account_id = from_account.id,
-            new_from_balance = new_from_balance,
-            "Updating source account"
-        );
-        self.repository.update(from_account).await?;
-        trace!(
-            to_account_id = to_account.id,
-            new_to_balance = new_to_balance,
-            "Updating destination account"
-        );
-        self.repository.update(to_account).await?;
+// Why the holder may not debit `amount` from an account, if they may not
+fn debit_error(permission: Option<HolderPermission>, amount: Amount) -> Option<DomainError> {
+    match permission {
+        None => Some(DomainError::AccountNotFound),
+        Some(permission) if !permission.can_debit(amount) => Some(operation_not_allowed()),
+        Some(_) => None,
+    }
+}
 
-        info!(
-            from_account_id = req.from_account_id,
-            to_account_id = req.to_account_id,
-            transfer_amount = transfer_amount,
-            "Transfer completed successfully"
-        );
+fn check_details(details: &TransactionDetails) -> Result<(), DomainError> {
+    let errors = validate_transaction_details(details);
+    if errors.is_empty() {
         Ok(())
+    } else {
+        Err(DomainError::InvalidFields(errors))
     }
 }
 
+fn limit_exceeded(limit: Amount) -> DomainError {
+    DomainError::Forbidden(format!(
+        "Amount exceeds the limit of {} for unverified customers, verify your identity to lift it",
+        limit.inner()
+    ))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::data::memory::InMemoryAccountRepository;
-    use crate::domain::models::{Account, Amount, CreateAccount, Transfer};
+    use crate::domain::models::{
+        Account, AccountHolder, Amount, BatchMode, BatchRowStatus, BatchTransferRow, CreateAccount,
+        TransactionKind, Transfer,
+    };
 
     #[tokio::test]
     async fn test_create_account_creates_account_with_zero_balance() {
@@ -219,7 +1782,7 @@ mod tests {
             name: "Test Account".to_string(),
         };
 
-        let account = service.create_account(req).await.unwrap();
+        let account = service.create_account("user-1", req).await.unwrap();
         assert_eq!(account.name, "Test Account");
         assert_eq!(account.balance.inner(), 0);
     }
@@ -236,13 +1799,659 @@ mod tests {
             name: "Account 2".to_string(),
         };
 
-        let account1 = service.create_account(req1).await.unwrap();
-        let account2 = service.create_account(req2).await.unwrap();
+        let account1 = service.create_account("user-1", req1).await.unwrap();
+        let account2 = service.create_account("user-1", req2).await.unwrap();
 
         // IDs might be the same due to randomness, but accounts should be different
         assert_ne!(account1.id, account2.id);
     }
 
+    // Lets only `verified-user` open accounts
+    struct VerifiedOnly;
+
+    #[async_trait::async_trait]
+    impl CustomerPolicy for VerifiedOnly {
+        async fn check_can_open_account(&self, user_id: &str) -> Result<()> {
+            if user_id != "verified-user" {
+                return Err(DomainError::Forbidden("Email not verified".to_string()).into());
+            }
+            Ok(())
+        }
+
+        async fn transaction_limit(&self, user_id: &str) -> Result<Option<Amount>> {
+            Ok((user_id != "verified-user").then_some(Amount::new(500)))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_create_account_consults_customer_policy() {
+        let repo = Arc::new(InMemoryAccountRepository::new());
+        let service = BankService::new(repo.clone()).with_customer_policy(Arc::new(VerifiedOnly));
+        let req = || CreateAccount {
+            name: "Savings".to_string(),
+        };
+
+        let err = service
+            .create_account("pending-user", req())
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<DomainError>(),
+            Some(DomainError::Forbidden(_))
+        ));
+        assert!(service.create_account("verified-user", req()).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_accounts_are_exported_and_must_be_empty_before_deletion() {
+        let repo = Arc::new(InMemoryAccountRepository::new());
+        let service = BankService::new(repo);
+        let account = service
+            .create_account(
+                "alice",
+                CreateAccount {
+                    name: "Savings".to_string(),
+                },
+            )
+            .await
+            .unwrap();
+        service
+            .deposit(
+                "alice",
+                account.id,
+                Amount::new(300),
+                TransactionDetails::default(),
+            )
+            .await
+            .unwrap();
+
+        let exports = service.export_accounts("alice").await.unwrap();
+        assert_eq!(exports.len(), 1);
+        assert_eq!(exports[0].transactions.len(), 1);
+        assert!(service.export_accounts("bob").await.unwrap().is_empty());
+
+        assert!(service.check_accounts_settled("alice").await.is_err());
+        service
+            .withdraw(
+                "alice",
+                account.id,
+                Amount::new(300),
+                TransactionDetails::default(),
+            )
+            .await
+            .unwrap();
+        assert!(service.check_accounts_settled("alice").await.is_ok());
+    }
+
+    fn co_owner(user_id: &str) -> AccountHolder {
+        AccountHolder {
+            user_id: user_id.to_string(),
+            permission: HolderPermission::CoOwner,
+        }
+    }
+
+    fn assert_forbidden(result: Result<impl std::fmt::Debug>) {
+        assert!(matches!(
+            result.unwrap_err().downcast::<DomainError>(),
+            Ok(DomainError::Forbidden(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_viewer_reads_balance_but_cannot_withdraw() {
+        let repo = Arc::new(InMemoryAccountRepository::new());
+        setup_batch_accounts(&repo).await;
+        let service = BankService::new(repo);
+        service
+            .add_holder(
+                "user-1",
+                1,
+                AccountHolder {
+                    user_id: "viewer".to_string(),
+                    permission: HolderPermission::Viewer,
+                },
+            )
+            .await
+            .unwrap();
+
+        let account = service.get_account("viewer", 1).await.unwrap();
+        assert_eq!(account.balance.inner(), 100);
+        assert_forbidden(
+            service
+                .withdraw("viewer", 1, Amount::new(10), TransactionDetails::default())
+                .await,
+        );
+        assert_forbidden(
+            service
+                .deposit("viewer", 1, Amount::new(10), TransactionDetails::default())
+                .await,
+        );
+        assert_forbidden(
+            service
+                .transfer(
+                    "viewer",
+                    Transfer {
+                        from_account_id: 1,
+                        to_account_id: 2,
+                        amount: Amount::new(10),
+                        beneficiary_id: None,
+                        details: Default::default(),
+                    },
+                )
+                .await,
+        );
+        // Viewers cannot hand out access either
+        assert_forbidden(service.add_holder("viewer", 1, co_owner("mallory")).await);
+
+        // Others do not see the account at all
+        assert!(matches!(
+            service
+                .get_account("stranger", 1)
+                .await
+                .unwrap_err()
+                .downcast::<DomainError>(),
+            Ok(DomainError::AccountNotFound)
+        ));
+        assert_eq!(
+            service
+                .get_account("user-1", 1)
+                .await
+                .unwrap()
+                .balance
+                .inner(),
+            100
+        );
+    }
+
+    #[tokio::test]
+    async fn test_limited_holder_transfers_up_to_limit_and_can_leave() {
+        let repo = Arc::new(InMemoryAccountRepository::new());
+        setup_batch_accounts(&repo).await;
+        let service = BankService::new(repo);
+        let limited = AccountHolder {
+            user_id: "assistant".to_string(),
+            permission: HolderPermission::TransferUpToLimit {
+                transfer_limit: Amount::new(30),
+            },
+        };
+        let holders = service.add_holder("user-1", 1, limited).await.unwrap();
+        assert_eq!(holders[0].permission, HolderPermission::Owner);
+        assert_eq!(holders.len(), 2);
+
+        assert!(
+            service
+                .withdraw(
+                    "assistant",
+                    1,
+                    Amount::new(30),
+                    TransactionDetails::default()
+                )
+                .await
+                .is_ok()
+        );
+        assert_forbidden(
+            service
+                .withdraw(
+                    "assistant",
+                    1,
+                    Amount::new(31),
+                    TransactionDetails::default(),
+                )
+                .await,
+        );
+        let rows = vec![batch_row(2, 1, 2, 20), batch_row(3, 1, 3, 40)];
+        let report = service
+            .batch_transfer("assistant", rows, BatchMode::BestEffort)
+            .await
+            .unwrap();
+        assert_eq!(report.succeeded, 1);
+
+        service
+            .remove_holder("assistant", 1, "assistant")
+            .await
+            .unwrap();
+        assert!(service.get_account("assistant", 1).await.is_err());
+        assert_eq!(service.list_holders("user-1", 1).await.unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_pay_by_email_credits_default_account_or_holds_money() {
+        let repo = Arc::new(InMemoryAccountRepository::new());
+        setup_batch_accounts(&repo).await;
+        for id in [9, 7] {
+            repo.save(Account {
+                id,
+                name: "Bob".to_string(),
+                balance: Amount::new(0),
+                owner_id: "bob".to_string(),
+                holders: Vec::new(),
+                closed: false,
+            })
+            .await
+            .unwrap();
+        }
+        let service = BankService::new(repo.clone()).with_claim_ttl(Duration::zero());
+        let pay = |amount| PayByEmail {
+            from_account_id: 1,
+            email: "bob@example.com".to_string(),
+            amount: Amount::new(amount),
+        };
+
+        let payment = service
+            .pay_by_email("user-1", pay(10), Some("bob"))
+            .await
+            .unwrap();
+        assert!(matches!(payment, P2pPayment::Completed { .. }));
+        assert_eq!(service.find_account(7).await.unwrap().balance.inner(), 10);
+
+        // Without an account the money waits, here until it expires
+        let P2pPayment::Pending { transfer } =
+            service.pay_by_email("user-1", pay(30), None).await.unwrap()
+        else {
+            panic!("Payment should be held");
+        };
+        assert_eq!(service.find_account(1).await.unwrap().balance.inner(), 60);
+        assert!(
+            service
+                .claim_transfer("bob", "bob@example.com", &transfer.id, None)
+                .await
+                .is_err()
+        );
+        assert_eq!(service.find_account(1).await.unwrap().balance.inner(), 90);
+        assert_eq!(
+            service.refund_expired_transfers(Utc::now()).await.unwrap(),
+            0
+        );
+        let statement = service
+            .get_statement("user-1", 1, None, None)
+            .await
+            .unwrap();
+        assert_eq!(statement.transactions.len(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_closed_accounts_neither_send_nor_receive_payments_by_email() {
+        let repo = Arc::new(InMemoryAccountRepository::new());
+        setup_batch_accounts(&repo).await;
+        let service = BankService::new(repo.clone());
+        let pay = |from_account_id| PayByEmail {
+            from_account_id,
+            email: "bob@example.com".to_string(),
+            amount: Amount::new(10),
+        };
+
+        assert!(matches!(
+            service
+                .pay_by_email("user-1", pay(1), Some("user-1"))
+                .await
+                .unwrap_err()
+                .downcast::<DomainError>(),
+            Ok(DomainError::Validation(_))
+        ));
+        let P2pPayment::Pending { transfer } =
+            service.pay_by_email("user-1", pay(1), None).await.unwrap()
+        else {
+            panic!("Payment should be held");
+        };
+
+        repo.set_closed(1, true).await.unwrap();
+        repo.set_closed(2, true).await.unwrap();
+        assert!(service.pay_by_email("user-1", pay(1), None).await.is_err());
+        assert!(
+            service
+                .claim_transfer("user-1", "bob@example.com", &transfer.id, Some(2))
+                .await
+                .is_err()
+        );
+
+        // The sender's account is closed, so the expired transfer is held instead
+        let far_future = Utc::now() + Duration::days(365);
+        assert_eq!(
+            service.refund_expired_transfers(far_future).await.unwrap(),
+            0
+        );
+        assert_eq!(service.find_account(1).await.unwrap().balance.inner(), 90);
+        let listed = service
+            .list_claimable_transfers("user-1", None)
+            .await
+            .unwrap();
+        assert_eq!(listed[0].status, ClaimStatus::Pending);
+    }
+
+    #[tokio::test]
+    async fn test_closing_accounts_refunds_unclaimed_payments_by_email() {
+        let repo = Arc::new(InMemoryAccountRepository::new());
+        setup_batch_accounts(&repo).await;
+        let service = BankService::new(repo);
+        let P2pPayment::Pending { transfer } = service
+            .pay_by_email(
+                "user-1",
+                PayByEmail {
+                    from_account_id: 1,
+                    email: "nobody@example.com".to_string(),
+                    amount: Amount::new(100),
+                },
+                None,
+            )
+            .await
+            .unwrap()
+        else {
+            panic!("Payment should be held");
+        };
+        assert!(service.check_accounts_settled("user-1").await.is_ok());
+
+        // The refund lands before the accounts close, and has to be withdrawn first
+        assert!(service.close_accounts("user-1").await.is_err());
+        assert!(!service.find_account(1).await.unwrap().closed);
+        let listed = service
+            .list_claimable_transfers("user-1", None)
+            .await
+            .unwrap();
+        assert_eq!(listed[0].id, transfer.id);
+        assert_eq!(listed[0].status, ClaimStatus::Refunded);
+
+        service
+            .withdraw("user-1", 1, Amount::new(100), TransactionDetails::default())
+            .await
+            .unwrap();
+        assert_eq!(service.close_accounts("user-1").await.unwrap().len(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_transfer_to_new_beneficiary_waits_for_cooling_off() {
+        let repo = Arc::new(InMemoryAccountRepository::new());
+        setup_batch_accounts(&repo).await;
+        let service = BankService::new(repo).with_payee_cooling_off_threshold(Amount::new(30));
+        let beneficiary = service
+            .add_beneficiary(
+                "user-1",
+                CreateBeneficiary {
+                    name: " Savings ".to_string(),
+                    account_id: 2,
+                    nickname: Some(String::new()),
+                },
+            )
+            .await
+            .unwrap();
+        assert_eq!(beneficiary.name, "Savings");
+        assert_eq!(beneficiary.nickname, None);
+        let to_beneficiary = |amount, to_account_id| Transfer {
+            from_account_id: 1,
+            to_account_id,
+            amount: Amount::new(amount),
+            beneficiary_id: Some(beneficiary.id.clone()),
+            details: Default::default(),
+        };
+
+        service
+            .transfer("user-1", to_beneficiary(30, 0))
+            .await
+            .unwrap();
+        assert_eq!(service.find_account(2).await.unwrap().balance.inner(), 30);
+        assert_forbidden(service.transfer("user-1", to_beneficiary(31, 0)).await);
+        assert!(
+            service
+                .transfer("user-1", to_beneficiary(10, 3))
+                .await
+                .is_err()
+        );
+        // Beneficiaries belong to the user who saved them
+        assert!(
+            service
+                .transfer("someone-else", to_beneficiary(10, 0))
+                .await
+                .is_err()
+        );
+
+        let service = service.with_payee_cooling_off(Duration::zero());
+        service
+            .transfer("user-1", to_beneficiary(31, 2))
+            .await
+            .unwrap();
+        assert_eq!(service.find_account(2).await.unwrap().balance.inner(), 61);
+    }
+
+    #[tokio::test]
+    async fn test_batch_rows_pay_beneficiaries_with_cooling_off() {
+        let repo = Arc::new(InMemoryAccountRepository::new());
+        setup_batch_accounts(&repo).await;
+        let service = BankService::new(repo).with_payee_cooling_off_threshold(Amount::new(30));
+        let beneficiary = service
+            .add_beneficiary(
+                "user-1",
+                CreateBeneficiary {
+                    name: "Savings".to_string(),
+                    account_id: 2,
+                    nickname: None,
+                },
+            )
+            .await
+            .unwrap();
+        let to_beneficiary = |line, amount| {
+            let mut row = batch_row(line, 1, 0, amount);
+            row.transfer.beneficiary_id = Some(beneficiary.id.clone());
+            row
+        };
+
+        let report = service
+            .batch_transfer(
+                "user-1",
+                vec![to_beneficiary(1, 30), to_beneficiary(2, 31)],
+                BatchMode::BestEffort,
+            )
+            .await
+            .unwrap();
+        assert_eq!(report.results[0].status, BatchRowStatus::Completed);
+        assert_eq!(report.results[1].status, BatchRowStatus::Failed);
+        assert!(
+            report.results[1]
+                .reason
+                .as_deref()
+                .unwrap()
+                .contains("newly added beneficiary")
+        );
+        assert_eq!(service.find_account(2).await.unwrap().balance.inner(), 30);
+        assert_eq!(service.find_account(1).await.unwrap().balance.inner(), 70);
+    }
+
+    #[tokio::test]
+    async fn test_cancelled_payment_requests_can_be_restored() {
+        let repo = Arc::new(InMemoryAccountRepository::new());
+        setup_batch_accounts(&repo).await;
+        repo.save(Account {
+            id: 4,
+            name: "Requester".to_string(),
+            balance: Amount::new(0),
+            owner_id: "user-2".to_string(),
+            holders: Vec::new(),
+            closed: false,
+        })
+        .await
+        .unwrap();
+        let service = BankService::new(repo)
+            .with_payment_request_repository(Arc::new(InMemoryPaymentRequestRepository::new()));
+        let request = service
+            .create_payment_request(
+                "user-2",
+                "user-1",
+                CreatePaymentRequest {
+                    payer_email: "user-1@example.com".to_string(),
+                    to_account_id: 4,
+                    amount: Amount::new(10),
+                    description: "Lunch".to_string(),
+                    due_date: Utc::now().date_naive(),
+                },
+            )
+            .await
+            .unwrap();
+
+        let cancelled = service.cancel_payment_requests("user-2").await.unwrap();
+        assert_eq!(cancelled.len(), 1);
+        assert!(
+            service
+                .pay_payment_request("user-1", &request.id, 1)
+                .await
+                .is_err()
+        );
+
+        // The deletion did not go through: the request is payable again
+        service.restore_payment_requests(cancelled).await.unwrap();
+        let paid = service
+            .pay_payment_request("user-1", &request.id, 1)
+            .await
+            .unwrap();
+        assert_eq!(paid.status, PaymentRequestStatus::Paid);
+    }
+
+    #[tokio::test]
+    async fn test_payment_request_is_paid_once_or_expires() {
+        let repo = Arc::new(InMemoryAccountRepository::new());
+        setup_batch_accounts(&repo).await;
+        repo.save(Account {
+            id: 4,
+            name: "Requester".to_string(),
+            balance: Amount::new(0),
+            owner_id: "user-2".to_string(),
+            holders: Vec::new(),
+            closed: false,
+        })
+        .await
+        .unwrap();
+        let requests = Arc::new(InMemoryPaymentRequestRepository::new());
+        let service = BankService::new(repo).with_payment_request_repository(requests.clone());
+        let today = Utc::now().date_naive();
+        let ask = |amount| CreatePaymentRequest {
+            payer_email: "user-1@example.com".to_string(),
+            to_account_id: 4,
+            amount: Amount::new(amount),
+            description: "Concert tickets".to_string(),
+            due_date: today,
+        };
+
+        let too_much = service
+            .create_payment_request("user-2", "user-1", ask(150))
+            .await
+            .unwrap();
+        assert!(
+            service
+                .pay_payment_request("user-1", &too_much.id, 1)
+                .await
+                .is_err()
+        );
+        // A failed payment leaves the request open
+        let too_much = service
+            .get_payment_request("user-1", &too_much.id)
+            .await
+            .unwrap();
+        assert_eq!(too_much.status, PaymentRequestStatus::Pending);
+
+        let request = service
+            .create_payment_request("user-2", "user-1", ask(60))
+            .await
+            .unwrap();
+        assert_forbidden(service.pay_payment_request("user-2", &request.id, 4).await);
+        let paid = service
+            .pay_payment_request("user-1", &request.id, 1)
+            .await
+            .unwrap();
+        assert_eq!(paid.status, PaymentRequestStatus::Paid);
+        assert_eq!(paid.from_account_id, Some(1));
+        assert_eq!(service.find_account(4).await.unwrap().balance.inner(), 60);
+        assert!(
+            service
+                .pay_payment_request("user-1", &request.id, 1)
+                .await
+                .is_err()
+        );
+        assert!(
+            service
+                .get_payment_request("someone-else", &request.id)
+                .await
+                .is_err()
+        );
+
+        let mut overdue = too_much;
+        overdue.due_date = today - Duration::days(1);
+        requests
+            .save_payment_request(overdue.clone())
+            .await
+            .unwrap();
+        let listed = service.list_payment_requests("user-2").await.unwrap();
+        assert_eq!(listed.len(), 2);
+        let expired = listed.iter().find(|r| r.id == overdue.id).unwrap();
+        assert_eq!(expired.status, PaymentRequestStatus::Expired);
+        assert!(
+            service
+                .decline_payment_request("user-1", &overdue.id)
+                .await
+                .is_err()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_customer_policy_limits_outgoing_amounts() {
+        let repo = Arc::new(InMemoryAccountRepository::new());
+        setup_batch_accounts(&repo).await;
+        let service = BankService::new(repo.clone()).with_customer_policy(Arc::new(VerifiedOnly));
+        service
+            .deposit(
+                "user-1",
+                1,
+                Amount::new(1000),
+                TransactionDetails::default(),
+            )
+            .await
+            .unwrap();
+        for holder in ["pending-user", "verified-user"] {
+            service
+                .add_holder("user-1", 1, co_owner(holder))
+                .await
+                .unwrap();
+        }
+
+        let err = service
+            .withdraw(
+                "pending-user",
+                1,
+                Amount::new(600),
+                TransactionDetails::default(),
+            )
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<DomainError>(),
+            Some(DomainError::Forbidden(_))
+        ));
+        assert!(
+            service
+                .withdraw(
+                    "verified-user",
+                    1,
+                    Amount::new(600),
+                    TransactionDetails::default()
+                )
+                .await
+                .is_ok()
+        );
+
+        let rows = vec![batch_row(2, 1, 2, 100), batch_row(3, 1, 3, 501)];
+        let report = service
+            .batch_transfer("pending-user", rows, BatchMode::BestEffort)
+            .await
+            .unwrap();
+        assert_eq!(report.succeeded, 1);
+        assert_eq!(report.results[1].status, BatchRowStatus::Failed);
+        assert_eq!(
+            service
+                .get_account("user-1", 1)
+                .await
+                .unwrap()
+                .balance
+                .inner(),
+            400
+        );
+    }
+
     #[tokio::test]
     async fn test_get_account_retrieves_existing_account() {
         let repo = Arc::new(InMemoryAccountRepository::new());
@@ -253,10 +2462,13 @@ mod tests {
             id: 42,
             name: "Existing Account".to_string(),
             balance: Amount::new(100),
+            owner_id: "user-1".to_string(),
+            holders: Vec::new(),
+            closed: false,
         };
         repo.save(account.clone()).await.unwrap();
 
-        let retrieved = service.get_account(42).await.unwrap();
+        let retrieved = service.get_account("user-1", 42).await.unwrap();
         assert_eq!(retrieved.id, 42);
         assert_eq!(retrieved.name, "Existing Account");
         assert_eq!(retrieved.balance.inner(), 100);
@@ -267,7 +2479,7 @@ mod tests {
         let repo = Arc::new(InMemoryAccountRepository::new());
         let service = BankService::new(repo);
 
-        let result = service.get_account(999).await;
+        let result = service.get_account("user-1", 999).await;
         assert!(result.is_err());
 
         let error = result.unwrap_err();
@@ -286,10 +2498,16 @@ mod tests {
             id: 1,
             name: "Test".to_string(),
             balance: Amount::new(100),
+            owner_id: "user-1".to_string(),
+            holders: Vec::new(),
+            closed: false,
         };
         repo.save(account).await.unwrap();
 
-        let updated = service.deposit(1, Amount::new(50)).await.unwrap();
+        let updated = service
+            .deposit("user-1", 1, Amount::new(50), TransactionDetails::default())
+            .await
+            .unwrap();
         assert_eq!(updated.balance.inner(), 150);
     }
 
@@ -298,7 +2516,14 @@ mod tests {
         let repo = Arc::new(InMemoryAccountRepository::new());
         let service = BankService::new(repo);
 
-        let result = service.deposit(999, Amount::new(100)).await;
+        let result = service
+            .deposit(
+                "user-1",
+                999,
+                Amount::new(100),
+                TransactionDetails::default(),
+            )
+            .await;
         assert!(result.is_err());
     }
 
@@ -311,10 +2536,16 @@ mod tests {
             id: 1,
             name: "Test".to_string(),
             balance: Amount::new(100),
+            owner_id: "user-1".to_string(),
+            holders: Vec::new(),
+            closed: false,
         };
         repo.save(account).await.unwrap();
 
-        let updated = service.withdraw(1, Amount::new(30)).await.unwrap();
+        let updated = service
+            .withdraw("user-1", 1, Amount::new(30), TransactionDetails::default())
+            .await
+            .unwrap();
         assert_eq!(updated.balance.inner(), 70);
     }
 
@@ -327,10 +2558,15 @@ mod tests {
             id: 1,
             name: "Test".to_string(),
             balance: Amount::new(50),
+            owner_id: "user-1".to_string(),
+            holders: Vec::new(),
+            closed: false,
         };
         repo.save(account).await.unwrap();
 
-        let result = service.withdraw(1, Amount::new(100)).await;
+        let result = service
+            .withdraw("user-1", 1, Amount::new(100), TransactionDetails::default())
+            .await;
         assert!(result.is_err());
 
         let error = result.unwrap_err();
@@ -344,7 +2580,14 @@ mod tests {
         let repo = Arc::new(InMemoryAccountRepository::new());
         let service = BankService::new(repo);
 
-        let result = service.withdraw(999, Amount::new(100)).await;
+        let result = service
+            .withdraw(
+                "user-1",
+                999,
+                Amount::new(100),
+                TransactionDetails::default(),
+            )
+            .await;
         assert!(result.is_err());
     }
 
@@ -357,10 +2600,16 @@ mod tests {
             id: 1,
             name: "Test".to_string(),
             balance: Amount::new(100),
+            owner_id: "user-1".to_string(),
+            holders: Vec::new(),
+            closed: false,
         };
         repo.save(account).await.unwrap();
 
-        let updated = service.withdraw(1, Amount::new(100)).await.unwrap();
+        let updated = service
+            .withdraw("user-1", 1, Amount::new(100), TransactionDetails::default())
+            .await
+            .unwrap();
         assert_eq!(updated.balance.inner(), 0);
     }
 
@@ -373,11 +2622,17 @@ mod tests {
             id: 1,
             name: "Alice".to_string(),
             balance: Amount::new(100),
+            owner_id: "user-1".to_string(),
+            holders: Vec::new(),
+            closed: false,
         };
         let account2 = Account {
             id: 2,
             name: "Bob".to_string(),
             balance: Amount::new(50),
+            owner_id: "user-1".to_string(),
+            holders: Vec::new(),
+            closed: false,
         };
         repo.save(account1).await.unwrap();
         repo.save(account2).await.unwrap();
@@ -386,17 +2641,175 @@ mod tests {
             from_account_id: 1,
             to_account_id: 2,
             amount: Amount::new(30),
+            beneficiary_id: None,
+            details: Default::default(),
         };
 
-        service.transfer(transfer).await.unwrap();
+        service.transfer("user-1", transfer).await.unwrap();
 
-        let alice = service.get_account(1).await.unwrap();
-        let bob = service.get_account(2).await.unwrap();
+        let alice = service.get_account("user-1", 1).await.unwrap();
+        let bob = service.get_account("user-1", 2).await.unwrap();
 
         assert_eq!(alice.balance.inner(), 70);
         assert_eq!(bob.balance.inner(), 80);
     }
 
+    #[tokio::test]
+    async fn test_transfer_books_details_and_returns_receipt() {
+        let repo = Arc::new(InMemoryAccountRepository::new());
+        setup_batch_accounts(&repo).await;
+        repo.save(Account {
+            id: 4,
+            name: "Someone else's".to_string(),
+            balance: Amount::new(0),
+            owner_id: "user-2".to_string(),
+            holders: Vec::new(),
+            closed: false,
+        })
+        .await
+        .unwrap();
+        let service = BankService::new(repo.clone());
+        let details = TransactionDetails {
+            description: Some("Rent for March".to_string()),
+            reference: Some("INV-2024-03".to_string()),
+            metadata: [("category".to_string(), "housing".to_string())].into(),
+        };
+        let transfer = |to_account_id, details: TransactionDetails| Transfer {
+            from_account_id: 1,
+            to_account_id,
+            amount: Amount::new(10),
+            beneficiary_id: None,
+            details,
+        };
+
+        let receipt = service
+            .transfer("user-1", transfer(2, details.clone()))
+            .await
+            .unwrap();
+        assert_eq!(receipt.details, details);
+        assert_eq!(receipt.from_balance, Some(Amount::new(90)));
+        assert_eq!(receipt.to_balance, Some(Amount::new(10)));
+        let booked = repo.find_transactions_by_account(2).await.unwrap();
+        assert_eq!(
+            booked[0].transfer_id.as_deref(),
+            Some(receipt.transfer_id.as_str())
+        );
+        assert_eq!(booked[0].details, details);
+
+        // The balance of an account the caller does not hold stays hidden
+        let receipt = service
+            .transfer("user-1", transfer(4, TransactionDetails::default()))
+            .await
+            .unwrap();
+        assert_eq!(receipt.from_balance, Some(Amount::new(80)));
+        assert_eq!(receipt.to_balance, None);
+
+        let too_long = TransactionDetails {
+            reference: Some("R".repeat(36)),
+            ..Default::default()
+        };
+        let result = service.transfer("user-1", transfer(2, too_long)).await;
+        assert!(matches!(
+            result.unwrap_err().downcast_ref::<DomainError>(),
+            Some(DomainError::InvalidFields(_))
+        ));
+        assert_eq!(service.find_account(1).await.unwrap().balance.inner(), 80);
+    }
+
+    #[tokio::test]
+    async fn test_reversals_refund_up_to_the_transfer() {
+        let repo = Arc::new(InMemoryAccountRepository::new());
+        setup_batch_accounts(&repo).await;
+        let service = BankService::new(repo.clone());
+        let receipt = service
+            .transfer("user-1", batch_row(1, 1, 2, 60).transfer)
+            .await
+            .unwrap();
+        let reverse = |amount: Option<u64>| ReverseTransfer {
+            amount: amount.map(Amount::new),
+            reason: "Sent to the wrong account".to_string(),
+        };
+
+        let partial = service
+            .reverse_transfer("operator", &receipt.transfer_id, reverse(Some(20)))
+            .await
+            .unwrap();
+        assert_eq!(partial.amount.inner(), 20);
+        assert!(
+            service
+                .reverse_transfer("operator", &receipt.transfer_id, reverse(Some(41)))
+                .await
+                .is_err()
+        );
+        let rest = service
+            .reverse_transfer("operator", &receipt.transfer_id, reverse(None))
+            .await
+            .unwrap();
+        assert_eq!(rest.amount.inner(), 40);
+        assert_eq!(service.find_account(1).await.unwrap().balance.inner(), 100);
+        assert_eq!(service.find_account(2).await.unwrap().balance.inner(), 0);
+
+        // Neither the transfer nor its reversals can be reversed again
+        assert!(
+            service
+                .reverse_transfer("operator", &receipt.transfer_id, reverse(None))
+                .await
+                .is_err()
+        );
+        assert!(
+            service
+                .reverse_transfer("operator", &rest.reversal_transfer_id, reverse(None))
+                .await
+                .is_err()
+        );
+        let compensation = repo
+            .find_transactions_by_transfer(&rest.reversal_transfer_id)
+            .await
+            .unwrap();
+        assert_eq!(compensation.len(), 2);
+        assert_eq!(
+            compensation[0].details.metadata[REVERSAL_OF_METADATA_KEY],
+            receipt.transfer_id
+        );
+    }
+
+    #[tokio::test]
+    async fn test_reversal_shortfall_policy() {
+        let repo = Arc::new(InMemoryAccountRepository::new());
+        setup_batch_accounts(&repo).await;
+        let service = BankService::new(repo.clone());
+        let receipt = service
+            .transfer("user-1", batch_row(1, 1, 3, 50).transfer)
+            .await
+            .unwrap();
+        service
+            .withdraw("user-1", 3, Amount::new(30), TransactionDetails::default())
+            .await
+            .unwrap();
+        let reverse = || ReverseTransfer {
+            amount: None,
+            reason: "Fraud".to_string(),
+        };
+
+        let result = service
+            .reverse_transfer("operator", &receipt.transfer_id, reverse())
+            .await;
+        assert!(matches!(
+            result.unwrap_err().downcast_ref::<DomainError>(),
+            Some(DomainError::InsufficientFunds)
+        ));
+
+        let service = service.with_shortfall_policy(ShortfallPolicy::Partial);
+        let reversal = service
+            .reverse_transfer("operator", &receipt.transfer_id, reverse())
+            .await
+            .unwrap();
+        assert_eq!(reversal.amount.inner(), 20);
+        assert_eq!(reversal.requested_amount.inner(), 50);
+        assert_eq!(service.find_account(3).await.unwrap().balance.inner(), 0);
+        assert_eq!(service.find_account(1).await.unwrap().balance.inner(), 70);
+    }
+
     #[tokio::test]
     async fn test_transfer_returns_error_for_same_account() {
         let repo = Arc::new(InMemoryAccountRepository::new());
@@ -406,6 +2819,9 @@ mod tests {
             id: 1,
             name: "Test".to_string(),
             balance: Amount::new(100),
+            owner_id: "user-1".to_string(),
+            holders: Vec::new(),
+            closed: false,
         };
         repo.save(account).await.unwrap();
 
@@ -413,9 +2829,11 @@ mod tests {
             from_account_id: 1,
             to_account_id: 1,
             amount: Amount::new(50),
+            beneficiary_id: None,
+            details: Default::default(),
         };
 
-        let result = service.transfer(transfer).await;
+        let result = service.transfer("user-1", transfer).await;
         assert!(result.is_err());
 
         let error = result.unwrap_err();
@@ -433,11 +2851,17 @@ mod tests {
             id: 1,
             name: "Alice".to_string(),
             balance: Amount::new(50),
+            owner_id: "user-1".to_string(),
+            holders: Vec::new(),
+            closed: false,
         };
         let account2 = Account {
             id: 2,
             name: "Bob".to_string(),
             balance: Amount::new(100),
+            owner_id: "user-1".to_string(),
+            holders: Vec::new(),
+            closed: false,
         };
         repo.save(account1).await.unwrap();
         repo.save(account2).await.unwrap();
@@ -446,9 +2870,11 @@ mod tests {
             from_account_id: 1,
             to_account_id: 2,
             amount: Amount::new(100),
+            beneficiary_id: None,
+            details: Default::default(),
         };
 
-        let result = service.transfer(transfer).await;
+        let result = service.transfer("user-1", transfer).await;
         assert!(result.is_err());
 
         let error = result.unwrap_err();
@@ -466,6 +2892,9 @@ mod tests {
             id: 2,
             name: "Bob".to_string(),
             balance: Amount::new(100),
+            owner_id: "user-1".to_string(),
+            holders: Vec::new(),
+            closed: false,
         };
         repo.save(account2).await.unwrap();
 
@@ -473,9 +2902,11 @@ mod tests {
             from_account_id: 999,
             to_account_id: 2,
             amount: Amount::new(50),
+            beneficiary_id: None,
+            details: Default::default(),
         };
 
-        let result = service.transfer(transfer).await;
+        let result = service.transfer("user-1", transfer).await;
         assert!(result.is_err());
     }
 
@@ -488,6 +2919,9 @@ mod tests {
             id: 1,
             name: "Alice".to_string(),
             balance: Amount::new(100),
+            owner_id: "user-1".to_string(),
+            holders: Vec::new(),
+            closed: false,
         };
         repo.save(account1).await.unwrap();
 
@@ -495,9 +2929,11 @@ mod tests {
             from_account_id: 1,
             to_account_id: 999,
             amount: Amount::new(50),
+            beneficiary_id: None,
+            details: Default::default(),
         };
 
-        let result = service.transfer(transfer).await;
+        let result = service.transfer("user-1", transfer).await;
         assert!(result.is_err());
     }
 
@@ -510,14 +2946,26 @@ mod tests {
             id: 1,
             name: "Test".to_string(),
             balance: Amount::new(100),
+            owner_id: "user-1".to_string(),
+            holders: Vec::new(),
+            closed: false,
         };
         repo.save(account).await.unwrap();
 
-        service.deposit(1, Amount::new(50)).await.unwrap();
-        service.deposit(1, Amount::new(25)).await.unwrap();
-        service.deposit(1, Amount::new(10)).await.unwrap();
-
-        let final_account = service.get_account(1).await.unwrap();
+        service
+            .deposit("user-1", 1, Amount::new(50), TransactionDetails::default())
+            .await
+            .unwrap();
+        service
+            .deposit("user-1", 1, Amount::new(25), TransactionDetails::default())
+            .await
+            .unwrap();
+        service
+            .deposit("user-1", 1, Amount::new(10), TransactionDetails::default())
+            .await
+            .unwrap();
+
+        let final_account = service.get_account("user-1", 1).await.unwrap();
         assert_eq!(final_account.balance.inner(), 185);
     }
 
@@ -530,13 +2978,399 @@ mod tests {
             id: 1,
             name: "Test".to_string(),
             balance: Amount::new(100),
+            owner_id: "user-1".to_string(),
+            holders: Vec::new(),
+            closed: false,
         };
         repo.save(account).await.unwrap();
 
-        service.withdraw(1, Amount::new(30)).await.unwrap();
-        service.withdraw(1, Amount::new(20)).await.unwrap();
+        service
+            .withdraw("user-1", 1, Amount::new(30), TransactionDetails::default())
+            .await
+            .unwrap();
+        service
+            .withdraw("user-1", 1, Amount::new(20), TransactionDetails::default())
+            .await
+            .unwrap();
 
-        let final_account = service.get_account(1).await.unwrap();
+        let final_account = service.get_account("user-1", 1).await.unwrap();
         assert_eq!(final_account.balance.inner(), 50);
     }
+
+    async fn setup_batch_accounts(repo: &InMemoryAccountRepository) {
+        for (id, balance) in [(1, 100), (2, 0), (3, 0)] {
+            repo.save(Account {
+                id,
+                name: format!("Account {}", id),
+                balance: Amount::new(balance),
+                owner_id: "user-1".to_string(),
+                holders: Vec::new(),
+                closed: false,
+            })
+            .await
+            .unwrap();
+        }
+    }
+
+    fn batch_row(line: usize, from: u32, to: u32, amount: u64) -> BatchTransferRow {
+        BatchTransferRow {
+            line,
+            transfer: Transfer {
+                from_account_id: from,
+                to_account_id: to,
+                amount: Amount::new(amount),
+                beneficiary_id: None,
+                details: Default::default(),
+            },
+        }
+    }
+
+    #[tokio::test]
+    async fn test_batch_transfer_executes_all_valid_rows() {
+        let repo = Arc::new(InMemoryAccountRepository::new());
+        setup_batch_accounts(&repo).await;
+        let service = BankService::new(repo.clone());
+
+        let rows = vec![batch_row(2, 1, 2, 60), batch_row(3, 2, 3, 60)];
+        let report = service
+            .batch_transfer("user-1", rows, BatchMode::AllOrNothing)
+            .await
+            .unwrap();
+
+        assert_eq!(report.succeeded, 2);
+        assert_eq!(report.failed, 0);
+        assert_eq!(
+            service
+                .get_account("user-1", 1)
+                .await
+                .unwrap()
+                .balance
+                .inner(),
+            40
+        );
+        assert_eq!(
+            service
+                .get_account("user-1", 2)
+                .await
+                .unwrap()
+                .balance
+                .inner(),
+            0
+        );
+        assert_eq!(
+            service
+                .get_account("user-1", 3)
+                .await
+                .unwrap()
+                .balance
+                .inner(),
+            60
+        );
+    }
+
+    #[tokio::test]
+    async fn test_batch_transfer_all_or_nothing_rejects_whole_batch() {
+        let repo = Arc::new(InMemoryAccountRepository::new());
+        setup_batch_accounts(&repo).await;
+        let service = BankService::new(repo.clone());
+
+        let rows = vec![
+            batch_row(2, 1, 2, 60),
+            batch_row(3, 1, 3, 60),
+            batch_row(4, 1, 999, 10),
+        ];
+        let report = service
+            .batch_transfer("user-1", rows, BatchMode::AllOrNothing)
+            .await
+            .unwrap();
+
+        assert_eq!(report.succeeded, 0);
+        assert_eq!(report.results[0].status, BatchRowStatus::Rejected);
+        assert_eq!(report.results[1].status, BatchRowStatus::Failed);
+        assert_eq!(
+            report.results[1].reason.as_deref(),
+            Some("Insufficient funds")
+        );
+        assert_eq!(report.results[2].line, 4);
+        assert_eq!(
+            report.results[2].reason.as_deref(),
+            Some("Account not found")
+        );
+        assert_eq!(
+            service
+                .get_account("user-1", 1)
+                .await
+                .unwrap()
+                .balance
+                .inner(),
+            100
+        );
+    }
+
+    #[tokio::test]
+    async fn test_batch_transfer_validates_rows_like_single_transfers() {
+        let repo = Arc::new(InMemoryAccountRepository::new());
+        setup_batch_accounts(&repo).await;
+        repo.set_closed(3, true).await.unwrap();
+        let service = BankService::new(repo.clone()).with_customer_policy(Arc::new(VerifiedOnly));
+
+        let rows = vec![batch_row(2, 1, 2, 10), batch_row(3, 1, 3, 10)];
+        let report = service
+            .batch_transfer("user-1", rows, BatchMode::AllOrNothing)
+            .await
+            .unwrap();
+        assert_eq!(report.results[0].status, BatchRowStatus::Rejected);
+        assert_eq!(
+            report.results[1].reason.as_deref(),
+            Some("Validation error: Account 3 is closed")
+        );
+        assert_eq!(service.find_account(2).await.unwrap().balance.inner(), 0);
+
+        // The customer policy's limit applies to every row
+        let report = service
+            .batch_transfer(
+                "user-1",
+                vec![batch_row(2, 1, 2, 600)],
+                BatchMode::BestEffort,
+            )
+            .await
+            .unwrap();
+        assert!(
+            report.results[0]
+                .reason
+                .as_deref()
+                .unwrap()
+                .contains("limit of 500")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_batch_transfer_best_effort_executes_valid_rows() {
+        let repo = Arc::new(InMemoryAccountRepository::new());
+        setup_batch_accounts(&repo).await;
+        let service = BankService::new(repo.clone());
+
+        let rows = vec![
+            batch_row(2, 1, 2, 60),
+            batch_row(3, 1, 3, 60),
+            batch_row(4, 1, 1, 10),
+            batch_row(5, 1, 3, 40),
+        ];
+        let report = service
+            .batch_transfer("user-1", rows, BatchMode::BestEffort)
+            .await
+            .unwrap();
+
+        assert_eq!(report.succeeded, 2);
+        assert_eq!(report.failed, 2);
+        assert_eq!(report.results[2].reason.as_deref(), Some("Invalid amount"));
+        assert_eq!(
+            service
+                .get_account("user-1", 1)
+                .await
+                .unwrap()
+                .balance
+                .inner(),
+            0
+        );
+        assert_eq!(
+            service
+                .get_account("user-1", 2)
+                .await
+                .unwrap()
+                .balance
+                .inner(),
+            60
+        );
+        assert_eq!(
+            service
+                .get_account("user-1", 3)
+                .await
+                .unwrap()
+                .balance
+                .inner(),
+            40
+        );
+    }
+
+    // Account storage that goes down after `updates_left` balance updates
+    struct FailingUpdates {
+        inner: InMemoryAccountRepository,
+        updates_left: std::sync::atomic::AtomicUsize,
+    }
+
+    #[async_trait::async_trait]
+    impl AccountRepository for FailingUpdates {
+        async fn save(&self, account: Account) -> Result<()> {
+            self.inner.save(account).await
+        }
+        async fn find_by_id(&self, id: u32) -> Result<Option<Account>> {
+            self.inner.find_by_id(id).await
+        }
+        async fn find_by_owner(&self, owner_id: &str) -> Result<Vec<Account>> {
+            self.inner.find_by_owner(owner_id).await
+        }
+        async fn set_closed(&self, id: u32, closed: bool) -> Result<Option<Account>> {
+            self.inner.set_closed(id, closed).await
+        }
+        async fn update(&self, account: Account) -> Result<()> {
+            use std::sync::atomic::Ordering;
+            self.updates_left
+                .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |n| n.checked_sub(1))
+                .map_err(|_| anyhow::anyhow!("Storage unavailable"))?;
+            self.inner.update(account).await
+        }
+        async fn save_transaction(&self, transaction: Transaction) -> Result<()> {
+            self.inner.save_transaction(transaction).await
+        }
+        async fn find_transactions_by_account(&self, account_id: u32) -> Result<Vec<Transaction>> {
+            self.inner.find_transactions_by_account(account_id).await
+        }
+        async fn find_transactions_by_transfer(
+            &self,
+            transfer_id: &str,
+        ) -> Result<Vec<Transaction>> {
+            self.inner.find_transactions_by_transfer(transfer_id).await
+        }
+    }
+
+    #[tokio::test]
+    async fn test_batch_transfer_reports_rows_the_rollback_could_not_undo() {
+        let inner = InMemoryAccountRepository::new();
+        setup_batch_accounts(&inner).await;
+        // Enough for the first row only; the second row and the rollback both fail
+        let repo = Arc::new(FailingUpdates {
+            inner,
+            updates_left: 2.into(),
+        });
+        let service = BankService::new(repo);
+
+        let rows = vec![batch_row(2, 1, 2, 60), batch_row(3, 1, 3, 30)];
+        let report = service
+            .batch_transfer("user-1", rows, BatchMode::AllOrNothing)
+            .await
+            .unwrap();
+
+        assert_eq!(report.succeeded, 1);
+        assert_eq!(report.results[0].status, BatchRowStatus::Completed);
+        assert!(
+            report.results[0]
+                .reason
+                .as_deref()
+                .unwrap()
+                .contains("rollback failed")
+        );
+        assert_eq!(report.results[1].status, BatchRowStatus::Failed);
+        assert_eq!(service.find_account(2).await.unwrap().balance.inner(), 60);
+    }
+
+    #[tokio::test]
+    async fn test_batch_transfer_rejects_empty_batch() {
+        let repo = Arc::new(InMemoryAccountRepository::new());
+        let service = BankService::new(repo);
+
+        let result = service
+            .batch_transfer("user-1", vec![], BatchMode::BestEffort)
+            .await;
+        assert!(matches!(
+            result.unwrap_err().downcast::<DomainError>(),
+            Ok(DomainError::Validation(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_operations_are_recorded_in_statement() {
+        let repo = Arc::new(InMemoryAccountRepository::new());
+        setup_batch_accounts(&repo).await;
+        let service = BankService::new(repo.clone());
+
+        service
+            .deposit("user-1", 2, Amount::new(50), TransactionDetails::default())
+            .await
+            .unwrap();
+        service
+            .transfer(
+                "user-1",
+                Transfer {
+                    from_account_id: 1,
+                    to_account_id: 2,
+                    amount: Amount::new(30),
+                    beneficiary_id: None,
+                    details: Default::default(),
+                },
+            )
+            .await
+            .unwrap();
+        service
+            .withdraw("user-1", 2, Amount::new(20), TransactionDetails::default())
+            .await
+            .unwrap();
+
+        let statement = service
+            .get_statement("user-1", 2, None, None)
+            .await
+            .unwrap();
+        assert_eq!(statement.opening_balance.inner(), 0);
+        assert_eq!(statement.closing_balance.inner(), 60);
+        let kinds: Vec<TransactionKind> = statement.transactions.iter().map(|t| t.kind).collect();
+        assert_eq!(
+            kinds,
+            vec![
+                TransactionKind::Deposit,
+                TransactionKind::TransferIn,
+                TransactionKind::Withdrawal
+            ]
+        );
+        assert_eq!(statement.transactions[1].counterparty_account_id, Some(1));
+
+        let source = service
+            .get_statement("user-1", 1, None, None)
+            .await
+            .unwrap();
+        assert_eq!(source.transactions[0].kind, TransactionKind::TransferOut);
+        assert_eq!(source.transactions[0].balance_after.inner(), 70);
+    }
+
+    #[tokio::test]
+    async fn test_statement_period_sets_opening_balance() {
+        let repo = Arc::new(InMemoryAccountRepository::new());
+        setup_batch_accounts(&repo).await;
+        let service = BankService::new(repo.clone());
+
+        service
+            .deposit("user-1", 2, Amount::new(50), TransactionDetails::default())
+            .await
+            .unwrap();
+        let from = Utc::now();
+        service
+            .deposit("user-1", 2, Amount::new(25), TransactionDetails::default())
+            .await
+            .unwrap();
+
+        let statement = service
+            .get_statement("user-1", 2, Some(from), None)
+            .await
+            .unwrap();
+        assert_eq!(statement.opening_balance.inner(), 50);
+        assert_eq!(statement.closing_balance.inner(), 75);
+        assert_eq!(statement.transactions.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_statement_rejects_inverted_period() {
+        let repo = Arc::new(InMemoryAccountRepository::new());
+        setup_batch_accounts(&repo).await;
+        let service = BankService::new(repo);
+
+        let now = Utc::now();
+        let result = service
+            .get_statement(
+                "user-1",
+                1,
+                Some(now),
+                Some(now - chrono::Duration::hours(1)),
+            )
+            .await;
+        assert!(result.is_err());
+    }
 }
@@ -1,2 +1,14 @@
+pub mod api_key_repository;
+pub mod beneficiary_repository;
+pub mod kyc_repository;
+pub mod login_attempt_repository;
 pub mod memory;
+pub mod oidc_repository;
+pub mod p2p_repository;
+pub mod payment_request_repository;
+pub mod reversal_repository;
+pub mod revocation_store;
+pub mod session_repository;
+pub mod token_repository;
+pub mod two_factor_repository;
 pub mod user_repository;
@@ -0,0 +1,126 @@
+use crate::domain::api_key::ApiKey;
+use crate::domain::repository::ApiKeyRepository;
+use anyhow::Result;
+use async_trait::async_trait;
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::sync::RwLock;
+use tracing::{debug, instrument, trace};
+
+#[derive(Clone)]
+pub struct InMemoryApiKeyRepository {
+    // key_hash -> key
+    storage: Arc<RwLock<HashMap<String, ApiKey>>>,
+}
+
+impl InMemoryApiKeyRepository {
+    pub fn new() -> Self {
+        Self {
+            storage: Arc::new(RwLock::new(HashMap::new())),
+        }
+    }
+}
+
+impl Default for InMemoryApiKeyRepository {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait]
+impl ApiKeyRepository for InMemoryApiKeyRepository {
+    #[instrument(skip(self, api_key), fields(key_id = %api_key.id, user_id = %api_key.user_id))]
+    async fn save_api_key(&self, api_key: ApiKey) -> Result<()> {
+        trace!("Acquiring write lock for API key storage");
+        let mut storage = self.storage.write().await;
+        debug!(key_id = %api_key.id, "API key saved");
+        storage.insert(api_key.key_hash.clone(), api_key);
+        Ok(())
+    }
+
+    #[instrument(skip(self, key_hash))]
+    async fn find_api_key_by_hash(&self, key_hash: &str) -> Result<Option<ApiKey>> {
+        trace!("Acquiring read lock for API key storage");
+        let storage = self.storage.read().await;
+        Ok(storage.get(key_hash).cloned())
+    }
+
+    #[instrument(skip(self))]
+    async fn list_user_api_keys(&self, user_id: &str) -> Result<Vec<ApiKey>> {
+        trace!("Acquiring read lock for API key storage");
+        let storage = self.storage.read().await;
+        let mut keys: Vec<ApiKey> = storage
+            .values()
+            .filter(|key| key.user_id == user_id)
+            .cloned()
+            .collect();
+        keys.sort_by(|a, b| a.created_at.cmp(&b.created_at).then(a.id.cmp(&b.id)));
+        Ok(keys)
+    }
+
+    #[instrument(skip(self))]
+    async fn delete_api_key(&self, user_id: &str, key_id: &str) -> Result<bool> {
+        trace!("Acquiring write lock for API key storage");
+        let mut storage = self.storage.write().await;
+        let before = storage.len();
+        storage.retain(|_, key| !(key.id == key_id && key.user_id == user_id));
+        let deleted = storage.len() < before;
+        if deleted {
+            debug!(key_id = key_id, user_id = user_id, "API key deleted");
+        }
+        Ok(deleted)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+
+    fn api_key(id: &str, user_id: &str) -> ApiKey {
+        ApiKey {
+            id: id.to_string(),
+            user_id: user_id.to_string(),
+            name: format!("key {}", id),
+            key_hash: format!("hash-{}", id),
+            prefix: "ybk_abcd".to_string(),
+            scopes: None,
+            created_at: Utc::now(),
+            expires_at: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_keys_are_found_by_hash_and_listed_per_user() {
+        let repo = InMemoryApiKeyRepository::new();
+        repo.save_api_key(api_key("1", "alice")).await.unwrap();
+        repo.save_api_key(api_key("2", "bob")).await.unwrap();
+        repo.save_api_key(api_key("3", "alice")).await.unwrap();
+
+        let found = repo.find_api_key_by_hash("hash-2").await.unwrap().unwrap();
+        assert_eq!(found.user_id, "bob");
+        assert!(repo.find_api_key_by_hash("hash-4").await.unwrap().is_none());
+
+        let ids: Vec<String> = repo
+            .list_user_api_keys("alice")
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|key| key.id)
+            .collect();
+        assert_eq!(ids, vec!["1", "3"]);
+    }
+
+    #[tokio::test]
+    async fn test_delete_api_key_only_deletes_own_keys() {
+        let repo = InMemoryApiKeyRepository::new();
+        repo.save_api_key(api_key("1", "alice")).await.unwrap();
+
+        assert!(!repo.delete_api_key("bob", "1").await.unwrap());
+        assert!(repo.find_api_key_by_hash("hash-1").await.unwrap().is_some());
+
+        assert!(repo.delete_api_key("alice", "1").await.unwrap());
+        assert!(repo.find_api_key_by_hash("hash-1").await.unwrap().is_none());
+        assert!(!repo.delete_api_key("alice", "1").await.unwrap());
+    }
+}
@@ -0,0 +1,119 @@
+use crate::domain::beneficiary::Beneficiary;
+use crate::domain::repository::BeneficiaryRepository;
+use anyhow::Result;
+use async_trait::async_trait;
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::sync::RwLock;
+use tracing::{debug, instrument, trace};
+
+#[derive(Clone)]
+pub struct InMemoryBeneficiaryRepository {
+    // user_id -> beneficiaries, oldest first
+    beneficiaries: Arc<RwLock<HashMap<String, Vec<Beneficiary>>>>,
+}
+
+impl InMemoryBeneficiaryRepository {
+    pub fn new() -> Self {
+        Self {
+            beneficiaries: Arc::new(RwLock::new(HashMap::new())),
+        }
+    }
+}
+
+impl Default for InMemoryBeneficiaryRepository {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait]
+impl BeneficiaryRepository for InMemoryBeneficiaryRepository {
+    #[instrument(skip(self, beneficiary), fields(id = %beneficiary.id))]
+    async fn save_beneficiary(&self, beneficiary: Beneficiary) -> Result<()> {
+        trace!("Acquiring write lock for beneficiary storage");
+        let mut storage = self.beneficiaries.write().await;
+        let saved = storage.entry(beneficiary.user_id.clone()).or_default();
+        match saved.iter_mut().find(|b| b.id == beneficiary.id) {
+            Some(existing) => *existing = beneficiary,
+            None => {
+                debug!(user_id = %beneficiary.user_id, "Beneficiary added");
+                saved.push(beneficiary);
+            }
+        }
+        Ok(())
+    }
+
+    #[instrument(skip(self))]
+    async fn find_beneficiary(&self, user_id: &str, id: &str) -> Result<Option<Beneficiary>> {
+        trace!("Acquiring read lock for beneficiary storage");
+        let storage = self.beneficiaries.read().await;
+        Ok(storage
+            .get(user_id)
+            .and_then(|saved| saved.iter().find(|b| b.id == id).cloned()))
+    }
+
+    #[instrument(skip(self))]
+    async fn list_beneficiaries(&self, user_id: &str) -> Result<Vec<Beneficiary>> {
+        trace!("Acquiring read lock for beneficiary storage");
+        let storage = self.beneficiaries.read().await;
+        Ok(storage.get(user_id).cloned().unwrap_or_default())
+    }
+
+    #[instrument(skip(self))]
+    async fn delete_beneficiary(&self, user_id: &str, id: &str) -> Result<bool> {
+        trace!("Acquiring write lock for beneficiary storage");
+        let mut storage = self.beneficiaries.write().await;
+        let Some(saved) = storage.get_mut(user_id) else {
+            return Ok(false);
+        };
+        let before = saved.len();
+        saved.retain(|b| b.id != id);
+        Ok(saved.len() < before)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+
+    fn beneficiary(id: &str, user_id: &str, name: &str) -> Beneficiary {
+        Beneficiary {
+            id: id.to_string(),
+            user_id: user_id.to_string(),
+            name: name.to_string(),
+            account_id: 42,
+            nickname: None,
+            created_at: Utc::now(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_beneficiaries_are_private_to_their_user() {
+        let repo = InMemoryBeneficiaryRepository::new();
+        repo.save_beneficiary(beneficiary("b1", "alice", "Landlord"))
+            .await
+            .unwrap();
+        repo.save_beneficiary(beneficiary("b2", "alice", "Gym"))
+            .await
+            .unwrap();
+        repo.save_beneficiary(beneficiary("b1", "alice", "New landlord"))
+            .await
+            .unwrap();
+
+        let saved = repo.list_beneficiaries("alice").await.unwrap();
+        let names: Vec<_> = saved.iter().map(|b| b.name.as_str()).collect();
+        assert_eq!(names, vec!["New landlord", "Gym"]);
+        assert!(repo.find_beneficiary("bob", "b1").await.unwrap().is_none());
+        assert!(!repo.delete_beneficiary("bob", "b1").await.unwrap());
+
+        assert!(repo.delete_beneficiary("alice", "b1").await.unwrap());
+        assert!(
+            repo.find_beneficiary("alice", "b1")
+                .await
+                .unwrap()
+                .is_none()
+        );
+    }
+}
@@ -0,0 +1,96 @@
+use crate::domain::repository::KycRepository;
+use crate::domain::user::KycTransition;
+use anyhow::Result;
+use async_trait::async_trait;
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::sync::RwLock;
+use tracing::{debug, instrument, trace};
+
+#[derive(Clone)]
+pub struct InMemoryKycRepository {
+    // user_id -> transitions, oldest first
+    transitions: Arc<RwLock<HashMap<String, Vec<KycTransition>>>>,
+}
+
+impl InMemoryKycRepository {
+    pub fn new() -> Self {
+        Self {
+            transitions: Arc::new(RwLock::new(HashMap::new())),
+        }
+    }
+}
+
+impl Default for InMemoryKycRepository {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait]
+impl KycRepository for InMemoryKycRepository {
+    #[instrument(skip(self, transition), fields(user_id = %transition.user_id))]
+    async fn record_kyc_transition(&self, transition: KycTransition) -> Result<()> {
+        trace!("Acquiring write lock for KYC transition storage");
+        let mut storage = self.transitions.write().await;
+        debug!(
+            user_id = %transition.user_id,
+            from = %transition.from,
+            to = %transition.to,
+            "KYC transition recorded"
+        );
+        storage
+            .entry(transition.user_id.clone())
+            .or_default()
+            .push(transition);
+        Ok(())
+    }
+
+    #[instrument(skip(self))]
+    async fn list_kyc_transitions(&self, user_id: &str) -> Result<Vec<KycTransition>> {
+        trace!("Acquiring read lock for KYC transition storage");
+        let storage = self.transitions.read().await;
+        Ok(storage.get(user_id).cloned().unwrap_or_default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::user::KycStatus;
+    use chrono::Utc;
+
+    fn transition(user_id: &str, from: KycStatus, to: KycStatus) -> KycTransition {
+        KycTransition {
+            user_id: user_id.to_string(),
+            from,
+            to,
+            reason: "documents checked".to_string(),
+            operator_id: "operator-1".to_string(),
+            at: Utc::now(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_transitions_are_listed_per_user_in_order() {
+        let repo = InMemoryKycRepository::new();
+        repo.record_kyc_transition(transition(
+            "alice",
+            KycStatus::Unverified,
+            KycStatus::Pending,
+        ))
+        .await
+        .unwrap();
+        repo.record_kyc_transition(transition("bob", KycStatus::Unverified, KycStatus::Pending))
+            .await
+            .unwrap();
+        repo.record_kyc_transition(transition("alice", KycStatus::Pending, KycStatus::Verified))
+            .await
+            .unwrap();
+
+        let history = repo.list_kyc_transitions("alice").await.unwrap();
+        let statuses: Vec<_> = history.iter().map(|t| t.to).collect();
+        assert_eq!(statuses, vec![KycStatus::Pending, KycStatus::Verified]);
+        assert!(repo.list_kyc_transitions("carol").await.unwrap().is_empty());
+    }
+}
@@ -0,0 +1,143 @@
+use crate::domain::login_attempt::FailedAttempts;
+use crate::domain::repository::LoginAttemptRepository;
+use anyhow::Result;
+use async_trait::async_trait;
+use chrono::{DateTime, Duration, Utc};
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::sync::RwLock;
+use tracing::{debug, instrument, trace};
+
+#[derive(Clone)]
+pub struct InMemoryLoginAttemptRepository {
+    storage: Arc<RwLock<HashMap<String, FailedAttempts>>>,
+}
+
+impl InMemoryLoginAttemptRepository {
+    pub fn new() -> Self {
+        Self {
+            storage: Arc::new(RwLock::new(HashMap::new())),
+        }
+    }
+}
+
+impl Default for InMemoryLoginAttemptRepository {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait]
+impl LoginAttemptRepository for InMemoryLoginAttemptRepository {
+    #[instrument(skip(self))]
+    async fn find_failed_attempts(&self, key: &str) -> Result<Option<FailedAttempts>> {
+        trace!("Acquiring read lock for login attempt storage");
+        let storage = self.storage.read().await;
+        Ok(storage.get(key).cloned())
+    }
+
+    #[instrument(skip(self))]
+    async fn record_failed_attempt(
+        &self,
+        key: &str,
+        at: DateTime<Utc>,
+        window: Duration,
+    ) -> Result<FailedAttempts> {
+        trace!("Acquiring write lock for login attempt storage");
+        let mut storage = self.storage.write().await;
+        let attempts = storage
+            .entry(key.to_string())
+            .and_modify(|attempts| {
+                if at - attempts.last_failure >= window {
+                    attempts.count = 0;
+                }
+                attempts.count += 1;
+                attempts.last_failure = at;
+            })
+            .or_insert(FailedAttempts {
+                count: 1,
+                last_failure: at,
+            })
+            .clone();
+        debug!(
+            key = key,
+            count = attempts.count,
+            "Failed login attempt recorded"
+        );
+        Ok(attempts)
+    }
+
+    #[instrument(skip(self))]
+    async fn clear_failed_attempts(&self, key: &str) -> Result<()> {
+        trace!("Acquiring write lock for login attempt storage");
+        let mut storage = self.storage.write().await;
+        if storage.remove(key).is_some() {
+            debug!(key = key, "Failed login attempts cleared");
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const KEY: &str = "email:alice@x.com";
+
+    #[tokio::test]
+    async fn test_record_failed_attempt_counts_failures() {
+        let repo = InMemoryLoginAttemptRepository::new();
+        let now = Utc::now();
+
+        repo.record_failed_attempt(KEY, now, Duration::hours(1))
+            .await
+            .unwrap();
+        let attempts = repo
+            .record_failed_attempt(KEY, now, Duration::hours(1))
+            .await
+            .unwrap();
+
+        assert_eq!(attempts.count, 2);
+        assert_eq!(
+            repo.find_failed_attempts(KEY).await.unwrap(),
+            Some(attempts)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_record_failed_attempt_restarts_after_window() {
+        let repo = InMemoryLoginAttemptRepository::new();
+        let now = Utc::now();
+
+        for _ in 0..3 {
+            repo.record_failed_attempt(KEY, now - Duration::hours(2), Duration::hours(1))
+                .await
+                .unwrap();
+        }
+        let attempts = repo
+            .record_failed_attempt(KEY, now, Duration::hours(1))
+            .await
+            .unwrap();
+
+        assert_eq!(attempts.count, 1);
+        assert_eq!(attempts.last_failure, now);
+    }
+
+    #[tokio::test]
+    async fn test_clear_failed_attempts() {
+        let repo = InMemoryLoginAttemptRepository::new();
+        repo.record_failed_attempt(KEY, Utc::now(), Duration::hours(1))
+            .await
+            .unwrap();
+
+        repo.clear_failed_attempts(KEY).await.unwrap();
+
+        assert!(repo.find_failed_attempts(KEY).await.unwrap().is_none());
+        assert!(
+            repo.find_failed_attempts("email:bob@x.com")
+                .await
+                .unwrap()
+                .is_none()
+        );
+    }
+}
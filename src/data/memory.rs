@@ -1,4 +1,4 @@
-use crate::domain::models::Account;
+use crate::domain::models::{Account, Transaction};
 use crate::domain::repository::AccountRepository;
 use anyhow::Result;
 use async_trait::async_trait;
@@ -10,12 +10,14 @@ use tracing::{debug, instrument, trace};
 #[derive(Clone)]
 pub struct InMemoryAccountRepository {
     storage: Arc<RwLock<HashMap<u32, Account>>>,
+    transactions: Arc<RwLock<HashMap<u32, Vec<Transaction>>>>,
 }
 
 impl InMemoryAccountRepository {
     pub fn new() -> Self {
         Self {
             storage: Arc::new(RwLock::new(HashMap::new())),
+            transactions: Arc::new(RwLock::new(HashMap::new())),
         }
     }
 }
@@ -64,11 +66,33 @@ impl AccountRepository for InMemoryAccountRepository {
         Ok(account)
     }
 
+    #[instrument(skip(self))]
+    async fn find_by_owner(&self, owner_id: &str) -> Result<Vec<Account>> {
+        trace!("Acquiring read lock for storage");
+        let storage = self.storage.read().await;
+        let mut accounts: Vec<Account> = storage
+            .values()
+            .filter(|a| a.owner_id == owner_id)
+            .cloned()
+            .collect();
+        accounts.sort_by_key(|a| a.id);
+        debug!(
+            owner_id = owner_id,
+            count = accounts.len(),
+            "Accounts of owner loaded from storage"
+        );
+        Ok(accounts)
+    }
+
     #[instrument(skip(self), fields(account_id = account.id))]
-    async fn update(&self, account: Account) -> Result<()> {
+    async fn update(&self, mut account: Account) -> Result<()> {
         trace!("Acquiring write lock for storage");
         let mut storage = self.storage.write().await;
         trace!(account_id = account.id, "Updating account in storage");
+        // A copy read before the account was closed must not reopen it
+        if let Some(stored) = storage.get(&account.id) {
+            account.closed = stored.closed;
+        }
         storage.insert(account.id, account.clone());
         debug!(
             account_id = account.id,
@@ -77,12 +101,70 @@ impl AccountRepository for InMemoryAccountRepository {
         );
         Ok(())
     }
+
+    #[instrument(skip(self), fields(account_id = id))]
+    async fn set_closed(&self, id: u32, closed: bool) -> Result<Option<Account>> {
+        trace!("Acquiring write lock for storage");
+        let mut storage = self.storage.write().await;
+        Ok(storage.get_mut(&id).map(|account| {
+            account.closed = closed;
+            debug!(
+                account_id = id,
+                closed = closed,
+                "Account closed flag changed"
+            );
+            account.clone()
+        }))
+    }
+
+    #[instrument(skip(self), fields(transaction_id = %transaction.id, account_id = transaction.account_id))]
+    async fn save_transaction(&self, transaction: Transaction) -> Result<()> {
+        trace!("Acquiring write lock for transaction storage");
+        let mut transactions = self.transactions.write().await;
+        debug!(
+            transaction_id = %transaction.id,
+            account_id = transaction.account_id,
+            amount = transaction.amount.inner(),
+            "Transaction saved to memory storage"
+        );
+        transactions
+            .entry(transaction.account_id)
+            .or_default()
+            .push(transaction);
+        Ok(())
+    }
+
+    #[instrument(skip(self), fields(account_id = account_id))]
+    async fn find_transactions_by_account(&self, account_id: u32) -> Result<Vec<Transaction>> {
+        trace!("Acquiring read lock for transaction storage");
+        let transactions = self.transactions.read().await;
+        let found = transactions.get(&account_id).cloned().unwrap_or_default();
+        debug!(
+            account_id = account_id,
+            count = found.len(),
+            "Transactions loaded from storage"
+        );
+        Ok(found)
+    }
+
+    #[instrument(skip(self))]
+    async fn find_transactions_by_transfer(&self, transfer_id: &str) -> Result<Vec<Transaction>> {
+        trace!("Acquiring read lock for transaction storage");
+        let transactions = self.transactions.read().await;
+        Ok(transactions
+            .values()
+            .flatten()
+            .filter(|t| t.transfer_id.as_deref() == Some(transfer_id))
+            .cloned()
+            .collect())
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::domain::models::{Account, Amount};
+    use crate::domain::models::{Account, Amount, TransactionKind};
+    use chrono::Utc;
 
     #[tokio::test]
     async fn test_save_saves_account_correctly() {
@@ -91,6 +173,9 @@ mod tests {
             id: 1,
             name: "Test Account".to_string(),
             balance: Amount::new(100),
+            owner_id: "user-1".to_string(),
+            holders: Vec::new(),
+            closed: false,
         };
 
         repo.save(account.clone()).await.unwrap();
@@ -110,6 +195,9 @@ mod tests {
             id: 42,
             name: "Found Account".to_string(),
             balance: Amount::new(500),
+            owner_id: "user-1".to_string(),
+            holders: Vec::new(),
+            closed: false,
         };
 
         repo.save(account.clone()).await.unwrap();
@@ -121,6 +209,30 @@ mod tests {
         assert_eq!(found_account.name, "Found Account");
     }
 
+    #[tokio::test]
+    async fn test_update_does_not_reopen_closed_account() {
+        let repo = InMemoryAccountRepository::new();
+        let mut account = Account {
+            id: 7,
+            name: "Closing".to_string(),
+            balance: Amount::new(0),
+            owner_id: "user-1".to_string(),
+            holders: Vec::new(),
+            closed: false,
+        };
+        repo.save(account.clone()).await.unwrap();
+
+        assert!(repo.set_closed(7, true).await.unwrap().unwrap().closed);
+        // A copy read before the closing
+        account.balance = Amount::new(10);
+        repo.update(account).await.unwrap();
+
+        let stored = repo.find_by_id(7).await.unwrap().unwrap();
+        assert!(stored.closed);
+        assert_eq!(stored.balance.inner(), 10);
+        assert!(repo.set_closed(8, true).await.unwrap().is_none());
+    }
+
     #[tokio::test]
     async fn test_find_by_id_returns_none_for_nonexistent_account() {
         let repo = InMemoryAccountRepository::new();
@@ -136,6 +248,9 @@ mod tests {
             id: 1,
             name: "Original Name".to_string(),
             balance: Amount::new(100),
+            owner_id: "user-1".to_string(),
+            holders: Vec::new(),
+            closed: false,
         };
 
         repo.save(account.clone()).await.unwrap();
@@ -157,11 +272,17 @@ mod tests {
             id: 1,
             name: "First".to_string(),
             balance: Amount::new(100),
+            owner_id: "user-1".to_string(),
+            holders: Vec::new(),
+            closed: false,
         };
         let account2 = Account {
             id: 1,
             name: "Second".to_string(),
             balance: Amount::new(200),
+            owner_id: "user-1".to_string(),
+            holders: Vec::new(),
+            closed: false,
         };
 
         repo.save(account1).await.unwrap();
@@ -179,6 +300,9 @@ mod tests {
             id: 1,
             name: "Concurrent".to_string(),
             balance: Amount::new(100),
+            owner_id: "user-1".to_string(),
+            holders: Vec::new(),
+            closed: false,
         };
 
         repo.save(account).await.unwrap();
@@ -210,6 +334,9 @@ mod tests {
                     id: i,
                     name: format!("Account {}", i),
                     balance: Amount::new(i as u64 * 10),
+                    owner_id: "user-1".to_string(),
+                    holders: Vec::new(),
+                    closed: false,
                 };
                 tokio::spawn(async move { repo_clone.save(account).await })
             })
@@ -236,6 +363,9 @@ mod tests {
                 id: i,
                 name: format!("Account {}", i),
                 balance: Amount::new(i as u64 * 100),
+                owner_id: "user-1".to_string(),
+                holders: Vec::new(),
+                closed: false,
             };
             repo.save(account).await.unwrap();
         }
@@ -247,4 +377,63 @@ mod tests {
             assert_eq!(found.unwrap().balance.inner(), i as u64 * 100);
         }
     }
+
+    #[tokio::test]
+    async fn test_find_by_owner_returns_only_their_accounts() {
+        let repo = InMemoryAccountRepository::new();
+        for (id, owner_id) in [(3, "alice"), (1, "bob"), (2, "alice")] {
+            repo.save(Account {
+                id,
+                name: format!("Account {}", id),
+                balance: Amount::new(0),
+                owner_id: owner_id.to_string(),
+                holders: Vec::new(),
+                closed: false,
+            })
+            .await
+            .unwrap();
+        }
+
+        let ids: Vec<u32> = repo
+            .find_by_owner("alice")
+            .await
+            .unwrap()
+            .iter()
+            .map(|a| a.id)
+            .collect();
+        assert_eq!(ids, vec![2, 3]);
+        assert!(repo.find_by_owner("carol").await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_transactions_are_stored_per_account_in_order() {
+        let repo = InMemoryAccountRepository::new();
+
+        for (i, account_id) in [1, 2, 1].into_iter().enumerate() {
+            repo.save_transaction(Transaction {
+                id: format!("tx-{}", i),
+                account_id,
+                kind: TransactionKind::Deposit,
+                amount: Amount::new(10),
+                balance_after: Amount::new(10),
+                counterparty_account_id: None,
+                transfer_id: None,
+                details: Default::default(),
+                created_at: Utc::now(),
+            })
+            .await
+            .unwrap();
+        }
+
+        let first = repo.find_transactions_by_account(1).await.unwrap();
+        assert_eq!(first.len(), 2);
+        assert_eq!(first[0].id, "tx-0");
+        assert_eq!(first[1].id, "tx-2");
+        assert!(
+            repo.find_transactions_by_account(3)
+                .await
+                .unwrap()
+                .is_empty()
+        );
+    }
 }
@@ -0,0 +1,161 @@
+use crate::domain::oidc::{ExternalIdentity, OidcAuthorization};
+use crate::domain::repository::OidcRepository;
+use anyhow::{Result, bail};
+use async_trait::async_trait;
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::sync::RwLock;
+use tracing::{debug, instrument, trace};
+
+#[derive(Clone)]
+pub struct InMemoryOidcRepository {
+    // state_hash -> pending authorization
+    authorizations: Arc<RwLock<HashMap<String, OidcAuthorization>>>,
+    // (issuer, subject) -> linked identity
+    identities: Arc<RwLock<HashMap<(String, String), ExternalIdentity>>>,
+}
+
+impl InMemoryOidcRepository {
+    pub fn new() -> Self {
+        Self {
+            authorizations: Arc::new(RwLock::new(HashMap::new())),
+            identities: Arc::new(RwLock::new(HashMap::new())),
+        }
+    }
+}
+
+impl Default for InMemoryOidcRepository {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait]
+impl OidcRepository for InMemoryOidcRepository {
+    #[instrument(skip(self, authorization))]
+    async fn save_authorization(&self, authorization: OidcAuthorization) -> Result<()> {
+        trace!("Acquiring write lock for OIDC authorization storage");
+        let mut storage = self.authorizations.write().await;
+        // Abandoned logins are useless, drop them while we hold the lock anyway
+        let now = chrono::Utc::now();
+        storage.retain(|_, a| a.expires_at > now);
+        storage.insert(authorization.state_hash.clone(), authorization);
+        Ok(())
+    }
+
+    #[instrument(skip(self, state_hash))]
+    async fn take_authorization(&self, state_hash: &str) -> Result<Option<OidcAuthorization>> {
+        trace!("Acquiring write lock for OIDC authorization storage");
+        let mut storage = self.authorizations.write().await;
+        Ok(storage.remove(state_hash))
+    }
+
+    #[instrument(skip(self, identity), fields(issuer = %identity.issuer, user_id = %identity.user_id))]
+    async fn link_identity(&self, identity: ExternalIdentity) -> Result<()> {
+        trace!("Acquiring write lock for external identity storage");
+        let mut storage = self.identities.write().await;
+        let key = (identity.issuer.clone(), identity.subject.clone());
+        if storage.contains_key(&key) {
+            bail!("External identity already linked: {}", identity.subject);
+        }
+        debug!(user_id = %identity.user_id, "External identity linked");
+        storage.insert(key, identity);
+        Ok(())
+    }
+
+    #[instrument(skip(self))]
+    async fn find_identity(&self, issuer: &str, subject: &str) -> Result<Option<ExternalIdentity>> {
+        trace!("Acquiring read lock for external identity storage");
+        let storage = self.identities.read().await;
+        Ok(storage
+            .get(&(issuer.to_string(), subject.to_string()))
+            .cloned())
+    }
+
+    #[instrument(skip(self))]
+    async fn delete_user_identities(&self, user_id: &str) -> Result<()> {
+        trace!("Acquiring write lock for external identity storage");
+        self.identities
+            .write()
+            .await
+            .retain(|_, i| i.user_id != user_id);
+        self.authorizations
+            .write()
+            .await
+            .retain(|_, a| a.link_user_id.as_deref() != Some(user_id));
+        debug!(user_id = user_id, "External identities unlinked");
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Duration, Utc};
+
+    fn identity(user_id: &str) -> ExternalIdentity {
+        ExternalIdentity {
+            issuer: "https://idp.example.com".to_string(),
+            subject: "sub-1".to_string(),
+            user_id: user_id.to_string(),
+            linked_at: Utc::now(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_authorization_can_be_taken_once() {
+        let repo = InMemoryOidcRepository::new();
+        repo.save_authorization(OidcAuthorization {
+            state_hash: "hash".to_string(),
+            code_verifier: "verifier".to_string(),
+            nonce: "nonce".to_string(),
+            link_user_id: None,
+            binding_hash: "binding".to_string(),
+            expires_at: Utc::now() + Duration::minutes(10),
+        })
+        .await
+        .unwrap();
+
+        assert!(repo.take_authorization("hash").await.unwrap().is_some());
+        assert!(repo.take_authorization("hash").await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_subject_is_linked_to_one_user_only() {
+        let repo = InMemoryOidcRepository::new();
+        repo.link_identity(identity("alice")).await.unwrap();
+
+        assert!(repo.link_identity(identity("bob")).await.is_err());
+
+        let found = repo
+            .find_identity("https://idp.example.com", "sub-1")
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(found.user_id, "alice");
+        assert!(
+            repo.find_identity("https://other.example.com", "sub-1")
+                .await
+                .unwrap()
+                .is_none()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_delete_user_identities_keeps_other_users() {
+        let repo = InMemoryOidcRepository::new();
+        repo.link_identity(identity("alice")).await.unwrap();
+        repo.link_identity(ExternalIdentity {
+            subject: "sub-2".to_string(),
+            ..identity("bob")
+        })
+        .await
+        .unwrap();
+
+        repo.delete_user_identities("alice").await.unwrap();
+
+        let issuer = "https://idp.example.com";
+        assert!(repo.find_identity(issuer, "sub-1").await.unwrap().is_none());
+        assert!(repo.find_identity(issuer, "sub-2").await.unwrap().is_some());
+    }
+}
@@ -0,0 +1,186 @@
+use crate::domain::p2p::{ClaimStatus, ClaimableTransfer};
+use crate::domain::repository::ClaimableTransferRepository;
+use crate::domain::validation::email_identity;
+use anyhow::Result;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::sync::RwLock;
+use tracing::{debug, instrument, trace};
+
+#[derive(Clone)]
+pub struct InMemoryClaimableTransferRepository {
+    // id -> transfer
+    transfers: Arc<RwLock<HashMap<String, ClaimableTransfer>>>,
+}
+
+impl InMemoryClaimableTransferRepository {
+    pub fn new() -> Self {
+        Self {
+            transfers: Arc::new(RwLock::new(HashMap::new())),
+        }
+    }
+}
+
+impl Default for InMemoryClaimableTransferRepository {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait]
+impl ClaimableTransferRepository for InMemoryClaimableTransferRepository {
+    #[instrument(skip(self, transfer), fields(id = %transfer.id))]
+    async fn save_claimable_transfer(&self, transfer: ClaimableTransfer) -> Result<()> {
+        trace!("Acquiring write lock for claimable transfer storage");
+        let mut storage = self.transfers.write().await;
+        storage.insert(transfer.id.clone(), transfer);
+        Ok(())
+    }
+
+    #[instrument(skip(self))]
+    async fn find_claimable_transfer(&self, id: &str) -> Result<Option<ClaimableTransfer>> {
+        trace!("Acquiring read lock for claimable transfer storage");
+        let storage = self.transfers.read().await;
+        Ok(storage.get(id).cloned())
+    }
+
+    #[instrument(skip(self, recipient_email))]
+    async fn list_claimable_transfers(
+        &self,
+        sender_id: &str,
+        recipient_email: Option<&str>,
+    ) -> Result<Vec<ClaimableTransfer>> {
+        trace!("Acquiring read lock for claimable transfer storage");
+        let storage = self.transfers.read().await;
+        let identity = recipient_email.map(email_identity);
+        let mut transfers: Vec<_> = storage
+            .values()
+            .filter(|t| {
+                t.sender_id == sender_id
+                    || identity
+                        .as_deref()
+                        .is_some_and(|identity| email_identity(&t.recipient_email) == identity)
+            })
+            .cloned()
+            .collect();
+        transfers.sort_by_key(|t| std::cmp::Reverse(t.created_at));
+        Ok(transfers)
+    }
+
+    #[instrument(skip(self))]
+    async fn list_expired_claimable_transfers(
+        &self,
+        now: DateTime<Utc>,
+    ) -> Result<Vec<ClaimableTransfer>> {
+        trace!("Acquiring read lock for claimable transfer storage");
+        let storage = self.transfers.read().await;
+        Ok(storage
+            .values()
+            .filter(|t| t.status == ClaimStatus::Pending && t.is_expired(now))
+            .cloned()
+            .collect())
+    }
+
+    #[instrument(skip(self))]
+    async fn settle_claimable_transfer(
+        &self,
+        id: &str,
+        status: ClaimStatus,
+        at: DateTime<Utc>,
+    ) -> Result<Option<ClaimableTransfer>> {
+        trace!("Acquiring write lock for claimable transfer storage");
+        let mut storage = self.transfers.write().await;
+        match storage.get_mut(id) {
+            Some(transfer) if transfer.status == ClaimStatus::Pending => {
+                transfer.status = status;
+                transfer.settled_at = Some(at);
+                debug!(id = id, status = ?status, "Claimable transfer settled");
+                Ok(Some(transfer.clone()))
+            }
+            _ => Ok(None),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::models::Amount;
+    use chrono::Duration;
+
+    fn transfer(id: &str, email: &str, expires_in: Duration) -> ClaimableTransfer {
+        let now = Utc::now();
+        ClaimableTransfer {
+            id: id.to_string(),
+            sender_id: "alice".to_string(),
+            from_account_id: 1,
+            recipient_email: email.to_string(),
+            amount: Amount::new(100),
+            status: ClaimStatus::Pending,
+            created_at: now,
+            expires_at: now + expires_in,
+            settled_at: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_transfer_is_settled_once() {
+        let repo = InMemoryClaimableTransferRepository::new();
+        repo.save_claimable_transfer(transfer("t1", "Bob@example.com", Duration::days(1)))
+            .await
+            .unwrap();
+
+        let claimed = repo
+            .settle_claimable_transfer("t1", ClaimStatus::Claimed, Utc::now())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(claimed.status, ClaimStatus::Claimed);
+        assert!(
+            repo.settle_claimable_transfer("t1", ClaimStatus::Refunded, Utc::now())
+                .await
+                .unwrap()
+                .is_none()
+        );
+        assert!(
+            repo.settle_claimable_transfer("unknown", ClaimStatus::Claimed, Utc::now())
+                .await
+                .unwrap()
+                .is_none()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_lists_by_sender_or_recipient_and_expired() {
+        let repo = InMemoryClaimableTransferRepository::new();
+        repo.save_claimable_transfer(transfer("t1", "Bob@example.com", Duration::days(1)))
+            .await
+            .unwrap();
+        repo.save_claimable_transfer(transfer("t2", "carol@example.com", -Duration::hours(1)))
+            .await
+            .unwrap();
+
+        let for_bob = repo
+            .list_claimable_transfers("bob", Some("bob@EXAMPLE.com"))
+            .await
+            .unwrap();
+        assert_eq!(for_bob.len(), 1);
+        assert_eq!(for_bob[0].id, "t1");
+        assert_eq!(
+            repo.list_claimable_transfers("alice", None)
+                .await
+                .unwrap()
+                .len(),
+            2
+        );
+
+        let expired = repo
+            .list_expired_claimable_transfers(Utc::now())
+            .await
+            .unwrap();
+        assert_eq!(expired.len(), 1);
+        assert_eq!(expired[0].id, "t2");
+    }
+}
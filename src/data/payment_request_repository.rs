@@ -0,0 +1,146 @@
+use crate::domain::payment_request::{PaymentRequest, PaymentRequestStatus};
+use crate::domain::repository::PaymentRequestRepository;
+use anyhow::Result;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::sync::RwLock;
+use tracing::{debug, instrument, trace};
+
+#[derive(Clone)]
+pub struct InMemoryPaymentRequestRepository {
+    // id -> request
+    requests: Arc<RwLock<HashMap<String, PaymentRequest>>>,
+}
+
+impl InMemoryPaymentRequestRepository {
+    pub fn new() -> Self {
+        Self {
+            requests: Arc::new(RwLock::new(HashMap::new())),
+        }
+    }
+}
+
+impl Default for InMemoryPaymentRequestRepository {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait]
+impl PaymentRequestRepository for InMemoryPaymentRequestRepository {
+    #[instrument(skip(self, request), fields(id = %request.id))]
+    async fn save_payment_request(&self, request: PaymentRequest) -> Result<()> {
+        trace!("Acquiring write lock for payment request storage");
+        let mut storage = self.requests.write().await;
+        storage.insert(request.id.clone(), request);
+        Ok(())
+    }
+
+    #[instrument(skip(self))]
+    async fn find_payment_request(&self, id: &str) -> Result<Option<PaymentRequest>> {
+        trace!("Acquiring read lock for payment request storage");
+        let storage = self.requests.read().await;
+        Ok(storage.get(id).cloned())
+    }
+
+    #[instrument(skip(self))]
+    async fn list_user_payment_requests(&self, user_id: &str) -> Result<Vec<PaymentRequest>> {
+        trace!("Acquiring read lock for payment request storage");
+        let storage = self.requests.read().await;
+        let mut requests: Vec<_> = storage
+            .values()
+            .filter(|r| r.requester_id == user_id || r.payer_id == user_id)
+            .cloned()
+            .collect();
+        requests.sort_by_key(|r| std::cmp::Reverse(r.created_at));
+        Ok(requests)
+    }
+
+    #[instrument(skip(self))]
+    async fn settle_payment_request(
+        &self,
+        id: &str,
+        status: PaymentRequestStatus,
+        at: DateTime<Utc>,
+    ) -> Result<Option<PaymentRequest>> {
+        trace!("Acquiring write lock for payment request storage");
+        let mut storage = self.requests.write().await;
+        match storage.get_mut(id) {
+            Some(request) if request.status == PaymentRequestStatus::Pending => {
+                request.status = status;
+                request.settled_at = Some(at);
+                debug!(id = id, status = ?status, "Payment request settled");
+                Ok(Some(request.clone()))
+            }
+            _ => Ok(None),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::models::Amount;
+    use chrono::NaiveDate;
+
+    fn request(id: &str, requester_id: &str, payer_id: &str) -> PaymentRequest {
+        PaymentRequest {
+            id: id.to_string(),
+            requester_id: requester_id.to_string(),
+            payer_id: payer_id.to_string(),
+            to_account_id: 1,
+            amount: Amount::new(100),
+            description: "Dinner".to_string(),
+            due_date: NaiveDate::from_ymd_opt(2030, 1, 1).unwrap(),
+            status: PaymentRequestStatus::Pending,
+            created_at: Utc::now(),
+            settled_at: None,
+            from_account_id: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_requests_are_listed_for_both_parties_and_settled_once() {
+        let repo = InMemoryPaymentRequestRepository::new();
+        repo.save_payment_request(request("r1", "alice", "bob"))
+            .await
+            .unwrap();
+        repo.save_payment_request(request("r2", "carol", "alice"))
+            .await
+            .unwrap();
+
+        assert_eq!(
+            repo.list_user_payment_requests("alice")
+                .await
+                .unwrap()
+                .len(),
+            2
+        );
+        assert_eq!(
+            repo.list_user_payment_requests("bob").await.unwrap().len(),
+            1
+        );
+        assert!(
+            repo.list_user_payment_requests("dave")
+                .await
+                .unwrap()
+                .is_empty()
+        );
+
+        let declined = repo
+            .settle_payment_request("r1", PaymentRequestStatus::Declined, Utc::now())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(declined.status, PaymentRequestStatus::Declined);
+        assert!(declined.settled_at.is_some());
+        assert!(
+            repo.settle_payment_request("r1", PaymentRequestStatus::Paid, Utc::now())
+                .await
+                .unwrap()
+                .is_none()
+        );
+    }
+}
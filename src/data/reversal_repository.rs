@@ -0,0 +1,104 @@
+use crate::domain::models::Amount;
+use crate::domain::repository::TransferReversalRepository;
+use crate::domain::reversal::TransferReversal;
+use anyhow::Result;
+use async_trait::async_trait;
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::sync::RwLock;
+use tracing::{debug, instrument, trace};
+
+#[derive(Clone)]
+pub struct InMemoryTransferReversalRepository {
+    // reversed transfer id -> reversals, oldest first
+    reversals: Arc<RwLock<HashMap<String, Vec<TransferReversal>>>>,
+}
+
+impl InMemoryTransferReversalRepository {
+    pub fn new() -> Self {
+        Self {
+            reversals: Arc::new(RwLock::new(HashMap::new())),
+        }
+    }
+}
+
+impl Default for InMemoryTransferReversalRepository {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait]
+impl TransferReversalRepository for InMemoryTransferReversalRepository {
+    #[instrument(skip(self, reversal), fields(transfer_id = %reversal.transfer_id))]
+    async fn add_reversal(&self, reversal: TransferReversal, limit: Amount) -> Result<bool> {
+        trace!("Acquiring write lock for reversal storage");
+        let mut storage = self.reversals.write().await;
+        let reversals = storage.entry(reversal.transfer_id.clone()).or_default();
+        let reversed: u64 = reversals.iter().map(|r| r.amount.inner()).sum();
+        if reversed + reversal.amount.inner() > limit.inner() {
+            debug!(reversed = reversed, "Reversal would exceed the transfer");
+            return Ok(false);
+        }
+        reversals.push(reversal);
+        Ok(true)
+    }
+
+    #[instrument(skip(self))]
+    async fn list_reversals(&self, transfer_id: &str) -> Result<Vec<TransferReversal>> {
+        trace!("Acquiring read lock for reversal storage");
+        let storage = self.reversals.read().await;
+        Ok(storage.get(transfer_id).cloned().unwrap_or_default())
+    }
+
+    #[instrument(skip(self))]
+    async fn remove_reversal(&self, transfer_id: &str, id: &str) -> Result<()> {
+        trace!("Acquiring write lock for reversal storage");
+        let mut storage = self.reversals.write().await;
+        if let Some(reversals) = storage.get_mut(transfer_id) {
+            reversals.retain(|r| r.id != id);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+
+    fn reversal(id: &str, amount: u64) -> TransferReversal {
+        TransferReversal {
+            id: id.to_string(),
+            transfer_id: "t1".to_string(),
+            reversal_transfer_id: format!("{}-transfer", id),
+            from_account_id: 2,
+            to_account_id: 1,
+            amount: Amount::new(amount),
+            requested_amount: Amount::new(amount),
+            reason: "Sent twice".to_string(),
+            operator_id: "operator".to_string(),
+            created_at: Utc::now(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_reversals_never_exceed_the_transfer() {
+        let repo = InMemoryTransferReversalRepository::new();
+        let limit = Amount::new(100);
+        assert!(repo.add_reversal(reversal("r1", 60), limit).await.unwrap());
+        assert!(!repo.add_reversal(reversal("r2", 41), limit).await.unwrap());
+        assert!(repo.add_reversal(reversal("r3", 40), limit).await.unwrap());
+        assert!(!repo.add_reversal(reversal("r4", 1), limit).await.unwrap());
+
+        repo.remove_reversal("t1", "r3").await.unwrap();
+        let ids: Vec<_> = repo
+            .list_reversals("t1")
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|r| r.id)
+            .collect();
+        assert_eq!(ids, vec!["r1"]);
+    }
+}
@@ -0,0 +1,214 @@
+use crate::domain::repository::RevocationStore;
+use anyhow::Result;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::sync::RwLock;
+use tracing::{debug, instrument, trace};
+
+#[derive(Clone)]
+pub struct InMemoryRevocationStore {
+    // jti -> expiry of the revoked token; entries are dropped once the token has expired
+    revoked_tokens: Arc<RwLock<HashMap<String, DateTime<Utc>>>>,
+    // user_id -> tokens issued at or before this instant are revoked
+    user_cutoffs: Arc<RwLock<HashMap<String, DateTime<Utc>>>>,
+    // session_id -> expiry of the session's last access token
+    revoked_sessions: Arc<RwLock<HashMap<String, DateTime<Utc>>>>,
+}
+
+impl InMemoryRevocationStore {
+    pub fn new() -> Self {
+        Self {
+            revoked_tokens: Arc::new(RwLock::new(HashMap::new())),
+            user_cutoffs: Arc::new(RwLock::new(HashMap::new())),
+            revoked_sessions: Arc::new(RwLock::new(HashMap::new())),
+        }
+    }
+}
+
+impl Default for InMemoryRevocationStore {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait]
+impl RevocationStore for InMemoryRevocationStore {
+    #[instrument(skip(self))]
+    async fn revoke_token(&self, jti: &str, expires_at: DateTime<Utc>) -> Result<()> {
+        trace!("Acquiring write lock for revoked tokens");
+        let mut revoked = self.revoked_tokens.write().await;
+        let now = Utc::now();
+        revoked.retain(|_, expiry| *expiry > now);
+        revoked.insert(jti.to_string(), expires_at);
+        debug!(jti = jti, revoked = revoked.len(), "Token revoked");
+        Ok(())
+    }
+
+    #[instrument(skip(self))]
+    async fn is_token_revoked(&self, jti: &str) -> Result<bool> {
+        trace!("Acquiring read lock for revoked tokens");
+        let revoked = self.revoked_tokens.read().await;
+        Ok(revoked.get(jti).is_some_and(|expiry| *expiry > Utc::now()))
+    }
+
+    #[instrument(skip(self))]
+    async fn revoke_user_tokens_before(&self, user_id: &str, cutoff: DateTime<Utc>) -> Result<()> {
+        trace!("Acquiring write lock for user cutoffs");
+        let mut cutoffs = self.user_cutoffs.write().await;
+        let entry = cutoffs.entry(user_id.to_string()).or_insert(cutoff);
+        // Never move the cutoff backwards
+        if cutoff > *entry {
+            *entry = cutoff;
+        }
+        debug!(user_id = user_id, cutoff = %entry, "User tokens revoked");
+        Ok(())
+    }
+
+    #[instrument(skip(self))]
+    async fn user_tokens_revoked_before(&self, user_id: &str) -> Result<Option<DateTime<Utc>>> {
+        trace!("Acquiring read lock for user cutoffs");
+        let cutoffs = self.user_cutoffs.read().await;
+        Ok(cutoffs.get(user_id).copied())
+    }
+
+    #[instrument(skip(self))]
+    async fn revoke_session(&self, session_id: &str, expires_at: DateTime<Utc>) -> Result<()> {
+        trace!("Acquiring write lock for revoked sessions");
+        let mut revoked = self.revoked_sessions.write().await;
+        let now = Utc::now();
+        revoked.retain(|_, expiry| *expiry > now);
+        revoked.insert(session_id.to_string(), expires_at);
+        debug!(session_id = session_id, "Session revoked");
+        Ok(())
+    }
+
+    #[instrument(skip(self))]
+    async fn is_session_revoked(&self, session_id: &str) -> Result<bool> {
+        trace!("Acquiring read lock for revoked sessions");
+        let revoked = self.revoked_sessions.read().await;
+        Ok(revoked
+            .get(session_id)
+            .is_some_and(|expiry| *expiry > Utc::now()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    #[tokio::test]
+    async fn test_revoked_token_is_reported_until_it_expires() {
+        let store = InMemoryRevocationStore::new();
+        store
+            .revoke_token("live", Utc::now() + Duration::hours(1))
+            .await
+            .unwrap();
+        store
+            .revoke_token("expired", Utc::now() - Duration::seconds(1))
+            .await
+            .unwrap();
+
+        assert!(store.is_token_revoked("live").await.unwrap());
+        assert!(!store.is_token_revoked("expired").await.unwrap());
+        assert!(!store.is_token_revoked("unknown").await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_revoke_token_drops_expired_entries() {
+        let store = InMemoryRevocationStore::new();
+        store
+            .revoke_token("expired", Utc::now() - Duration::seconds(1))
+            .await
+            .unwrap();
+        store
+            .revoke_token("live", Utc::now() + Duration::hours(1))
+            .await
+            .unwrap();
+
+        let revoked = store.revoked_tokens.read().await;
+        assert!(!revoked.contains_key("expired"));
+        assert!(revoked.contains_key("live"));
+    }
+
+    #[tokio::test]
+    async fn test_user_cutoff_revokes_tokens_issued_before_it() {
+        let store = InMemoryRevocationStore::new();
+        let cutoff = Utc::now();
+        store
+            .revoke_user_tokens_before("user-1", cutoff)
+            .await
+            .unwrap();
+
+        let before = cutoff - Duration::minutes(5);
+        let after = cutoff + Duration::seconds(1);
+        assert!(
+            store
+                .is_access_token_revoked("user-1", "jti-1", None, before)
+                .await
+                .unwrap()
+        );
+        assert!(
+            !store
+                .is_access_token_revoked("user-1", "jti-2", None, after)
+                .await
+                .unwrap()
+        );
+        assert!(
+            !store
+                .is_access_token_revoked("user-2", "jti-3", None, before)
+                .await
+                .unwrap()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_user_cutoff_never_moves_backwards() {
+        let store = InMemoryRevocationStore::new();
+        let cutoff = Utc::now();
+        store
+            .revoke_user_tokens_before("user-1", cutoff)
+            .await
+            .unwrap();
+        store
+            .revoke_user_tokens_before("user-1", cutoff - Duration::hours(1))
+            .await
+            .unwrap();
+
+        assert_eq!(
+            store.user_tokens_revoked_before("user-1").await.unwrap(),
+            Some(cutoff)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_revoked_session_revokes_its_access_tokens() {
+        let store = InMemoryRevocationStore::new();
+        store
+            .revoke_session("session-1", Utc::now() + Duration::minutes(15))
+            .await
+            .unwrap();
+
+        let now = Utc::now();
+        assert!(
+            store
+                .is_access_token_revoked("user-1", "jti-1", Some("session-1"), now)
+                .await
+                .unwrap()
+        );
+        assert!(
+            !store
+                .is_access_token_revoked("user-1", "jti-2", Some("session-2"), now)
+                .await
+                .unwrap()
+        );
+        assert!(
+            !store
+                .is_access_token_revoked("user-1", "jti-3", None, now)
+                .await
+                .unwrap()
+        );
+    }
+}
@@ -0,0 +1,120 @@
+use crate::domain::repository::SessionRepository;
+use crate::domain::session::Session;
+use anyhow::Result;
+use async_trait::async_trait;
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::sync::RwLock;
+use tracing::{debug, instrument, trace};
+
+#[derive(Clone)]
+pub struct InMemorySessionRepository {
+    // session_id -> session
+    sessions: Arc<RwLock<HashMap<String, Session>>>,
+}
+
+impl InMemorySessionRepository {
+    pub fn new() -> Self {
+        Self {
+            sessions: Arc::new(RwLock::new(HashMap::new())),
+        }
+    }
+}
+
+impl Default for InMemorySessionRepository {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait]
+impl SessionRepository for InMemorySessionRepository {
+    #[instrument(skip(self, session), fields(session_id = %session.id, user_id = %session.user_id))]
+    async fn save_session(&self, session: Session) -> Result<()> {
+        trace!("Acquiring write lock for session storage");
+        let mut storage = self.sessions.write().await;
+        // Sessions past their refresh token expiry cannot come back
+        let now = chrono::Utc::now();
+        storage.retain(|_, s| s.expires_at > now);
+        debug!("Session saved");
+        storage.insert(session.id.clone(), session);
+        Ok(())
+    }
+
+    #[instrument(skip(self))]
+    async fn find_session(&self, session_id: &str) -> Result<Option<Session>> {
+        trace!("Acquiring read lock for session storage");
+        let storage = self.sessions.read().await;
+        Ok(storage.get(session_id).cloned())
+    }
+
+    #[instrument(skip(self))]
+    async fn list_user_sessions(&self, user_id: &str) -> Result<Vec<Session>> {
+        trace!("Acquiring read lock for session storage");
+        let storage = self.sessions.read().await;
+        let mut sessions: Vec<Session> = storage
+            .values()
+            .filter(|s| s.user_id == user_id)
+            .cloned()
+            .collect();
+        sessions.sort_by_key(|s| s.created_at);
+        Ok(sessions)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Duration, Utc};
+
+    fn session(id: &str, user_id: &str, expires_in: Duration) -> Session {
+        let now = Utc::now();
+        Session {
+            id: id.to_string(),
+            user_id: user_id.to_string(),
+            user_agent: Some("curl/8.5.0".to_string()),
+            ip: Some("10.0.0.1".to_string()),
+            created_at: now,
+            last_seen_at: now,
+            expires_at: now + expires_in,
+            revoked_at: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_list_user_sessions_returns_only_that_user() {
+        let repo = InMemorySessionRepository::new();
+        repo.save_session(session("s1", "alice", Duration::days(1)))
+            .await
+            .unwrap();
+        repo.save_session(session("s2", "bob", Duration::days(1)))
+            .await
+            .unwrap();
+        repo.save_session(session("s3", "alice", Duration::days(1)))
+            .await
+            .unwrap();
+
+        let ids: Vec<String> = repo
+            .list_user_sessions("alice")
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|s| s.id)
+            .collect();
+        assert_eq!(ids, vec!["s1".to_string(), "s3".to_string()]);
+    }
+
+    #[tokio::test]
+    async fn test_save_session_drops_expired_sessions() {
+        let repo = InMemorySessionRepository::new();
+        repo.save_session(session("old", "alice", -Duration::seconds(1)))
+            .await
+            .unwrap();
+        repo.save_session(session("new", "alice", Duration::days(1)))
+            .await
+            .unwrap();
+
+        assert!(repo.find_session("old").await.unwrap().is_none());
+        assert!(repo.find_session("new").await.unwrap().is_some());
+    }
+}
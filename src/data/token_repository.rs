@@ -0,0 +1,398 @@
+use crate::domain::repository::{
+    EmailVerificationTokenRepository, PasswordResetTokenRepository, RefreshTokenRepository,
+};
+use crate::domain::token::{EmailVerificationToken, PasswordResetToken, RefreshToken};
+use anyhow::Result;
+use async_trait::async_trait;
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::sync::RwLock;
+use tracing::{debug, instrument, trace};
+
+#[derive(Clone)]
+pub struct InMemoryRefreshTokenRepository {
+    storage: Arc<RwLock<HashMap<String, RefreshToken>>>,
+}
+
+impl InMemoryRefreshTokenRepository {
+    pub fn new() -> Self {
+        Self {
+            storage: Arc::new(RwLock::new(HashMap::new())),
+        }
+    }
+}
+
+impl Default for InMemoryRefreshTokenRepository {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait]
+impl RefreshTokenRepository for InMemoryRefreshTokenRepository {
+    #[instrument(skip(self, token), fields(family_id = %token.family_id, user_id = %token.user_id))]
+    async fn save_refresh_token(&self, token: RefreshToken) -> Result<()> {
+        trace!("Acquiring write lock for refresh token storage");
+        let mut storage = self.storage.write().await;
+        // Expired tokens are useless, drop them while we hold the lock anyway
+        let now = chrono::Utc::now();
+        storage.retain(|_, t| t.expires_at > now);
+        debug!(
+            family_id = %token.family_id,
+            user_id = %token.user_id,
+            "Refresh token saved to memory storage"
+        );
+        storage.insert(token.token_hash.clone(), token);
+        Ok(())
+    }
+
+    #[instrument(skip(self, token_hash))]
+    async fn mark_refresh_token_used(&self, token_hash: &str) -> Result<Option<RefreshToken>> {
+        trace!("Acquiring write lock for refresh token storage");
+        let mut storage = self.storage.write().await;
+        let token = storage.get_mut(token_hash).map(|token| {
+            let previous = token.clone();
+            token.used = true;
+            previous
+        });
+        match &token {
+            Some(t) => debug!(family_id = %t.family_id, "Refresh token marked as used"),
+            None => trace!("Refresh token not found in storage"),
+        }
+        Ok(token)
+    }
+
+    #[instrument(skip(self, token_hash))]
+    async fn find_refresh_token(&self, token_hash: &str) -> Result<Option<RefreshToken>> {
+        trace!("Acquiring read lock for refresh token storage");
+        let storage = self.storage.read().await;
+        Ok(storage.get(token_hash).cloned())
+    }
+
+    #[instrument(skip(self))]
+    async fn revoke_refresh_token_family(&self, family_id: &str) -> Result<()> {
+        trace!("Acquiring write lock for refresh token storage");
+        let mut storage = self.storage.write().await;
+        let mut revoked = 0;
+        for token in storage.values_mut().filter(|t| t.family_id == family_id) {
+            token.revoked = true;
+            revoked += 1;
+        }
+        debug!(
+            family_id = family_id,
+            revoked = revoked,
+            "Refresh token family revoked"
+        );
+        Ok(())
+    }
+
+    #[instrument(skip(self))]
+    async fn revoke_user_refresh_tokens(&self, user_id: &str) -> Result<()> {
+        trace!("Acquiring write lock for refresh token storage");
+        let mut storage = self.storage.write().await;
+        let mut revoked = 0;
+        for token in storage.values_mut().filter(|t| t.user_id == user_id) {
+            token.revoked = true;
+            revoked += 1;
+        }
+        debug!(
+            user_id = user_id,
+            revoked = revoked,
+            "User refresh tokens revoked"
+        );
+        Ok(())
+    }
+}
+
+#[derive(Clone)]
+pub struct InMemoryPasswordResetTokenRepository {
+    storage: Arc<RwLock<HashMap<String, PasswordResetToken>>>,
+}
+
+impl InMemoryPasswordResetTokenRepository {
+    pub fn new() -> Self {
+        Self {
+            storage: Arc::new(RwLock::new(HashMap::new())),
+        }
+    }
+}
+
+impl Default for InMemoryPasswordResetTokenRepository {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait]
+impl PasswordResetTokenRepository for InMemoryPasswordResetTokenRepository {
+    #[instrument(skip(self, token), fields(user_id = %token.user_id))]
+    async fn save_reset_token(&self, token: PasswordResetToken) -> Result<()> {
+        trace!("Acquiring write lock for reset token storage");
+        let mut storage = self.storage.write().await;
+        let now = chrono::Utc::now();
+        storage.retain(|_, t| t.expires_at > now);
+        debug!(user_id = %token.user_id, "Password reset token saved to memory storage");
+        storage.insert(token.token_hash.clone(), token);
+        Ok(())
+    }
+
+    #[instrument(skip(self, token_hash))]
+    async fn take_reset_token(&self, token_hash: &str) -> Result<Option<PasswordResetToken>> {
+        trace!("Acquiring write lock for reset token storage");
+        let mut storage = self.storage.write().await;
+        let token = storage.remove(token_hash);
+        match &token {
+            Some(t) => debug!(user_id = %t.user_id, "Password reset token redeemed"),
+            None => trace!("Password reset token not found in storage"),
+        }
+        Ok(token)
+    }
+
+    #[instrument(skip(self))]
+    async fn delete_user_reset_tokens(&self, user_id: &str) -> Result<()> {
+        trace!("Acquiring write lock for reset token storage");
+        let mut storage = self.storage.write().await;
+        storage.retain(|_, t| t.user_id != user_id);
+        debug!(user_id = user_id, "Password reset tokens deleted");
+        Ok(())
+    }
+}
+
+#[derive(Clone)]
+pub struct InMemoryEmailVerificationTokenRepository {
+    storage: Arc<RwLock<HashMap<String, EmailVerificationToken>>>,
+}
+
+impl InMemoryEmailVerificationTokenRepository {
+    pub fn new() -> Self {
+        Self {
+            storage: Arc::new(RwLock::new(HashMap::new())),
+        }
+    }
+}
+
+impl Default for InMemoryEmailVerificationTokenRepository {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait]
+impl EmailVerificationTokenRepository for InMemoryEmailVerificationTokenRepository {
+    #[instrument(skip(self, token), fields(user_id = %token.user_id))]
+    async fn save_verification_token(&self, token: EmailVerificationToken) -> Result<()> {
+        trace!("Acquiring write lock for verification token storage");
+        let mut storage = self.storage.write().await;
+        let now = chrono::Utc::now();
+        storage.retain(|_, t| t.expires_at > now);
+        debug!(user_id = %token.user_id, "Email verification token saved to memory storage");
+        storage.insert(token.token_hash.clone(), token);
+        Ok(())
+    }
+
+    #[instrument(skip(self, token_hash))]
+    async fn take_verification_token(
+        &self,
+        token_hash: &str,
+    ) -> Result<Option<EmailVerificationToken>> {
+        trace!("Acquiring write lock for verification token storage");
+        let mut storage = self.storage.write().await;
+        let token = storage.remove(token_hash);
+        match &token {
+            Some(t) => debug!(user_id = %t.user_id, "Email verification token redeemed"),
+            None => trace!("Email verification token not found in storage"),
+        }
+        Ok(token)
+    }
+
+    #[instrument(skip(self))]
+    async fn list_user_verification_tokens(
+        &self,
+        user_id: &str,
+    ) -> Result<Vec<EmailVerificationToken>> {
+        trace!("Acquiring read lock for verification token storage");
+        let storage = self.storage.read().await;
+        let now = chrono::Utc::now();
+        Ok(storage
+            .values()
+            .filter(|t| t.user_id == user_id && t.expires_at > now)
+            .cloned()
+            .collect())
+    }
+
+    #[instrument(skip(self))]
+    async fn delete_user_verification_tokens(&self, user_id: &str) -> Result<()> {
+        trace!("Acquiring write lock for verification token storage");
+        let mut storage = self.storage.write().await;
+        storage.retain(|_, t| t.user_id != user_id);
+        debug!(user_id = user_id, "Email verification tokens deleted");
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Duration, Utc};
+
+    fn refresh_token(hash: &str, family_id: &str) -> RefreshToken {
+        RefreshToken {
+            token_hash: hash.to_string(),
+            family_id: family_id.to_string(),
+            user_id: "user-1".to_string(),
+            created_at: Utc::now(),
+            expires_at: Utc::now() + Duration::days(1),
+            used: false,
+            revoked: false,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_mark_refresh_token_used_returns_previous_state() {
+        let repo = InMemoryRefreshTokenRepository::new();
+        repo.save_refresh_token(refresh_token("hash-1", "family-1"))
+            .await
+            .unwrap();
+
+        let first = repo.mark_refresh_token_used("hash-1").await.unwrap();
+        assert!(!first.unwrap().used);
+
+        let second = repo.mark_refresh_token_used("hash-1").await.unwrap();
+        assert!(second.unwrap().used);
+    }
+
+    #[tokio::test]
+    async fn test_mark_refresh_token_used_returns_none_for_unknown_token() {
+        let repo = InMemoryRefreshTokenRepository::new();
+
+        let token = repo.mark_refresh_token_used("unknown").await.unwrap();
+        assert!(token.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_revoke_refresh_token_family_revokes_only_that_family() {
+        let repo = InMemoryRefreshTokenRepository::new();
+        repo.save_refresh_token(refresh_token("hash-1", "family-1"))
+            .await
+            .unwrap();
+        repo.save_refresh_token(refresh_token("hash-2", "family-1"))
+            .await
+            .unwrap();
+        repo.save_refresh_token(refresh_token("hash-3", "family-2"))
+            .await
+            .unwrap();
+
+        repo.revoke_refresh_token_family("family-1").await.unwrap();
+
+        let storage = repo.storage.read().await;
+        assert!(storage["hash-1"].revoked);
+        assert!(storage["hash-2"].revoked);
+        assert!(!storage["hash-3"].revoked);
+    }
+
+    #[tokio::test]
+    async fn test_revoke_user_refresh_tokens_revokes_only_that_user() {
+        let repo = InMemoryRefreshTokenRepository::new();
+        repo.save_refresh_token(refresh_token("hash-1", "family-1"))
+            .await
+            .unwrap();
+        let mut other = refresh_token("hash-2", "family-2");
+        other.user_id = "user-2".to_string();
+        repo.save_refresh_token(other).await.unwrap();
+
+        repo.revoke_user_refresh_tokens("user-1").await.unwrap();
+
+        let first = repo.find_refresh_token("hash-1").await.unwrap().unwrap();
+        let second = repo.find_refresh_token("hash-2").await.unwrap().unwrap();
+        assert!(first.revoked);
+        assert!(!second.revoked);
+    }
+
+    #[tokio::test]
+    async fn test_save_refresh_token_drops_expired_tokens() {
+        let repo = InMemoryRefreshTokenRepository::new();
+        let mut expired = refresh_token("expired", "family-1");
+        expired.expires_at = Utc::now() - Duration::seconds(1);
+        repo.save_refresh_token(expired).await.unwrap();
+        repo.save_refresh_token(refresh_token("fresh", "family-2"))
+            .await
+            .unwrap();
+
+        let storage = repo.storage.read().await;
+        assert!(!storage.contains_key("expired"));
+        assert!(storage.contains_key("fresh"));
+    }
+
+    fn reset_token(hash: &str, user_id: &str) -> PasswordResetToken {
+        PasswordResetToken {
+            token_hash: hash.to_string(),
+            user_id: user_id.to_string(),
+            created_at: Utc::now(),
+            expires_at: Utc::now() + Duration::minutes(30),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_take_reset_token_is_single_use() {
+        let repo = InMemoryPasswordResetTokenRepository::new();
+        repo.save_reset_token(reset_token("reset-1", "user-1"))
+            .await
+            .unwrap();
+
+        assert!(repo.take_reset_token("reset-1").await.unwrap().is_some());
+        assert!(repo.take_reset_token("reset-1").await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_delete_user_reset_tokens_keeps_other_users() {
+        let repo = InMemoryPasswordResetTokenRepository::new();
+        repo.save_reset_token(reset_token("reset-1", "user-1"))
+            .await
+            .unwrap();
+        repo.save_reset_token(reset_token("reset-2", "user-2"))
+            .await
+            .unwrap();
+
+        repo.delete_user_reset_tokens("user-1").await.unwrap();
+
+        assert!(repo.take_reset_token("reset-1").await.unwrap().is_none());
+        assert!(repo.take_reset_token("reset-2").await.unwrap().is_some());
+    }
+
+    #[tokio::test]
+    async fn test_list_user_verification_tokens_skips_expired_and_other_users() {
+        let repo = InMemoryEmailVerificationTokenRepository::new();
+        let token = |hash: &str, user_id: &str, expires_in: Duration| EmailVerificationToken {
+            token_hash: hash.to_string(),
+            user_id: user_id.to_string(),
+            created_at: Utc::now(),
+            expires_at: Utc::now() + expires_in,
+        };
+        repo.save_verification_token(token("expired", "user-1", -Duration::seconds(1)))
+            .await
+            .unwrap();
+        repo.save_verification_token(token("verify-1", "user-1", Duration::hours(24)))
+            .await
+            .unwrap();
+        repo.save_verification_token(token("verify-2", "user-2", Duration::hours(24)))
+            .await
+            .unwrap();
+
+        let tokens = repo.list_user_verification_tokens("user-1").await.unwrap();
+        assert_eq!(tokens.len(), 1);
+        assert_eq!(tokens[0].token_hash, "verify-1");
+
+        assert!(
+            repo.take_verification_token("verify-1")
+                .await
+                .unwrap()
+                .is_some()
+        );
+        assert!(
+            repo.take_verification_token("verify-1")
+                .await
+                .unwrap()
+                .is_none()
+        );
+    }
+}
@@ -0,0 +1,186 @@
+use crate::domain::repository::TwoFactorRepository;
+use crate::domain::two_factor::{LoginChallenge, TwoFactorSettings};
+use anyhow::Result;
+use async_trait::async_trait;
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::sync::RwLock;
+use tracing::{debug, instrument, trace};
+
+#[derive(Clone)]
+pub struct InMemoryTwoFactorRepository {
+    // user_id -> settings
+    settings: Arc<RwLock<HashMap<String, TwoFactorSettings>>>,
+    // token_hash -> challenge
+    challenges: Arc<RwLock<HashMap<String, LoginChallenge>>>,
+}
+
+impl InMemoryTwoFactorRepository {
+    pub fn new() -> Self {
+        Self {
+            settings: Arc::new(RwLock::new(HashMap::new())),
+            challenges: Arc::new(RwLock::new(HashMap::new())),
+        }
+    }
+}
+
+impl Default for InMemoryTwoFactorRepository {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait]
+impl TwoFactorRepository for InMemoryTwoFactorRepository {
+    #[instrument(skip(self, settings), fields(user_id = %settings.user_id, enabled = settings.enabled))]
+    async fn save_two_factor(&self, settings: TwoFactorSettings) -> Result<()> {
+        trace!("Acquiring write lock for two-factor storage");
+        let mut storage = self.settings.write().await;
+        debug!(user_id = %settings.user_id, "Two-factor settings saved");
+        storage.insert(settings.user_id.clone(), settings);
+        Ok(())
+    }
+
+    #[instrument(skip(self))]
+    async fn find_two_factor(&self, user_id: &str) -> Result<Option<TwoFactorSettings>> {
+        trace!("Acquiring read lock for two-factor storage");
+        let storage = self.settings.read().await;
+        Ok(storage.get(user_id).cloned())
+    }
+
+    #[instrument(skip(self))]
+    async fn advance_totp_step(&self, user_id: &str, step: i64) -> Result<bool> {
+        trace!("Acquiring write lock for two-factor storage");
+        let mut storage = self.settings.write().await;
+        let Some(settings) = storage.get_mut(user_id) else {
+            return Ok(false);
+        };
+        if settings.last_used_step.is_some_and(|last| step <= last) {
+            debug!(user_id = user_id, step = step, "TOTP step already used");
+            return Ok(false);
+        }
+        settings.last_used_step = Some(step);
+        Ok(true)
+    }
+
+    #[instrument(skip(self, code_hash))]
+    async fn consume_recovery_code(&self, user_id: &str, code_hash: &str) -> Result<bool> {
+        trace!("Acquiring write lock for two-factor storage");
+        let mut storage = self.settings.write().await;
+        let Some(settings) = storage.get_mut(user_id) else {
+            return Ok(false);
+        };
+        let before = settings.recovery_code_hashes.len();
+        settings
+            .recovery_code_hashes
+            .retain(|hash| hash != code_hash);
+        let consumed = settings.recovery_code_hashes.len() < before;
+        if consumed {
+            debug!(
+                user_id = user_id,
+                remaining = settings.recovery_code_hashes.len(),
+                "Recovery code consumed"
+            );
+        }
+        Ok(consumed)
+    }
+
+    #[instrument(skip(self))]
+    async fn delete_two_factor(&self, user_id: &str) -> Result<()> {
+        trace!("Acquiring write lock for two-factor storage");
+        self.settings.write().await.remove(user_id);
+        self.challenges
+            .write()
+            .await
+            .retain(|_, c| c.user_id != user_id);
+        debug!(user_id = user_id, "Two-factor settings deleted");
+        Ok(())
+    }
+
+    #[instrument(skip(self, challenge), fields(user_id = %challenge.user_id))]
+    async fn save_login_challenge(&self, challenge: LoginChallenge) -> Result<()> {
+        trace!("Acquiring write lock for login challenge storage");
+        let mut storage = self.challenges.write().await;
+        // Expired challenges are useless, drop them while we hold the lock anyway
+        let now = chrono::Utc::now();
+        storage.retain(|_, c| c.expires_at > now);
+        storage.insert(challenge.token_hash.clone(), challenge);
+        Ok(())
+    }
+
+    #[instrument(skip(self, token_hash))]
+    async fn find_login_challenge(&self, token_hash: &str) -> Result<Option<LoginChallenge>> {
+        trace!("Acquiring read lock for login challenge storage");
+        let storage = self.challenges.read().await;
+        Ok(storage.get(token_hash).cloned())
+    }
+
+    #[instrument(skip(self, token_hash))]
+    async fn delete_login_challenge(&self, token_hash: &str) -> Result<()> {
+        trace!("Acquiring write lock for login challenge storage");
+        let mut storage = self.challenges.write().await;
+        storage.remove(token_hash);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Duration, Utc};
+
+    fn settings(recovery_code_hashes: Vec<String>) -> TwoFactorSettings {
+        TwoFactorSettings {
+            user_id: "user-1".to_string(),
+            secret: "SECRET".to_string(),
+            enabled: true,
+            recovery_code_hashes,
+            last_used_step: None,
+            created_at: Utc::now(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_advance_totp_step_rejects_replayed_steps() {
+        let repo = InMemoryTwoFactorRepository::new();
+        repo.save_two_factor(settings(vec![])).await.unwrap();
+
+        assert!(repo.advance_totp_step("user-1", 10).await.unwrap());
+        assert!(!repo.advance_totp_step("user-1", 10).await.unwrap());
+        assert!(!repo.advance_totp_step("user-1", 9).await.unwrap());
+        assert!(repo.advance_totp_step("user-1", 11).await.unwrap());
+        assert!(!repo.advance_totp_step("unknown", 12).await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_consume_recovery_code_is_single_use() {
+        let repo = InMemoryTwoFactorRepository::new();
+        repo.save_two_factor(settings(vec!["a".to_string(), "b".to_string()]))
+            .await
+            .unwrap();
+
+        assert!(repo.consume_recovery_code("user-1", "a").await.unwrap());
+        assert!(!repo.consume_recovery_code("user-1", "a").await.unwrap());
+
+        let stored = repo.find_two_factor("user-1").await.unwrap().unwrap();
+        assert_eq!(stored.recovery_code_hashes, vec!["b".to_string()]);
+    }
+
+    #[tokio::test]
+    async fn test_login_challenge_round_trip() {
+        let repo = InMemoryTwoFactorRepository::new();
+        let challenge = LoginChallenge {
+            token_hash: "hash".to_string(),
+            user_id: "user-1".to_string(),
+            expires_at: Utc::now() + Duration::minutes(5),
+            failed_attempts: 0,
+            client: Default::default(),
+        };
+
+        repo.save_login_challenge(challenge).await.unwrap();
+        assert!(repo.find_login_challenge("hash").await.unwrap().is_some());
+
+        repo.delete_login_challenge("hash").await.unwrap();
+        assert!(repo.find_login_challenge("hash").await.unwrap().is_none());
+    }
+}
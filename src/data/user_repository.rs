@@ -1,25 +1,96 @@
+use crate::domain::error::DomainError;
 use crate::domain::repository::UserRepository;
 use crate::domain::user::User;
+use crate::domain::validation::email_identity;
 use anyhow::Result;
 use async_trait::async_trait;
 use std::collections::HashMap;
 use std::sync::Arc;
 use tokio::sync::RwLock;
-use tracing::{debug, instrument, trace};
+use tracing::{debug, instrument, trace, warn};
 
 #[derive(Clone)]
 pub struct InMemoryUserRepository {
-    storage: Arc<RwLock<HashMap<String, User>>>,
+    storage: Arc<RwLock<UserStorage>>,
+}
+
+// Users by ID, plus the IDs of the users behind each `email_identity`. An identity maps to
+// more than one user only in data loaded before emails were normalized.
+#[derive(Default)]
+struct UserStorage {
+    users: HashMap<String, User>,
+    by_email: HashMap<String, Vec<String>>,
+}
+
+impl UserStorage {
+    fn insert(&mut self, user: User) {
+        self.remove_from_index(&user.id);
+        self.by_email
+            .entry(email_identity(&user.email))
+            .or_default()
+            .push(user.id.clone());
+        self.users.insert(user.id.clone(), user);
+    }
+
+    fn remove_from_index(&mut self, id: &str) {
+        let Some(old) = self.users.get(id) else {
+            return;
+        };
+        let identity = email_identity(&old.email);
+        if let Some(ids) = self.by_email.get_mut(&identity) {
+            ids.retain(|other| other != id);
+            if ids.is_empty() {
+                self.by_email.remove(&identity);
+            }
+        }
+    }
+
+    fn find_by_identity(&self, identity: &str) -> Vec<&User> {
+        self.by_email
+            .get(identity)
+            .into_iter()
+            .flatten()
+            .filter_map(|id| self.users.get(id))
+            .collect()
+    }
 }
 
 impl InMemoryUserRepository {
     pub fn new() -> Self {
         Self {
-            storage: Arc::new(RwLock::new(HashMap::new())),
+            storage: Arc::new(RwLock::new(UserStorage::default())),
         }
     }
 }
 
+impl InMemoryUserRepository {
+    // Loads existing users as they are, without the uniqueness check, e.g. from a snapshot
+    // taken before emails were normalized. Run `AuthService::migrate_email_identities` next.
+    pub fn with_users(users: Vec<User>) -> Self {
+        let mut storage = UserStorage::default();
+        for user in users {
+            storage.insert(user);
+        }
+        Self {
+            storage: Arc::new(RwLock::new(storage)),
+        }
+    }
+}
+
+fn ensure_email_available(storage: &UserStorage, user: &User) -> Result<()> {
+    if storage
+        .find_by_identity(&email_identity(&user.email))
+        .iter()
+        .any(|u| u.id != user.id)
+    {
+        warn!(user_id = %user.id, email = %user.email, "Email already taken");
+        return Err(
+            DomainError::Validation("User with this email already exists".to_string()).into(),
+        );
+    }
+    Ok(())
+}
+
 impl Default for InMemoryUserRepository {
     fn default() -> Self {
         Self::new()
@@ -32,8 +103,9 @@ impl UserRepository for InMemoryUserRepository {
     async fn save_user(&self, user: User) -> Result<()> {
         trace!("Acquiring write lock for user storage");
         let mut storage = self.storage.write().await;
+        ensure_email_available(&storage, &user)?;
         trace!(user_id = %user.id, email = %user.email, "Inserting user into storage");
-        storage.insert(user.id.clone(), user.clone());
+        storage.insert(user.clone());
         debug!(
             user_id = %user.id,
             email = %user.email,
@@ -47,7 +119,24 @@ impl UserRepository for InMemoryUserRepository {
         trace!("Acquiring read lock for user storage");
         let storage = self.storage.read().await;
         trace!(email = email, "Looking up user by email in storage");
-        let user = storage.values().find(|u| u.email == email).cloned();
+        let matches = storage.find_by_identity(&email_identity(email));
+        // Duplicates can only exist in data loaded before normalization; until they are
+        // merged, only an exact match is trusted
+        let user = match matches.as_slice() {
+            [user] => Some((*user).clone()),
+            [] => None,
+            duplicates => {
+                warn!(
+                    email = email,
+                    count = duplicates.len(),
+                    "Ambiguous email lookup, duplicates need migration"
+                );
+                duplicates
+                    .iter()
+                    .find(|u| u.email == email)
+                    .map(|u| (*u).clone())
+            }
+        };
         match &user {
             Some(u) => {
                 debug!(
@@ -68,7 +157,7 @@ impl UserRepository for InMemoryUserRepository {
         trace!("Acquiring read lock for user storage");
         let storage = self.storage.read().await;
         trace!(user_id = id, "Looking up user by ID in storage");
-        let user = storage.get(id).cloned();
+        let user = storage.users.get(id).cloned();
         match &user {
             Some(u) => {
                 debug!(
@@ -83,12 +172,51 @@ impl UserRepository for InMemoryUserRepository {
         }
         Ok(user)
     }
+
+    #[instrument(skip(self), fields(user_id = %user.id, email = %user.email))]
+    async fn update_user(&self, user: User) -> Result<()> {
+        trace!("Acquiring write lock for user storage");
+        let mut storage = self.storage.write().await;
+        // Legacy duplicates stay updatable as long as their email identity does not change
+        let identity_changed = storage
+            .users
+            .get(&user.id)
+            .is_none_or(|old| email_identity(&old.email) != email_identity(&user.email));
+        if identity_changed {
+            ensure_email_available(&storage, &user)?;
+        }
+        trace!(user_id = %user.id, "Updating user in storage");
+        storage.insert(user.clone());
+        debug!(user_id = %user.id, "User updated in memory storage");
+        Ok(())
+    }
+
+    #[instrument(skip(self))]
+    async fn list_users(&self) -> Result<Vec<User>> {
+        trace!("Acquiring read lock for user storage");
+        let storage = self.storage.read().await;
+        Ok(storage.users.values().cloned().collect())
+    }
+
+    #[instrument(skip(self), fields(user_id = id))]
+    async fn anonymize_user(&self, id: &str) -> Result<Option<User>> {
+        trace!("Acquiring write lock for user storage");
+        let mut storage = self.storage.write().await;
+        let Some(mut user) = storage.users.get(id).cloned() else {
+            trace!(user_id = id, "User not found in storage");
+            return Ok(None);
+        };
+        user.anonymize();
+        storage.insert(user.clone());
+        debug!(user_id = id, "User anonymized in memory storage");
+        Ok(Some(user))
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::domain::user::User;
+    use crate::domain::user::{KycStatus, Profile, Role, User, UserStatus};
 
     #[tokio::test]
     async fn test_save_user_saves_user_correctly() {
@@ -97,6 +225,10 @@ mod tests {
             id: "user-1".to_string(),
             email: "test@example.com".to_string(),
             password_hash: "hash123".to_string(),
+            role: Role::Customer,
+            status: UserStatus::Active,
+            profile: Profile::default(),
+            kyc_status: KycStatus::Unverified,
         };
 
         repo.save_user(user.clone()).await.unwrap();
@@ -116,6 +248,10 @@ mod tests {
             id: "user-2".to_string(),
             email: "alice@example.com".to_string(),
             password_hash: "hash456".to_string(),
+            role: Role::Customer,
+            status: UserStatus::Active,
+            profile: Profile::default(),
+            kyc_status: KycStatus::Unverified,
         };
 
         repo.save_user(user.clone()).await.unwrap();
@@ -145,6 +281,10 @@ mod tests {
             id: "user-3".to_string(),
             email: "bob@example.com".to_string(),
             password_hash: "hash789".to_string(),
+            role: Role::Customer,
+            status: UserStatus::Active,
+            profile: Profile::default(),
+            kyc_status: KycStatus::Unverified,
         };
 
         repo.save_user(user.clone()).await.unwrap();
@@ -171,11 +311,19 @@ mod tests {
             id: "user-4".to_string(),
             email: "first@example.com".to_string(),
             password_hash: "hash1".to_string(),
+            role: Role::Customer,
+            status: UserStatus::Active,
+            profile: Profile::default(),
+            kyc_status: KycStatus::Unverified,
         };
         let user2 = User {
             id: "user-4".to_string(),
             email: "second@example.com".to_string(),
             password_hash: "hash2".to_string(),
+            role: Role::Customer,
+            status: UserStatus::Active,
+            profile: Profile::default(),
+            kyc_status: KycStatus::Unverified,
         };
 
         repo.save_user(user1).await.unwrap();
@@ -187,23 +335,125 @@ mod tests {
     }
 
     #[tokio::test]
-    async fn test_find_user_by_email_case_sensitive() {
+    async fn test_find_user_by_email_case_insensitive() {
         let repo = InMemoryUserRepository::new();
         let user = User {
             id: "user-5".to_string(),
-            email: "Test@Example.com".to_string(),
+            email: "Test@example.com".to_string(),
             password_hash: "hash".to_string(),
+            role: Role::Customer,
+            status: UserStatus::Active,
+            profile: Profile::default(),
+            kyc_status: KycStatus::Unverified,
         };
 
         repo.save_user(user).await.unwrap();
 
         // Exact match should work
-        let found = repo.find_user_by_email("Test@Example.com").await.unwrap();
+        let found = repo.find_user_by_email("Test@example.com").await.unwrap();
         assert!(found.is_some());
 
-        // Different case should not match
-        let not_found = repo.find_user_by_email("test@example.com").await.unwrap();
-        assert!(not_found.is_none());
+        // Different case and Unicode form match the same user
+        for email in [
+            "test@example.com",
+            "TEST@EXAMPLE.COM",
+            "ｔｅｓｔ@example.com",
+        ] {
+            let found = repo.find_user_by_email(email).await.unwrap();
+            assert_eq!(found.unwrap().id, "user-5");
+        }
+    }
+
+    #[tokio::test]
+    async fn test_save_user_rejects_email_differing_only_in_case() {
+        let repo = InMemoryUserRepository::new();
+        repo.save_user(User {
+            id: "user-1".to_string(),
+            email: "alice@x.com".to_string(),
+            password_hash: "hash".to_string(),
+            role: Role::Customer,
+            status: UserStatus::Active,
+            profile: Profile::default(),
+            kyc_status: KycStatus::Unverified,
+        })
+        .await
+        .unwrap();
+
+        let result = repo
+            .save_user(User {
+                id: "user-2".to_string(),
+                email: "Alice@X.com".to_string(),
+                password_hash: "hash".to_string(),
+                role: Role::Customer,
+                status: UserStatus::Active,
+                profile: Profile::default(),
+                kyc_status: KycStatus::Unverified,
+            })
+            .await;
+
+        assert!(result.is_err());
+        assert_eq!(repo.list_users().await.unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_update_user_rejects_taken_email() {
+        let repo = InMemoryUserRepository::new();
+        for (id, email) in [("user-1", "alice@x.com"), ("user-2", "bob@x.com")] {
+            repo.save_user(User {
+                id: id.to_string(),
+                email: email.to_string(),
+                password_hash: "hash".to_string(),
+                role: Role::Customer,
+                status: UserStatus::Active,
+                profile: Profile::default(),
+                kyc_status: KycStatus::Unverified,
+            })
+            .await
+            .unwrap();
+        }
+
+        let result = repo
+            .update_user(User {
+                id: "user-2".to_string(),
+                email: "ALICE@x.com".to_string(),
+                password_hash: "hash".to_string(),
+                role: Role::Customer,
+                status: UserStatus::Active,
+                profile: Profile::default(),
+                kyc_status: KycStatus::Unverified,
+            })
+            .await;
+
+        assert!(result.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_find_user_by_email_with_legacy_duplicates_requires_exact_match() {
+        let repo = InMemoryUserRepository::with_users(vec![
+            User {
+                id: "user-1".to_string(),
+                email: "alice@x.com".to_string(),
+                password_hash: "hash".to_string(),
+                role: Role::Customer,
+                status: UserStatus::Active,
+                profile: Profile::default(),
+                kyc_status: KycStatus::Unverified,
+            },
+            User {
+                id: "user-2".to_string(),
+                email: "Alice@x.com".to_string(),
+                password_hash: "hash".to_string(),
+                role: Role::Customer,
+                status: UserStatus::Active,
+                profile: Profile::default(),
+                kyc_status: KycStatus::Unverified,
+            },
+        ]);
+
+        let exact = repo.find_user_by_email("Alice@x.com").await.unwrap();
+        assert_eq!(exact.unwrap().id, "user-2");
+        let ambiguous = repo.find_user_by_email("ALICE@x.com").await.unwrap();
+        assert!(ambiguous.is_none());
     }
 
     #[tokio::test]
@@ -213,6 +463,10 @@ mod tests {
             id: "user-6".to_string(),
             email: "concurrent@example.com".to_string(),
             password_hash: "hash".to_string(),
+            role: Role::Customer,
+            status: UserStatus::Active,
+            profile: Profile::default(),
+            kyc_status: KycStatus::Unverified,
         };
 
         repo.save_user(user).await.unwrap();
@@ -244,6 +498,10 @@ mod tests {
                     id: format!("user-{}", i),
                     email: format!("user{}@example.com", i),
                     password_hash: format!("hash{}", i),
+                    role: Role::Customer,
+                    status: UserStatus::Active,
+                    profile: Profile::default(),
+                    kyc_status: KycStatus::Unverified,
                 };
                 tokio::spawn(async move { repo_clone.save_user(user).await })
             })
@@ -270,6 +528,10 @@ mod tests {
                 id: format!("user-{}", i),
                 email: format!("user{}@example.com", i),
                 password_hash: format!("hash{}", i),
+                role: Role::Customer,
+                status: UserStatus::Active,
+                profile: Profile::default(),
+                kyc_status: KycStatus::Unverified,
             };
             repo.save_user(user).await.unwrap();
         }
@@ -289,4 +551,119 @@ mod tests {
             assert!(found.is_some());
         }
     }
+
+    #[tokio::test]
+    async fn test_update_user_replaces_password_hash() {
+        let repo = InMemoryUserRepository::new();
+        let mut user = User {
+            id: "user-1".to_string(),
+            email: "update@example.com".to_string(),
+            password_hash: "old".to_string(),
+            role: Role::Customer,
+            status: UserStatus::Active,
+            profile: Profile::default(),
+            kyc_status: KycStatus::Unverified,
+        };
+        repo.save_user(user.clone()).await.unwrap();
+
+        user.password_hash = "new".to_string();
+        repo.update_user(user).await.unwrap();
+
+        let found = repo.find_user_by_id("user-1").await.unwrap().unwrap();
+        assert_eq!(found.password_hash, "new");
+    }
+
+    #[tokio::test]
+    async fn test_anonymize_user_frees_the_email() {
+        let repo = InMemoryUserRepository::new();
+        let user = User {
+            id: "user-1".to_string(),
+            email: "leaving@example.com".to_string(),
+            password_hash: "hash".to_string(),
+            role: Role::Customer,
+            status: UserStatus::Active,
+            profile: Profile {
+                full_name: Some("Alice Smith".to_string()),
+                ..Profile::default()
+            },
+            kyc_status: KycStatus::Verified,
+        };
+        repo.save_user(user).await.unwrap();
+
+        let anonymized = repo.anonymize_user("user-1").await.unwrap().unwrap();
+        assert_eq!(anonymized.status, UserStatus::Deleted);
+        assert_eq!(anonymized.profile, Profile::default());
+        assert_eq!(anonymized.kyc_status, KycStatus::Verified);
+        assert!(
+            repo.find_user_by_email("leaving@example.com")
+                .await
+                .unwrap()
+                .is_none()
+        );
+        assert!(repo.anonymize_user("unknown").await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_update_user_moves_the_email_index() {
+        let repo = InMemoryUserRepository::new();
+        let user = |id: &str, email: &str| User {
+            id: id.to_string(),
+            email: email.to_string(),
+            password_hash: "hash".to_string(),
+            role: Role::Customer,
+            status: UserStatus::Active,
+            profile: Profile::default(),
+            kyc_status: KycStatus::Unverified,
+        };
+        repo.save_user(user("user-1", "old@example.com"))
+            .await
+            .unwrap();
+
+        repo.update_user(user("user-1", "New@Example.com"))
+            .await
+            .unwrap();
+
+        let found = repo.find_user_by_email("new@example.com").await.unwrap();
+        assert_eq!(found.unwrap().id, "user-1");
+        assert!(
+            repo.find_user_by_email("old@example.com")
+                .await
+                .unwrap()
+                .is_none()
+        );
+        // The old email is free for someone else
+        repo.save_user(user("user-2", "OLD@example.com"))
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_update_user_keeps_legacy_duplicates_updatable() {
+        let legacy = |id: &str, email: &str| User {
+            id: id.to_string(),
+            email: email.to_string(),
+            password_hash: "hash".to_string(),
+            role: Role::Customer,
+            status: UserStatus::Active,
+            profile: Profile::default(),
+            kyc_status: KycStatus::Unverified,
+        };
+        let repo = InMemoryUserRepository::with_users(vec![
+            legacy("user-1", "alice@x.com"),
+            legacy("user-2", "Alice@X.com"),
+        ]);
+
+        let mut alice = repo.find_user_by_id("user-2").await.unwrap().unwrap();
+        alice.password_hash = "new-hash".to_string();
+        alice.kyc_status = KycStatus::Verified;
+        repo.update_user(alice).await.unwrap();
+
+        let alice = repo.find_user_by_id("user-2").await.unwrap().unwrap();
+        assert_eq!(alice.password_hash, "new-hash");
+        // Moving onto the other duplicate's identity is still refused
+        let mut other = legacy("user-3", "bob@x.com");
+        repo.save_user(other.clone()).await.unwrap();
+        other.email = "ALICE@x.com".to_string();
+        assert!(repo.update_user(other).await.is_err());
+    }
 }
@@ -1,4 +1,16 @@
+pub mod api_key;
+pub mod beneficiary;
 pub mod error;
+pub mod login_attempt;
 pub mod models;
+pub mod notifier;
+pub mod oidc;
+pub mod p2p;
+pub mod payment_request;
 pub mod repository;
+pub mod reversal;
+pub mod session;
+pub mod token;
+pub mod two_factor;
 pub mod user;
+pub mod validation;
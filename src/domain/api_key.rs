@@ -0,0 +1,62 @@
+use crate::domain::token::Scope;
+use crate::domain::user::Role;
+use anyhow::Result;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+// A personal API key for server-to-server access. The key itself is shown once on creation;
+// only its SHA-256 hash is kept, along with a short prefix that lets users tell keys apart.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ApiKey {
+    pub id: String,
+    pub user_id: String,
+    pub name: String,
+    #[serde(skip_serializing)]
+    pub key_hash: String,
+    pub prefix: String,
+    // Restricts the key like a scoped token; `None` for full access
+    pub scopes: Option<Vec<Scope>>,
+    pub created_at: DateTime<Utc>,
+    pub expires_at: Option<DateTime<Utc>>,
+}
+
+impl ApiKey {
+    pub fn is_expired(&self, now: DateTime<Utc>) -> bool {
+        self.expires_at.is_some_and(|expires_at| expires_at <= now)
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CreateApiKeyRequest {
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expires_at: Option<DateTime<Utc>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub scopes: Option<Vec<Scope>>,
+}
+
+// A newly created key together with the only copy of its secret
+#[derive(Debug, Serialize)]
+pub struct NewApiKey {
+    #[serde(flatten)]
+    pub api_key: ApiKey,
+    pub key: String,
+}
+
+// Who a presented API key acts for. The role is the user's current one, not the one they
+// had when the key was created.
+#[derive(Debug, Clone)]
+pub struct ApiKeyIdentity {
+    pub key_id: String,
+    pub user_id: String,
+    pub role: Role,
+    pub scopes: Option<Vec<Scope>>,
+}
+
+// Resolves `Authorization: ApiKey ...` headers for `JwtAuthMiddleware`
+#[async_trait]
+pub trait ApiKeyAuthenticator: Send + Sync {
+    // `None` for unknown, revoked or expired keys
+    async fn authenticate_api_key(&self, key: &str) -> Result<Option<ApiKeyIdentity>>;
+}
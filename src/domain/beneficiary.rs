@@ -0,0 +1,32 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+// An account the user saved to pay again later
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Beneficiary {
+    pub id: String,
+    #[serde(skip_serializing)]
+    pub user_id: String,
+    pub name: String,
+    pub account_id: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub nickname: Option<String>,
+    // Large transfers to the beneficiary wait for a cooling-off period counted from here
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CreateBeneficiary {
+    pub name: String,
+    pub account_id: u32,
+    #[serde(default)]
+    pub nickname: Option<String>,
+}
+
+// Fields present are changed; an empty nickname removes it. The account cannot be changed,
+// which would skip the cooling-off period; save a new beneficiary instead.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct UpdateBeneficiary {
+    pub name: Option<String>,
+    pub nickname: Option<String>,
+}
@@ -1,5 +1,32 @@
+use serde::Serialize;
 use thiserror::Error;
 
+// A single broken validation rule of a request field
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct FieldError {
+    pub field: String,
+    pub rule: String,
+    pub message: String,
+}
+
+impl FieldError {
+    pub fn new(field: &str, rule: &str, message: String) -> Self {
+        Self {
+            field: field.to_string(),
+            rule: rule.to_string(),
+            message,
+        }
+    }
+}
+
+fn join_messages(errors: &[FieldError]) -> String {
+    errors
+        .iter()
+        .map(|e| e.message.as_str())
+        .collect::<Vec<_>>()
+        .join("; ")
+}
+
 #[derive(Error, Debug)]
 pub enum DomainError {
     #[error("Insufficient funds")]
@@ -10,10 +37,16 @@ pub enum DomainError {
     InvalidAmount,
     #[error("Validation error: {0}")]
     Validation(String),
+    #[error("Validation error: {}", join_messages(.0))]
+    InvalidFields(Vec<FieldError>),
     #[error("Not found: {0}")]
     NotFound(String),
     #[error("Unauthorized: {0}")]
     Unauthorized(String),
+    #[error("Forbidden: {0}")]
+    Forbidden(String),
+    #[error("Too many attempts, retry after {0} seconds")]
+    TooManyAttempts(u64),
     #[error("Internal error: {0}")]
     Internal(String),
 }
@@ -46,6 +79,22 @@ mod tests {
         assert_eq!(error.to_string(), "Validation error: Email already exists");
     }
 
+    #[test]
+    fn test_invalid_fields_error_display() {
+        let error = DomainError::InvalidFields(vec![
+            FieldError::new("email", "format", "Email is required".to_string()),
+            FieldError::new(
+                "password",
+                "digit",
+                "Password must contain a digit".to_string(),
+            ),
+        ]);
+        assert_eq!(
+            error.to_string(),
+            "Validation error: Email is required; Password must contain a digit"
+        );
+    }
+
     #[test]
     fn test_not_found_error_display() {
         let error = DomainError::NotFound("User not found".to_string());
@@ -58,6 +107,21 @@ mod tests {
         assert_eq!(error.to_string(), "Unauthorized: Invalid token");
     }
 
+    #[test]
+    fn test_forbidden_error_display() {
+        let error = DomainError::Forbidden("Two-factor code required".to_string());
+        assert_eq!(error.to_string(), "Forbidden: Two-factor code required");
+    }
+
+    #[test]
+    fn test_too_many_attempts_error_display() {
+        let error = DomainError::TooManyAttempts(30);
+        assert_eq!(
+            error.to_string(),
+            "Too many attempts, retry after 30 seconds"
+        );
+    }
+
     #[test]
     fn test_internal_error_display() {
         let error = DomainError::Internal("Database connection failed".to_string());
@@ -0,0 +1,181 @@
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+
+// Failed login attempts recorded under one key (an email identity or a client IP)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FailedAttempts {
+    pub count: u32,
+    pub last_failure: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone)]
+pub struct LoginThrottlePolicy {
+    // Failures per email allowed before backoff starts
+    pub free_attempts: u32,
+    // Delay after the first throttled failure, doubled for every further one
+    pub base_delay: Duration,
+    pub max_delay: Duration,
+    // Failures per email after which logins are refused for `lockout_duration`
+    pub lockout_threshold: u32,
+    pub lockout_duration: Duration,
+    // Same limits for a single client IP, which may try many emails
+    pub ip_free_attempts: u32,
+    pub ip_lockout_threshold: u32,
+    // Failures older than this are forgotten
+    pub attempt_window: Duration,
+}
+
+impl Default for LoginThrottlePolicy {
+    fn default() -> Self {
+        Self {
+            free_attempts: 3,
+            base_delay: Duration::seconds(1),
+            max_delay: Duration::minutes(5),
+            lockout_threshold: 10,
+            lockout_duration: Duration::minutes(15),
+            ip_free_attempts: 20,
+            ip_lockout_threshold: 100,
+            attempt_window: Duration::hours(1),
+        }
+    }
+}
+
+impl LoginThrottlePolicy {
+    pub fn email_blocked_until(
+        &self,
+        attempts: &FailedAttempts,
+        now: DateTime<Utc>,
+    ) -> Option<DateTime<Utc>> {
+        self.blocked_until(attempts, self.free_attempts, self.lockout_threshold, now)
+    }
+
+    pub fn ip_blocked_until(
+        &self,
+        attempts: &FailedAttempts,
+        now: DateTime<Utc>,
+    ) -> Option<DateTime<Utc>> {
+        self.blocked_until(
+            attempts,
+            self.ip_free_attempts,
+            self.ip_lockout_threshold,
+            now,
+        )
+    }
+
+    // Whether attempts recorded at `last_failure` still count at `now`
+    pub fn is_active(&self, attempts: &FailedAttempts, now: DateTime<Utc>) -> bool {
+        now - attempts.last_failure < self.attempt_window
+    }
+
+    fn blocked_until(
+        &self,
+        attempts: &FailedAttempts,
+        free_attempts: u32,
+        lockout_threshold: u32,
+        now: DateTime<Utc>,
+    ) -> Option<DateTime<Utc>> {
+        if !self.is_active(attempts, now) {
+            return None;
+        }
+
+        let until = if attempts.count >= lockout_threshold {
+            attempts.last_failure + self.lockout_duration
+        } else if attempts.count >= free_attempts {
+            let exponent = (attempts.count - free_attempts).min(30);
+            let delay = self
+                .base_delay
+                .checked_mul(1 << exponent)
+                .unwrap_or(self.max_delay)
+                .min(self.max_delay);
+            attempts.last_failure + delay
+        } else {
+            return None;
+        };
+
+        (until > now).then_some(until)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn attempts(count: u32, last_failure: DateTime<Utc>) -> FailedAttempts {
+        FailedAttempts {
+            count,
+            last_failure,
+        }
+    }
+
+    #[test]
+    fn test_free_attempts_are_not_throttled() {
+        let policy = LoginThrottlePolicy::default();
+        let now = Utc::now();
+
+        assert!(policy.email_blocked_until(&attempts(2, now), now).is_none());
+    }
+
+    #[test]
+    fn test_backoff_doubles_per_failure() {
+        let policy = LoginThrottlePolicy::default();
+        let now = Utc::now();
+
+        for (count, seconds) in [(3, 1), (4, 2), (5, 4), (6, 8)] {
+            let until = policy.email_blocked_until(&attempts(count, now), now);
+            assert_eq!(until, Some(now + Duration::seconds(seconds)));
+        }
+    }
+
+    #[test]
+    fn test_backoff_is_capped() {
+        let policy = LoginThrottlePolicy {
+            lockout_threshold: 1000,
+            ..LoginThrottlePolicy::default()
+        };
+        let now = Utc::now();
+
+        let until = policy.email_blocked_until(&attempts(500, now), now);
+        assert_eq!(until, Some(now + Duration::minutes(5)));
+    }
+
+    #[test]
+    fn test_lockout_after_threshold() {
+        let policy = LoginThrottlePolicy::default();
+        let now = Utc::now();
+
+        let until = policy.email_blocked_until(&attempts(10, now), now);
+        assert_eq!(until, Some(now + Duration::minutes(15)));
+    }
+
+    #[test]
+    fn test_block_ends_after_delay() {
+        let policy = LoginThrottlePolicy::default();
+        let last_failure = Utc::now();
+        let later = last_failure + Duration::seconds(2);
+
+        assert!(
+            policy
+                .email_blocked_until(&attempts(3, last_failure), later)
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn test_ip_limits_are_separate() {
+        let policy = LoginThrottlePolicy::default();
+        let now = Utc::now();
+
+        assert!(policy.ip_blocked_until(&attempts(10, now), now).is_none());
+        assert!(policy.ip_blocked_until(&attempts(20, now), now).is_some());
+    }
+
+    #[test]
+    fn test_old_failures_are_forgotten() {
+        let policy = LoginThrottlePolicy::default();
+        let now = Utc::now();
+        let old = attempts(50, now - Duration::hours(2));
+
+        assert!(!policy.is_active(&old, now));
+        assert!(policy.email_blocked_until(&old, now).is_none());
+    }
+}
@@ -1,13 +1,84 @@
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct Account {
     pub id: u32,
     pub name: String,
     pub balance: Amount,
+    // User who opened the account; accounts stored before owners existed have none
+    #[serde(default)]
+    pub owner_id: String,
+    // Users the owner gave access to, besides the owner
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub holders: Vec<AccountHolder>,
+    // Closed when the owner was deleted; a closed account takes no money in or out
+    #[serde(default)]
+    pub closed: bool,
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, PartialOrd)]
+impl Account {
+    // What the user may do with the account; `None` if they have no access to it
+    pub fn permission_of(&self, user_id: &str) -> Option<HolderPermission> {
+        if !self.owner_id.is_empty() && self.owner_id == user_id {
+            return Some(HolderPermission::Owner);
+        }
+        self.holders
+            .iter()
+            .find(|h| h.user_id == user_id)
+            .map(|h| h.permission)
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(tag = "permission", rename_all = "snake_case")]
+pub enum HolderPermission {
+    // Everything, including managing holders; only the user who opened the account
+    Owner,
+    // Everything except managing holders
+    CoOwner,
+    // Balance and statements only
+    Viewer,
+    // Like a co-owner, but each withdrawal or transfer is capped
+    TransferUpToLimit { transfer_limit: Amount },
+}
+
+impl HolderPermission {
+    pub fn can_deposit(&self) -> bool {
+        !matches!(self, HolderPermission::Viewer)
+    }
+
+    // Withdrawals and outgoing transfers
+    pub fn can_debit(&self, amount: Amount) -> bool {
+        match self {
+            HolderPermission::Owner | HolderPermission::CoOwner => true,
+            HolderPermission::Viewer => false,
+            HolderPermission::TransferUpToLimit { transfer_limit } => amount <= *transfer_limit,
+        }
+    }
+
+    pub fn can_manage_holders(&self) -> bool {
+        matches!(self, HolderPermission::Owner)
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct AccountHolder {
+    pub user_id: String,
+    #[serde(flatten)]
+    pub permission: HolderPermission,
+}
+
+// Adds a holder, or changes the permission of an existing one
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AddHolderRequest {
+    pub email: String,
+    #[serde(flatten)]
+    pub permission: HolderPermission,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
 #[serde(transparent)]
 pub struct Amount(u64);
 
@@ -26,21 +97,164 @@ pub struct CreateAccount {
     pub name: String,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct Transfer {
     pub from_account_id: u32,
+    // May be left out when `beneficiary_id` names the destination; no account has id 0
+    #[serde(default)]
     pub to_account_id: u32,
     pub amount: Amount,
+    // One of the user's saved beneficiaries, paid instead of `to_account_id`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub beneficiary_id: Option<String>,
+    #[serde(flatten)]
+    pub details: TransactionDetails,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct Deposit {
     pub amount: Amount,
+    #[serde(flatten)]
+    pub details: TransactionDetails,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct Withdraw {
     pub amount: Amount,
+    #[serde(flatten)]
+    pub details: TransactionDetails,
+}
+
+// What the customer wrote about a transfer, deposit or withdrawal; booked with its
+// transactions
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+pub struct TransactionDetails {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    // For the counterparty, e.g. an invoice number
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reference: Option<String>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub metadata: BTreeMap<String, String>,
+}
+
+// What the caller gets back for a completed transfer
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct TransferReceipt {
+    pub transfer_id: String,
+    pub from_account_id: u32,
+    pub to_account_id: u32,
+    pub amount: Amount,
+    #[serde(flatten)]
+    pub details: TransactionDetails,
+    pub created_at: DateTime<Utc>,
+    // Balances after the transfer, each only if the caller holds the account
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub from_balance: Option<Amount>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub to_balance: Option<Amount>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum TransactionKind {
+    Deposit,
+    Withdrawal,
+    TransferIn,
+    TransferOut,
+}
+
+impl TransactionKind {
+    // Credits increase the balance of the account the transaction is booked on
+    pub fn is_credit(&self) -> bool {
+        matches!(self, TransactionKind::Deposit | TransactionKind::TransferIn)
+    }
+}
+
+// A booked movement on a single account. A transfer produces two transactions,
+// one on each side.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct Transaction {
+    pub id: String,
+    pub account_id: u32,
+    pub kind: TransactionKind,
+    pub amount: Amount,
+    pub balance_after: Amount,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub counterparty_account_id: Option<u32>,
+    // Shared by both sides of a transfer
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub transfer_id: Option<String>,
+    #[serde(flatten)]
+    pub details: TransactionDetails,
+    pub created_at: DateTime<Utc>,
+}
+
+// An account with its whole history, for the data export
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AccountExport {
+    pub account: Account,
+    pub transactions: Vec<Transaction>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct AccountStatement {
+    pub account_id: u32,
+    pub account_name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub from: Option<DateTime<Utc>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub to: Option<DateTime<Utc>>,
+    pub opening_balance: Amount,
+    pub closing_balance: Amount,
+    pub transactions: Vec<Transaction>,
+}
+
+// How a batch of transfers is executed once every row has been validated
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum BatchMode {
+    // Either every row is executed or none is
+    #[default]
+    AllOrNothing,
+    // Valid rows are executed, invalid rows are reported and skipped
+    BestEffort,
+}
+
+// A single transfer of a batch together with its line number in the source file
+#[derive(Debug, Clone)]
+pub struct BatchTransferRow {
+    pub line: usize,
+    pub transfer: Transfer,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum BatchRowStatus {
+    // The transfer was executed
+    Completed,
+    // The row itself is invalid or failed during execution
+    Failed,
+    // The row is valid but was not executed (or was rolled back) because the batch as a
+    // whole was rejected
+    Rejected,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct BatchRowResult {
+    pub line: usize,
+    pub status: BatchRowStatus,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct BatchTransferReport {
+    pub mode: BatchMode,
+    pub total: usize,
+    pub succeeded: usize,
+    pub failed: usize,
+    pub results: Vec<BatchRowResult>,
 }
 
 #[cfg(test)]
@@ -106,4 +320,25 @@ mod tests {
         let amount = Amount::new(u64::MAX);
         assert_eq!(amount.inner(), u64::MAX);
     }
+
+    #[test]
+    fn test_transaction_kind_is_credit() {
+        assert!(TransactionKind::Deposit.is_credit());
+        assert!(TransactionKind::TransferIn.is_credit());
+        assert!(!TransactionKind::Withdrawal.is_credit());
+        assert!(!TransactionKind::TransferOut.is_credit());
+    }
+
+    #[test]
+    fn test_batch_mode_defaults_to_all_or_nothing() {
+        assert_eq!(BatchMode::default(), BatchMode::AllOrNothing);
+    }
+
+    #[test]
+    fn test_batch_mode_deserialization() {
+        let mode: BatchMode = serde_json::from_str("\"best_effort\"").unwrap();
+        assert_eq!(mode, BatchMode::BestEffort);
+        let mode: BatchMode = serde_json::from_str("\"all_or_nothing\"").unwrap();
+        assert_eq!(mode, BatchMode::AllOrNothing);
+    }
 }
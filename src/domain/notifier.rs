@@ -0,0 +1,47 @@
+use crate::domain::models::Amount;
+use anyhow::Result;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+// Messages sent to users out of band, e.g. by email
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Notification {
+    PasswordReset {
+        email: String,
+        token: String,
+        expires_at: DateTime<Utc>,
+    },
+    PasswordChanged {
+        email: String,
+    },
+    EmailVerification {
+        email: String,
+        token: String,
+        expires_at: DateTime<Utc>,
+    },
+    // Money sent by email to someone who has to sign up or open an account to claim it
+    TransferToClaim {
+        email: String,
+        transfer_id: String,
+        amount: Amount,
+        expires_at: DateTime<Utc>,
+    },
+}
+
+impl Notification {
+    pub fn recipient(&self) -> &str {
+        match self {
+            Notification::PasswordReset { email, .. } => email,
+            Notification::PasswordChanged { email } => email,
+            Notification::EmailVerification { email, .. } => email,
+            Notification::TransferToClaim { email, .. } => email,
+        }
+    }
+}
+
+#[async_trait]
+pub trait Notifier: Send + Sync {
+    async fn notify(&self, notification: Notification) -> Result<()>;
+}
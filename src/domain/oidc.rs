@@ -0,0 +1,69 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+// A login started against the identity provider and not yet completed. The PKCE verifier and
+// the nonce stay on the server; the state travels through the provider and is kept as a
+// SHA-256 hash only, like the browser binding.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OidcAuthorization {
+    pub state_hash: String,
+    pub code_verifier: String,
+    pub nonce: String,
+    // Set when a signed-in user links the external identity to their account
+    pub link_user_id: Option<String>,
+    // Hash of the secret the initiating browser keeps in a cookie; a callback from any other
+    // browser is refused, so nobody can make a victim finish a login they started
+    pub binding_hash: String,
+    pub expires_at: DateTime<Utc>,
+}
+
+// A local user known to the identity provider under `subject`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExternalIdentity {
+    pub issuer: String,
+    pub subject: String,
+    pub user_id: String,
+    pub linked_at: DateTime<Utc>,
+}
+
+// Claims of a verified ID token that matter to us
+#[derive(Debug, Clone, PartialEq)]
+pub struct OidcIdentity {
+    pub issuer: String,
+    pub subject: String,
+    pub email: Option<String>,
+    pub email_verified: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct OidcAuthorizationStart {
+    // Where the user agent must be sent to sign in
+    pub authorization_url: String,
+    pub state: String,
+    pub expires_in: u64,
+    // Handed to the browser as an HttpOnly cookie, never in the body
+    #[serde(skip)]
+    pub binding: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct OidcCallback {
+    pub code: String,
+    pub state: String,
+}
+
+// An OpenID Connect provider using the authorization code flow with PKCE (S256)
+#[async_trait]
+pub trait IdentityProvider: Send + Sync {
+    fn issuer(&self) -> &str;
+    fn authorization_url(&self, state: &str, nonce: &str, code_challenge: &str) -> String;
+    // Redeems the authorization code and verifies the returned ID token, including its nonce
+    async fn exchange_code(
+        &self,
+        code: &str,
+        code_verifier: &str,
+        nonce: &str,
+    ) -> Result<OidcIdentity>;
+}
@@ -0,0 +1,65 @@
+use crate::domain::models::Amount;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+// Money sent to whoever owns the email address
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PayByEmail {
+    pub from_account_id: u32,
+    pub email: String,
+    pub amount: Amount,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ClaimStatus {
+    Pending,
+    Claimed,
+    // Expired unclaimed; the money went back to the sender's account
+    Refunded,
+}
+
+// Money sent by email to someone without an account. It has left the sender's account and
+// waits until the recipient claims it into one of theirs, or is refunded once it expires.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ClaimableTransfer {
+    pub id: String,
+    pub sender_id: String,
+    pub from_account_id: u32,
+    // Normalized by `validation::normalize_email`; matched by `validation::email_identity`
+    pub recipient_email: String,
+    pub amount: Amount,
+    pub status: ClaimStatus,
+    pub created_at: DateTime<Utc>,
+    pub expires_at: DateTime<Utc>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub settled_at: Option<DateTime<Utc>>,
+}
+
+impl ClaimableTransfer {
+    pub fn is_expired(&self, now: DateTime<Utc>) -> bool {
+        self.expires_at <= now
+    }
+}
+
+#[derive(Debug, Serialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum P2pPayment {
+    // Credited to the recipient's default account
+    Completed {
+        email: String,
+        amount: Amount,
+    },
+    // Held until the recipient claims it
+    Pending {
+        #[serde(flatten)]
+        transfer: ClaimableTransfer,
+    },
+}
+
+// Where to put claimed money; the claimant's default account if not given
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct ClaimTransferRequest {
+    #[serde(default)]
+    pub account_id: Option<u32>,
+}
@@ -0,0 +1,56 @@
+use crate::domain::models::Amount;
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PaymentRequestStatus {
+    Pending,
+    Paid,
+    Declined,
+    // Not paid by the end of the due date
+    Expired,
+    // The requester or the payer was deleted while it was pending
+    Cancelled,
+}
+
+// One user asking another for money, to be paid into `to_account_id`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PaymentRequest {
+    pub id: String,
+    pub requester_id: String,
+    pub payer_id: String,
+    pub to_account_id: u32,
+    pub amount: Amount,
+    pub description: String,
+    // Payable until the end of this day, UTC
+    pub due_date: NaiveDate,
+    pub status: PaymentRequestStatus,
+    pub created_at: DateTime<Utc>,
+    // When the request was paid, declined or expired
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub settled_at: Option<DateTime<Utc>>,
+    // The payer's account the money came from
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub from_account_id: Option<u32>,
+}
+
+impl PaymentRequest {
+    pub fn is_overdue(&self, today: NaiveDate) -> bool {
+        self.due_date < today
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CreatePaymentRequest {
+    pub payer_email: String,
+    pub to_account_id: u32,
+    pub amount: Amount,
+    pub description: String,
+    pub due_date: NaiveDate,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PayPaymentRequest {
+    pub from_account_id: u32,
+}
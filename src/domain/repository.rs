@@ -1,18 +1,250 @@
-use crate::domain::models::Account;
-use crate::domain::user::User;
+use crate::domain::api_key::ApiKey;
+use crate::domain::beneficiary::Beneficiary;
+use crate::domain::login_attempt::FailedAttempts;
+use crate::domain::models::{Account, Amount, Transaction};
+use crate::domain::oidc::{ExternalIdentity, OidcAuthorization};
+use crate::domain::p2p::{ClaimStatus, ClaimableTransfer};
+use crate::domain::payment_request::{PaymentRequest, PaymentRequestStatus};
+use crate::domain::reversal::TransferReversal;
+use crate::domain::session::Session;
+use crate::domain::token::{EmailVerificationToken, PasswordResetToken, RefreshToken};
+use crate::domain::two_factor::{LoginChallenge, TwoFactorSettings};
+use crate::domain::user::{KycTransition, User};
 use anyhow::Result;
 use async_trait::async_trait;
+use chrono::{DateTime, Duration, Utc};
 
 #[async_trait]
 pub trait AccountRepository: Send + Sync {
     async fn save(&self, account: Account) -> Result<()>;
     async fn find_by_id(&self, id: u32) -> Result<Option<Account>>;
+    async fn find_by_owner(&self, owner_id: &str) -> Result<Vec<Account>>;
+    // Replaces the account, except for `closed`, which only `set_closed` changes
     async fn update(&self, account: Account) -> Result<()>;
+    // Opens or closes the account and returns it; `None` if there is no such account
+    async fn set_closed(&self, id: u32, closed: bool) -> Result<Option<Account>>;
+    async fn save_transaction(&self, transaction: Transaction) -> Result<()>;
+    async fn find_transactions_by_account(&self, account_id: u32) -> Result<Vec<Transaction>>;
+    // Both sides of a transfer
+    async fn find_transactions_by_transfer(&self, transfer_id: &str) -> Result<Vec<Transaction>>;
 }
 
+// Emails are unique by `validation::email_identity`: saving or updating a user whose email
+// matches another user's fails, and lookups by email ignore case and Unicode form.
 #[async_trait]
 pub trait UserRepository: Send + Sync {
     async fn save_user(&self, user: User) -> Result<()>;
     async fn find_user_by_email(&self, email: &str) -> Result<Option<User>>;
     async fn find_user_by_id(&self, id: &str) -> Result<Option<User>>;
+    async fn update_user(&self, user: User) -> Result<()>;
+    async fn list_users(&self) -> Result<Vec<User>>;
+    // Erases the user's personal data in place with `User::anonymize` and returns the result;
+    // `None` for unknown users
+    async fn anonymize_user(&self, id: &str) -> Result<Option<User>>;
+}
+
+#[async_trait]
+pub trait RefreshTokenRepository: Send + Sync {
+    async fn save_refresh_token(&self, token: RefreshToken) -> Result<()>;
+    // Atomically marks the token as used and returns it as it was before the update
+    async fn mark_refresh_token_used(&self, token_hash: &str) -> Result<Option<RefreshToken>>;
+    async fn find_refresh_token(&self, token_hash: &str) -> Result<Option<RefreshToken>>;
+    async fn revoke_refresh_token_family(&self, family_id: &str) -> Result<()>;
+    async fn revoke_user_refresh_tokens(&self, user_id: &str) -> Result<()>;
+}
+
+#[async_trait]
+pub trait PasswordResetTokenRepository: Send + Sync {
+    async fn save_reset_token(&self, token: PasswordResetToken) -> Result<()>;
+    // Removes and returns the token, so that it can be redeemed only once
+    async fn take_reset_token(&self, token_hash: &str) -> Result<Option<PasswordResetToken>>;
+    async fn delete_user_reset_tokens(&self, user_id: &str) -> Result<()>;
+}
+
+#[async_trait]
+pub trait EmailVerificationTokenRepository: Send + Sync {
+    async fn save_verification_token(&self, token: EmailVerificationToken) -> Result<()>;
+    // Removes and returns the token, so that it can be redeemed only once
+    async fn take_verification_token(
+        &self,
+        token_hash: &str,
+    ) -> Result<Option<EmailVerificationToken>>;
+    // Unexpired tokens sent to the user, used to rate limit resends
+    async fn list_user_verification_tokens(
+        &self,
+        user_id: &str,
+    ) -> Result<Vec<EmailVerificationToken>>;
+    async fn delete_user_verification_tokens(&self, user_id: &str) -> Result<()>;
+}
+
+// Server-side revocation of access tokens, which are otherwise valid until they expire
+#[async_trait]
+pub trait RevocationStore: Send + Sync {
+    // Revokes a single token by its `jti`. The entry only needs to be kept until `expires_at`,
+    // after which the token is rejected anyway.
+    async fn revoke_token(&self, jti: &str, expires_at: DateTime<Utc>) -> Result<()>;
+    async fn is_token_revoked(&self, jti: &str) -> Result<bool>;
+    // Revokes every token of the user issued at or before `cutoff`
+    async fn revoke_user_tokens_before(&self, user_id: &str, cutoff: DateTime<Utc>) -> Result<()>;
+    async fn user_tokens_revoked_before(&self, user_id: &str) -> Result<Option<DateTime<Utc>>>;
+    // Revokes every token of a login session; kept until the last of them expires
+    async fn revoke_session(&self, session_id: &str, expires_at: DateTime<Utc>) -> Result<()>;
+    async fn is_session_revoked(&self, session_id: &str) -> Result<bool>;
+
+    async fn is_access_token_revoked(
+        &self,
+        user_id: &str,
+        jti: &str,
+        session_id: Option<&str>,
+        issued_at: DateTime<Utc>,
+    ) -> Result<bool> {
+        if self.is_token_revoked(jti).await? {
+            return Ok(true);
+        }
+        if let Some(session_id) = session_id
+            && self.is_session_revoked(session_id).await?
+        {
+            return Ok(true);
+        }
+        Ok(self
+            .user_tokens_revoked_before(user_id)
+            .await?
+            .is_some_and(|cutoff| issued_at <= cutoff))
+    }
+}
+
+// Failed login attempts keyed by email identity or client IP
+#[async_trait]
+pub trait LoginAttemptRepository: Send + Sync {
+    async fn find_failed_attempts(&self, key: &str) -> Result<Option<FailedAttempts>>;
+    // Atomically counts one more failure at `at`. Failures older than `window` are
+    // forgotten, so the count restarts from one.
+    async fn record_failed_attempt(
+        &self,
+        key: &str,
+        at: DateTime<Utc>,
+        window: Duration,
+    ) -> Result<FailedAttempts>;
+    async fn clear_failed_attempts(&self, key: &str) -> Result<()>;
+}
+
+#[async_trait]
+pub trait TwoFactorRepository: Send + Sync {
+    // Replaces any previous settings of the user
+    async fn save_two_factor(&self, settings: TwoFactorSettings) -> Result<()>;
+    async fn find_two_factor(&self, user_id: &str) -> Result<Option<TwoFactorSettings>>;
+    // Atomically records `step` as used; false if it or a later step was used already
+    async fn advance_totp_step(&self, user_id: &str, step: i64) -> Result<bool>;
+    // Atomically removes the recovery code; false if the user has no such code
+    async fn consume_recovery_code(&self, user_id: &str, code_hash: &str) -> Result<bool>;
+    // Removes the settings and pending login challenges of the user
+    async fn delete_two_factor(&self, user_id: &str) -> Result<()>;
+
+    async fn save_login_challenge(&self, challenge: LoginChallenge) -> Result<()>;
+    async fn find_login_challenge(&self, token_hash: &str) -> Result<Option<LoginChallenge>>;
+    async fn delete_login_challenge(&self, token_hash: &str) -> Result<()>;
+}
+
+#[async_trait]
+pub trait ApiKeyRepository: Send + Sync {
+    async fn save_api_key(&self, api_key: ApiKey) -> Result<()>;
+    async fn find_api_key_by_hash(&self, key_hash: &str) -> Result<Option<ApiKey>>;
+    async fn list_user_api_keys(&self, user_id: &str) -> Result<Vec<ApiKey>>;
+    // False if the user has no key with this id
+    async fn delete_api_key(&self, user_id: &str, key_id: &str) -> Result<bool>;
+}
+
+#[async_trait]
+pub trait OidcRepository: Send + Sync {
+    async fn save_authorization(&self, authorization: OidcAuthorization) -> Result<()>;
+    // Removes and returns the authorization, so that a state can be redeemed only once
+    async fn take_authorization(&self, state_hash: &str) -> Result<Option<OidcAuthorization>>;
+
+    // Fails if the subject is already linked to a user
+    async fn link_identity(&self, identity: ExternalIdentity) -> Result<()>;
+    async fn find_identity(&self, issuer: &str, subject: &str) -> Result<Option<ExternalIdentity>>;
+    // Unlinks every identity of the user and drops the logins that would link one
+    async fn delete_user_identities(&self, user_id: &str) -> Result<()>;
+}
+
+#[async_trait]
+pub trait SessionRepository: Send + Sync {
+    // Inserts or replaces the session with the same id
+    async fn save_session(&self, session: Session) -> Result<()>;
+    async fn find_session(&self, session_id: &str) -> Result<Option<Session>>;
+    // Every session of the user, revoked and expired ones included
+    async fn list_user_sessions(&self, user_id: &str) -> Result<Vec<Session>>;
+}
+
+#[async_trait]
+pub trait KycRepository: Send + Sync {
+    async fn record_kyc_transition(&self, transition: KycTransition) -> Result<()>;
+    // Oldest first
+    async fn list_kyc_transitions(&self, user_id: &str) -> Result<Vec<KycTransition>>;
+}
+
+// Beneficiaries are private to the user who saved them
+#[async_trait]
+pub trait BeneficiaryRepository: Send + Sync {
+    // Inserts or replaces the beneficiary with the same id
+    async fn save_beneficiary(&self, beneficiary: Beneficiary) -> Result<()>;
+    async fn find_beneficiary(&self, user_id: &str, id: &str) -> Result<Option<Beneficiary>>;
+    // Oldest first
+    async fn list_beneficiaries(&self, user_id: &str) -> Result<Vec<Beneficiary>>;
+    // False if the user has no such beneficiary
+    async fn delete_beneficiary(&self, user_id: &str, id: &str) -> Result<bool>;
+}
+
+#[async_trait]
+pub trait PaymentRequestRepository: Send + Sync {
+    // Inserts or replaces the request with the same id
+    async fn save_payment_request(&self, request: PaymentRequest) -> Result<()>;
+    async fn find_payment_request(&self, id: &str) -> Result<Option<PaymentRequest>>;
+    // Requests the user sent or received, newest first
+    async fn list_user_payment_requests(&self, user_id: &str) -> Result<Vec<PaymentRequest>>;
+    // Atomically moves a pending request to `status` and returns it; `None` if it is not
+    // pending, so that a request is paid or declined only once
+    async fn settle_payment_request(
+        &self,
+        id: &str,
+        status: PaymentRequestStatus,
+        at: DateTime<Utc>,
+    ) -> Result<Option<PaymentRequest>>;
+}
+
+#[async_trait]
+pub trait TransferReversalRepository: Send + Sync {
+    // Stores the reversal unless the reversals of the same transfer would then add up to
+    // more than `limit`; false if refused. Checked atomically, so that concurrent
+    // reversals cannot give back more than was transferred.
+    async fn add_reversal(&self, reversal: TransferReversal, limit: Amount) -> Result<bool>;
+    // Oldest first
+    async fn list_reversals(&self, transfer_id: &str) -> Result<Vec<TransferReversal>>;
+    // Undoes `add_reversal` when the compensating transfer fails
+    async fn remove_reversal(&self, transfer_id: &str, id: &str) -> Result<()>;
+}
+
+#[async_trait]
+pub trait ClaimableTransferRepository: Send + Sync {
+    async fn save_claimable_transfer(&self, transfer: ClaimableTransfer) -> Result<()>;
+    async fn find_claimable_transfer(&self, id: &str) -> Result<Option<ClaimableTransfer>>;
+    // Transfers sent by the user or, when given, to the email identity; newest first
+    async fn list_claimable_transfers(
+        &self,
+        sender_id: &str,
+        recipient_email: Option<&str>,
+    ) -> Result<Vec<ClaimableTransfer>>;
+    // Pending transfers expired at `now`
+    async fn list_expired_claimable_transfers(
+        &self,
+        now: DateTime<Utc>,
+    ) -> Result<Vec<ClaimableTransfer>>;
+    // Atomically moves a pending transfer to `status` and returns it; `None` if it is not
+    // pending, so that a transfer is claimed or refunded only once
+    async fn settle_claimable_transfer(
+        &self,
+        id: &str,
+        status: ClaimStatus,
+        at: DateTime<Utc>,
+    ) -> Result<Option<ClaimableTransfer>>;
 }
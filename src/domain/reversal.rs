@@ -0,0 +1,57 @@
+use crate::domain::models::Amount;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::str::FromStr;
+
+// Metadata of a compensating transfer naming the transfer it reverses; customers cannot set it
+pub const REVERSAL_OF_METADATA_KEY: &str = "reversal_of";
+
+// What to do when the recipient of a transfer being reversed no longer has the money
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ShortfallPolicy {
+    // Refuse the reversal
+    #[default]
+    Reject,
+    // Reverse what the recipient has left
+    Partial,
+}
+
+impl FromStr for ShortfallPolicy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "reject" => Ok(ShortfallPolicy::Reject),
+            "partial" => Ok(ShortfallPolicy::Partial),
+            _ => Err(format!("Unknown shortfall policy: {}", s)),
+        }
+    }
+}
+
+// A transfer made by an operator to give back all or part of an earlier transfer
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TransferReversal {
+    pub id: String,
+    // The transfer being reversed
+    pub transfer_id: String,
+    // The compensating transfer
+    pub reversal_transfer_id: String,
+    // The compensating transfer goes from the original recipient's account to the sender's
+    pub from_account_id: u32,
+    pub to_account_id: u32,
+    pub amount: Amount,
+    // More than `amount` when the recipient was short of funds
+    pub requested_amount: Amount,
+    pub reason: String,
+    pub operator_id: String,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ReverseTransfer {
+    // What is left to reverse when left out
+    #[serde(default)]
+    pub amount: Option<Amount>,
+    pub reason: String,
+}
@@ -0,0 +1,145 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+
+// Permission carried by a scoped access token. Tokens without scopes (user sessions) are
+// not restricted; scoped tokens may only call routes that require one of their scopes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Scope {
+    #[serde(rename = "accounts:read")]
+    AccountsRead,
+    #[serde(rename = "accounts:write")]
+    AccountsWrite,
+    #[serde(rename = "payments:write")]
+    PaymentsWrite,
+}
+
+impl Scope {
+    pub const ALL: [Scope; 3] = [
+        Scope::AccountsRead,
+        Scope::AccountsWrite,
+        Scope::PaymentsWrite,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Scope::AccountsRead => "accounts:read",
+            Scope::AccountsWrite => "accounts:write",
+            Scope::PaymentsWrite => "payments:write",
+        }
+    }
+}
+
+impl fmt::Display for Scope {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for Scope {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Scope::ALL
+            .into_iter()
+            .find(|scope| scope.as_str() == s)
+            .ok_or_else(|| format!("Unknown scope: {}", s))
+    }
+}
+
+// A refresh token as stored server side. Only the SHA-256 hash of the token is kept.
+// All tokens obtained by rotating the token issued at login share the same family.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RefreshToken {
+    pub token_hash: String,
+    pub family_id: String,
+    pub user_id: String,
+    pub created_at: DateTime<Utc>,
+    pub expires_at: DateTime<Utc>,
+    pub used: bool,
+    pub revoked: bool,
+}
+
+// A single-use password reset token. Only the SHA-256 hash of the token is kept.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PasswordResetToken {
+    pub token_hash: String,
+    pub user_id: String,
+    pub created_at: DateTime<Utc>,
+    pub expires_at: DateTime<Utc>,
+}
+
+// Sent to a new user to confirm their email address. Only the SHA-256 hash is kept.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EmailVerificationToken {
+    pub token_hash: String,
+    pub user_id: String,
+    pub created_at: DateTime<Utc>,
+    pub expires_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AuthTokens {
+    pub access_token: String,
+    pub refresh_token: String,
+    pub expires_in: u64,
+}
+
+// Result of a password login: tokens, or a challenge to complete with a second factor
+#[derive(Debug, Clone)]
+pub enum LoginOutcome {
+    Authenticated(AuthTokens),
+    TwoFactorRequired {
+        challenge_token: String,
+        expires_in: u64,
+    },
+}
+
+impl LoginOutcome {
+    pub fn into_tokens(self) -> Option<AuthTokens> {
+        match self {
+            LoginOutcome::Authenticated(tokens) => Some(tokens),
+            LoginOutcome::TwoFactorRequired { .. } => None,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RefreshRequest {
+    pub refresh_token: String,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct LogoutRequest {
+    pub refresh_token: Option<String>,
+}
+
+// Credentials of a trusted backend client allowed to obtain access tokens on behalf of users
+#[derive(Debug, Clone)]
+pub struct ClientCredentials {
+    pub client_id: String,
+    pub client_secret: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_scope_round_trips_through_string() {
+        for scope in Scope::ALL {
+            assert_eq!(scope.as_str().parse::<Scope>().unwrap(), scope);
+            assert_eq!(
+                serde_json::to_string(&scope).unwrap(),
+                format!("\"{}\"", scope)
+            );
+        }
+    }
+
+    #[test]
+    fn test_unknown_scope_is_rejected() {
+        assert!("accounts:delete".parse::<Scope>().is_err());
+        assert!(serde_json::from_str::<Scope>("\"admin\"").is_err());
+    }
+}
@@ -0,0 +1,53 @@
+use crate::domain::session::ClientInfo;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+// TOTP settings of a user. The secret must be kept in a recoverable form to compute codes;
+// recovery codes are single use and only their SHA-256 hashes are kept.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TwoFactorSettings {
+    pub user_id: String,
+    pub secret: String,
+    // False between enrollment and the first verified code
+    pub enabled: bool,
+    pub recovery_code_hashes: Vec<String>,
+    // Last accepted TOTP step; a code is never accepted twice
+    pub last_used_step: Option<i64>,
+    pub created_at: DateTime<Utc>,
+}
+
+// Issued by a password login when 2FA is enabled, exchanged for tokens with a valid code.
+// Only the SHA-256 hash of the challenge token is kept.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LoginChallenge {
+    pub token_hash: String,
+    pub user_id: String,
+    pub expires_at: DateTime<Utc>,
+    pub failed_attempts: u32,
+    // Recorded on the session started once the code is verified
+    #[serde(default)]
+    pub client: ClientInfo,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TwoFactorEnrollment {
+    pub secret: String,
+    pub otpauth_uri: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct VerifyTwoFactorRequest {
+    pub code: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RecoveryCodes {
+    pub recovery_codes: Vec<String>,
+}
+
+// `code` is either a current TOTP code or one of the recovery codes
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TwoFactorLoginRequest {
+    pub challenge_token: String,
+    pub code: String,
+}
@@ -1,10 +1,198 @@
+use crate::domain::models::Amount;
+use anyhow::Result;
+use async_trait::async_trait;
+use chrono::{DateTime, NaiveDate, Utc};
 use serde::{Deserialize, Serialize};
+use std::fmt;
+
+// Operators handle customers' requests; admins also manage users and roles
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Role {
+    #[default]
+    Customer,
+    Operator,
+    Admin,
+}
+
+impl Role {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Role::Customer => "customer",
+            Role::Operator => "operator",
+            Role::Admin => "admin",
+        }
+    }
+}
+
+impl fmt::Display for Role {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+// New users stay pending until they confirm their email address
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum UserStatus {
+    PendingVerification,
+    #[default]
+    Active,
+    // Personal data erased on the user's request; the record stays for the accounts
+    Deleted,
+}
+
+// Identity verification of a customer, moved along by operators
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum KycStatus {
+    #[default]
+    Unverified,
+    Pending,
+    Verified,
+    Rejected,
+}
+
+impl KycStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            KycStatus::Unverified => "unverified",
+            KycStatus::Pending => "pending",
+            KycStatus::Verified => "verified",
+            KycStatus::Rejected => "rejected",
+        }
+    }
+
+    // Every decision goes through a review; a verified identity can be put back under
+    // review or rejected when new information comes up
+    pub fn can_transition_to(&self, next: KycStatus) -> bool {
+        use KycStatus::*;
+        matches!(
+            (self, next),
+            (Unverified, Pending)
+                | (Pending, Verified)
+                | (Pending, Rejected)
+                | (Pending, Unverified)
+                | (Verified, Pending)
+                | (Verified, Rejected)
+                | (Rejected, Pending)
+        )
+    }
+}
+
+impl fmt::Display for KycStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Address {
+    pub line1: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub line2: Option<String>,
+    pub city: String,
+    pub postal_code: String,
+    // ISO 3166-1 alpha-2
+    pub country: String,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Profile {
+    pub full_name: Option<String>,
+    pub date_of_birth: Option<NaiveDate>,
+    // E.164, e.g. +79161234567
+    pub phone: Option<String>,
+    pub address: Option<Address>,
+}
+
+impl Profile {
+    // Everything an identity check needs
+    pub fn is_complete(&self) -> bool {
+        self.full_name.is_some() && self.date_of_birth.is_some() && self.address.is_some()
+    }
+}
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct User {
     pub id: String,
     pub email: String,
     pub password_hash: String,
+    // Users stored before roles existed are customers
+    #[serde(default)]
+    pub role: Role,
+    // Users stored before email verification existed are active
+    #[serde(default)]
+    pub status: UserStatus,
+    #[serde(default)]
+    pub profile: Profile,
+    #[serde(default)]
+    pub kyc_status: KycStatus,
+}
+
+impl User {
+    // Erases the personal data. The id stays, as accounts and transactions refer to it, and
+    // so do the role and KYC status, which audits need.
+    pub fn anonymize(&mut self) {
+        self.email = format!("deleted-{}@deleted.invalid", self.id);
+        // Not a valid hash, so no password matches it
+        self.password_hash = String::new();
+        self.profile = Profile::default();
+        self.status = UserStatus::Deleted;
+    }
+}
+
+// Everything stored about a user, for the data export
+#[derive(Debug, Serialize, Deserialize)]
+pub struct UserExport {
+    pub id: String,
+    pub email: String,
+    pub role: Role,
+    pub status: UserStatus,
+    pub kyc_status: KycStatus,
+    pub profile: Profile,
+    pub kyc_history: Vec<KycTransition>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DeleteUserRequest {
+    pub password: String,
+}
+
+// What a customer may do with their money, consulted by `BankService`
+#[async_trait]
+pub trait CustomerPolicy: Send + Sync {
+    // Fails with `DomainError::Forbidden` when the user may not open accounts
+    async fn check_can_open_account(&self, user_id: &str) -> Result<()>;
+    // Largest amount the user may move out of an account at once; `None` for no limit
+    async fn transaction_limit(&self, user_id: &str) -> Result<Option<Amount>>;
+}
+
+// Fields left out are not changed
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct UpdateProfileRequest {
+    pub full_name: Option<String>,
+    pub date_of_birth: Option<NaiveDate>,
+    pub phone: Option<String>,
+    pub address: Option<Address>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct UpdateKycStatusRequest {
+    pub status: KycStatus,
+    // Why the operator made the decision, kept in the audit trail
+    pub reason: String,
+}
+
+// Audit record of a KYC status change
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KycTransition {
+    pub user_id: String,
+    pub from: KycStatus,
+    pub to: KycStatus,
+    pub reason: String,
+    pub operator_id: String,
+    pub at: DateTime<Utc>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -18,3 +206,53 @@ pub struct LoginRequest {
     pub email: String,
     pub password: String,
 }
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ChangePasswordRequest {
+    pub current_password: String,
+    pub new_password: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PasswordResetRequest {
+    pub email: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct UpdateRoleRequest {
+    pub role: Role,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct UnlockLoginRequest {
+    pub email: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct VerifyEmailRequest {
+    pub token: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ResetPasswordRequest {
+    pub token: String,
+    pub new_password: String,
+}
+
+// Users whose emails collapse to the same identity after normalization
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DuplicateEmail {
+    pub email: String,
+    pub user_ids: Vec<String>,
+}
+
+// Outcome of `AuthService::migrate_email_identities`
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct EmailMigrationReport {
+    // Users whose stored email was rewritten to its normalized form
+    pub normalized: Vec<String>,
+    // Left untouched; every group has to be merged or renamed by an operator
+    pub duplicates: Vec<DuplicateEmail>,
+    // Users whose email does not pass validation; left untouched
+    pub invalid: Vec<String>,
+}
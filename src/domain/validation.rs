@@ -0,0 +1,594 @@
+use crate::domain::error::FieldError;
+use crate::domain::models::TransactionDetails;
+use crate::domain::payment_request::CreatePaymentRequest;
+use crate::domain::reversal::REVERSAL_OF_METADATA_KEY;
+use crate::domain::user::UpdateProfileRequest;
+use chrono::NaiveDate;
+use std::collections::HashSet;
+use std::sync::LazyLock;
+use unicode_normalization::UnicodeNormalization;
+
+const COMMON_PASSWORDS_LIST: &str = include_str!("common_passwords.txt");
+
+static COMMON_PASSWORDS: LazyLock<HashSet<String>> = LazyLock::new(|| {
+    COMMON_PASSWORDS_LIST
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(str::to_lowercase)
+        .collect()
+});
+
+const EMAIL_MAX_LENGTH: usize = 254;
+const EMAIL_LOCAL_MAX_LENGTH: usize = 64;
+const DOMAIN_LABEL_MAX_LENGTH: usize = 63;
+// Characters allowed in the local part besides letters and digits (RFC 5322 atext)
+const LOCAL_PART_SYMBOLS: &str = "!#$%&'*+-/=?^_`{|}~.";
+const PROFILE_TEXT_MAX_LENGTH: usize = 200;
+const BENEFICIARY_NAME_MAX_LENGTH: usize = 100;
+const BENEFICIARY_NICKNAME_MAX_LENGTH: usize = 50;
+const PAYMENT_REQUEST_DESCRIPTION_MAX_LENGTH: usize = 140;
+const TRANSACTION_DESCRIPTION_MAX_LENGTH: usize = 140;
+// As the end-to-end id of ISO 20022 payments
+const TRANSACTION_REFERENCE_MAX_LENGTH: usize = 35;
+const METADATA_MAX_ENTRIES: usize = 20;
+const METADATA_KEY_MAX_LENGTH: usize = 40;
+const METADATA_VALUE_MAX_LENGTH: usize = 500;
+// E.164 allows up to 15 digits; fewer than 8 is never a complete number
+const PHONE_MIN_DIGITS: usize = 8;
+const PHONE_MAX_DIGITS: usize = 15;
+
+#[derive(Debug, Clone)]
+pub struct PasswordPolicy {
+    pub min_length: usize,
+    // Bounds the cost of hashing with argon2
+    pub max_length: usize,
+    pub require_lowercase: bool,
+    pub require_uppercase: bool,
+    pub require_digit: bool,
+    pub require_symbol: bool,
+    pub reject_common: bool,
+}
+
+impl Default for PasswordPolicy {
+    fn default() -> Self {
+        Self {
+            min_length: 10,
+            max_length: 128,
+            require_lowercase: true,
+            require_uppercase: true,
+            require_digit: true,
+            require_symbol: false,
+            reject_common: true,
+        }
+    }
+}
+
+impl PasswordPolicy {
+    // Returns every rule the password breaks; an empty list means the password is acceptable.
+    // The email, when known, is used to reject passwords built from the user's address.
+    pub fn validate(&self, password: &str, email: Option<&str>) -> Vec<FieldError> {
+        let mut errors = Vec::new();
+        let length = password.chars().count();
+
+        if length < self.min_length {
+            errors.push(password_error(
+                "min_length",
+                format!(
+                    "Password must be at least {} characters long",
+                    self.min_length
+                ),
+            ));
+        }
+        if length > self.max_length {
+            errors.push(password_error(
+                "max_length",
+                format!(
+                    "Password must be at most {} characters long",
+                    self.max_length
+                ),
+            ));
+        }
+        if self.require_lowercase && !password.chars().any(char::is_lowercase) {
+            errors.push(password_error(
+                "lowercase",
+                "Password must contain a lowercase letter".to_string(),
+            ));
+        }
+        if self.require_uppercase && !password.chars().any(char::is_uppercase) {
+            errors.push(password_error(
+                "uppercase",
+                "Password must contain an uppercase letter".to_string(),
+            ));
+        }
+        if self.require_digit && !password.chars().any(|c| c.is_ascii_digit()) {
+            errors.push(password_error(
+                "digit",
+                "Password must contain a digit".to_string(),
+            ));
+        }
+        if self.require_symbol && password.chars().all(char::is_alphanumeric) {
+            errors.push(password_error(
+                "symbol",
+                "Password must contain a symbol".to_string(),
+            ));
+        }
+        if self.reject_common && is_common_password(password) {
+            errors.push(password_error(
+                "common",
+                "Password is too common or has appeared in a data breach".to_string(),
+            ));
+        }
+        if let Some(local_part) = email.and_then(|e| e.split('@').next())
+            && local_part.chars().count() >= 3
+            && password.to_lowercase().contains(&local_part.to_lowercase())
+        {
+            errors.push(password_error(
+                "contains_email",
+                "Password must not contain the email address".to_string(),
+            ));
+        }
+
+        errors
+    }
+}
+
+fn password_error(rule: &str, message: String) -> FieldError {
+    FieldError::new("password", rule, message)
+}
+
+pub fn is_common_password(password: &str) -> bool {
+    COMMON_PASSWORDS.contains(&password.to_lowercase())
+}
+
+// Validates the email syntax and returns its normalized form: trimmed, NFKC-normalized and
+// with a lowercase domain. The case of the local part is kept for display.
+pub fn normalize_email(email: &str) -> Result<String, FieldError> {
+    let invalid = |message: &str| FieldError::new("email", "format", message.to_string());
+    let email: String = email.trim().nfkc().collect();
+    let email = email.as_str();
+
+    if email.is_empty() {
+        return Err(invalid("Email is required"));
+    }
+    if email.chars().count() > EMAIL_MAX_LENGTH {
+        return Err(invalid("Email is too long"));
+    }
+    let (local_part, domain) = email
+        .split_once('@')
+        .ok_or_else(|| invalid("Email must contain '@'"))?;
+    if domain.contains('@') {
+        return Err(invalid("Email must contain a single '@'"));
+    }
+
+    if local_part.is_empty() || local_part.chars().count() > EMAIL_LOCAL_MAX_LENGTH {
+        return Err(invalid("Email local part must be 1 to 64 characters long"));
+    }
+    if !local_part
+        .chars()
+        .all(|c| c.is_alphanumeric() || LOCAL_PART_SYMBOLS.contains(c))
+        || local_part.starts_with('.')
+        || local_part.ends_with('.')
+        || local_part.contains("..")
+    {
+        return Err(invalid("Email local part contains invalid characters"));
+    }
+
+    let labels: Vec<&str> = domain.split('.').collect();
+    if labels.len() < 2 {
+        return Err(invalid("Email domain must contain a dot"));
+    }
+    let valid_label = |label: &&str| {
+        !label.is_empty()
+            && label.chars().count() <= DOMAIN_LABEL_MAX_LENGTH
+            && label.chars().all(|c| c.is_alphanumeric() || c == '-')
+            && !label.starts_with('-')
+            && !label.ends_with('-')
+    };
+    if !labels.iter().all(valid_label) {
+        return Err(invalid("Email domain is invalid"));
+    }
+    let tld = labels[labels.len() - 1];
+    if tld.chars().count() < 2 || !tld.chars().all(char::is_alphabetic) {
+        return Err(invalid("Email domain is invalid"));
+    }
+
+    Ok(format!("{}@{}", local_part, domain.to_lowercase()))
+}
+
+// Key under which emails are unique: two emails identify the same user when their keys match
+pub fn email_identity(email: &str) -> String {
+    email.trim().nfkc().collect::<String>().to_lowercase()
+}
+
+// Checks the fields present in a profile update; `today` bounds the date of birth
+pub fn validate_profile_update(req: &UpdateProfileRequest, today: NaiveDate) -> Vec<FieldError> {
+    let mut errors = Vec::new();
+    let mut text = |field: &str, value: &str, required: bool| {
+        if required && value.trim().is_empty() {
+            errors.push(FieldError::new(
+                field,
+                "required",
+                format!("{} is required", field),
+            ));
+        } else if value.chars().count() > PROFILE_TEXT_MAX_LENGTH {
+            errors.push(FieldError::new(
+                field,
+                "max_length",
+                format!(
+                    "{} must be at most {} characters",
+                    field, PROFILE_TEXT_MAX_LENGTH
+                ),
+            ));
+        }
+    };
+
+    if let Some(full_name) = &req.full_name {
+        text("full_name", full_name, true);
+    }
+    if let Some(address) = &req.address {
+        text("address.line1", &address.line1, true);
+        if let Some(line2) = &address.line2 {
+            text("address.line2", line2, false);
+        }
+        text("address.city", &address.city, true);
+        text("address.postal_code", &address.postal_code, true);
+        if address.country.len() != 2 || !address.country.chars().all(|c| c.is_ascii_uppercase()) {
+            errors.push(FieldError::new(
+                "address.country",
+                "format",
+                "Country must be an ISO 3166-1 alpha-2 code, e.g. RU".to_string(),
+            ));
+        }
+    }
+    if let Some(date_of_birth) = req.date_of_birth
+        && date_of_birth > today
+    {
+        errors.push(FieldError::new(
+            "date_of_birth",
+            "future",
+            "Date of birth must not be in the future".to_string(),
+        ));
+    }
+    if let Some(phone) = &req.phone
+        && !is_e164(phone)
+    {
+        errors.push(FieldError::new(
+            "phone",
+            "format",
+            "Phone must be in E.164 format, e.g. +79161234567".to_string(),
+        ));
+    }
+
+    errors
+}
+
+// Checks the name and nickname of a beneficiary being saved or changed
+pub fn validate_beneficiary(name: Option<&str>, nickname: Option<&str>) -> Vec<FieldError> {
+    let mut errors = Vec::new();
+    if let Some(name) = name {
+        if name.trim().is_empty() {
+            errors.push(FieldError::new(
+                "name",
+                "required",
+                "name is required".to_string(),
+            ));
+        } else if name.chars().count() > BENEFICIARY_NAME_MAX_LENGTH {
+            errors.push(FieldError::new(
+                "name",
+                "max_length",
+                format!(
+                    "name must be at most {} characters",
+                    BENEFICIARY_NAME_MAX_LENGTH
+                ),
+            ));
+        }
+    }
+    if let Some(nickname) = nickname
+        && nickname.chars().count() > BENEFICIARY_NICKNAME_MAX_LENGTH
+    {
+        errors.push(FieldError::new(
+            "nickname",
+            "max_length",
+            format!(
+                "nickname must be at most {} characters",
+                BENEFICIARY_NICKNAME_MAX_LENGTH
+            ),
+        ));
+    }
+    errors
+}
+
+pub fn validate_payment_request(req: &CreatePaymentRequest, today: NaiveDate) -> Vec<FieldError> {
+    let mut errors = Vec::new();
+    if req.amount.inner() == 0 {
+        errors.push(FieldError::new(
+            "amount",
+            "positive",
+            "amount must be greater than zero".to_string(),
+        ));
+    }
+    if req.description.trim().is_empty() {
+        errors.push(FieldError::new(
+            "description",
+            "required",
+            "description is required".to_string(),
+        ));
+    } else if req.description.chars().count() > PAYMENT_REQUEST_DESCRIPTION_MAX_LENGTH {
+        errors.push(FieldError::new(
+            "description",
+            "max_length",
+            format!(
+                "description must be at most {} characters",
+                PAYMENT_REQUEST_DESCRIPTION_MAX_LENGTH
+            ),
+        ));
+    }
+    if req.due_date < today {
+        errors.push(FieldError::new(
+            "due_date",
+            "past",
+            "due_date must not be in the past".to_string(),
+        ));
+    }
+    errors
+}
+
+// Checks what the customer attached to a transfer, deposit or withdrawal
+pub fn validate_transaction_details(details: &TransactionDetails) -> Vec<FieldError> {
+    let mut errors = Vec::new();
+    if let Some(description) = &details.description
+        && description.chars().count() > TRANSACTION_DESCRIPTION_MAX_LENGTH
+    {
+        errors.push(FieldError::new(
+            "description",
+            "max_length",
+            format!(
+                "description must be at most {} characters",
+                TRANSACTION_DESCRIPTION_MAX_LENGTH
+            ),
+        ));
+    }
+    if let Some(reference) = &details.reference {
+        if reference.chars().count() > TRANSACTION_REFERENCE_MAX_LENGTH {
+            errors.push(FieldError::new(
+                "reference",
+                "max_length",
+                format!(
+                    "reference must be at most {} characters",
+                    TRANSACTION_REFERENCE_MAX_LENGTH
+                ),
+            ));
+        } else if reference.chars().any(char::is_control) {
+            errors.push(FieldError::new(
+                "reference",
+                "format",
+                "reference must not contain control characters".to_string(),
+            ));
+        }
+    }
+    if details.metadata.len() > METADATA_MAX_ENTRIES {
+        errors.push(FieldError::new(
+            "metadata",
+            "max_entries",
+            format!(
+                "metadata must have at most {} entries",
+                METADATA_MAX_ENTRIES
+            ),
+        ));
+    }
+    for (key, value) in &details.metadata {
+        let key_length = key.chars().count();
+        if key_length == 0 || key_length > METADATA_KEY_MAX_LENGTH {
+            errors.push(FieldError::new(
+                "metadata",
+                "key_length",
+                format!(
+                    "metadata keys must have 1 to {} characters",
+                    METADATA_KEY_MAX_LENGTH
+                ),
+            ));
+        }
+        if key == REVERSAL_OF_METADATA_KEY {
+            errors.push(FieldError::new(
+                "metadata",
+                "reserved",
+                format!("metadata key '{}' is reserved", key),
+            ));
+        }
+        if value.chars().count() > METADATA_VALUE_MAX_LENGTH {
+            errors.push(FieldError::new(
+                "metadata",
+                "value_length",
+                format!(
+                    "metadata value of '{}' must be at most {} characters",
+                    key, METADATA_VALUE_MAX_LENGTH
+                ),
+            ));
+        }
+    }
+    errors
+}
+
+fn is_e164(phone: &str) -> bool {
+    phone.strip_prefix('+').is_some_and(|digits| {
+        (PHONE_MIN_DIGITS..=PHONE_MAX_DIGITS).contains(&digits.len())
+            && digits.chars().all(|c| c.is_ascii_digit())
+            && !digits.starts_with('0')
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::user::Address;
+
+    fn rules(errors: &[FieldError]) -> Vec<&str> {
+        errors.iter().map(|e| e.rule.as_str()).collect()
+    }
+
+    #[test]
+    fn test_password_policy_accepts_strong_password() {
+        let policy = PasswordPolicy::default();
+        assert!(policy.validate("Tr1cky-Horse-Battery", None).is_empty());
+    }
+
+    #[test]
+    fn test_password_policy_reports_every_failed_rule() {
+        let policy = PasswordPolicy::default();
+        let errors = policy.validate("", None);
+
+        assert_eq!(
+            rules(&errors),
+            vec!["min_length", "lowercase", "uppercase", "digit"]
+        );
+        assert!(errors.iter().all(|e| e.field == "password"));
+    }
+
+    #[test]
+    fn test_password_policy_enforces_max_length() {
+        let policy = PasswordPolicy::default();
+        let password = format!("Aa1{}", "x".repeat(200));
+
+        assert_eq!(rules(&policy.validate(&password, None)), vec!["max_length"]);
+    }
+
+    #[test]
+    fn test_password_policy_rejects_common_password_case_insensitively() {
+        let policy = PasswordPolicy::default();
+
+        assert_eq!(rules(&policy.validate("Password123", None)), vec!["common"]);
+    }
+
+    #[test]
+    fn test_password_policy_rejects_password_containing_email() {
+        let policy = PasswordPolicy::default();
+        let errors = policy.validate("Alice-Secret-42", Some("alice@example.com"));
+
+        assert_eq!(rules(&errors), vec!["contains_email"]);
+    }
+
+    #[test]
+    fn test_password_policy_is_configurable() {
+        let policy = PasswordPolicy {
+            min_length: 4,
+            require_uppercase: false,
+            require_digit: false,
+            require_symbol: true,
+            ..PasswordPolicy::default()
+        };
+
+        assert_eq!(rules(&policy.validate("abcd", None)), vec!["symbol"]);
+        assert!(policy.validate("ab-cd", None).is_empty());
+    }
+
+    #[test]
+    fn test_normalize_email_trims_whitespace() {
+        assert_eq!(
+            normalize_email("  alice@example.com \n").unwrap(),
+            "alice@example.com"
+        );
+    }
+
+    #[test]
+    fn test_normalize_email_lowercases_domain_only() {
+        assert_eq!(
+            normalize_email("Alice.Smith@Example.COM").unwrap(),
+            "Alice.Smith@example.com"
+        );
+    }
+
+    #[test]
+    fn test_normalize_email_applies_nfkc() {
+        // Fullwidth letters and the "ﬁ" ligature fold to their plain forms
+        assert_eq!(
+            normalize_email("ａｌｉｃｅ@ﬁnance.example.com").unwrap(),
+            "alice@finance.example.com"
+        );
+    }
+
+    #[test]
+    fn test_email_identity_is_case_insensitive() {
+        assert_eq!(
+            email_identity(" Alice@X.com"),
+            email_identity("alice@x.COM ")
+        );
+        assert_eq!(email_identity("ＡＬＩＣＥ@x.com"), "alice@x.com");
+        assert_ne!(email_identity("alice@x.com"), email_identity("bob@x.com"));
+    }
+
+    #[test]
+    fn test_normalize_email_accepts_valid_addresses() {
+        for email in [
+            "a@b.co",
+            "first.last+tag@sub.example.org",
+            "o'brien@example-mail.com",
+            "иван@пример.рф",
+        ] {
+            assert!(normalize_email(email).is_ok(), "{} should be valid", email);
+        }
+    }
+
+    #[test]
+    fn test_normalize_email_rejects_invalid_addresses() {
+        for email in [
+            "",
+            "plainaddress",
+            "@example.com",
+            "alice@",
+            "alice@@example.com",
+            "alice@localhost",
+            "alice@example.c",
+            "alice@-example.com",
+            "alice@example..com",
+            ".alice@example.com",
+            "al..ice@example.com",
+            "al ice@example.com",
+            "alice@exa_mple.com",
+        ] {
+            let error = normalize_email(email).unwrap_err();
+            assert_eq!(error.field, "email", "{} should be invalid", email);
+        }
+    }
+
+    #[test]
+    fn test_profile_update_accepts_valid_fields() {
+        let req = UpdateProfileRequest {
+            full_name: Some("Alice Smith".to_string()),
+            date_of_birth: NaiveDate::from_ymd_opt(1990, 5, 17),
+            phone: Some("+79161234567".to_string()),
+            address: Some(Address {
+                line1: "Tverskaya 1".to_string(),
+                line2: None,
+                city: "Moscow".to_string(),
+                postal_code: "125009".to_string(),
+                country: "RU".to_string(),
+            }),
+        };
+        let today = NaiveDate::from_ymd_opt(2024, 1, 1).unwrap();
+        assert!(validate_profile_update(&req, today).is_empty());
+    }
+
+    #[test]
+    fn test_profile_update_rejects_invalid_fields() {
+        let req = UpdateProfileRequest {
+            full_name: Some("  ".to_string()),
+            date_of_birth: NaiveDate::from_ymd_opt(2030, 1, 1),
+            phone: Some("89161234567".to_string()),
+            address: Some(Address {
+                line1: "Tverskaya 1".to_string(),
+                line2: None,
+                city: "Moscow".to_string(),
+                postal_code: "125009".to_string(),
+                country: "Russia".to_string(),
+            }),
+        };
+        let today = NaiveDate::from_ymd_opt(2024, 1, 1).unwrap();
+        let errors = validate_profile_update(&req, today);
+        let fields: Vec<&str> = errors.iter().map(|e| e.field.as_str()).collect();
+        assert_eq!(
+            fields,
+            vec!["full_name", "address.country", "date_of_birth", "phone"]
+        );
+    }
+}
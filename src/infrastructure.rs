@@ -1,2 +1,7 @@
+pub mod iso20022;
+pub mod jwt_keys;
 pub mod logging;
+pub mod notifier;
+pub mod oidc;
 pub mod security;
+pub mod totp;
@@ -0,0 +1,857 @@
+// ISO 20022 message support:
+// - pain.001 (customer credit transfer initiation) is imported into batch transfers
+//   and can be generated from a list of transfers;
+// - camt.053 (bank to customer statement) is generated from account statements
+//   and can be parsed back.
+//
+// `Amount` values are minor currency units, ISO 20022 amounts carry two decimals,
+// so `12345` is exchanged as `123.45`. Accounts are identified by their numeric id
+// in `<Id><Othr><Id>`.
+use crate::domain::error::DomainError;
+use crate::domain::models::{
+    AccountStatement, Amount, BatchTransferRow, Transaction, TransactionDetails, TransactionKind,
+    Transfer,
+};
+use chrono::{DateTime, SecondsFormat, Utc};
+use quick_xml::se::Serializer;
+use serde::{Deserialize, Serialize};
+use tracing::{debug, instrument, warn};
+
+pub const PAIN001_NAMESPACE: &str = "urn:iso:std:iso:20022:tech:xsd:pain.001.001.09";
+pub const CAMT053_NAMESPACE: &str = "urn:iso:std:iso:20022:tech:xsd:camt.053.001.08";
+pub const CURRENCY: &str = "RUB";
+
+const XML_DECLARATION: &str = "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n";
+const INITIATING_PARTY: &str = "Yandex Bank API";
+
+// Shared building blocks
+
+#[derive(Debug, Serialize, Deserialize)]
+struct CurrencyAmount {
+    #[serde(rename = "@Ccy")]
+    currency: String,
+    #[serde(rename = "$text")]
+    value: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct CashAccount {
+    #[serde(rename = "Id")]
+    id: AccountIdentification,
+    #[serde(rename = "Ccy", skip_serializing_if = "Option::is_none")]
+    currency: Option<String>,
+    #[serde(rename = "Nm", skip_serializing_if = "Option::is_none")]
+    name: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct AccountIdentification {
+    #[serde(rename = "Othr")]
+    other: GenericIdentification,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct GenericIdentification {
+    #[serde(rename = "Id")]
+    id: String,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct Party {
+    #[serde(rename = "Nm", skip_serializing_if = "Option::is_none")]
+    name: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct Agent {
+    #[serde(rename = "FinInstnId")]
+    institution: FinancialInstitution,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct FinancialInstitution {
+    #[serde(rename = "Othr")]
+    other: GenericIdentification,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct Code {
+    #[serde(rename = "Cd")]
+    code: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct DateTimeChoice {
+    #[serde(rename = "DtTm")]
+    date_time: DateTime<Utc>,
+}
+
+// pain.001
+
+#[derive(Debug, Serialize, Deserialize)]
+struct Pain001Document {
+    #[serde(rename = "@xmlns", default)]
+    xmlns: String,
+    #[serde(rename = "CstmrCdtTrfInitn")]
+    initiation: CustomerCreditTransferInitiation,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct CustomerCreditTransferInitiation {
+    #[serde(rename = "GrpHdr")]
+    group_header: Pain001GroupHeader,
+    #[serde(rename = "PmtInf", default)]
+    payments: Vec<PaymentInformation>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct Pain001GroupHeader {
+    #[serde(rename = "MsgId")]
+    message_id: String,
+    #[serde(rename = "CreDtTm")]
+    created_at: String,
+    #[serde(rename = "NbOfTxs")]
+    number_of_transactions: String,
+    #[serde(rename = "CtrlSum", skip_serializing_if = "Option::is_none")]
+    control_sum: Option<String>,
+    #[serde(rename = "InitgPty", default)]
+    initiating_party: Party,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct PaymentInformation {
+    #[serde(rename = "PmtInfId")]
+    id: String,
+    #[serde(rename = "PmtMtd")]
+    method: String,
+    #[serde(rename = "ReqdExctnDt")]
+    requested_execution_date: RequestedExecutionDate,
+    #[serde(rename = "Dbtr", default)]
+    debtor: Party,
+    #[serde(rename = "DbtrAcct")]
+    debtor_account: CashAccount,
+    #[serde(rename = "DbtrAgt", skip_serializing_if = "Option::is_none")]
+    debtor_agent: Option<Agent>,
+    #[serde(rename = "CdtTrfTxInf", default)]
+    transactions: Vec<CreditTransferTransaction>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct RequestedExecutionDate {
+    #[serde(rename = "Dt")]
+    date: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct CreditTransferTransaction {
+    #[serde(rename = "PmtId")]
+    payment_id: PaymentIdentification,
+    #[serde(rename = "Amt")]
+    amount: InstructedAmount,
+    #[serde(rename = "Cdtr", default)]
+    creditor: Party,
+    #[serde(rename = "CdtrAcct")]
+    creditor_account: CashAccount,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct PaymentIdentification {
+    #[serde(rename = "EndToEndId")]
+    end_to_end_id: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct InstructedAmount {
+    #[serde(rename = "InstdAmt")]
+    instructed: CurrencyAmount,
+}
+
+// camt.053
+
+#[derive(Debug, Serialize, Deserialize)]
+struct Camt053Document {
+    #[serde(rename = "@xmlns", default)]
+    xmlns: String,
+    #[serde(rename = "BkToCstmrStmt")]
+    statement: BankToCustomerStatement,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct BankToCustomerStatement {
+    #[serde(rename = "GrpHdr")]
+    group_header: Camt053GroupHeader,
+    #[serde(rename = "Stmt")]
+    statement: Statement,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct Camt053GroupHeader {
+    #[serde(rename = "MsgId")]
+    message_id: String,
+    #[serde(rename = "CreDtTm")]
+    created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct Statement {
+    #[serde(rename = "Id")]
+    id: String,
+    #[serde(rename = "CreDtTm")]
+    created_at: DateTime<Utc>,
+    #[serde(rename = "FrToDt", skip_serializing_if = "Option::is_none")]
+    period: Option<DateTimePeriod>,
+    #[serde(rename = "Acct")]
+    account: CashAccount,
+    #[serde(rename = "Bal", default)]
+    balances: Vec<Balance>,
+    #[serde(rename = "Ntry", default)]
+    entries: Vec<Entry>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct DateTimePeriod {
+    #[serde(rename = "FrDtTm")]
+    from: DateTime<Utc>,
+    #[serde(rename = "ToDtTm")]
+    to: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct Balance {
+    #[serde(rename = "Tp")]
+    balance_type: BalanceType,
+    #[serde(rename = "Amt")]
+    amount: CurrencyAmount,
+    #[serde(rename = "CdtDbtInd")]
+    credit_debit: String,
+    #[serde(rename = "Dt")]
+    date: DateTimeChoice,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct BalanceType {
+    #[serde(rename = "CdOrPrtry")]
+    code: Code,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct Entry {
+    #[serde(rename = "NtryRef")]
+    reference: String,
+    #[serde(rename = "Amt")]
+    amount: CurrencyAmount,
+    #[serde(rename = "CdtDbtInd")]
+    credit_debit: String,
+    #[serde(rename = "Sts")]
+    status: Code,
+    #[serde(rename = "BookgDt")]
+    booking_date: DateTimeChoice,
+    #[serde(rename = "BkTxCd")]
+    bank_transaction_code: BankTransactionCode,
+    #[serde(rename = "NtryDtls", skip_serializing_if = "Option::is_none")]
+    details: Option<EntryDetails>,
+    // The description the customer gave
+    #[serde(rename = "AddtlNtryInf", skip_serializing_if = "Option::is_none")]
+    additional_info: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct BankTransactionCode {
+    #[serde(rename = "Prtry")]
+    proprietary: Code,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct EntryDetails {
+    #[serde(rename = "TxDtls")]
+    transaction: EntryTransaction,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct EntryTransaction {
+    #[serde(rename = "RltdPties")]
+    related_parties: RelatedParties,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct RelatedParties {
+    #[serde(rename = "DbtrAcct", skip_serializing_if = "Option::is_none")]
+    debtor_account: Option<CashAccount>,
+    #[serde(rename = "CdtrAcct", skip_serializing_if = "Option::is_none")]
+    creditor_account: Option<CashAccount>,
+}
+
+const CREDIT: &str = "CRDT";
+const DEBIT: &str = "DBIT";
+const OPENING_BOOKED: &str = "OPBD";
+const CLOSING_BOOKED: &str = "CLBD";
+const BOOKED: &str = "BOOK";
+const NOT_PROVIDED: &str = "NOTPROVIDED";
+
+// Parses a pain.001 document into batch rows. Rows are numbered by the position
+// of the `CdtTrfTxInf` element in the document, starting from 1.
+#[instrument(skip(xml), fields(size = xml.len()))]
+pub fn parse_pain001(xml: &str) -> Result<Vec<BatchTransferRow>, DomainError> {
+    let document: Pain001Document = quick_xml::de::from_str(xml).map_err(|e| {
+        warn!(error = %e, "Failed to parse pain.001 document");
+        DomainError::Validation(format!("Invalid pain.001 document: {}", e))
+    })?;
+    check_namespace(&document.xmlns, "pain.001")?;
+
+    let mut rows = Vec::new();
+    let mut errors = Vec::new();
+    let mut control_sum: u64 = 0;
+    for payment in &document.initiation.payments {
+        let from_account_id = parse_account_id(&payment.debtor_account);
+        for transaction in &payment.transactions {
+            let line = rows.len() + errors.len() + 1;
+            let row = from_account_id
+                .clone()
+                .and_then(|from_account_id| {
+                    let to_account_id = parse_account_id(&transaction.creditor_account)?;
+                    let amount = parse_currency_amount(&transaction.amount.instructed)?;
+                    Ok(Transfer {
+                        from_account_id,
+                        to_account_id,
+                        amount,
+                        beneficiary_id: None,
+                        details: TransactionDetails {
+                            reference: end_to_end_reference(&transaction.payment_id),
+                            ..Default::default()
+                        },
+                    })
+                })
+                .map(|transfer| BatchTransferRow { line, transfer });
+            match row {
+                Ok(row) => {
+                    control_sum = control_sum.saturating_add(row.transfer.amount.inner());
+                    rows.push(row);
+                }
+                Err(e) => errors.push(format!("transaction {}: {}", line, e)),
+            }
+        }
+    }
+
+    if !errors.is_empty() {
+        return Err(DomainError::Validation(format!(
+            "Malformed pain.001 transactions: {}",
+            errors.join("; ")
+        )));
+    }
+
+    let header = &document.initiation.group_header;
+    if header.number_of_transactions.trim() != rows.len().to_string() {
+        return Err(DomainError::Validation(format!(
+            "NbOfTxs is {} but the document contains {} transactions",
+            header.number_of_transactions.trim(),
+            rows.len()
+        )));
+    }
+    if let Some(expected) = &header.control_sum {
+        let expected = parse_amount(expected)
+            .map_err(|e| DomainError::Validation(format!("Invalid CtrlSum: {}", e)))?;
+        if expected.inner() != control_sum {
+            return Err(DomainError::Validation(format!(
+                "CtrlSum is {} but the transactions sum up to {}",
+                format_amount(expected),
+                format_amount(Amount::new(control_sum))
+            )));
+        }
+    }
+
+    debug!(
+        message_id = %header.message_id,
+        transactions = rows.len(),
+        "pain.001 document parsed"
+    );
+    Ok(rows)
+}
+
+// Generates a pain.001 document. Consecutive transfers from the same account are
+// grouped into one payment information block, so the transfer order is preserved.
+#[instrument(skip(transfers), fields(transfers = transfers.len()))]
+pub fn write_pain001(message_id: &str, transfers: &[Transfer]) -> Result<String, DomainError> {
+    let now = Utc::now();
+    let mut payments: Vec<PaymentInformation> = Vec::new();
+    for (index, transfer) in transfers.iter().enumerate() {
+        let transaction = CreditTransferTransaction {
+            payment_id: PaymentIdentification {
+                end_to_end_id: transfer
+                    .details
+                    .reference
+                    .clone()
+                    .unwrap_or_else(|| format!("{}-{}", message_id, index + 1)),
+            },
+            amount: InstructedAmount {
+                instructed: currency_amount(transfer.amount),
+            },
+            creditor: Party::default(),
+            creditor_account: cash_account(transfer.to_account_id, None),
+        };
+        match payments.last_mut() {
+            Some(payment)
+                if payment.debtor_account.id.other.id == transfer.from_account_id.to_string() =>
+            {
+                payment.transactions.push(transaction)
+            }
+            _ => payments.push(PaymentInformation {
+                id: format!("{}-PMT-{}", message_id, payments.len() + 1),
+                method: "TRF".to_string(),
+                requested_execution_date: RequestedExecutionDate {
+                    date: now.format("%Y-%m-%d").to_string(),
+                },
+                debtor: Party::default(),
+                debtor_account: cash_account(transfer.from_account_id, None),
+                debtor_agent: Some(Agent {
+                    institution: FinancialInstitution {
+                        other: GenericIdentification {
+                            id: NOT_PROVIDED.to_string(),
+                        },
+                    },
+                }),
+                transactions: vec![transaction],
+            }),
+        }
+    }
+
+    let control_sum = transfers
+        .iter()
+        .fold(0u64, |sum, t| sum.saturating_add(t.amount.inner()));
+    let document = Pain001Document {
+        xmlns: PAIN001_NAMESPACE.to_string(),
+        initiation: CustomerCreditTransferInitiation {
+            group_header: Pain001GroupHeader {
+                message_id: message_id.to_string(),
+                created_at: now.to_rfc3339_opts(SecondsFormat::Secs, true),
+                number_of_transactions: transfers.len().to_string(),
+                control_sum: Some(format_amount(Amount::new(control_sum))),
+                initiating_party: Party {
+                    name: Some(INITIATING_PARTY.to_string()),
+                },
+            },
+            payments,
+        },
+    };
+    to_xml(&document)
+}
+
+// Generates a camt.053 statement for the account.
+#[instrument(skip(statement), fields(account_id = statement.account_id))]
+pub fn write_camt053(statement: &AccountStatement) -> Result<String, DomainError> {
+    let now = Utc::now();
+    let statement_id = format!(
+        "STMT-{}-{}",
+        statement.account_id,
+        now.format("%Y%m%d%H%M%S")
+    );
+    let period = match (statement.from, statement.to) {
+        (Some(from), Some(to)) => Some(DateTimePeriod { from, to }),
+        _ => None,
+    };
+    let opening_date = statement
+        .from
+        .or_else(|| statement.transactions.first().map(|t| t.created_at))
+        .unwrap_or(now);
+    let closing_date = statement.to.unwrap_or(now);
+
+    let entries = statement
+        .transactions
+        .iter()
+        .map(|transaction| {
+            let counterparty = transaction
+                .counterparty_account_id
+                .map(|id| cash_account(id, None));
+            let details = counterparty.map(|account| {
+                let related_parties = if transaction.kind.is_credit() {
+                    RelatedParties {
+                        debtor_account: Some(account),
+                        creditor_account: None,
+                    }
+                } else {
+                    RelatedParties {
+                        debtor_account: None,
+                        creditor_account: Some(account),
+                    }
+                };
+                EntryDetails {
+                    transaction: EntryTransaction { related_parties },
+                }
+            });
+            Entry {
+                reference: transaction.id.clone(),
+                amount: currency_amount(transaction.amount),
+                credit_debit: credit_debit(transaction.kind.is_credit()).to_string(),
+                status: Code {
+                    code: BOOKED.to_string(),
+                },
+                booking_date: DateTimeChoice {
+                    date_time: transaction.created_at,
+                },
+                bank_transaction_code: BankTransactionCode {
+                    proprietary: Code {
+                        code: kind_code(transaction.kind).to_string(),
+                    },
+                },
+                details,
+                additional_info: transaction.details.description.clone(),
+            }
+        })
+        .collect();
+
+    let document = Camt053Document {
+        xmlns: CAMT053_NAMESPACE.to_string(),
+        statement: BankToCustomerStatement {
+            group_header: Camt053GroupHeader {
+                message_id: statement_id.clone(),
+                created_at: now,
+            },
+            statement: Statement {
+                id: statement_id,
+                created_at: now,
+                period,
+                account: cash_account(statement.account_id, Some(&statement.account_name)),
+                balances: vec![
+                    balance(OPENING_BOOKED, statement.opening_balance, opening_date),
+                    balance(CLOSING_BOOKED, statement.closing_balance, closing_date),
+                ],
+                entries,
+            },
+        },
+    };
+    to_xml(&document)
+}
+
+// Parses a camt.053 statement. Running balances of the entries are recomputed
+// from the opening balance.
+#[instrument(skip(xml), fields(size = xml.len()))]
+pub fn parse_camt053(xml: &str) -> Result<AccountStatement, DomainError> {
+    let document: Camt053Document = quick_xml::de::from_str(xml).map_err(|e| {
+        warn!(error = %e, "Failed to parse camt.053 document");
+        DomainError::Validation(format!("Invalid camt.053 document: {}", e))
+    })?;
+    check_namespace(&document.xmlns, "camt.053")?;
+    let statement = document.statement.statement;
+
+    let account_id = parse_account_id(&statement.account)
+        .map_err(|e| DomainError::Validation(format!("Invalid statement account: {}", e)))?;
+    let find_balance = |code: &str| -> Result<Amount, DomainError> {
+        let balance = statement
+            .balances
+            .iter()
+            .find(|b| b.balance_type.code.code == code)
+            .ok_or_else(|| DomainError::Validation(format!("Missing {} balance", code)))?;
+        if balance.credit_debit != CREDIT {
+            return Err(DomainError::Validation(format!(
+                "{} balance must be a credit balance",
+                code
+            )));
+        }
+        parse_currency_amount(&balance.amount).map_err(DomainError::Validation)
+    };
+    let opening_balance = find_balance(OPENING_BOOKED)?;
+    let closing_balance = find_balance(CLOSING_BOOKED)?;
+
+    let mut running = opening_balance.inner();
+    let mut transactions = Vec::with_capacity(statement.entries.len());
+    for (index, entry) in statement.entries.iter().enumerate() {
+        let invalid =
+            |msg: String| DomainError::Validation(format!("entry {}: {}", index + 1, msg));
+        let kind = parse_kind_code(&entry.bank_transaction_code.proprietary.code)
+            .ok_or_else(|| invalid("unknown bank transaction code".to_string()))?;
+        if entry.credit_debit != credit_debit(kind.is_credit()) {
+            return Err(invalid(
+                "credit/debit indicator does not match the transaction code".to_string(),
+            ));
+        }
+        let amount = parse_currency_amount(&entry.amount).map_err(invalid)?;
+        running = if kind.is_credit() {
+            running.checked_add(amount.inner())
+        } else {
+            running.checked_sub(amount.inner())
+        }
+        .ok_or_else(|| invalid("running balance out of range".to_string()))?;
+
+        let counterparty_account_id = entry
+            .details
+            .as_ref()
+            .and_then(|d| {
+                let parties = &d.transaction.related_parties;
+                if kind.is_credit() {
+                    parties.debtor_account.as_ref()
+                } else {
+                    parties.creditor_account.as_ref()
+                }
+            })
+            .map(parse_account_id)
+            .transpose()
+            .map_err(invalid)?;
+
+        transactions.push(Transaction {
+            id: entry.reference.clone(),
+            account_id,
+            kind,
+            amount,
+            balance_after: Amount::new(running),
+            counterparty_account_id,
+            transfer_id: None,
+            details: TransactionDetails {
+                description: entry.additional_info.clone(),
+                ..Default::default()
+            },
+            created_at: entry.booking_date.date_time,
+        });
+    }
+
+    if running != closing_balance.inner() {
+        return Err(DomainError::Validation(format!(
+            "Closing balance {} does not match the entries (expected {})",
+            format_amount(closing_balance),
+            format_amount(Amount::new(running))
+        )));
+    }
+
+    Ok(AccountStatement {
+        account_id,
+        account_name: statement.account.name.unwrap_or_default(),
+        from: statement.period.as_ref().map(|p| p.from),
+        to: statement.period.as_ref().map(|p| p.to),
+        opening_balance,
+        closing_balance,
+        transactions,
+    })
+}
+
+// The end-to-end id becomes the reference of the transfer, unless the initiator left it out
+fn end_to_end_reference(payment_id: &PaymentIdentification) -> Option<String> {
+    let id = payment_id.end_to_end_id.trim();
+    (!id.is_empty() && id != NOT_PROVIDED).then(|| id.to_string())
+}
+
+pub fn format_amount(amount: Amount) -> String {
+    format!("{}.{:02}", amount.inner() / 100, amount.inner() % 100)
+}
+
+pub fn parse_amount(value: &str) -> Result<Amount, String> {
+    let value = value.trim();
+    let (units, fraction) = value.split_once('.').unwrap_or((value, ""));
+    let is_digits = |s: &str| s.chars().all(|c| c.is_ascii_digit());
+    if units.is_empty() || !is_digits(units) || !is_digits(fraction) || fraction.len() > 2 {
+        return Err(format!("invalid amount '{}'", value));
+    }
+    let units: u64 = units
+        .parse()
+        .map_err(|_| format!("invalid amount '{}'", value))?;
+    let cents: u64 = format!("{:0<2}", fraction).parse().unwrap_or(0);
+    units
+        .checked_mul(100)
+        .and_then(|v| v.checked_add(cents))
+        .map(Amount::new)
+        .ok_or_else(|| format!("amount '{}' is too large", value))
+}
+
+fn parse_currency_amount(amount: &CurrencyAmount) -> Result<Amount, String> {
+    if amount.currency != CURRENCY {
+        return Err(format!("unsupported currency '{}'", amount.currency));
+    }
+    parse_amount(&amount.value)
+}
+
+fn parse_account_id(account: &CashAccount) -> Result<u32, String> {
+    let id = account.id.other.id.trim();
+    id.parse()
+        .map_err(|_| format!("invalid account id '{}'", id))
+}
+
+fn check_namespace(xmlns: &str, message: &str) -> Result<(), DomainError> {
+    let prefix = format!("urn:iso:std:iso:20022:tech:xsd:{}.", message);
+    if !xmlns.starts_with(&prefix) {
+        return Err(DomainError::Validation(format!(
+            "Not a {} document (namespace '{}')",
+            message, xmlns
+        )));
+    }
+    Ok(())
+}
+
+fn currency_amount(amount: Amount) -> CurrencyAmount {
+    CurrencyAmount {
+        currency: CURRENCY.to_string(),
+        value: format_amount(amount),
+    }
+}
+
+fn cash_account(id: u32, name: Option<&str>) -> CashAccount {
+    CashAccount {
+        id: AccountIdentification {
+            other: GenericIdentification { id: id.to_string() },
+        },
+        currency: name.map(|_| CURRENCY.to_string()),
+        name: name.map(str::to_string),
+    }
+}
+
+fn balance(code: &str, amount: Amount, date_time: DateTime<Utc>) -> Balance {
+    Balance {
+        balance_type: BalanceType {
+            code: Code {
+                code: code.to_string(),
+            },
+        },
+        amount: currency_amount(amount),
+        credit_debit: CREDIT.to_string(),
+        date: DateTimeChoice { date_time },
+    }
+}
+
+fn credit_debit(is_credit: bool) -> &'static str {
+    if is_credit { CREDIT } else { DEBIT }
+}
+
+fn kind_code(kind: TransactionKind) -> &'static str {
+    match kind {
+        TransactionKind::Deposit => "DEPOSIT",
+        TransactionKind::Withdrawal => "WITHDRAWAL",
+        TransactionKind::TransferIn => "TRANSFER_IN",
+        TransactionKind::TransferOut => "TRANSFER_OUT",
+    }
+}
+
+fn parse_kind_code(code: &str) -> Option<TransactionKind> {
+    match code {
+        "DEPOSIT" => Some(TransactionKind::Deposit),
+        "WITHDRAWAL" => Some(TransactionKind::Withdrawal),
+        "TRANSFER_IN" => Some(TransactionKind::TransferIn),
+        "TRANSFER_OUT" => Some(TransactionKind::TransferOut),
+        _ => None,
+    }
+}
+
+fn to_xml<T: Serialize>(document: &T) -> Result<String, DomainError> {
+    let mut body = String::new();
+    let mut serializer = Serializer::with_root(&mut body, Some("Document"))
+        .map_err(|e| DomainError::Internal(format!("Failed to create XML serializer: {}", e)))?;
+    serializer.indent(' ', 2);
+    document
+        .serialize(serializer)
+        .map_err(|e| DomainError::Internal(format!("Failed to write XML: {}", e)))?;
+    Ok(format!("{}{}\n", XML_DECLARATION, body))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_amount() {
+        assert_eq!(format_amount(Amount::new(12345)), "123.45");
+        assert_eq!(format_amount(Amount::new(5)), "0.05");
+        assert_eq!(format_amount(Amount::new(100)), "1.00");
+    }
+
+    #[test]
+    fn test_parse_amount() {
+        assert_eq!(parse_amount("123.45").unwrap().inner(), 12345);
+        assert_eq!(parse_amount("1.5").unwrap().inner(), 150);
+        assert_eq!(parse_amount("7").unwrap().inner(), 700);
+        assert!(parse_amount("1.234").is_err());
+        assert!(parse_amount("-1.00").is_err());
+        assert!(parse_amount(".50").is_err());
+        assert!(parse_amount("abc").is_err());
+        assert!(parse_amount("184467440737095516.16").is_err());
+    }
+
+    #[test]
+    fn test_write_pain001_groups_consecutive_debtors() {
+        let transfers = vec![
+            Transfer {
+                from_account_id: 1,
+                to_account_id: 2,
+                amount: Amount::new(100),
+                beneficiary_id: None,
+                details: Default::default(),
+            },
+            Transfer {
+                from_account_id: 1,
+                to_account_id: 3,
+                amount: Amount::new(200),
+                beneficiary_id: None,
+                details: Default::default(),
+            },
+            Transfer {
+                from_account_id: 4,
+                to_account_id: 1,
+                amount: Amount::new(50),
+                beneficiary_id: None,
+                details: Default::default(),
+            },
+        ];
+
+        let xml = write_pain001("MSG-1", &transfers).unwrap();
+        assert!(xml.starts_with(XML_DECLARATION));
+        assert_eq!(xml.matches("<PmtInf>").count(), 2);
+        assert!(xml.contains("<NbOfTxs>3</NbOfTxs>"));
+        assert!(xml.contains("<CtrlSum>3.50</CtrlSum>"));
+
+        let rows = parse_pain001(&xml).unwrap();
+        assert_eq!(rows.len(), 3);
+        assert_eq!(rows[2].line, 3);
+        assert_eq!(rows[2].transfer.from_account_id, 4);
+    }
+
+    #[test]
+    fn test_parse_pain001_rejects_wrong_namespace() {
+        let xml = write_pain001("MSG-1", &[])
+            .unwrap()
+            .replace("pain.001", "pain.008");
+        assert!(matches!(
+            parse_pain001(&xml),
+            Err(DomainError::Validation(_))
+        ));
+    }
+
+    #[test]
+    fn test_write_camt053_marks_credits_and_debits() {
+        let now = Utc::now();
+        let statement = AccountStatement {
+            account_id: 7,
+            account_name: "Savings".to_string(),
+            from: None,
+            to: None,
+            opening_balance: Amount::new(0),
+            closing_balance: Amount::new(70),
+            transactions: vec![
+                Transaction {
+                    id: "tx-1".to_string(),
+                    account_id: 7,
+                    kind: TransactionKind::Deposit,
+                    amount: Amount::new(100),
+                    balance_after: Amount::new(100),
+                    counterparty_account_id: None,
+                    transfer_id: None,
+                    details: Default::default(),
+                    created_at: now,
+                },
+                Transaction {
+                    id: "tx-2".to_string(),
+                    account_id: 7,
+                    kind: TransactionKind::TransferOut,
+                    amount: Amount::new(30),
+                    balance_after: Amount::new(70),
+                    counterparty_account_id: Some(9),
+                    transfer_id: None,
+                    details: Default::default(),
+                    created_at: now,
+                },
+            ],
+        };
+
+        let xml = write_camt053(&statement).unwrap();
+        assert!(xml.contains(CAMT053_NAMESPACE));
+        assert!(xml.contains("<CdtDbtInd>DBIT</CdtDbtInd>"));
+        assert!(xml.contains("<CdtrAcct>"));
+
+        let parsed = parse_camt053(&xml).unwrap();
+        assert_eq!(parsed.transactions, statement.transactions);
+        assert_eq!(parsed.closing_balance.inner(), 70);
+    }
+}
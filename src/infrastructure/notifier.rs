@@ -0,0 +1,87 @@
+use crate::domain::notifier::{Notification, Notifier};
+use anyhow::Result;
+use async_trait::async_trait;
+use std::sync::Arc;
+use tokio::sync::RwLock;
+use tracing::{debug, info, instrument};
+
+// Development notifier: writes notifications to the log instead of delivering them.
+// Secrets such as reset tokens end up in the log, so never use it in production.
+#[derive(Clone, Default)]
+pub struct LogNotifier;
+
+impl LogNotifier {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+#[async_trait]
+impl Notifier for LogNotifier {
+    #[instrument(skip(self, notification), fields(recipient = %notification.recipient()))]
+    async fn notify(&self, notification: Notification) -> Result<()> {
+        info!(notification = ?notification, "Notification sent");
+        Ok(())
+    }
+}
+
+// Keeps sent notifications in memory so tests can inspect them
+#[derive(Clone, Default)]
+pub struct InMemoryNotifier {
+    sent: Arc<RwLock<Vec<Notification>>>,
+}
+
+impl InMemoryNotifier {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub async fn sent(&self) -> Vec<Notification> {
+        self.sent.read().await.clone()
+    }
+
+    pub async fn sent_to(&self, email: &str) -> Vec<Notification> {
+        self.sent
+            .read()
+            .await
+            .iter()
+            .filter(|n| n.recipient() == email)
+            .cloned()
+            .collect()
+    }
+}
+
+#[async_trait]
+impl Notifier for InMemoryNotifier {
+    #[instrument(skip(self, notification), fields(recipient = %notification.recipient()))]
+    async fn notify(&self, notification: Notification) -> Result<()> {
+        debug!("Storing notification in memory");
+        self.sent.write().await.push(notification);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_in_memory_notifier_records_notifications_per_recipient() {
+        let notifier = InMemoryNotifier::new();
+        notifier
+            .notify(Notification::PasswordChanged {
+                email: "alice@example.com".to_string(),
+            })
+            .await
+            .unwrap();
+        notifier
+            .notify(Notification::PasswordChanged {
+                email: "bob@example.com".to_string(),
+            })
+            .await
+            .unwrap();
+
+        assert_eq!(notifier.sent().await.len(), 2);
+        assert_eq!(notifier.sent_to("alice@example.com").await.len(), 1);
+    }
+}
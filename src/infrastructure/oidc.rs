@@ -0,0 +1,354 @@
+// OpenID Connect relying party: authorization code flow with PKCE against a provider found
+// through discovery. ID tokens are verified with the provider's published JWK set, which is
+// refetched when a token names a key we have not seen (the provider rotated its keys).
+use crate::domain::oidc::{IdentityProvider, OidcIdentity};
+use anyhow::{Context, Result, anyhow, bail};
+use async_trait::async_trait;
+use base64::Engine;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use jsonwebtoken::jwk::{AlgorithmParameters, Jwk, JwkSet};
+use jsonwebtoken::{Algorithm, DecodingKey, Validation, decode, decode_header};
+use reqwest::Url;
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+use std::time::Duration;
+use tokio::sync::RwLock;
+use tracing::{debug, info, instrument, warn};
+
+const HTTP_TIMEOUT_SECS: u64 = 10;
+
+#[derive(Debug, Clone)]
+pub struct OidcConfig {
+    pub issuer: String,
+    pub client_id: String,
+    // Sent with the code exchange (`client_secret_post`); public clients rely on PKCE alone
+    pub client_secret: Option<String>,
+    // Our callback, as registered with the provider
+    pub redirect_uri: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct ProviderMetadata {
+    issuer: String,
+    authorization_endpoint: String,
+    token_endpoint: String,
+    jwks_uri: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct TokenResponse {
+    id_token: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct IdTokenClaims {
+    sub: String,
+    email: Option<String>,
+    #[serde(default)]
+    email_verified: bool,
+    nonce: Option<String>,
+}
+
+pub struct OidcClient {
+    config: OidcConfig,
+    metadata: ProviderMetadata,
+    http: reqwest::Client,
+    jwks: RwLock<JwkSet>,
+}
+
+impl OidcClient {
+    // Reads the provider metadata from `{issuer}/.well-known/openid-configuration` and
+    // fetches its keys
+    #[instrument(skip(config), fields(issuer = %config.issuer))]
+    pub async fn discover(config: OidcConfig) -> Result<Self> {
+        let http = reqwest::Client::builder()
+            .timeout(Duration::from_secs(HTTP_TIMEOUT_SECS))
+            .build()?;
+        let url = format!(
+            "{}/.well-known/openid-configuration",
+            config.issuer.trim_end_matches('/')
+        );
+        let metadata: ProviderMetadata = http
+            .get(&url)
+            .send()
+            .await
+            .and_then(|resp| resp.error_for_status())
+            .with_context(|| format!("Failed to fetch OIDC discovery document {}", url))?
+            .json()
+            .await
+            .context("Invalid OIDC discovery document")?;
+        // Tokens are checked against the configured issuer, which must be the provider's own
+        if metadata.issuer != config.issuer {
+            bail!(
+                "OIDC issuer mismatch: configured {}, provider says {}",
+                config.issuer,
+                metadata.issuer
+            );
+        }
+
+        let jwks = fetch_jwks(&http, &metadata.jwks_uri).await?;
+        info!(keys = jwks.keys.len(), "OIDC provider discovered");
+        Ok(Self {
+            config,
+            metadata,
+            http,
+            jwks: RwLock::new(jwks),
+        })
+    }
+
+    #[instrument(skip(self, id_token, nonce))]
+    pub async fn verify_id_token(&self, id_token: &str, nonce: &str) -> Result<OidcIdentity> {
+        let header = decode_header(id_token).context("Malformed ID token")?;
+        let jwk = match self.find_key(header.kid.as_deref()).await {
+            Some(jwk) => jwk,
+            None => {
+                debug!(kid = ?header.kid, "Unknown ID token key, refreshing provider keys");
+                let jwks = fetch_jwks(&self.http, &self.metadata.jwks_uri).await?;
+                *self.jwks.write().await = jwks;
+                self.find_key(header.kid.as_deref())
+                    .await
+                    .ok_or_else(|| anyhow!("ID token signed with an unknown key"))?
+            }
+        };
+
+        // The key decides the algorithm, whatever the token header claims
+        let mut validation = Validation::new(key_algorithm(&jwk)?);
+        validation.set_issuer(&[&self.config.issuer]);
+        validation.set_audience(&[&self.config.client_id]);
+        validation.set_required_spec_claims(&["exp", "iss", "aud", "sub"]);
+        let claims = decode::<IdTokenClaims>(id_token, &DecodingKey::from_jwk(&jwk)?, &validation)
+            .context("Invalid ID token")?
+            .claims;
+
+        if claims.nonce.as_deref() != Some(nonce) {
+            bail!("ID token nonce mismatch");
+        }
+
+        Ok(OidcIdentity {
+            issuer: self.config.issuer.clone(),
+            subject: claims.sub,
+            email: claims.email,
+            email_verified: claims.email_verified,
+        })
+    }
+
+    // The key named by `kid`; tokens without one are accepted from single-key providers
+    async fn find_key(&self, kid: Option<&str>) -> Option<Jwk> {
+        let jwks = self.jwks.read().await;
+        match kid {
+            Some(kid) => jwks.find(kid).cloned(),
+            None if jwks.keys.len() == 1 => jwks.keys.first().cloned(),
+            None => None,
+        }
+    }
+}
+
+#[async_trait]
+impl IdentityProvider for OidcClient {
+    fn issuer(&self) -> &str {
+        &self.config.issuer
+    }
+
+    fn authorization_url(&self, state: &str, nonce: &str, code_challenge: &str) -> String {
+        let params = [
+            ("response_type", "code"),
+            ("client_id", self.config.client_id.as_str()),
+            ("redirect_uri", self.config.redirect_uri.as_str()),
+            ("scope", "openid email"),
+            ("state", state),
+            ("nonce", nonce),
+            ("code_challenge", code_challenge),
+            ("code_challenge_method", "S256"),
+        ];
+        match Url::parse_with_params(&self.metadata.authorization_endpoint, &params) {
+            Ok(url) => url.into(),
+            // Discovery succeeded with this endpoint, so it is not expected to happen
+            Err(e) => {
+                warn!(error = %e, "Invalid OIDC authorization endpoint");
+                self.metadata.authorization_endpoint.clone()
+            }
+        }
+    }
+
+    #[instrument(skip(self, code, code_verifier, nonce))]
+    async fn exchange_code(
+        &self,
+        code: &str,
+        code_verifier: &str,
+        nonce: &str,
+    ) -> Result<OidcIdentity> {
+        let mut form = vec![
+            ("grant_type", "authorization_code"),
+            ("code", code),
+            ("redirect_uri", self.config.redirect_uri.as_str()),
+            ("client_id", self.config.client_id.as_str()),
+            ("code_verifier", code_verifier),
+        ];
+        if let Some(secret) = &self.config.client_secret {
+            form.push(("client_secret", secret.as_str()));
+        }
+
+        let resp = self
+            .http
+            .post(&self.metadata.token_endpoint)
+            .form(&form)
+            .send()
+            .await
+            .context("OIDC token request failed")?;
+        let status = resp.status();
+        if !status.is_success() {
+            let body = resp.text().await.unwrap_or_default();
+            bail!("OIDC token endpoint answered {}: {}", status, body);
+        }
+        let tokens: TokenResponse = resp.json().await.context("Invalid OIDC token response")?;
+        let id_token = tokens
+            .id_token
+            .ok_or_else(|| anyhow!("OIDC token response without an ID token"))?;
+
+        self.verify_id_token(&id_token, nonce).await
+    }
+}
+
+async fn fetch_jwks(http: &reqwest::Client, jwks_uri: &str) -> Result<JwkSet> {
+    http.get(jwks_uri)
+        .send()
+        .await
+        .and_then(|resp| resp.error_for_status())
+        .with_context(|| format!("Failed to fetch OIDC provider keys {}", jwks_uri))?
+        .json()
+        .await
+        .context("Invalid OIDC provider JWK set")
+}
+
+// Only asymmetric keys: a provider cannot share a secret with us through a public JWK set
+fn key_algorithm(jwk: &Jwk) -> Result<Algorithm> {
+    if let Some(algorithm) = jwk.common.key_algorithm {
+        return algorithm
+            .to_string()
+            .parse::<Algorithm>()
+            .ok()
+            .filter(|a| !matches!(a, Algorithm::HS256 | Algorithm::HS384 | Algorithm::HS512))
+            .ok_or_else(|| anyhow!("Unsupported ID token algorithm: {}", algorithm));
+    }
+    match &jwk.algorithm {
+        AlgorithmParameters::RSA(_) => Ok(Algorithm::RS256),
+        AlgorithmParameters::EllipticCurve(_) => Ok(Algorithm::ES256),
+        AlgorithmParameters::OctetKeyPair(_) => Ok(Algorithm::EdDSA),
+        AlgorithmParameters::OctetKey(_) => bail!("Symmetric keys cannot verify ID tokens"),
+    }
+}
+
+// PKCE (RFC 7636): a random verifier kept by us, and its S256 challenge sent to the provider
+pub fn generate_code_verifier() -> String {
+    crate::infrastructure::security::generate_opaque_token()
+}
+
+pub fn code_challenge(code_verifier: &str) -> String {
+    URL_SAFE_NO_PAD.encode(Sha256::digest(code_verifier.as_bytes()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::infrastructure::jwt_keys::JwtKey;
+    use jsonwebtoken::{Header, encode};
+
+    const ISSUER: &str = "https://idp.example.com";
+
+    fn client(key: &JwtKey) -> OidcClient {
+        let jwks = crate::infrastructure::jwt_keys::JwtKeySet::new(key.clone()).jwks();
+        OidcClient {
+            config: OidcConfig {
+                issuer: ISSUER.to_string(),
+                client_id: "bank".to_string(),
+                client_secret: None,
+                redirect_uri: "https://bank.example.com/callback".to_string(),
+            },
+            metadata: ProviderMetadata {
+                issuer: ISSUER.to_string(),
+                authorization_endpoint: format!("{}/authorize", ISSUER),
+                token_endpoint: format!("{}/token", ISSUER),
+                jwks_uri: format!("{}/jwks", ISSUER),
+            },
+            http: reqwest::Client::new(),
+            jwks: RwLock::new(jwks),
+        }
+    }
+
+    fn id_token(key: &JwtKey, audience: &str, nonce: &str) -> String {
+        let mut header = Header::new(key.algorithm());
+        header.kid = key.kid().map(str::to_string);
+        let claims = serde_json::json!({
+            "iss": ISSUER,
+            "aud": audience,
+            "sub": "employee-42",
+            "email": "alice@corp.example.com",
+            "email_verified": true,
+            "nonce": nonce,
+            "exp": chrono::Utc::now().timestamp() + 300,
+        });
+        encode(&header, &claims, key.encoding_key()).unwrap()
+    }
+
+    #[test]
+    fn test_code_challenge_matches_rfc_7636_example() {
+        assert_eq!(
+            code_challenge("dBjftJeZ4CVP-mB92K27uhbUJU1p1r_wW1gFWFOEjXk"),
+            "E9Melhoa2OwvFrEMTJguCHaoeK1t8URWbuGJSstw-cM"
+        );
+    }
+
+    #[test]
+    fn test_authorization_url_carries_pkce_challenge() {
+        let client = client(&JwtKey::generate_ed25519("idp-1"));
+
+        let url = Url::parse(&client.authorization_url("st", "no", "ch")).unwrap();
+        let params: std::collections::HashMap<_, _> = url.query_pairs().into_owned().collect();
+
+        assert_eq!(url.path(), "/authorize");
+        assert_eq!(params["response_type"], "code");
+        assert_eq!(params["client_id"], "bank");
+        assert_eq!(params["state"], "st");
+        assert_eq!(params["nonce"], "no");
+        assert_eq!(params["code_challenge"], "ch");
+        assert_eq!(params["code_challenge_method"], "S256");
+    }
+
+    #[tokio::test]
+    async fn test_verify_id_token_accepts_provider_token() {
+        let key = JwtKey::generate_ed25519("idp-1");
+        let client = client(&key);
+
+        let identity = client
+            .verify_id_token(&id_token(&key, "bank", "nonce-1"), "nonce-1")
+            .await
+            .unwrap();
+
+        assert_eq!(identity.issuer, ISSUER);
+        assert_eq!(identity.subject, "employee-42");
+        assert_eq!(identity.email.as_deref(), Some("alice@corp.example.com"));
+        assert!(identity.email_verified);
+    }
+
+    #[tokio::test]
+    async fn test_verify_id_token_rejects_wrong_nonce_audience_and_key() {
+        let key = JwtKey::generate_ed25519("idp-1");
+        let client = client(&key);
+
+        assert!(
+            client
+                .verify_id_token(&id_token(&key, "bank", "other"), "nonce-1")
+                .await
+                .is_err()
+        );
+        assert!(
+            client
+                .verify_id_token(&id_token(&key, "another-app", "nonce-1"), "nonce-1")
+                .await
+                .is_err()
+        );
+        // Same kid, different key
+        let forged = id_token(&JwtKey::generate_ed25519("idp-1"), "bank", "nonce-1");
+        assert!(client.verify_id_token(&forged, "nonce-1").await.is_err());
+    }
+}
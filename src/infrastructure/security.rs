@@ -1,8 +1,14 @@
+use crate::domain::token::Scope;
+use crate::domain::user::Role;
+use crate::infrastructure::jwt_keys::JwtKeySet;
 use argon2::Argon2;
 use argon2::password_hash::{PasswordHash, PasswordHasher, PasswordVerifier, SaltString};
-use jsonwebtoken::{Algorithm, DecodingKey, EncodingKey, Header, Validation, decode, encode};
-use rand_core::OsRng;
+use chrono::{DateTime, Utc};
+use jsonwebtoken::{Header, Validation, decode, decode_header, encode};
+use rand_core::{OsRng, RngCore};
 use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::sync::LazyLock;
 use std::time::{SystemTime, UNIX_EPOCH};
 
 // Argon2 parameters for 50-150ms target latency
@@ -10,11 +16,54 @@ const ARGON2_M_COST: u32 = 19456; // 19 MB
 const ARGON2_T_COST: u32 = 2; // 2 iterations
 const ARGON2_P_COST: u32 = 1; // 1 parallelism
 
+pub const ACCESS_TOKEN_TTL_SECS: u64 = 3600; // 1 hour
+const OPAQUE_TOKEN_BYTES: usize = 32;
+
+// Verified against when the user is unknown, so that a failed login costs the same
+// whether or not the email is registered
+static DUMMY_PASSWORD_HASH: LazyLock<String> = LazyLock::new(|| {
+    hash_password(&generate_opaque_token()).expect("Failed to hash dummy password")
+});
+
 #[derive(Debug, Serialize, Deserialize)]
 struct Claims {
     sub: String, // user_id
     exp: usize,
     iat: usize,
+    // Issue time in milliseconds; `iat` has whole seconds only, too coarse to compare with
+    // revocation cutoffs
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    iat_ms: Option<i64>,
+    jti: String, // unique token id, used for revocation
+    #[serde(default)]
+    role: Role,
+    // Space-separated scopes (RFC 8693); absent for unrestricted tokens
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    scope: Option<String>,
+    // Login session the token belongs to; absent for tokens issued outside a login
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    sid: Option<String>,
+}
+
+// Validated access token claims
+#[derive(Debug, Clone)]
+pub struct AccessTokenClaims {
+    pub user_id: String,
+    pub role: Role,
+    // `None` for unrestricted tokens
+    pub scopes: Option<Vec<Scope>>,
+    pub session_id: Option<String>,
+    pub jti: String,
+    pub issued_at: DateTime<Utc>,
+    pub expires_at: DateTime<Utc>,
+}
+
+impl AccessTokenClaims {
+    pub fn has_scope(&self, scope: Scope) -> bool {
+        self.scopes
+            .as_ref()
+            .is_none_or(|scopes| scopes.contains(&scope))
+    }
 }
 
 pub fn hash_password(password: &str) -> Result<String, argon2::password_hash::Error> {
@@ -45,43 +94,161 @@ pub fn verify_password(password: &str, hash: &str) -> Result<bool, argon2::passw
     }
 }
 
-pub fn generate_token(user_id: &str, secret: &str) -> Result<String, jsonwebtoken::errors::Error> {
-    let now = SystemTime::now()
-        .duration_since(UNIX_EPOCH)
-        .unwrap()
-        .as_secs() as usize;
+// Runs a full password verification that never succeeds
+pub fn verify_dummy_password(password: &str) -> bool {
+    let _ = verify_password(password, &DUMMY_PASSWORD_HASH);
+    false
+}
+
+pub fn generate_token(
+    user_id: &str,
+    role: Role,
+    secret: &str,
+) -> Result<String, jsonwebtoken::errors::Error> {
+    sign_access_token(&JwtKeySet::from_secret(secret), user_id, role, None)
+}
+
+// Token restricted to `scopes`, e.g. for an integration acting on behalf of the user
+pub fn generate_scoped_token(
+    user_id: &str,
+    role: Role,
+    scopes: &[Scope],
+    secret: &str,
+) -> Result<String, jsonwebtoken::errors::Error> {
+    sign_access_token(&JwtKeySet::from_secret(secret), user_id, role, Some(scopes))
+}
+
+// Signs with the signing key of the set and names it in the `kid` header
+pub fn sign_access_token(
+    keys: &JwtKeySet,
+    user_id: &str,
+    role: Role,
+    scopes: Option<&[Scope]>,
+) -> Result<String, jsonwebtoken::errors::Error> {
+    sign_claims(keys, user_id, role, scopes, None)
+}
+
+// Unrestricted token of a login session, rejected once the session is revoked
+pub fn sign_session_token(
+    keys: &JwtKeySet,
+    user_id: &str,
+    role: Role,
+    session_id: &str,
+) -> Result<String, jsonwebtoken::errors::Error> {
+    sign_claims(keys, user_id, role, None, Some(session_id))
+}
+
+fn sign_claims(
+    keys: &JwtKeySet,
+    user_id: &str,
+    role: Role,
+    scopes: Option<&[Scope]>,
+    session_id: Option<&str>,
+) -> Result<String, jsonwebtoken::errors::Error> {
+    let issued = SystemTime::now().duration_since(UNIX_EPOCH).unwrap();
+    let now = issued.as_secs() as usize;
 
-    let exp = now + 3600; // 1 hour expiration
+    let exp = now + ACCESS_TOKEN_TTL_SECS as usize;
 
     let claims = Claims {
         sub: user_id.to_string(),
         exp,
         iat: now,
+        iat_ms: Some(issued.as_millis() as i64),
+        jti: uuid::Uuid::new_v4().to_string(),
+        role,
+        scope: scopes.map(|scopes| {
+            scopes
+                .iter()
+                .map(Scope::as_str)
+                .collect::<Vec<_>>()
+                .join(" ")
+        }),
+        sid: session_id.map(str::to_string),
     };
 
-    encode(
-        &Header::new(Algorithm::HS256),
-        &claims,
-        &EncodingKey::from_secret(secret.as_ref()),
-    )
+    let key = keys.signing_key();
+    let mut header = Header::new(key.algorithm());
+    header.kid = key.kid().map(str::to_string);
+    encode(&header, &claims, key.encoding_key())
 }
 
 pub fn validate_token(token: &str, secret: &str) -> Result<String, jsonwebtoken::errors::Error> {
-    let mut validation = Validation::new(Algorithm::HS256);
+    decode_access_token(token, secret).map(|claims| claims.user_id)
+}
+
+pub fn decode_access_token(
+    token: &str,
+    secret: &str,
+) -> Result<AccessTokenClaims, jsonwebtoken::errors::Error> {
+    verify_access_token(token, &JwtKeySet::from_secret(secret))
+}
+
+// Verifies with the key named by the `kid` header. The algorithm is the key's own, whatever
+// the header claims, so a token cannot pick a weaker check.
+pub fn verify_access_token(
+    token: &str,
+    keys: &JwtKeySet,
+) -> Result<AccessTokenClaims, jsonwebtoken::errors::Error> {
+    let header = decode_header(token)?;
+    let key = keys
+        .verification_key(header.kid.as_deref())
+        .ok_or_else(|| {
+            jsonwebtoken::errors::Error::from(jsonwebtoken::errors::ErrorKind::InvalidToken)
+        })?;
+    let mut validation = Validation::new(key.algorithm());
     validation.leeway = 60; // 60 seconds leeway
 
-    let token_data = decode::<Claims>(
-        token,
-        &DecodingKey::from_secret(secret.as_ref()),
-        &validation,
-    )?;
+    let token_data = decode::<Claims>(token, key.decoding_key(), &validation)?;
 
-    Ok(token_data.claims.sub)
+    let claims = token_data.claims;
+    let invalid =
+        || jsonwebtoken::errors::Error::from(jsonwebtoken::errors::ErrorKind::InvalidToken);
+    let timestamp = |secs: usize| DateTime::from_timestamp(secs as i64, 0).ok_or_else(invalid);
+    let issued_at = match claims.iat_ms {
+        Some(millis) => DateTime::from_timestamp_millis(millis).ok_or_else(invalid)?,
+        None => timestamp(claims.iat)?,
+    };
+    Ok(AccessTokenClaims {
+        issued_at,
+        expires_at: timestamp(claims.exp)?,
+        user_id: claims.sub,
+        role: claims.role,
+        // Scopes this server does not know are dropped, so they never grant access
+        scopes: claims.scope.map(|scope| {
+            scope
+                .split_whitespace()
+                .filter_map(|s| s.parse().ok())
+                .collect()
+        }),
+        session_id: claims.sid,
+        jti: claims.jti,
+    })
+}
+
+// Random, URL-safe token for server-side lookups (refresh tokens, etc.)
+pub fn generate_opaque_token() -> String {
+    let mut bytes = [0u8; OPAQUE_TOKEN_BYTES];
+    OsRng.fill_bytes(&mut bytes);
+    to_hex(&bytes)
+}
+
+// Opaque tokens have enough entropy to be stored as a plain SHA-256 digest
+pub fn hash_token(token: &str) -> String {
+    to_hex(&Sha256::digest(token.as_bytes()))
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::infrastructure::jwt_keys::JwtKey;
+    use jsonwebtoken::{Algorithm, EncodingKey};
+
+    const RSA_PEM: &str = include_str!("../../tests/fixtures/jwt_rs256_private.pem");
 
     #[test]
     fn test_hash_password_generates_valid_hash() {
@@ -146,12 +313,18 @@ mod tests {
         assert!(result.is_err());
     }
 
+    #[test]
+    fn test_verify_dummy_password_always_fails() {
+        assert!(!verify_dummy_password("anything"));
+        assert!(!verify_dummy_password(""));
+    }
+
     #[test]
     fn test_generate_token_creates_valid_token() {
         let user_id = "test_user_123";
         let secret = "test_secret_key";
 
-        let token = generate_token(user_id, secret).unwrap();
+        let token = generate_token(user_id, Role::Customer, secret).unwrap();
 
         // Token should not be empty
         assert!(!token.is_empty());
@@ -165,7 +338,7 @@ mod tests {
         let user_id = "user_456";
         let secret = "test_secret";
 
-        let token = generate_token(user_id, secret).unwrap();
+        let token = generate_token(user_id, Role::Customer, secret).unwrap();
         let extracted_user_id = validate_token(&token, secret).unwrap();
 
         assert_eq!(extracted_user_id, user_id);
@@ -176,12 +349,110 @@ mod tests {
         let user_id = "test_user";
         let secret = "secret_key";
 
-        let token = generate_token(user_id, secret).unwrap();
+        let token = generate_token(user_id, Role::Customer, secret).unwrap();
         let extracted_user_id = validate_token(&token, secret).unwrap();
 
         assert_eq!(extracted_user_id, user_id);
     }
 
+    #[test]
+    fn test_generate_token_assigns_unique_jti() {
+        let secret = "secret_key";
+
+        let first = decode_access_token(
+            &generate_token("user", Role::Customer, secret).unwrap(),
+            secret,
+        )
+        .unwrap();
+        let second = decode_access_token(
+            &generate_token("user", Role::Customer, secret).unwrap(),
+            secret,
+        )
+        .unwrap();
+
+        assert!(!first.jti.is_empty());
+        assert_ne!(first.jti, second.jti);
+        assert_eq!(
+            first.expires_at.timestamp() - first.issued_at.timestamp(),
+            ACCESS_TOKEN_TTL_SECS as i64
+        );
+    }
+
+    #[test]
+    fn test_issued_at_keeps_milliseconds() {
+        let secret = "secret_key";
+        let before = Utc::now();
+        let claims = decode_access_token(
+            &generate_token("user", Role::Customer, secret).unwrap(),
+            secret,
+        )
+        .unwrap();
+
+        // Not cut down to the start of the second, so a revocation cutoff earlier in the
+        // same second does not catch the token
+        assert!(claims.issued_at >= before - chrono::Duration::milliseconds(1));
+        assert!(claims.issued_at <= Utc::now());
+    }
+
+    #[test]
+    fn test_generate_token_includes_role() {
+        let secret = "secret_key";
+
+        let token = generate_token("admin_user", Role::Admin, secret).unwrap();
+        let claims = decode_access_token(&token, secret).unwrap();
+
+        assert_eq!(claims.role, Role::Admin);
+    }
+
+    #[test]
+    fn test_token_without_role_is_customer() {
+        let secret = "secret_key";
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_secs() as usize;
+        // Tokens issued before roles were added carry no role claim
+        let legacy = serde_json::json!({
+            "sub": "legacy_user",
+            "exp": now + 60,
+            "iat": now,
+            "jti": "legacy",
+        });
+        let token = encode(
+            &Header::new(Algorithm::HS256),
+            &legacy,
+            &EncodingKey::from_secret(secret.as_ref()),
+        )
+        .unwrap();
+
+        let claims = decode_access_token(&token, secret).unwrap();
+        assert_eq!(claims.role, Role::Customer);
+    }
+
+    #[test]
+    fn test_generate_token_is_unscoped() {
+        let secret = "secret_key";
+
+        let token = generate_token("user", Role::Customer, secret).unwrap();
+        let claims = decode_access_token(&token, secret).unwrap();
+
+        assert!(claims.scopes.is_none());
+        assert!(Scope::ALL.iter().all(|scope| claims.has_scope(*scope)));
+    }
+
+    #[test]
+    fn test_generate_scoped_token_carries_scopes() {
+        let secret = "secret_key";
+
+        let token =
+            generate_scoped_token("user", Role::Customer, &[Scope::AccountsRead], secret).unwrap();
+        let claims = decode_access_token(&token, secret).unwrap();
+
+        assert_eq!(claims.scopes, Some(vec![Scope::AccountsRead]));
+        assert!(claims.has_scope(Scope::AccountsRead));
+        assert!(!claims.has_scope(Scope::PaymentsWrite));
+    }
+
     #[test]
     fn test_validate_token_rejects_invalid_token() {
         let secret = "secret_key";
@@ -197,7 +468,7 @@ mod tests {
         let correct_secret = "correct_secret";
         let wrong_secret = "wrong_secret";
 
-        let token = generate_token(user_id, correct_secret).unwrap();
+        let token = generate_token(user_id, Role::Customer, correct_secret).unwrap();
         let result = validate_token(&token, wrong_secret);
 
         assert!(result.is_err());
@@ -209,7 +480,7 @@ mod tests {
         let secret = "round_trip_secret";
 
         // Generate token
-        let token = generate_token(user_id, secret).unwrap();
+        let token = generate_token(user_id, Role::Customer, secret).unwrap();
 
         // Validate token
         let extracted_user_id = validate_token(&token, secret).unwrap();
@@ -224,8 +495,8 @@ mod tests {
         let user1 = "user1";
         let user2 = "user2";
 
-        let token1 = generate_token(user1, secret).unwrap();
-        let token2 = generate_token(user2, secret).unwrap();
+        let token1 = generate_token(user1, Role::Customer, secret).unwrap();
+        let token2 = generate_token(user2, Role::Customer, secret).unwrap();
 
         assert_ne!(token1, token2);
     }
@@ -256,4 +527,99 @@ mod tests {
         let result = verify_password(password, &hash).unwrap();
         assert!(result);
     }
+
+    #[test]
+    fn test_generate_opaque_token_is_random_hex() {
+        let token1 = generate_opaque_token();
+        let token2 = generate_opaque_token();
+
+        assert_eq!(token1.len(), OPAQUE_TOKEN_BYTES * 2);
+        assert!(token1.chars().all(|c| c.is_ascii_hexdigit()));
+        assert_ne!(token1, token2);
+    }
+
+    #[test]
+    fn test_hash_token_is_deterministic() {
+        let hash = hash_token("token");
+
+        assert_eq!(hash, hash_token("token"));
+        assert_ne!(hash, hash_token("other"));
+        assert_eq!(
+            hash,
+            "3c469e9d6c5875d37a43f353d4f88e61fcf812c66eee3457465a40b0da4153e0"
+        );
+    }
+
+    #[test]
+    fn test_asymmetric_tokens_carry_kid_and_verify() {
+        for key in [
+            JwtKey::from_pem("rsa-1", RSA_PEM).unwrap(),
+            JwtKey::generate_ed25519("ed-1"),
+        ] {
+            let algorithm = key.algorithm();
+            let keys = JwtKeySet::new(key);
+
+            let token = sign_access_token(&keys, "user", Role::Customer, None).unwrap();
+            let header = decode_header(&token).unwrap();
+            assert_eq!(header.alg, algorithm);
+            assert_eq!(header.kid.as_deref(), keys.signing_key().kid());
+
+            let claims = verify_access_token(&token, &keys).unwrap();
+            assert_eq!(claims.user_id, "user");
+        }
+    }
+
+    #[test]
+    fn test_rotated_key_verifies_but_does_not_sign() {
+        let old = JwtKey::generate_ed25519("old");
+        let before_rotation = JwtKeySet::new(old.clone());
+        let old_token = sign_access_token(&before_rotation, "user", Role::Customer, None).unwrap();
+
+        let after_rotation =
+            JwtKeySet::new(JwtKey::from_pem("new", RSA_PEM).unwrap()).with_verification_key(old);
+        let new_token = sign_access_token(&after_rotation, "user", Role::Customer, None).unwrap();
+
+        assert!(verify_access_token(&old_token, &after_rotation).is_ok());
+        assert_eq!(
+            decode_header(&new_token).unwrap().kid.as_deref(),
+            Some("new")
+        );
+        // Once the old key is dropped, its tokens are rejected
+        assert!(
+            verify_access_token(&old_token, &JwtKeySet::new(JwtKey::generate_ed25519("new")))
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_verify_rejects_unknown_kid_and_kidless_tokens_without_secret() {
+        let keys = JwtKeySet::new(JwtKey::generate_ed25519("current"));
+        let other = JwtKeySet::new(JwtKey::generate_ed25519("unknown"));
+
+        let unknown = sign_access_token(&other, "user", Role::Customer, None).unwrap();
+        let kidless = generate_token("user", Role::Customer, "secret_key").unwrap();
+
+        assert!(verify_access_token(&unknown, &keys).is_err());
+        assert!(verify_access_token(&kidless, &keys).is_err());
+    }
+
+    #[test]
+    fn test_verify_uses_the_key_algorithm_not_the_header() {
+        // A token claiming HS256 under the kid of an RSA key, signed with the public modulus
+        // as HMAC secret, must not pass
+        let keys = JwtKeySet::new(JwtKey::from_pem("rsa-1", RSA_PEM).unwrap());
+        let jwks = serde_json::to_value(keys.jwks()).unwrap();
+        let public = jwks["keys"][0]["n"].as_str().unwrap();
+        let mut header = Header::new(Algorithm::HS256);
+        header.kid = Some("rsa-1".to_string());
+        let now = Utc::now().timestamp() as usize;
+        let forged = encode(
+            &header,
+            &serde_json::json!({"sub": "admin", "exp": now + 60, "iat": now, "jti": "x", "role": "admin"}),
+            &EncodingKey::from_secret(public.as_bytes()),
+        )
+        .unwrap();
+
+        assert!(verify_access_token(&forged, &keys).is_err());
+    }
 }
@@ -0,0 +1,147 @@
+// Time-based one-time passwords (RFC 6238) with the parameters authenticator apps
+// expect by default: HMAC-SHA1, 6 digits, 30-second steps
+use base32::Alphabet;
+use hmac::{Hmac, Mac};
+use rand_core::{OsRng, RngCore};
+use sha1::Sha1;
+
+pub const TOTP_DIGITS: u32 = 6;
+pub const TOTP_STEP_SECS: i64 = 30;
+const TOTP_SECRET_BYTES: usize = 20;
+// Codes from one step before or after the current one are accepted to absorb clock skew
+const TOTP_SKEW_STEPS: i64 = 1;
+const BASE32: Alphabet = Alphabet::Rfc4648 { padding: false };
+
+// Random secret, base32-encoded as authenticator apps expect it
+pub fn generate_totp_secret() -> String {
+    let mut bytes = [0u8; TOTP_SECRET_BYTES];
+    OsRng.fill_bytes(&mut bytes);
+    base32::encode(BASE32, &bytes)
+}
+
+pub fn totp_step(unix_secs: i64) -> i64 {
+    unix_secs.div_euclid(TOTP_STEP_SECS)
+}
+
+// The code of the given step, or `None` if the secret is not valid base32
+pub fn totp_code(secret: &str, step: i64) -> Option<String> {
+    let key = base32::decode(BASE32, secret)?;
+    let mut mac = Hmac::<Sha1>::new_from_slice(&key).ok()?;
+    mac.update(&step.to_be_bytes());
+    let digest = mac.finalize().into_bytes();
+
+    // Dynamic truncation (RFC 4226, section 5.3)
+    let offset = (digest[digest.len() - 1] & 0x0f) as usize;
+    let binary = u32::from_be_bytes([
+        digest[offset] & 0x7f,
+        digest[offset + 1],
+        digest[offset + 2],
+        digest[offset + 3],
+    ]);
+    let code = binary % 10u32.pow(TOTP_DIGITS);
+    Some(format!("{:0width$}", code, width = TOTP_DIGITS as usize))
+}
+
+// Returns the step the code belongs to if it matches one of the steps around `unix_secs`
+pub fn verify_totp(secret: &str, code: &str, unix_secs: i64) -> Option<i64> {
+    let code = code.trim();
+    if code.len() != TOTP_DIGITS as usize || !code.chars().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+    let current = totp_step(unix_secs);
+    (current - TOTP_SKEW_STEPS..=current + TOTP_SKEW_STEPS)
+        .find(|&step| totp_code(secret, step).is_some_and(|expected| expected == code))
+}
+
+// Key URI understood by authenticator apps, usually shown as a QR code
+pub fn otpauth_uri(issuer: &str, account: &str, secret: &str) -> String {
+    format!(
+        "otpauth://totp/{}:{}?secret={}&issuer={}&algorithm=SHA1&digits={}&period={}",
+        percent_encode(issuer),
+        percent_encode(account),
+        secret,
+        percent_encode(issuer),
+        TOTP_DIGITS,
+        TOTP_STEP_SECS
+    )
+}
+
+fn percent_encode(value: &str) -> String {
+    value
+        .bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' | b'@' => {
+                (b as char).to_string()
+            }
+            _ => format!("%{:02X}", b),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // "12345678901234567890", the SHA-1 key of the RFC 6238 test vectors
+    const RFC_SECRET: &str = "GEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQ";
+
+    #[test]
+    fn test_totp_code_matches_rfc_6238_vectors() {
+        // RFC 6238 lists 8-digit codes; the last 6 digits are the 6-digit codes
+        for (time, expected) in [
+            (59, "287082"),
+            (1111111109, "081804"),
+            (1111111111, "050471"),
+            (1234567890, "005924"),
+            (2000000000, "279037"),
+        ] {
+            assert_eq!(totp_code(RFC_SECRET, totp_step(time)).unwrap(), expected);
+        }
+    }
+
+    #[test]
+    fn test_verify_totp_accepts_adjacent_steps() {
+        let now = 1111111111;
+        let previous = totp_code(RFC_SECRET, totp_step(now) - 1).unwrap();
+        let next = totp_code(RFC_SECRET, totp_step(now) + 1).unwrap();
+        let stale = totp_code(RFC_SECRET, totp_step(now) - 2).unwrap();
+
+        assert_eq!(
+            verify_totp(RFC_SECRET, &previous, now),
+            Some(totp_step(now) - 1)
+        );
+        assert_eq!(
+            verify_totp(RFC_SECRET, &next, now),
+            Some(totp_step(now) + 1)
+        );
+        assert_eq!(verify_totp(RFC_SECRET, &stale, now), None);
+    }
+
+    #[test]
+    fn test_verify_totp_rejects_malformed_codes() {
+        let now = 59;
+        assert!(verify_totp(RFC_SECRET, "287082", now).is_some());
+        assert!(verify_totp(RFC_SECRET, "28708", now).is_none());
+        assert!(verify_totp(RFC_SECRET, "28708a", now).is_none());
+        assert!(verify_totp("not base32!", "287082", now).is_none());
+    }
+
+    #[test]
+    fn test_generate_totp_secret_is_random_base32() {
+        let secret = generate_totp_secret();
+
+        assert_eq!(secret.len(), 32);
+        assert!(base32::decode(BASE32, &secret).is_some());
+        assert_ne!(secret, generate_totp_secret());
+    }
+
+    #[test]
+    fn test_otpauth_uri_encodes_label() {
+        let uri = otpauth_uri("Yandex Bank", "alice@example.com", "ABC");
+
+        assert_eq!(
+            uri,
+            "otpauth://totp/Yandex%20Bank:alice@example.com?secret=ABC&issuer=Yandex%20Bank&algorithm=SHA1&digits=6&period=30"
+        );
+    }
+}
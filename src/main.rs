@@ -1,19 +1,48 @@
 use actix_cors::Cors;
 use actix_web::{App, HttpServer, middleware::DefaultHeaders, web};
 use std::sync::Arc;
-use tracing::{info, instrument};
+use tracing::{info, instrument, warn};
 use yandex_bank_api::application::auth_service::AuthService;
 use yandex_bank_api::application::service::BankService;
 use yandex_bank_api::data::memory::InMemoryAccountRepository;
+use yandex_bank_api::data::revocation_store::InMemoryRevocationStore;
 use yandex_bank_api::data::user_repository::InMemoryUserRepository;
+use yandex_bank_api::domain::api_key::ApiKeyAuthenticator;
+use yandex_bank_api::domain::models::Amount;
+use yandex_bank_api::domain::repository::RevocationStore;
+use yandex_bank_api::domain::reversal::ShortfallPolicy;
+use yandex_bank_api::domain::token::Scope;
+use yandex_bank_api::domain::user::{CreateUser, Role};
+use yandex_bank_api::infrastructure::jwt_keys::{JwtKey, JwtKeySet};
 use yandex_bank_api::infrastructure::logging::init_logging;
-use yandex_bank_api::presentation::auth::{get_token, login, register};
+use yandex_bank_api::infrastructure::oidc::{OidcClient, OidcConfig};
+use yandex_bank_api::presentation::admin::{
+    find_user, get_any_account, get_user, kyc_history, reverse_transfer, unlock_login,
+    update_kyc_status, update_user_role,
+};
+use yandex_bank_api::presentation::auth::{
+    change_password, create_api_key, enroll_two_factor, get_token, jwks, list_api_keys,
+    list_sessions, login, login_two_factor, logout, logout_all, oidc_authorize, oidc_callback,
+    refresh, register, request_password_reset, resend_verification_email, reset_password,
+    revoke_api_key, revoke_session, verify_email, verify_two_factor,
+};
+use yandex_bank_api::presentation::batch::batch_transfer;
+use yandex_bank_api::presentation::beneficiaries::{
+    add_beneficiary, delete_beneficiary, get_beneficiary, list_beneficiaries, update_beneficiary,
+};
 use yandex_bank_api::presentation::handlers::{
-    AppState, create_account, deposit, get_account, health_check, transfer, withdraw,
+    AppState, create_account, deposit, get_account, get_statement, health_check, transfer, withdraw,
 };
+use yandex_bank_api::presentation::holders::{add_holder, list_holders, remove_holder};
 use yandex_bank_api::presentation::middleware::{
-    JwtAuthMiddleware, RequestIdMiddleware, TimingMiddleware,
+    JwtAuthMiddleware, RequestIdMiddleware, RequireRole, RequireScope, TimingMiddleware,
+};
+use yandex_bank_api::presentation::p2p::{claim_transfer, list_claimable_transfers, pay_by_email};
+use yandex_bank_api::presentation::payment_requests::{
+    create_payment_request, decline_payment_request, get_payment_request, list_payment_requests,
+    pay_payment_request,
 };
+use yandex_bank_api::presentation::users::{delete_me, export_me, get_me, update_me};
 
 #[tokio::main]
 #[instrument]
@@ -44,20 +73,174 @@ async fn main() -> std::io::Result<()> {
     let user_repository = InMemoryUserRepository::new();
     info!("User repository created");
 
-    info!("Creating bank service");
-    let service = BankService::new(Arc::new(repository));
-    info!("Bank service created");
+    info!("Creating token revocation store");
+    let revocation_store: Arc<dyn RevocationStore> = Arc::new(InMemoryRevocationStore::new());
+    info!("Token revocation store created");
+
+    info!("Loading JWT keys");
+    let jwt_keys = Arc::new(load_jwt_keys(&jwt_secret));
+    info!(
+        kid = ?jwt_keys.signing_key().kid(),
+        algorithm = ?jwt_keys.signing_key().algorithm(),
+        "JWT keys loaded"
+    );
 
     info!("Creating auth service");
-    let auth_service = AuthService::new(Arc::new(user_repository), jwt_secret.clone());
+    let mut auth_service = AuthService::new(Arc::new(user_repository), jwt_secret.clone())
+        .with_jwt_keys(jwt_keys.clone())
+        .with_revocation_store(revocation_store.clone());
+    match (
+        std::env::var("TOKEN_CLIENT_ID"),
+        std::env::var("TOKEN_CLIENT_SECRET"),
+    ) {
+        (Ok(client_id), Ok(client_secret)) => {
+            info!(client_id = %client_id, "Registering token client");
+            auth_service = auth_service.with_token_client(client_id, &client_secret);
+        }
+        _ => info!("No token client configured, POST /api/auth/token is disabled"),
+    }
+    // Transfers above this amount require a two-factor code; 0 disables the check
+    if let Ok(threshold) = std::env::var("STEP_UP_TRANSFER_THRESHOLD") {
+        let threshold = threshold
+            .parse::<u64>()
+            .expect("STEP_UP_TRANSFER_THRESHOLD must be a valid number");
+        info!(
+            threshold = threshold,
+            "Configuring transfer step-up threshold"
+        );
+        auth_service =
+            auth_service.with_step_up_threshold((threshold > 0).then(|| Amount::new(threshold)));
+    }
+    // Sign-in with an OpenID Connect provider, discovered from its issuer URL
+    if let Ok(issuer) = std::env::var("OIDC_ISSUER") {
+        let config = OidcConfig {
+            issuer,
+            client_id: std::env::var("OIDC_CLIENT_ID")
+                .expect("OIDC_CLIENT_ID must be set with OIDC_ISSUER"),
+            client_secret: std::env::var("OIDC_CLIENT_SECRET").ok(),
+            redirect_uri: std::env::var("OIDC_REDIRECT_URI")
+                .expect("OIDC_REDIRECT_URI must be set with OIDC_ISSUER"),
+        };
+        info!(issuer = %config.issuer, "Discovering OIDC provider");
+        let provider = OidcClient::discover(config)
+            .await
+            .expect("Failed to discover OIDC provider");
+        auth_service = auth_service.with_identity_provider(Arc::new(provider));
+    }
+    // Largest withdrawal or transfer of a customer whose identity is not verified
+    if let Ok(limit) = std::env::var("UNVERIFIED_TRANSACTION_LIMIT") {
+        let limit = limit
+            .parse::<u64>()
+            .expect("UNVERIFIED_TRANSACTION_LIMIT must be a valid number");
+        info!(limit = limit, "Configuring unverified transaction limit");
+        auth_service = auth_service.with_unverified_transaction_limit(Amount::new(limit));
+    }
     info!("Auth service created");
 
+    // No-op for the in-memory repository, which starts empty; kept so that persistent
+    // storage gets its emails normalized on startup
+    info!("Migrating user email identities");
+    let migration = auth_service
+        .migrate_email_identities()
+        .await
+        .expect("Failed to migrate user email identities");
+    for duplicate in &migration.duplicates {
+        warn!(
+            email = %duplicate.email,
+            users = ?duplicate.user_ids,
+            "Users share an email identity and must be merged manually"
+        );
+    }
+
+    // Bootstraps the first admin; further roles are granted through the admin API
+    if let (Ok(email), Ok(password)) = (
+        std::env::var("ADMIN_EMAIL"),
+        std::env::var("ADMIN_PASSWORD"),
+    ) {
+        match auth_service.get_user_by_email(&email).await {
+            Ok(user) if user.role == Role::Admin => info!(user_id = %user.id, "Admin user exists"),
+            Ok(user) => {
+                warn!(user_id = %user.id, "Promoting existing user to admin");
+                auth_service
+                    .set_user_role(&user.id, Role::Admin)
+                    .await
+                    .expect("Failed to promote admin user");
+            }
+            Err(_) => {
+                let user = auth_service
+                    .register_user_with_role(CreateUser { email, password }, Role::Admin)
+                    .await
+                    .expect("Failed to create admin user");
+                info!(user_id = %user.id, "Admin user created");
+            }
+        }
+    }
+
+    let auth_service = Arc::new(auth_service);
+
+    info!("Creating bank service");
+    // Account opening and transaction limits depend on email and identity verification
+    let mut service =
+        BankService::new(Arc::new(repository)).with_customer_policy(auth_service.clone());
+    // Time the recipient of a payment by email has to claim it
+    if let Ok(hours) = std::env::var("CLAIMABLE_TRANSFER_TTL_HOURS") {
+        let hours = hours
+            .parse::<i64>()
+            .expect("CLAIMABLE_TRANSFER_TTL_HOURS must be a valid number");
+        info!(hours = hours, "Configuring claimable transfer lifetime");
+        service = service.with_claim_ttl(chrono::Duration::hours(hours));
+    }
+    // Large transfers to a newly saved beneficiary wait for a cooling-off period
+    if let Ok(hours) = std::env::var("PAYEE_COOLING_OFF_HOURS") {
+        let hours = hours
+            .parse::<i64>()
+            .expect("PAYEE_COOLING_OFF_HOURS must be a valid number");
+        info!(hours = hours, "Configuring payee cooling-off period");
+        service = service.with_payee_cooling_off(chrono::Duration::hours(hours));
+    }
+    if let Ok(threshold) = std::env::var("PAYEE_COOLING_OFF_THRESHOLD") {
+        let threshold = threshold
+            .parse::<u64>()
+            .expect("PAYEE_COOLING_OFF_THRESHOLD must be a valid number");
+        info!(
+            threshold = threshold,
+            "Configuring payee cooling-off threshold"
+        );
+        service = service.with_payee_cooling_off_threshold(Amount::new(threshold));
+    }
+    // Reversals of transfers whose recipient no longer has the money
+    if let Ok(policy) = std::env::var("REVERSAL_SHORTFALL_POLICY") {
+        let policy = policy
+            .parse::<ShortfallPolicy>()
+            .expect("REVERSAL_SHORTFALL_POLICY must be reject or partial");
+        info!(policy = ?policy, "Configuring reversal shortfall policy");
+        service = service.with_shortfall_policy(policy);
+    }
+    info!("Bank service created");
+
     info!("Initializing application state");
     let state = web::Data::new(AppState {
         service,
-        auth_service: Arc::new(auth_service),
+        auth_service,
     });
     info!("Application state initialized");
+    let api_key_authenticator: Arc<dyn ApiKeyAuthenticator> = state.auth_service.clone();
+
+    // Unclaimed payments by email go back to their senders once they expire
+    let refund_state = state.clone();
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(std::time::Duration::from_secs(60));
+        loop {
+            interval.tick().await;
+            if let Err(e) = refund_state
+                .service
+                .refund_expired_transfers(chrono::Utc::now())
+                .await
+            {
+                warn!(error = %e, "Failed to refund expired claimable transfers");
+            }
+        }
+    });
 
     // Parse allowed origins
     let origins: Vec<String> = allowed_origins
@@ -73,10 +256,11 @@ async fn main() -> std::io::Result<()> {
 
         // Configure CORS
         let mut cors = Cors::default()
-            .allowed_methods(vec!["GET", "POST", "PUT", "DELETE"])
+            .allowed_methods(vec!["GET", "POST", "PUT", "PATCH", "DELETE"])
             .allowed_headers(vec![
                 actix_web::http::header::CONTENT_TYPE,
                 actix_web::http::header::AUTHORIZATION,
+                actix_web::http::header::HeaderName::from_static("x-two-factor-code"),
             ])
             .max_age(3600)
             .expose_headers(vec![
@@ -100,22 +284,230 @@ async fn main() -> std::io::Result<()> {
                     .add(("Permissions-Policy", "geolocation=()"))
                     .add(("Cross-Origin-Opener-Policy", "same-origin")),
             )
-            .wrap(JwtAuthMiddleware::new(jwt_secret.clone()))
+            .wrap(
+                JwtAuthMiddleware::new(jwt_secret.clone())
+                    .with_jwt_keys(jwt_keys.clone())
+                    .with_revocation_store(revocation_store.clone())
+                    .with_api_key_authenticator(api_key_authenticator.clone()),
+            )
             .wrap(TimingMiddleware)
             .wrap(RequestIdMiddleware)
+            .route("/.well-known/jwks.json", web::get().to(jwks))
             .service(
                 web::scope("/api")
                     // Public routes
                     .route("/health", web::get().to(health_check))
                     .route("/auth/register", web::post().to(register))
                     .route("/auth/login", web::post().to(login))
+                    .route("/auth/login/2fa", web::post().to(login_two_factor))
+                    .route("/auth/refresh", web::post().to(refresh))
+                    .route("/auth/logout", web::post().to(logout))
+                    .route("/auth/logout-all", web::post().to(logout_all))
+                    .route("/auth/password", web::post().to(change_password))
+                    .route(
+                        "/auth/password/forgot",
+                        web::post().to(request_password_reset),
+                    )
+                    .route("/auth/password/reset", web::post().to(reset_password))
+                    .route("/auth/verify-email", web::post().to(verify_email))
+                    .route(
+                        "/auth/verify-email/resend",
+                        web::post().to(resend_verification_email),
+                    )
                     .route("/auth/token", web::post().to(get_token))
-                    // Protected routes (require JWT)
-                    .route("/accounts", web::post().to(create_account))
-                    .route("/accounts/{id}", web::get().to(get_account))
-                    .route("/accounts/{id}/deposit", web::post().to(deposit))
-                    .route("/accounts/{id}/withdraw", web::post().to(withdraw))
-                    .route("/transfers", web::post().to(transfer)),
+                    .route("/auth/2fa/enroll", web::post().to(enroll_two_factor))
+                    .route("/auth/2fa/verify", web::post().to(verify_two_factor))
+                    .route("/auth/oidc/authorize", web::post().to(oidc_authorize))
+                    .route("/auth/oidc/callback", web::get().to(oidc_callback))
+                    .route("/auth/api-keys", web::post().to(create_api_key))
+                    .route("/auth/api-keys", web::get().to(list_api_keys))
+                    .route("/auth/api-keys/{id}", web::delete().to(revoke_api_key))
+                    .route("/auth/sessions", web::get().to(list_sessions))
+                    .route("/auth/sessions/{id}", web::delete().to(revoke_session))
+                    // Protected routes (require JWT); scoped tokens need the scope each
+                    // route declares and are refused on routes that declare none
+                    .route("/users/me", web::get().to(get_me))
+                    .route("/users/me", web::patch().to(update_me))
+                    .route("/users/me", web::delete().to(delete_me))
+                    .route("/users/me/export", web::get().to(export_me))
+                    .route(
+                        "/accounts",
+                        web::post()
+                            .to(create_account)
+                            .wrap(RequireScope::new(Scope::AccountsWrite)),
+                    )
+                    .route(
+                        "/accounts/{id}",
+                        web::get()
+                            .to(get_account)
+                            .wrap(RequireScope::new(Scope::AccountsRead)),
+                    )
+                    .route(
+                        "/accounts/{id}/deposit",
+                        web::post()
+                            .to(deposit)
+                            .wrap(RequireScope::new(Scope::AccountsWrite)),
+                    )
+                    .route(
+                        "/accounts/{id}/withdraw",
+                        web::post()
+                            .to(withdraw)
+                            .wrap(RequireScope::new(Scope::AccountsWrite)),
+                    )
+                    .route(
+                        "/accounts/{id}/statement",
+                        web::get()
+                            .to(get_statement)
+                            .wrap(RequireScope::new(Scope::AccountsRead)),
+                    )
+                    .route("/accounts/{id}/holders", web::get().to(list_holders))
+                    .route("/accounts/{id}/holders", web::post().to(add_holder))
+                    .route(
+                        "/accounts/{id}/holders/{user_id}",
+                        web::delete().to(remove_holder),
+                    )
+                    .route(
+                        "/transfers",
+                        web::post()
+                            .to(transfer)
+                            .wrap(RequireScope::new(Scope::PaymentsWrite)),
+                    )
+                    .route(
+                        "/transfers/batch",
+                        web::post()
+                            .to(batch_transfer)
+                            .wrap(RequireScope::new(Scope::PaymentsWrite)),
+                    )
+                    .route(
+                        "/transfers/email",
+                        web::post()
+                            .to(pay_by_email)
+                            .wrap(RequireScope::new(Scope::PaymentsWrite)),
+                    )
+                    .route(
+                        "/beneficiaries",
+                        web::get()
+                            .to(list_beneficiaries)
+                            .wrap(RequireScope::new(Scope::AccountsRead)),
+                    )
+                    .route(
+                        "/beneficiaries",
+                        web::post()
+                            .to(add_beneficiary)
+                            .wrap(RequireScope::new(Scope::PaymentsWrite)),
+                    )
+                    .route(
+                        "/beneficiaries/{id}",
+                        web::get()
+                            .to(get_beneficiary)
+                            .wrap(RequireScope::new(Scope::AccountsRead)),
+                    )
+                    .route(
+                        "/beneficiaries/{id}",
+                        web::patch()
+                            .to(update_beneficiary)
+                            .wrap(RequireScope::new(Scope::PaymentsWrite)),
+                    )
+                    .route(
+                        "/beneficiaries/{id}",
+                        web::delete()
+                            .to(delete_beneficiary)
+                            .wrap(RequireScope::new(Scope::PaymentsWrite)),
+                    )
+                    .route(
+                        "/transfers/claims",
+                        web::get()
+                            .to(list_claimable_transfers)
+                            .wrap(RequireScope::new(Scope::AccountsRead)),
+                    )
+                    .route(
+                        "/transfers/claims/{id}/claim",
+                        web::post()
+                            .to(claim_transfer)
+                            .wrap(RequireScope::new(Scope::PaymentsWrite)),
+                    )
+                    .route(
+                        "/payment-requests",
+                        web::get()
+                            .to(list_payment_requests)
+                            .wrap(RequireScope::new(Scope::AccountsRead)),
+                    )
+                    .route(
+                        "/payment-requests",
+                        web::post()
+                            .to(create_payment_request)
+                            .wrap(RequireScope::new(Scope::PaymentsWrite)),
+                    )
+                    .route(
+                        "/payment-requests/{id}",
+                        web::get()
+                            .to(get_payment_request)
+                            .wrap(RequireScope::new(Scope::AccountsRead)),
+                    )
+                    .route(
+                        "/payment-requests/{id}/pay",
+                        web::post()
+                            .to(pay_payment_request)
+                            .wrap(RequireScope::new(Scope::PaymentsWrite)),
+                    )
+                    .route(
+                        "/payment-requests/{id}/decline",
+                        web::post()
+                            .to(decline_payment_request)
+                            .wrap(RequireScope::new(Scope::PaymentsWrite)),
+                    )
+                    // Admin routes (require the admin role)
+                    .route(
+                        "/admin/users",
+                        web::get()
+                            .to(find_user)
+                            .wrap(RequireRole::new(&[Role::Admin])),
+                    )
+                    .route(
+                        "/admin/users/{id}",
+                        web::get()
+                            .to(get_user)
+                            .wrap(RequireRole::new(&[Role::Admin])),
+                    )
+                    .route(
+                        "/admin/users/{id}/role",
+                        web::put()
+                            .to(update_user_role)
+                            .wrap(RequireRole::new(&[Role::Admin])),
+                    )
+                    // KYC decisions are the back office's job
+                    .route(
+                        "/admin/users/{id}/kyc",
+                        web::put()
+                            .to(update_kyc_status)
+                            .wrap(RequireRole::new(&[Role::Operator, Role::Admin])),
+                    )
+                    .route(
+                        "/admin/users/{id}/kyc",
+                        web::get()
+                            .to(kyc_history)
+                            .wrap(RequireRole::new(&[Role::Operator, Role::Admin])),
+                    )
+                    .route(
+                        "/admin/unlock",
+                        web::post()
+                            .to(unlock_login)
+                            .wrap(RequireRole::new(&[Role::Admin])),
+                    )
+                    .route(
+                        "/admin/accounts/{id}",
+                        web::get()
+                            .to(get_any_account)
+                            .wrap(RequireRole::new(&[Role::Admin])),
+                    )
+                    // Undoing mistaken transfers is the back office's job too
+                    .route(
+                        "/transfers/{id}/reverse",
+                        web::post()
+                            .to(reverse_transfer)
+                            .wrap(RequireRole::new(&[Role::Operator, Role::Admin]))
+                            .wrap(RequireScope::new(Scope::PaymentsWrite)),
+                    ),
             )
     });
 
@@ -126,8 +518,56 @@ async fn main() -> std::io::Result<()> {
 
     info!(
         address = %bind_addr,
-        routes = %"GET /api/health, POST /api/auth/register, POST /api/auth/login, POST /api/auth/token, POST /api/accounts, GET /api/accounts/{id}, POST /api/accounts/{id}/deposit, POST /api/accounts/{id}/withdraw, POST /api/transfers",
+        routes = %"GET /.well-known/jwks.json, GET /api/health, POST /api/auth/register, POST /api/auth/login, POST /api/auth/login/2fa, POST /api/auth/refresh, POST /api/auth/logout, POST /api/auth/logout-all, POST /api/auth/password, POST /api/auth/password/forgot, POST /api/auth/password/reset, POST /api/auth/verify-email, POST /api/auth/verify-email/resend, POST /api/auth/token, POST /api/auth/2fa/enroll, POST /api/auth/2fa/verify, POST /api/auth/oidc/authorize, GET /api/auth/oidc/callback, POST /api/auth/api-keys, GET /api/auth/api-keys, DELETE /api/auth/api-keys/{id}, GET /api/auth/sessions, DELETE /api/auth/sessions/{id}, GET /api/users/me, PATCH /api/users/me, DELETE /api/users/me, GET /api/users/me/export, POST /api/accounts, GET /api/accounts/{id}, POST /api/accounts/{id}/deposit, POST /api/accounts/{id}/withdraw, GET /api/accounts/{id}/statement, GET /api/accounts/{id}/holders, POST /api/accounts/{id}/holders, DELETE /api/accounts/{id}/holders/{user_id}, POST /api/transfers, POST /api/transfers/batch, POST /api/transfers/email, GET /api/transfers/claims, POST /api/transfers/claims/{id}/claim, GET /api/beneficiaries, POST /api/beneficiaries, GET /api/beneficiaries/{id}, PATCH /api/beneficiaries/{id}, DELETE /api/beneficiaries/{id}, GET /api/payment-requests, POST /api/payment-requests, GET /api/payment-requests/{id}, POST /api/payment-requests/{id}/pay, POST /api/payment-requests/{id}/decline, GET /api/admin/users?email=, GET /api/admin/users/{id}, PUT /api/admin/users/{id}/role, PUT /api/admin/users/{id}/kyc, GET /api/admin/users/{id}/kyc, POST /api/admin/unlock, GET /api/admin/accounts/{id}, POST /api/transfers/{id}/reverse",
         "Starting HTTP server"
     );
     server.run().await
 }
+
+// Tokens are signed with HS256 and JWT_SECRET, unless JWT_SIGNING_KEY_FILE names a PEM private
+// key (RSA for RS256, Ed25519 for EdDSA) published under JWT_SIGNING_KEY_ID. The secret then
+// only verifies tokens issued before the switch. JWT_VERIFICATION_KEYS ("kid=path,...") lists
+// keys that verify but no longer (or not yet) sign.
+fn load_jwt_keys(jwt_secret: &str) -> JwtKeySet {
+    let mut keys = match std::env::var("JWT_SIGNING_KEY_FILE") {
+        Ok(path) => {
+            let kid = std::env::var("JWT_SIGNING_KEY_ID")
+                .expect("JWT_SIGNING_KEY_ID must be set with JWT_SIGNING_KEY_FILE");
+            let key = JwtKey::from_pem_file(&kid, &path).expect("Failed to load JWT signing key");
+            let keys = JwtKeySet::new(key);
+            // The shared secret can forge tokens, so it only keeps verifying while tokens
+            // it signed before the switch are still live
+            let accept_legacy_secret = std::env::var("JWT_ACCEPT_LEGACY_SECRET")
+                .map(|value| {
+                    value
+                        .parse::<bool>()
+                        .expect("JWT_ACCEPT_LEGACY_SECRET must be true or false")
+                })
+                .unwrap_or(false);
+            if accept_legacy_secret {
+                warn!(
+                    "JWT_SECRET still verifies tokens; unset JWT_ACCEPT_LEGACY_SECRET once they have expired"
+                );
+                keys.with_verification_key(JwtKey::from_secret(jwt_secret))
+            } else {
+                keys
+            }
+        }
+        Err(_) => JwtKeySet::from_secret(jwt_secret),
+    };
+
+    let verification_keys = std::env::var("JWT_VERIFICATION_KEYS").unwrap_or_default();
+    for entry in verification_keys.split(',').map(str::trim) {
+        if entry.is_empty() {
+            continue;
+        }
+        let (kid, path) = entry
+            .split_once('=')
+            .expect("JWT_VERIFICATION_KEYS entries must look like kid=path");
+        let key = JwtKey::from_pem_file(kid, path).expect("Failed to load JWT verification key");
+        info!(kid = kid, "Loaded JWT verification key");
+        keys = keys.with_verification_key(key);
+    }
+
+    keys
+}
@@ -1,3 +1,10 @@
+pub mod admin;
 pub mod auth;
+pub mod batch;
+pub mod beneficiaries;
 pub mod handlers;
+pub mod holders;
 pub mod middleware;
+pub mod p2p;
+pub mod payment_requests;
+pub mod users;
@@ -0,0 +1,210 @@
+// Back-office endpoints, registered behind `RequireRole` in main.rs
+use crate::domain::reversal::ReverseTransfer;
+use crate::domain::user::{
+    KycStatus, Role, UnlockLoginRequest, UpdateKycStatusRequest, UpdateRoleRequest, User,
+    UserStatus,
+};
+use crate::presentation::handlers::{AppState, BankError};
+use crate::presentation::middleware::AuthenticatedUser;
+use actix_web::{HttpResponse, web};
+use serde::{Deserialize, Serialize};
+use tracing::{error, info, instrument, warn};
+
+// A user as shown to admins, without credentials
+#[derive(Debug, Serialize, Deserialize)]
+pub struct UserResponse {
+    pub id: String,
+    pub email: String,
+    pub role: Role,
+    pub status: UserStatus,
+    pub kyc_status: KycStatus,
+}
+
+impl From<User> for UserResponse {
+    fn from(user: User) -> Self {
+        Self {
+            id: user.id,
+            email: user.email,
+            role: user.role,
+            status: user.status,
+            kyc_status: user.kyc_status,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct UserLookupQuery {
+    pub email: String,
+}
+
+#[instrument(skip(state), fields(user_id = %*path))]
+pub async fn get_user(
+    state: web::Data<AppState>,
+    path: web::Path<String>,
+) -> Result<HttpResponse, BankError> {
+    let user_id = path.into_inner();
+    info!(user_id = %user_id, "Admin user lookup");
+
+    let user = state.auth_service.get_user(&user_id).await.map_err(|e| {
+        error!(user_id = %user_id, error = %e, "Failed to get user");
+        BankError::from(e)
+    })?;
+
+    Ok(HttpResponse::Ok().json(UserResponse::from(user)))
+}
+
+#[instrument(skip(state))]
+pub async fn find_user(
+    state: web::Data<AppState>,
+    query: web::Query<UserLookupQuery>,
+) -> Result<HttpResponse, BankError> {
+    info!(email = %query.email, "Admin user lookup by email");
+
+    let user = state
+        .auth_service
+        .get_user_by_email(&query.email)
+        .await
+        .map_err(|e| {
+            error!(email = %query.email, error = %e, "Failed to find user");
+            BankError::from(e)
+        })?;
+
+    Ok(HttpResponse::Ok().json(UserResponse::from(user)))
+}
+
+#[instrument(skip(state, admin), fields(user_id = %*path, admin_id = %admin.user_id))]
+pub async fn update_user_role(
+    state: web::Data<AppState>,
+    admin: AuthenticatedUser,
+    path: web::Path<String>,
+    req: web::Json<UpdateRoleRequest>,
+) -> Result<HttpResponse, BankError> {
+    let user_id = path.into_inner();
+    info!(user_id = %user_id, role = %req.role, "Role change requested");
+
+    // Keeps at least the acting admin around; demoting oneself by mistake is not recoverable
+    // without another admin
+    if user_id == admin.user_id && req.role != Role::Admin {
+        warn!(admin_id = %admin.user_id, "Admin tried to demote themselves");
+        return Err(BankError::Validation(
+            "Admins cannot change their own role".to_string(),
+        ));
+    }
+
+    let user = state
+        .auth_service
+        .set_user_role(&user_id, req.role)
+        .await
+        .map_err(|e| {
+            error!(user_id = %user_id, error = %e, "Failed to change role");
+            BankError::from(e)
+        })?;
+
+    info!(user_id = %user.id, role = %user.role, admin_id = %admin.user_id, "Role changed");
+    Ok(HttpResponse::Ok().json(UserResponse::from(user)))
+}
+
+// Lifts the login lockout of an email, e.g. after the owner proved their identity to support
+#[instrument(skip(state, admin), fields(admin_id = %admin.user_id))]
+pub async fn unlock_login(
+    state: web::Data<AppState>,
+    admin: AuthenticatedUser,
+    req: web::Json<UnlockLoginRequest>,
+) -> Result<HttpResponse, BankError> {
+    info!(email = %req.email, "Login unlock requested");
+
+    state
+        .auth_service
+        .unlock_login(&req.email)
+        .await
+        .map_err(|e| {
+            error!(email = %req.email, error = %e, "Failed to unlock login");
+            BankError::from(e)
+        })?;
+
+    info!(email = %req.email, admin_id = %admin.user_id, "Login unlocked");
+    Ok(HttpResponse::NoContent().finish())
+}
+
+#[instrument(skip(state, operator, req), fields(user_id = %*path, operator_id = %operator.user_id))]
+pub async fn update_kyc_status(
+    state: web::Data<AppState>,
+    operator: AuthenticatedUser,
+    path: web::Path<String>,
+    req: web::Json<UpdateKycStatusRequest>,
+) -> Result<HttpResponse, BankError> {
+    let user_id = path.into_inner();
+    info!(user_id = %user_id, status = %req.status, "KYC status change requested");
+
+    let user = state
+        .auth_service
+        .set_kyc_status(&user_id, &operator.user_id, req.into_inner())
+        .await
+        .map_err(|e| {
+            error!(user_id = %user_id, error = %e, "Failed to change KYC status");
+            BankError::from(e)
+        })?;
+
+    Ok(HttpResponse::Ok().json(UserResponse::from(user)))
+}
+
+#[instrument(skip(state), fields(user_id = %*path))]
+pub async fn kyc_history(
+    state: web::Data<AppState>,
+    path: web::Path<String>,
+) -> Result<HttpResponse, BankError> {
+    let user_id = path.into_inner();
+
+    let history = state
+        .auth_service
+        .kyc_history(&user_id)
+        .await
+        .map_err(|e| {
+            error!(user_id = %user_id, error = %e, "Failed to get KYC history");
+            BankError::from(e)
+        })?;
+
+    Ok(HttpResponse::Ok().json(history))
+}
+
+#[instrument(skip(state), fields(account_id = %*path))]
+pub async fn get_any_account(
+    state: web::Data<AppState>,
+    path: web::Path<u32>,
+) -> Result<HttpResponse, BankError> {
+    let account_id = path.into_inner();
+    info!(account_id = account_id, "Admin account lookup");
+
+    let account = state
+        .service
+        .get_any_account(account_id)
+        .await
+        .map_err(|e| {
+            error!(account_id = account_id, error = %e, "Failed to get account");
+            BankError::from(e)
+        })?;
+
+    Ok(HttpResponse::Ok().json(account))
+}
+
+#[instrument(skip(state, operator, req), fields(transfer_id = %*path, operator_id = %operator.user_id))]
+pub async fn reverse_transfer(
+    state: web::Data<AppState>,
+    operator: AuthenticatedUser,
+    path: web::Path<String>,
+    req: web::Json<ReverseTransfer>,
+) -> Result<HttpResponse, BankError> {
+    let transfer_id = path.into_inner();
+    info!(transfer_id = %transfer_id, "Transfer reversal requested");
+
+    let reversal = state
+        .service
+        .reverse_transfer(&operator.user_id, &transfer_id, req.into_inner())
+        .await
+        .map_err(|e| {
+            error!(transfer_id = %transfer_id, error = %e, "Failed to reverse transfer");
+            BankError::from(e)
+        })?;
+
+    Ok(HttpResponse::Created().json(reversal))
+}
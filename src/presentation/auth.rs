@@ -1,18 +1,54 @@
-use crate::domain::user::{CreateUser, LoginRequest};
+use crate::domain::api_key::CreateApiKeyRequest;
+use crate::domain::oidc::OidcCallback;
+use crate::domain::session::{ClientInfo, SessionResponse};
+use crate::domain::token::{
+    AuthTokens, ClientCredentials, LoginOutcome, LogoutRequest, RefreshRequest, Scope,
+};
+use crate::domain::two_factor::{RecoveryCodes, TwoFactorLoginRequest, VerifyTwoFactorRequest};
+use crate::domain::user::{
+    ChangePasswordRequest, CreateUser, LoginRequest, PasswordResetRequest, ResetPasswordRequest,
+    UserStatus, VerifyEmailRequest,
+};
+use crate::infrastructure::security::AccessTokenClaims;
 use crate::presentation::handlers::{AppState, BankError};
-use actix_web::{HttpResponse, web};
+use actix_web::cookie::{Cookie, SameSite, time};
+use actix_web::{HttpRequest, HttpResponse, web};
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
 use serde::{Deserialize, Serialize};
-use tracing::{error, info, instrument};
+use tracing::{error, info, instrument, warn};
 
 #[derive(Serialize)]
 pub struct RegisterResponse {
     pub id: String,
     pub email: String,
+    pub status: UserStatus,
 }
 
 #[derive(Serialize)]
 pub struct LoginResponse {
     pub access_token: String,
+    pub refresh_token: String,
+    pub token_type: &'static str,
+    pub expires_in: u64,
+}
+
+impl From<AuthTokens> for LoginResponse {
+    fn from(tokens: AuthTokens) -> Self {
+        Self {
+            access_token: tokens.access_token,
+            refresh_token: tokens.refresh_token,
+            token_type: "Bearer",
+            expires_in: tokens.expires_in,
+        }
+    }
+}
+
+#[derive(Serialize)]
+pub struct TwoFactorChallengeResponse {
+    pub two_factor_required: bool,
+    pub challenge_token: String,
+    pub expires_in: u64,
 }
 
 #[derive(Serialize)]
@@ -23,6 +59,9 @@ pub struct TokenResponse {
 #[derive(Debug, Serialize, Deserialize)]
 pub struct GetTokenRequest {
     pub user_id: String,
+    // Restricts the token to these scopes; omitted for an unrestricted token
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub scopes: Option<Vec<Scope>>,
 }
 
 #[instrument(skip(state))]
@@ -44,46 +83,343 @@ pub async fn register(
     let response = RegisterResponse {
         id: user.id,
         email: user.email,
+        status: user.status,
     };
 
     info!(user_id = %response.id, email = %response.email, "User registered successfully");
     Ok(HttpResponse::Created().json(response))
 }
 
-#[instrument(skip(state))]
+// The socket address rather than X-Forwarded-For, which any client can set
+fn client_info(http_req: &HttpRequest) -> ClientInfo {
+    ClientInfo {
+        ip: http_req.peer_addr().map(|addr| addr.ip().to_string()),
+        user_agent: http_req
+            .headers()
+            .get(actix_web::http::header::USER_AGENT)
+            .and_then(|value| value.to_str().ok())
+            .map(str::to_string),
+    }
+}
+
+#[instrument(skip(state, http_req))]
 pub async fn login(
     state: web::Data<AppState>,
+    http_req: HttpRequest,
     req: web::Json<LoginRequest>,
 ) -> Result<HttpResponse, BankError> {
     info!(email = %req.email, "Login request received");
 
-    let token = state
+    let outcome = state
         .auth_service
-        .login(req.into_inner())
+        .login_from(req.into_inner(), &client_info(&http_req))
         .await
         .map_err(|e| {
             error!(error = %e, "Failed to login");
             BankError::from(e)
         })?;
 
-    let response = LoginResponse {
-        access_token: token,
-    };
+    match outcome {
+        LoginOutcome::Authenticated(tokens) => {
+            info!("Login successful");
+            Ok(HttpResponse::Ok().json(LoginResponse::from(tokens)))
+        }
+        LoginOutcome::TwoFactorRequired {
+            challenge_token,
+            expires_in,
+        } => {
+            info!("Login requires a two-factor code");
+            Ok(HttpResponse::Ok().json(TwoFactorChallengeResponse {
+                two_factor_required: true,
+                challenge_token,
+                expires_in,
+            }))
+        }
+    }
+}
 
-    info!("Login successful");
-    Ok(HttpResponse::Ok().json(response))
+#[instrument(skip(state, req))]
+pub async fn login_two_factor(
+    state: web::Data<AppState>,
+    req: web::Json<TwoFactorLoginRequest>,
+) -> Result<HttpResponse, BankError> {
+    info!("Two-factor login request received");
+
+    let tokens = state
+        .auth_service
+        .complete_two_factor_login(&req.challenge_token, &req.code)
+        .await
+        .map_err(|e| {
+            error!(error = %e, "Failed to complete two-factor login");
+            BankError::from(e)
+        })?;
+
+    info!("Two-factor login successful");
+    Ok(HttpResponse::Ok().json(LoginResponse::from(tokens)))
+}
+
+#[instrument(skip(state, http_req))]
+pub async fn enroll_two_factor(
+    state: web::Data<AppState>,
+    http_req: HttpRequest,
+) -> Result<HttpResponse, BankError> {
+    info!("Two-factor enrollment request received");
+
+    let claims = session_claims(&state, &http_req).await?;
+
+    let enrollment = state
+        .auth_service
+        .enroll_two_factor(&claims.user_id)
+        .await
+        .map_err(|e| {
+            error!(error = %e, "Failed to start two-factor enrollment");
+            BankError::from(e)
+        })?;
+
+    info!(user_id = %claims.user_id, "Two-factor enrollment started");
+    Ok(HttpResponse::Ok().json(enrollment))
+}
+
+#[instrument(skip(state, http_req, req))]
+pub async fn verify_two_factor(
+    state: web::Data<AppState>,
+    http_req: HttpRequest,
+    req: web::Json<VerifyTwoFactorRequest>,
+) -> Result<HttpResponse, BankError> {
+    info!("Two-factor verification request received");
+
+    let claims = session_claims(&state, &http_req).await?;
+
+    let recovery_codes = state
+        .auth_service
+        .activate_two_factor(&claims.user_id, &req.code)
+        .await
+        .map_err(|e| {
+            error!(error = %e, "Failed to activate two-factor authentication");
+            BankError::from(e)
+        })?;
+
+    info!(user_id = %claims.user_id, "Two-factor authentication enabled");
+    Ok(HttpResponse::Ok().json(RecoveryCodes { recovery_codes }))
+}
+
+#[instrument(skip(state, req))]
+pub async fn refresh(
+    state: web::Data<AppState>,
+    req: web::Json<RefreshRequest>,
+) -> Result<HttpResponse, BankError> {
+    info!("Token refresh request received");
+
+    let tokens = state
+        .auth_service
+        .refresh(&req.refresh_token)
+        .await
+        .map_err(|e| {
+            error!(error = %e, "Failed to refresh tokens");
+            BankError::from(e)
+        })?;
+
+    info!("Tokens refreshed successfully");
+    Ok(HttpResponse::Ok().json(LoginResponse::from(tokens)))
+}
+
+fn bearer_token(req: &HttpRequest) -> Result<&str, BankError> {
+    req.headers()
+        .get("Authorization")
+        .and_then(|h| h.to_str().ok())
+        .and_then(|h| h.strip_prefix("Bearer "))
+        .ok_or_else(|| BankError::Unauthorized("missing bearer".to_string()))
+}
+
+#[instrument(skip(state, http_req, req))]
+pub async fn logout(
+    state: web::Data<AppState>,
+    http_req: HttpRequest,
+    req: Option<web::Json<LogoutRequest>>,
+) -> Result<HttpResponse, BankError> {
+    info!("Logout request received");
+
+    let claims = session_claims(&state, &http_req).await?;
+    let req = req.map(|r| r.into_inner()).unwrap_or_default();
+
+    state
+        .auth_service
+        .logout(&claims, req.refresh_token.as_deref())
+        .await
+        .map_err(|e| {
+            error!(error = %e, "Failed to logout");
+            BankError::from(e)
+        })?;
+
+    info!(user_id = %claims.user_id, "Logout successful");
+    Ok(HttpResponse::NoContent().finish())
+}
+
+#[instrument(skip(state, http_req))]
+pub async fn logout_all(
+    state: web::Data<AppState>,
+    http_req: HttpRequest,
+) -> Result<HttpResponse, BankError> {
+    info!("Logout from all sessions request received");
+
+    let claims = session_claims(&state, &http_req).await?;
+
+    state
+        .auth_service
+        .logout_all_sessions(&claims.user_id)
+        .await
+        .map_err(|e| {
+            error!(error = %e, "Failed to logout from all sessions");
+            BankError::from(e)
+        })?;
+
+    info!(user_id = %claims.user_id, "Logged out from all sessions");
+    Ok(HttpResponse::NoContent().finish())
+}
+
+#[instrument(skip(state, http_req, req))]
+pub async fn change_password(
+    state: web::Data<AppState>,
+    http_req: HttpRequest,
+    req: web::Json<ChangePasswordRequest>,
+) -> Result<HttpResponse, BankError> {
+    info!("Password change request received");
+
+    let claims = session_claims(&state, &http_req).await?;
+
+    state
+        .auth_service
+        .change_password(&claims.user_id, req.into_inner())
+        .await
+        .map_err(|e| {
+            error!(error = %e, "Failed to change password");
+            BankError::from(e)
+        })?;
+
+    info!(user_id = %claims.user_id, "Password changed successfully");
+    Ok(HttpResponse::NoContent().finish())
 }
 
 #[instrument(skip(state))]
+pub async fn request_password_reset(
+    state: web::Data<AppState>,
+    req: web::Json<PasswordResetRequest>,
+) -> Result<HttpResponse, BankError> {
+    info!(email = %req.email, "Password reset request received");
+
+    state
+        .auth_service
+        .request_password_reset(&req.email)
+        .await
+        .map_err(|e| {
+            error!(error = %e, "Failed to request password reset");
+            BankError::from(e)
+        })?;
+
+    // Same response whether or not the email is registered
+    Ok(HttpResponse::Accepted().finish())
+}
+
+#[instrument(skip(state, req))]
+pub async fn reset_password(
+    state: web::Data<AppState>,
+    req: web::Json<ResetPasswordRequest>,
+) -> Result<HttpResponse, BankError> {
+    info!("Password reset received");
+
+    state
+        .auth_service
+        .reset_password(req.into_inner())
+        .await
+        .map_err(|e| {
+            error!(error = %e, "Failed to reset password");
+            BankError::from(e)
+        })?;
+
+    info!("Password reset successfully");
+    Ok(HttpResponse::NoContent().finish())
+}
+
+#[instrument(skip(state, req))]
+pub async fn verify_email(
+    state: web::Data<AppState>,
+    req: web::Json<VerifyEmailRequest>,
+) -> Result<HttpResponse, BankError> {
+    info!("Email verification received");
+
+    let user = state
+        .auth_service
+        .verify_email(&req.token)
+        .await
+        .map_err(|e| {
+            error!(error = %e, "Failed to verify email");
+            BankError::from(e)
+        })?;
+
+    info!(user_id = %user.id, "Email verified");
+    Ok(HttpResponse::NoContent().finish())
+}
+
+#[instrument(skip(state, http_req))]
+pub async fn resend_verification_email(
+    state: web::Data<AppState>,
+    http_req: HttpRequest,
+) -> Result<HttpResponse, BankError> {
+    info!("Verification email resend request received");
+
+    let claims = session_claims(&state, &http_req).await?;
+
+    state
+        .auth_service
+        .resend_verification_email(&claims.user_id)
+        .await
+        .map_err(|e| {
+            error!(error = %e, "Failed to resend verification email");
+            BankError::from(e)
+        })?;
+
+    Ok(HttpResponse::Accepted().finish())
+}
+
+// Extracts client credentials from an `Authorization: Basic base64(client_id:client_secret)`
+// header, as in the OAuth 2.0 client credentials grant.
+pub fn client_credentials(req: &HttpRequest) -> Result<ClientCredentials, BankError> {
+    let unauthorized = || BankError::Unauthorized("Client authentication required".to_string());
+
+    let encoded = req
+        .headers()
+        .get("Authorization")
+        .and_then(|h| h.to_str().ok())
+        .and_then(|h| h.strip_prefix("Basic "))
+        .ok_or_else(unauthorized)?;
+    let decoded = STANDARD
+        .decode(encoded.trim())
+        .ok()
+        .and_then(|bytes| String::from_utf8(bytes).ok())
+        .ok_or_else(unauthorized)?;
+    let (client_id, client_secret) = decoded.split_once(':').ok_or_else(unauthorized)?;
+
+    Ok(ClientCredentials {
+        client_id: client_id.to_string(),
+        client_secret: client_secret.to_string(),
+    })
+}
+
+#[instrument(skip(state, http_req))]
 pub async fn get_token(
     state: web::Data<AppState>,
+    http_req: HttpRequest,
     req: web::Json<GetTokenRequest>,
 ) -> Result<HttpResponse, BankError> {
     info!(user_id = %req.user_id, "Token request received");
 
+    let credentials = client_credentials(&http_req).inspect_err(|_| {
+        warn!("Token request without client authentication");
+    })?;
+
     let token = state
         .auth_service
-        .get_token(&req.user_id)
+        .get_token(&credentials, &req.user_id, req.scopes.as_deref())
         .await
         .map_err(|e| {
             error!(error = %e, "Failed to generate token");
@@ -97,3 +433,229 @@ pub async fn get_token(
     info!("Token generated successfully");
     Ok(HttpResponse::Ok().json(response))
 }
+
+// Public keys for verifying our access tokens, cached by verifiers between rotations
+#[instrument(skip(state))]
+pub async fn jwks(state: web::Data<AppState>) -> HttpResponse {
+    info!("JWKS requested");
+    HttpResponse::Ok()
+        .insert_header(("Cache-Control", "public, max-age=300"))
+        .json(state.auth_service.jwks())
+}
+
+// For operations that grant access, such as API keys or linked identities, and for managing
+// sessions and the password: neither an API key nor a scoped token may obtain more access
+// than it has itself
+async fn session_claims(
+    state: &web::Data<AppState>,
+    http_req: &HttpRequest,
+) -> Result<AccessTokenClaims, BankError> {
+    let claims = state
+        .auth_service
+        .authenticate(bearer_token(http_req)?)
+        .await
+        .map_err(BankError::from)?;
+    if claims.scopes.is_some() {
+        warn!(user_id = %claims.user_id, "Session-only endpoint called with a scoped token");
+        return Err(BankError::Forbidden(
+            "This endpoint requires a session token".to_string(),
+        ));
+    }
+    Ok(claims)
+}
+
+#[instrument(skip(state, http_req, req))]
+pub async fn create_api_key(
+    state: web::Data<AppState>,
+    http_req: HttpRequest,
+    req: web::Json<CreateApiKeyRequest>,
+) -> Result<HttpResponse, BankError> {
+    info!(name = %req.name, "API key creation request received");
+
+    let claims = session_claims(&state, &http_req).await?;
+
+    let api_key = state
+        .auth_service
+        .create_api_key(&claims.user_id, req.into_inner())
+        .await
+        .map_err(|e| {
+            error!(error = %e, "Failed to create API key");
+            BankError::from(e)
+        })?;
+
+    info!(user_id = %claims.user_id, key_id = %api_key.api_key.id, "API key created");
+    Ok(HttpResponse::Created().json(api_key))
+}
+
+#[instrument(skip(state, http_req))]
+pub async fn list_api_keys(
+    state: web::Data<AppState>,
+    http_req: HttpRequest,
+) -> Result<HttpResponse, BankError> {
+    info!("API key list request received");
+
+    let claims = session_claims(&state, &http_req).await?;
+
+    let api_keys = state
+        .auth_service
+        .list_api_keys(&claims.user_id)
+        .await
+        .map_err(|e| {
+            error!(error = %e, "Failed to list API keys");
+            BankError::from(e)
+        })?;
+
+    Ok(HttpResponse::Ok().json(api_keys))
+}
+
+#[instrument(skip(state, http_req))]
+pub async fn revoke_api_key(
+    state: web::Data<AppState>,
+    http_req: HttpRequest,
+    path: web::Path<String>,
+) -> Result<HttpResponse, BankError> {
+    let key_id = path.into_inner();
+    info!(key_id = %key_id, "API key revocation request received");
+
+    let claims = session_claims(&state, &http_req).await?;
+
+    state
+        .auth_service
+        .revoke_api_key(&claims.user_id, &key_id)
+        .await
+        .map_err(|e| {
+            error!(error = %e, "Failed to revoke API key");
+            BankError::from(e)
+        })?;
+
+    info!(user_id = %claims.user_id, key_id = %key_id, "API key revoked");
+    Ok(HttpResponse::NoContent().finish())
+}
+
+#[instrument(skip(state, http_req))]
+pub async fn list_sessions(
+    state: web::Data<AppState>,
+    http_req: HttpRequest,
+) -> Result<HttpResponse, BankError> {
+    info!("Session list request received");
+
+    let claims = session_claims(&state, &http_req).await?;
+
+    let sessions = state
+        .auth_service
+        .list_sessions(&claims.user_id)
+        .await
+        .map_err(|e| {
+            error!(error = %e, "Failed to list sessions");
+            BankError::from(e)
+        })?;
+
+    let response: Vec<SessionResponse> = sessions
+        .into_iter()
+        .map(|session| SessionResponse {
+            current: claims.session_id.as_deref() == Some(session.id.as_str()),
+            session,
+        })
+        .collect();
+    Ok(HttpResponse::Ok().json(response))
+}
+
+#[instrument(skip(state, http_req))]
+pub async fn revoke_session(
+    state: web::Data<AppState>,
+    http_req: HttpRequest,
+    path: web::Path<String>,
+) -> Result<HttpResponse, BankError> {
+    let session_id = path.into_inner();
+    info!(session_id = %session_id, "Session revocation request received");
+
+    let claims = session_claims(&state, &http_req).await?;
+
+    state
+        .auth_service
+        .revoke_session(&claims.user_id, &session_id)
+        .await
+        .map_err(|e| {
+            error!(error = %e, "Failed to revoke session");
+            BankError::from(e)
+        })?;
+
+    info!(user_id = %claims.user_id, session_id = %session_id, "Session revoked");
+    Ok(HttpResponse::NoContent().finish())
+}
+
+// Ties a pending OIDC login to the browser that started it. Lax, so the provider's
+// top-level redirect to the callback still carries it.
+const OIDC_BINDING_COOKIE: &str = "oidc_binding";
+
+fn oidc_binding_cookie(value: String, max_age: time::Duration) -> Cookie<'static> {
+    Cookie::build(OIDC_BINDING_COOKIE, value)
+        .path("/api/auth/oidc")
+        .http_only(true)
+        .secure(true)
+        .same_site(SameSite::Lax)
+        .max_age(max_age)
+        .finish()
+}
+
+// Starts an OIDC sign-in. Signed-in users (session token) link the external identity to
+// their account instead.
+#[instrument(skip(state, http_req))]
+pub async fn oidc_authorize(
+    state: web::Data<AppState>,
+    http_req: HttpRequest,
+) -> Result<HttpResponse, BankError> {
+    info!("OIDC authorization request received");
+
+    let link_user_id = match http_req.headers().contains_key("Authorization") {
+        true => Some(session_claims(&state, &http_req).await?.user_id),
+        false => None,
+    };
+
+    let start = state
+        .auth_service
+        .start_oidc_login(link_user_id.as_deref())
+        .await
+        .map_err(|e| {
+            error!(error = %e, "Failed to start OIDC login");
+            BankError::from(e)
+        })?;
+
+    let cookie = oidc_binding_cookie(
+        start.binding.clone(),
+        time::Duration::seconds(start.expires_in as i64),
+    );
+    Ok(HttpResponse::Ok().cookie(cookie).json(start))
+}
+
+// Redirect target registered with the identity provider
+#[instrument(skip(state, http_req, query))]
+pub async fn oidc_callback(
+    state: web::Data<AppState>,
+    http_req: HttpRequest,
+    query: web::Query<OidcCallback>,
+) -> Result<HttpResponse, BankError> {
+    info!("OIDC callback received");
+
+    let tokens = state
+        .auth_service
+        .complete_oidc_login(
+            &query.code,
+            &query.state,
+            http_req
+                .cookie(OIDC_BINDING_COOKIE)
+                .as_ref()
+                .map(Cookie::value),
+            &client_info(&http_req),
+        )
+        .await
+        .map_err(|e| {
+            error!(error = %e, "Failed to complete OIDC login");
+            BankError::from(e)
+        })?;
+
+    info!("OIDC login successful");
+    Ok(HttpResponse::Ok()
+        .cookie(oidc_binding_cookie(String::new(), time::Duration::ZERO))
+        .json(LoginResponse::from(tokens)))
+}
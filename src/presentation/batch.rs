@@ -0,0 +1,191 @@
+use crate::domain::models::{Amount, BatchMode, BatchTransferRow, TransactionDetails, Transfer};
+use crate::infrastructure::iso20022::parse_pain001;
+use crate::presentation::handlers::{AppState, BankError, two_factor_code};
+use crate::presentation::middleware::AuthenticatedUser;
+use actix_web::{HttpMessage, HttpRequest, HttpResponse, web};
+use serde::Deserialize;
+use tracing::{error, info, instrument, warn};
+
+#[derive(Debug, Deserialize)]
+pub struct BatchQuery {
+    #[serde(default)]
+    pub mode: BatchMode,
+}
+
+// A CSV row; `Transfer` flattens its details, which the csv crate cannot deserialize
+#[derive(Debug, Deserialize)]
+struct CsvTransfer {
+    from_account_id: u32,
+    to_account_id: u32,
+    amount: Amount,
+    #[serde(default)]
+    description: Option<String>,
+    #[serde(default)]
+    reference: Option<String>,
+}
+
+impl From<CsvTransfer> for Transfer {
+    fn from(row: CsvTransfer) -> Self {
+        let non_empty = |value: Option<String>| value.filter(|v| !v.is_empty());
+        Transfer {
+            from_account_id: row.from_account_id,
+            to_account_id: row.to_account_id,
+            amount: row.amount,
+            beneficiary_id: None,
+            details: TransactionDetails {
+                description: non_empty(row.description),
+                reference: non_empty(row.reference),
+                ..Default::default()
+            },
+        }
+    }
+}
+
+// Parses a CSV file with a `from_account_id,to_account_id,amount` header and optional
+// `description` and `reference` columns.
+// Line numbers refer to the physical lines of the file, so the first data row is line 2.
+pub fn parse_transfers_csv(body: &[u8]) -> Result<Vec<BatchTransferRow>, BankError> {
+    let mut reader = csv::ReaderBuilder::new()
+        .trim(csv::Trim::All)
+        .from_reader(body);
+    let headers = reader
+        .headers()
+        .map_err(|e| BankError::Validation(format!("Invalid CSV header: {}", e)))?
+        .clone();
+
+    let mut rows = Vec::new();
+    let mut errors = Vec::new();
+    for record in reader.records() {
+        let record = match record {
+            Ok(record) => record,
+            Err(e) => {
+                let line = e.position().map(|p| p.line()).unwrap_or_default();
+                errors.push(format!("line {}: {}", line, e));
+                continue;
+            }
+        };
+        let line = record.position().map(|p| p.line()).unwrap_or_default() as usize;
+        match record.deserialize::<CsvTransfer>(Some(&headers)) {
+            Ok(row) => rows.push(BatchTransferRow {
+                line,
+                transfer: row.into(),
+            }),
+            Err(e) => errors.push(format!("line {}: {}", line, e)),
+        }
+    }
+
+    if !errors.is_empty() {
+        return Err(BankError::Validation(format!(
+            "Malformed CSV rows: {}",
+            errors.join("; ")
+        )));
+    }
+    Ok(rows)
+}
+
+// Parses a JSON array of transfers. Line numbers are the 1-based positions in the array.
+pub fn parse_transfers_json(body: &[u8]) -> Result<Vec<BatchTransferRow>, BankError> {
+    let transfers: Vec<Transfer> = serde_json::from_slice(body)
+        .map_err(|e| BankError::Validation(format!("Invalid JSON batch: {}", e)))?;
+    Ok(transfers
+        .into_iter()
+        .enumerate()
+        .map(|(index, transfer)| BatchTransferRow {
+            line: index + 1,
+            transfer,
+        })
+        .collect())
+}
+
+// Parses an ISO 20022 pain.001 credit transfer initiation. Line numbers are the
+// 1-based positions of the transactions in the document.
+pub fn parse_transfers_pain001(body: &[u8]) -> Result<Vec<BatchTransferRow>, BankError> {
+    let xml = std::str::from_utf8(body)
+        .map_err(|_| BankError::Validation("pain.001 document must be UTF-8".to_string()))?;
+    parse_pain001(xml).map_err(|e| BankError::from(anyhow::Error::from(e)))
+}
+
+#[instrument(skip(state, user, req, body), fields(mode = ?query.mode))]
+pub async fn batch_transfer(
+    state: web::Data<AppState>,
+    user: AuthenticatedUser,
+    req: HttpRequest,
+    query: web::Query<BatchQuery>,
+    body: web::Bytes,
+) -> Result<HttpResponse, BankError> {
+    let mode = query.into_inner().mode;
+    let rows = match req.content_type() {
+        "text/csv" => parse_transfers_csv(&body)?,
+        "application/xml" | "text/xml" => parse_transfers_pain001(&body)?,
+        _ => parse_transfers_json(&body)?,
+    };
+    info!(rows = rows.len(), "Processing batch transfer");
+
+    // A batch is stepped up on its total, so splitting a large payment into rows
+    // below the threshold still needs a second factor
+    let total = rows.iter().fold(0u64, |total, row| {
+        total.saturating_add(row.transfer.amount.inner())
+    });
+    state
+        .auth_service
+        .authorize_transfer(&user.user_id, Amount::new(total), two_factor_code(&req))
+        .await?;
+
+    let report = state
+        .service
+        .batch_transfer(&user.user_id, rows, mode)
+        .await
+        .map_err(|e| {
+            error!(error = %e, "Failed to process batch transfer");
+            BankError::from(e)
+        })?;
+
+    if mode == BatchMode::AllOrNothing && report.failed > 0 {
+        warn!(failed = report.failed, "Batch transfer rejected");
+        return Ok(HttpResponse::UnprocessableEntity().json(report));
+    }
+
+    info!(
+        succeeded = report.succeeded,
+        failed = report.failed,
+        "Batch transfer processed"
+    );
+    Ok(HttpResponse::Ok().json(report))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_transfers_csv_reports_line_numbers() {
+        let csv = b"from_account_id,to_account_id,amount\n1,2,100\n2, 3 ,50\n";
+        let rows = parse_transfers_csv(csv).unwrap();
+
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[0].line, 2);
+        assert_eq!(rows[0].transfer.amount.inner(), 100);
+        assert_eq!(rows[1].line, 3);
+        assert_eq!(rows[1].transfer.to_account_id, 3);
+    }
+
+    #[test]
+    fn test_parse_transfers_csv_rejects_malformed_rows() {
+        let csv = b"from_account_id,to_account_id,amount\n1,2,100\n1,2,abc\n";
+        let result = parse_transfers_csv(csv);
+
+        match result {
+            Err(BankError::Validation(msg)) => assert!(msg.contains("line 3")),
+            _ => panic!("Expected Validation error"),
+        }
+    }
+
+    #[test]
+    fn test_parse_transfers_json_numbers_rows_from_one() {
+        let json = br#"[{"from_account_id":1,"to_account_id":2,"amount":10}]"#;
+        let rows = parse_transfers_json(json).unwrap();
+
+        assert_eq!(rows.len(), 1);
+        assert_eq!(rows[0].line, 1);
+    }
+}
@@ -0,0 +1,77 @@
+// Saved payees of the caller
+use crate::domain::beneficiary::{CreateBeneficiary, UpdateBeneficiary};
+use crate::presentation::handlers::{AppState, BankError};
+use crate::presentation::middleware::AuthenticatedUser;
+use actix_web::{HttpResponse, web};
+use tracing::{error, info, instrument};
+
+#[instrument(skip(state, user))]
+pub async fn list_beneficiaries(
+    state: web::Data<AppState>,
+    user: AuthenticatedUser,
+) -> Result<HttpResponse, BankError> {
+    let beneficiaries = state.service.list_beneficiaries(&user.user_id).await?;
+    Ok(HttpResponse::Ok().json(beneficiaries))
+}
+
+#[instrument(skip(state, user, req), fields(account_id = req.account_id))]
+pub async fn add_beneficiary(
+    state: web::Data<AppState>,
+    user: AuthenticatedUser,
+    req: web::Json<CreateBeneficiary>,
+) -> Result<HttpResponse, BankError> {
+    info!("Adding beneficiary");
+    let beneficiary = state
+        .service
+        .add_beneficiary(&user.user_id, req.into_inner())
+        .await
+        .map_err(|e| {
+            error!(error = %e, "Failed to add beneficiary");
+            BankError::from(e)
+        })?;
+    Ok(HttpResponse::Created().json(beneficiary))
+}
+
+#[instrument(skip(state, user), fields(beneficiary_id = %*path))]
+pub async fn get_beneficiary(
+    state: web::Data<AppState>,
+    user: AuthenticatedUser,
+    path: web::Path<String>,
+) -> Result<HttpResponse, BankError> {
+    let beneficiary = state
+        .service
+        .get_beneficiary(&user.user_id, &path.into_inner())
+        .await?;
+    Ok(HttpResponse::Ok().json(beneficiary))
+}
+
+#[instrument(skip(state, user, req), fields(beneficiary_id = %*path))]
+pub async fn update_beneficiary(
+    state: web::Data<AppState>,
+    user: AuthenticatedUser,
+    path: web::Path<String>,
+    req: web::Json<UpdateBeneficiary>,
+) -> Result<HttpResponse, BankError> {
+    let beneficiary = state
+        .service
+        .update_beneficiary(&user.user_id, &path.into_inner(), req.into_inner())
+        .await
+        .map_err(|e| {
+            error!(error = %e, "Failed to update beneficiary");
+            BankError::from(e)
+        })?;
+    Ok(HttpResponse::Ok().json(beneficiary))
+}
+
+#[instrument(skip(state, user), fields(beneficiary_id = %*path))]
+pub async fn delete_beneficiary(
+    state: web::Data<AppState>,
+    user: AuthenticatedUser,
+    path: web::Path<String>,
+) -> Result<HttpResponse, BankError> {
+    state
+        .service
+        .delete_beneficiary(&user.user_id, &path.into_inner())
+        .await?;
+    Ok(HttpResponse::NoContent().finish())
+}
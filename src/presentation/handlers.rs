@@ -2,17 +2,21 @@ use crate::application::auth_service::AuthService;
 use crate::application::service::BankService;
 use crate::data::memory::InMemoryAccountRepository;
 use crate::data::user_repository::InMemoryUserRepository;
-use crate::domain::error::DomainError;
+use crate::domain::error::{DomainError, FieldError};
 use crate::domain::models::{CreateAccount, Deposit, Transfer, Withdraw};
-use crate::presentation::middleware::AuthenticatedUser;
-use actix_web::{FromRequest, HttpMessage, HttpResponse, ResponseError, web};
-use chrono::Utc;
-use serde::Serialize;
+use crate::infrastructure::iso20022::write_camt053;
+use crate::presentation::middleware::{AuthenticatedUser, check_scope_declared};
+use actix_web::http::header::RETRY_AFTER;
+use actix_web::{FromRequest, HttpMessage, HttpRequest, HttpResponse, ResponseError, web};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
 use std::pin::Pin;
 use std::sync::Arc;
 use thiserror::Error;
 use tracing::{error, info, instrument, warn};
 
+pub const TWO_FACTOR_CODE_HEADER: &str = "X-Two-Factor-Code";
+
 // AppState holding the service
 pub struct AppState {
     pub service: BankService<InMemoryAccountRepository>,
@@ -31,12 +35,18 @@ struct ErrorResponse {
 pub enum BankError {
     #[error("Validation error: {0}")]
     Validation(String),
+    #[error("Validation error: {} rule(s) failed", .0.len())]
+    InvalidFields(Vec<FieldError>),
     #[error("Not found: {0}")]
     NotFound(String),
     #[error("Insufficient funds")]
     InsufficientFunds,
     #[error("Unauthorized: {0}")]
     Unauthorized(String),
+    #[error("Forbidden: {0}")]
+    Forbidden(String),
+    #[error("Too many attempts, retry after {0} seconds")]
+    TooManyAttempts(u64),
     #[error("Database error: {0}")]
     Database(String),
     #[error("Internal error: {0}")]
@@ -47,9 +57,12 @@ impl ResponseError for BankError {
     fn status_code(&self) -> actix_web::http::StatusCode {
         match self {
             BankError::Validation(_) => actix_web::http::StatusCode::BAD_REQUEST,
+            BankError::InvalidFields(_) => actix_web::http::StatusCode::BAD_REQUEST,
             BankError::NotFound(_) => actix_web::http::StatusCode::NOT_FOUND,
             BankError::InsufficientFunds => actix_web::http::StatusCode::BAD_REQUEST,
             BankError::Unauthorized(_) => actix_web::http::StatusCode::UNAUTHORIZED,
+            BankError::Forbidden(_) => actix_web::http::StatusCode::FORBIDDEN,
+            BankError::TooManyAttempts(_) => actix_web::http::StatusCode::TOO_MANY_REQUESTS,
             BankError::Database(_) => actix_web::http::StatusCode::INTERNAL_SERVER_ERROR,
             BankError::Internal(_) => actix_web::http::StatusCode::INTERNAL_SERVER_ERROR,
         }
@@ -61,16 +74,25 @@ impl ResponseError for BankError {
 
         let details = match self {
             BankError::Validation(msg) => serde_json::json!({ "message": msg }),
+            BankError::InvalidFields(errors) => serde_json::json!({
+                "message": "Request validation failed",
+                "errors": errors,
+            }),
             BankError::NotFound(msg) => serde_json::json!({ "message": msg }),
             BankError::InsufficientFunds => serde_json::json!({ "message": "Insufficient funds" }),
             BankError::Unauthorized(msg) => serde_json::json!({ "message": msg }),
+            BankError::Forbidden(msg) => serde_json::json!({ "message": msg }),
+            BankError::TooManyAttempts(retry_after) => serde_json::json!({
+                "message": "Too many failed attempts",
+                "retry_after": retry_after,
+            }),
             BankError::Database(msg) => serde_json::json!({ "message": msg }),
             BankError::Internal(msg) => serde_json::json!({ "message": msg }),
         };
 
         // Log error based on severity
         match self {
-            BankError::Validation(_) => {
+            BankError::Validation(_) | BankError::InvalidFields(_) => {
                 warn!(error = %error_msg, status = %status, "Validation error")
             }
             BankError::NotFound(_) => {
@@ -82,6 +104,12 @@ impl ResponseError for BankError {
             BankError::Unauthorized(_) => {
                 warn!(error = %error_msg, status = %status, "Unauthorized")
             }
+            BankError::Forbidden(_) => {
+                warn!(error = %error_msg, status = %status, "Forbidden")
+            }
+            BankError::TooManyAttempts(_) => {
+                warn!(error = %error_msg, status = %status, "Too many attempts")
+            }
             BankError::Database(_) => {
                 error!(error = %error_msg, status = %status, "Database error")
             }
@@ -95,7 +123,11 @@ impl ResponseError for BankError {
             details,
         };
 
-        HttpResponse::build(status).json(error_response)
+        let mut response = HttpResponse::build(status);
+        if let BankError::TooManyAttempts(retry_after) = self {
+            response.insert_header((RETRY_AFTER, retry_after.to_string()));
+        }
+        response.json(error_response)
     }
 }
 
@@ -108,27 +140,42 @@ impl From<anyhow::Error> for BankError {
             }
             Some(DomainError::InvalidAmount) => BankError::Validation("Invalid amount".to_string()),
             Some(DomainError::Validation(msg)) => BankError::Validation(msg.clone()),
+            Some(DomainError::InvalidFields(errors)) => BankError::InvalidFields(errors.clone()),
             Some(DomainError::NotFound(msg)) => BankError::NotFound(msg.clone()),
             Some(DomainError::Unauthorized(msg)) => BankError::Unauthorized(msg.clone()),
+            Some(DomainError::Forbidden(msg)) => BankError::Forbidden(msg.clone()),
+            Some(DomainError::TooManyAttempts(retry_after)) => {
+                BankError::TooManyAttempts(*retry_after)
+            }
             Some(DomainError::Internal(msg)) => BankError::Internal(msg.clone()),
             None => BankError::Database(err.to_string()),
         }
     }
 }
 
+// Second factor for step-up checks, sent alongside the bearer token
+pub fn two_factor_code(req: &HttpRequest) -> Option<&str> {
+    req.headers()
+        .get(TWO_FACTOR_CODE_HEADER)
+        .and_then(|h| h.to_str().ok())
+}
+
 // AuthenticatedUser extractor
 impl FromRequest for AuthenticatedUser {
-    type Error = BankError;
+    type Error = actix_web::Error;
     type Future = Pin<Box<dyn std::future::Future<Output = Result<Self, Self::Error>>>>;
 
     fn from_request(
         req: &actix_web::HttpRequest,
         _payload: &mut actix_web::dev::Payload,
     ) -> Self::Future {
-        let user = req.extensions().get::<AuthenticatedUser>().cloned();
-        Box::pin(async move {
-            user.ok_or_else(|| BankError::Unauthorized("User not authenticated".to_string()))
-        })
+        let user = req
+            .extensions()
+            .get::<AuthenticatedUser>()
+            .cloned()
+            .ok_or_else(|| BankError::Unauthorized("User not authenticated".to_string()).into())
+            .and_then(|user| check_scope_declared(req, &user).map(|()| user));
+        Box::pin(async move { user })
     }
 }
 
@@ -150,15 +197,16 @@ pub async fn health_check() -> HttpResponse {
     HttpResponse::Ok().json(response)
 }
 
-#[instrument(skip(state), fields(account_id))]
+#[instrument(skip(state, user), fields(account_id, user_id = %user.user_id))]
 pub async fn create_account(
     state: web::Data<AppState>,
+    user: AuthenticatedUser,
     req: web::Json<CreateAccount>,
 ) -> Result<HttpResponse, BankError> {
     info!(name = %req.name, "Creating new account");
     let account = state
         .service
-        .create_account(req.into_inner())
+        .create_account(&user.user_id, req.into_inner())
         .await
         .map_err(|e| {
             error!(error = %e, "Failed to create account");
@@ -173,17 +221,22 @@ pub async fn create_account(
     Ok(HttpResponse::Created().json(account))
 }
 
-#[instrument(skip(state), fields(account_id = %*path))]
+#[instrument(skip(state, user), fields(account_id = %*path))]
 pub async fn get_account(
     state: web::Data<AppState>,
+    user: AuthenticatedUser,
     path: web::Path<u32>,
 ) -> Result<HttpResponse, BankError> {
     let account_id = path.into_inner();
     info!(account_id = account_id, "Getting account balance");
-    let account = state.service.get_account(account_id).await.map_err(|e| {
-        error!(account_id = account_id, error = %e, "Failed to get account");
-        e
-    })?;
+    let account = state
+        .service
+        .get_account(&user.user_id, account_id)
+        .await
+        .map_err(|e| {
+            error!(account_id = account_id, error = %e, "Failed to get account");
+            e
+        })?;
     info!(
         account_id = account.id,
         balance = account.balance.inner(),
@@ -192,13 +245,49 @@ pub async fn get_account(
     Ok(HttpResponse::Ok().json(account))
 }
 
-#[instrument(skip(state), fields(account_id = %*path, amount))]
+#[derive(Debug, Deserialize)]
+pub struct StatementQuery {
+    pub from: Option<DateTime<Utc>>,
+    pub to: Option<DateTime<Utc>>,
+}
+
+// Exports the account statement as an ISO 20022 camt.053 document
+#[instrument(skip(state, user), fields(account_id = %*path))]
+pub async fn get_statement(
+    state: web::Data<AppState>,
+    user: AuthenticatedUser,
+    path: web::Path<u32>,
+    query: web::Query<StatementQuery>,
+) -> Result<HttpResponse, BankError> {
+    let account_id = path.into_inner();
+    let query = query.into_inner();
+    info!(account_id = account_id, "Exporting account statement");
+    let statement = state
+        .service
+        .get_statement(&user.user_id, account_id, query.from, query.to)
+        .await
+        .map_err(|e| {
+            error!(account_id = account_id, error = %e, "Failed to build statement");
+            e
+        })?;
+    let xml = write_camt053(&statement).map_err(|e| BankError::from(anyhow::Error::from(e)))?;
+    info!(
+        account_id = account_id,
+        transactions = statement.transactions.len(),
+        "Account statement exported"
+    );
+    Ok(HttpResponse::Ok().content_type("application/xml").body(xml))
+}
+
+#[instrument(skip(state, user), fields(account_id = %*path, amount))]
 pub async fn deposit(
     state: web::Data<AppState>,
+    user: AuthenticatedUser,
     path: web::Path<u32>,
     req: web::Json<Deposit>,
 ) -> Result<HttpResponse, BankError> {
     let account_id = path.into_inner();
+    let req = req.into_inner();
     let amount = req.amount.inner();
     tracing::Span::current().record("amount", amount);
     info!(
@@ -208,7 +297,7 @@ pub async fn deposit(
     );
     let account = state
         .service
-        .deposit(account_id, req.into_inner().amount)
+        .deposit(&user.user_id, account_id, req.amount, req.details)
         .await
         .map_err(|e| {
             error!(account_id = account_id, amount = amount, error = %e, "Failed to deposit");
@@ -222,13 +311,15 @@ pub async fn deposit(
     Ok(HttpResponse::Ok().json(account))
 }
 
-#[instrument(skip(state), fields(account_id = %*path, amount))]
+#[instrument(skip(state, user), fields(account_id = %*path, amount))]
 pub async fn withdraw(
     state: web::Data<AppState>,
+    user: AuthenticatedUser,
     path: web::Path<u32>,
     req: web::Json<Withdraw>,
 ) -> Result<HttpResponse, BankError> {
     let account_id = path.into_inner();
+    let req = req.into_inner();
     let amount = req.amount.inner();
     tracing::Span::current().record("amount", amount);
     info!(
@@ -238,7 +329,7 @@ pub async fn withdraw(
     );
     let account = state
         .service
-        .withdraw(account_id, req.into_inner().amount)
+        .withdraw(&user.user_id, account_id, req.amount, req.details)
         .await
         .map_err(|e| {
             error!(account_id = account_id, amount = amount, error = %e, "Failed to withdraw");
@@ -252,9 +343,14 @@ pub async fn withdraw(
     Ok(HttpResponse::Ok().json(account))
 }
 
-#[instrument(skip(state), fields(from_account_id, to_account_id, amount))]
+#[instrument(
+    skip(state, user, http_req),
+    fields(from_account_id, to_account_id, amount)
+)]
 pub async fn transfer(
     state: web::Data<AppState>,
+    user: AuthenticatedUser,
+    http_req: HttpRequest,
     req: web::Json<Transfer>,
 ) -> Result<HttpResponse, BankError> {
     let transfer_req = req.into_inner();
@@ -271,21 +367,34 @@ pub async fn transfer(
         amount = amount,
         "Processing transfer"
     );
-    state.service.transfer(transfer_req).await.map_err(|e| {
-        error!(
-            from_account_id = from_id,
-            to_account_id = to_id,
-            amount = amount,
-            error = %e,
-            "Failed to transfer"
-        );
-        e
-    })?;
+    state
+        .auth_service
+        .authorize_transfer(
+            &user.user_id,
+            transfer_req.amount,
+            two_factor_code(&http_req),
+        )
+        .await?;
+    let receipt = state
+        .service
+        .transfer(&user.user_id, transfer_req)
+        .await
+        .map_err(|e| {
+            error!(
+                from_account_id = from_id,
+                to_account_id = to_id,
+                amount = amount,
+                error = %e,
+                "Failed to transfer"
+            );
+            e
+        })?;
     info!(
+        transfer_id = %receipt.transfer_id,
         from_account_id = from_id,
-        to_account_id = to_id,
+        to_account_id = receipt.to_account_id,
         amount = amount,
         "Transfer completed successfully"
     );
-    Ok(HttpResponse::Ok().finish())
+    Ok(HttpResponse::Ok().json(receipt))
 }
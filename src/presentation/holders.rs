@@ -0,0 +1,84 @@
+// Joint accounts: the owner gives other users access to an account
+use crate::domain::models::{AccountHolder, AddHolderRequest};
+use crate::presentation::handlers::{AppState, BankError};
+use crate::presentation::middleware::AuthenticatedUser;
+use actix_web::{HttpResponse, web};
+use tracing::{error, info, instrument};
+
+#[instrument(skip(state, user), fields(account_id = %*path))]
+pub async fn list_holders(
+    state: web::Data<AppState>,
+    user: AuthenticatedUser,
+    path: web::Path<u32>,
+) -> Result<HttpResponse, BankError> {
+    let account_id = path.into_inner();
+
+    let holders = state
+        .service
+        .list_holders(&user.user_id, account_id)
+        .await
+        .map_err(|e| {
+            error!(account_id = account_id, error = %e, "Failed to list holders");
+            BankError::from(e)
+        })?;
+
+    Ok(HttpResponse::Ok().json(holders))
+}
+
+#[instrument(skip(state, user, req), fields(account_id = %*path))]
+pub async fn add_holder(
+    state: web::Data<AppState>,
+    user: AuthenticatedUser,
+    path: web::Path<u32>,
+    req: web::Json<AddHolderRequest>,
+) -> Result<HttpResponse, BankError> {
+    let account_id = path.into_inner();
+    let req = req.into_inner();
+    info!(account_id = account_id, permission = ?req.permission, "Adding account holder");
+
+    let invitee = state
+        .auth_service
+        .get_user_by_email(&req.email)
+        .await
+        .map_err(|e| {
+            error!(account_id = account_id, error = %e, "Invited user not found");
+            BankError::from(e)
+        })?;
+    let holders = state
+        .service
+        .add_holder(
+            &user.user_id,
+            account_id,
+            AccountHolder {
+                user_id: invitee.id,
+                permission: req.permission,
+            },
+        )
+        .await
+        .map_err(|e| {
+            error!(account_id = account_id, error = %e, "Failed to add holder");
+            BankError::from(e)
+        })?;
+
+    Ok(HttpResponse::Ok().json(holders))
+}
+
+#[instrument(skip(state, user), fields(account_id = %path.0, holder_id = %path.1))]
+pub async fn remove_holder(
+    state: web::Data<AppState>,
+    user: AuthenticatedUser,
+    path: web::Path<(u32, String)>,
+) -> Result<HttpResponse, BankError> {
+    let (account_id, holder_id) = path.into_inner();
+
+    state
+        .service
+        .remove_holder(&user.user_id, account_id, &holder_id)
+        .await
+        .map_err(|e| {
+            error!(account_id = account_id, error = %e, "Failed to remove holder");
+            BankError::from(e)
+        })?;
+
+    Ok(HttpResponse::NoContent().finish())
+}
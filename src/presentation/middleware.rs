@@ -1,14 +1,21 @@
 use actix_web::{
-    Error, HttpMessage,
+    Error, HttpMessage, HttpRequest,
     dev::{Service, ServiceRequest, ServiceResponse, Transform},
     http::header::{HeaderName, HeaderValue},
 };
 
+use crate::domain::api_key::ApiKeyAuthenticator;
+use crate::domain::repository::RevocationStore;
+use crate::domain::token::Scope;
+use crate::domain::user::Role;
+use crate::infrastructure::jwt_keys::JwtKeySet;
+use crate::infrastructure::security::verify_access_token;
 use serde_json;
 use std::{
     future::{Ready, ready},
     pin::Pin,
     rc::Rc,
+    sync::Arc,
     task::{Context, Poll},
     time::Instant,
 };
@@ -19,6 +26,11 @@ use uuid::Uuid;
 #[derive(Clone, Debug)]
 pub struct AuthenticatedUser {
     pub user_id: String,
+    pub role: Role,
+    // `None` for unrestricted tokens and API keys without scopes
+    pub scopes: Option<Vec<Scope>>,
+    // Set when the request was authenticated with an API key rather than a token
+    pub api_key_id: Option<String>,
 }
 
 // Request ID Middleware
@@ -195,18 +207,54 @@ where
     }
 }
 
+enum Credentials {
+    Bearer(String),
+    ApiKey(String),
+}
+
 // JWT Authentication Middleware
 pub struct JwtAuthMiddleware {
-    jwt_secret: String,
+    jwt_keys: Arc<JwtKeySet>,
+    revocation_store: Option<Arc<dyn RevocationStore>>,
+    api_key_authenticator: Option<Arc<dyn ApiKeyAuthenticator>>,
 }
 
 impl JwtAuthMiddleware {
     pub fn new(jwt_secret: String) -> Self {
-        Self { jwt_secret }
+        Self {
+            jwt_keys: Arc::new(JwtKeySet::from_secret(&jwt_secret)),
+            revocation_store: None,
+            api_key_authenticator: None,
+        }
+    }
+
+    // Replaces the key set built from the secret given to `new`. Must be the set used by
+    // `AuthService`.
+    pub fn with_jwt_keys(mut self, jwt_keys: Arc<JwtKeySet>) -> Self {
+        self.jwt_keys = jwt_keys;
+        self
+    }
+
+    // Rejects tokens revoked through logout. Must be the store used by `AuthService`.
+    pub fn with_revocation_store(mut self, revocation_store: Arc<dyn RevocationStore>) -> Self {
+        self.revocation_store = Some(revocation_store);
+        self
     }
 
+    // Also accepts `Authorization: ApiKey <key>`. API keys with scopes are restricted by
+    // `RequireScope` like scoped tokens.
+    pub fn with_api_key_authenticator(
+        mut self,
+        api_key_authenticator: Arc<dyn ApiKeyAuthenticator>,
+    ) -> Self {
+        self.api_key_authenticator = Some(api_key_authenticator);
+        self
+    }
+
+    // Auth routes authenticate callers themselves: by password, refresh token or,
+    // for /api/auth/token, by client credentials. /.well-known/ only serves public keys.
     fn is_public_route(path: &str) -> bool {
-        path == "/api/health" || path.starts_with("/api/auth/")
+        path == "/api/health" || path.starts_with("/api/auth/") || path.starts_with("/.well-known/")
     }
 }
 
@@ -224,14 +272,18 @@ where
     fn new_transform(&self, service: S) -> Self::Future {
         ready(Ok(JwtAuthMiddlewareService {
             service: Rc::new(service),
-            jwt_secret: self.jwt_secret.clone(),
+            jwt_keys: self.jwt_keys.clone(),
+            revocation_store: self.revocation_store.clone(),
+            api_key_authenticator: self.api_key_authenticator.clone(),
         }))
     }
 }
 
 pub struct JwtAuthMiddlewareService<S> {
     service: Rc<S>,
-    jwt_secret: String,
+    jwt_keys: Arc<JwtKeySet>,
+    revocation_store: Option<Arc<dyn RevocationStore>>,
+    api_key_authenticator: Option<Arc<dyn ApiKeyAuthenticator>>,
 }
 
 impl<S, B> Service<ServiceRequest> for JwtAuthMiddlewareService<S>
@@ -249,7 +301,7 @@ where
 
     fn call(&self, req: ServiceRequest) -> Self::Future {
         let service = self.service.clone();
-        let jwt_secret = self.jwt_secret.clone();
+        let revocation_store = self.revocation_store.clone();
         let path = req.path().to_string();
 
         // Check if route is public
@@ -260,48 +312,294 @@ where
         }
 
         // Extract Authorization header
-        let auth_header = req
+        let header = req
             .headers()
             .get("Authorization")
-            .and_then(|h| h.to_str().ok())
-            .and_then(|s| s.strip_prefix("Bearer "))
-            .map(|s| s.to_string());
-
-        let token = match auth_header {
-            Some(t) => t,
-            None => {
-                warn!(path = %path, "Missing Authorization header");
-                return Box::pin(async move {
-                    Err(actix_web::error::ErrorUnauthorized(
-                        serde_json::json!({"error": "missing bearer"}).to_string(),
-                    ))
-                });
+            .and_then(|h| h.to_str().ok());
+        let credentials = match header {
+            Some(h) if h.starts_with("Bearer ") => {
+                Some(Credentials::Bearer(h["Bearer ".len()..].to_string()))
             }
+            Some(h) if h.starts_with("ApiKey ") && self.api_key_authenticator.is_some() => {
+                Some(Credentials::ApiKey(h["ApiKey ".len()..].trim().to_string()))
+            }
+            _ => None,
         };
 
-        // Validate token
-        let user_id = match crate::infrastructure::security::validate_token(&token, &jwt_secret) {
-            Ok(uid) => uid,
-            Err(e) => {
-                warn!(path = %path, error = %e, "Invalid JWT token");
-                return Box::pin(async move {
-                    Err(actix_web::error::ErrorUnauthorized(
-                        serde_json::json!({"error": "invalid token"}).to_string(),
-                    ))
-                });
-            }
+        let Some(credentials) = credentials else {
+            warn!(path = %path, "Missing Authorization header");
+            return Box::pin(async move {
+                Err(actix_web::error::ErrorUnauthorized(
+                    serde_json::json!({"error": "missing bearer"}).to_string(),
+                ))
+            });
         };
 
-        trace!(user_id = %user_id, path = %path, "JWT token validated");
+        let jwt_keys = self.jwt_keys.clone();
+        let api_key_authenticator = self.api_key_authenticator.clone();
+
+        Box::pin(async move {
+            let user = match credentials {
+                Credentials::Bearer(token) => {
+                    // Validate token
+                    let claims = verify_access_token(&token, &jwt_keys).map_err(|e| {
+                        warn!(path = %path, error = %e, "Invalid JWT token");
+                        actix_web::error::ErrorUnauthorized(
+                            serde_json::json!({"error": "invalid token"}).to_string(),
+                        )
+                    })?;
+                    trace!(user_id = %claims.user_id, path = %path, "JWT token validated");
+
+                    if let Some(store) = revocation_store {
+                        let revoked = store
+                            .is_access_token_revoked(
+                                &claims.user_id,
+                                &claims.jti,
+                                claims.session_id.as_deref(),
+                                claims.issued_at,
+                            )
+                            .await
+                            .map_err(actix_web::error::ErrorInternalServerError)?;
+                        if revoked {
+                            warn!(user_id = %claims.user_id, jti = %claims.jti, path = %path, "Revoked JWT token");
+                            return Err(actix_web::error::ErrorUnauthorized(
+                                serde_json::json!({"error": "token revoked"}).to_string(),
+                            ));
+                        }
+                    }
+
+                    AuthenticatedUser {
+                        user_id: claims.user_id,
+                        role: claims.role,
+                        scopes: claims.scopes,
+                        api_key_id: None,
+                    }
+                }
+                Credentials::ApiKey(key) => {
+                    let authenticator = api_key_authenticator
+                        .expect("API keys are only accepted with an authenticator");
+                    let identity = authenticator
+                        .authenticate_api_key(&key)
+                        .await
+                        .map_err(actix_web::error::ErrorInternalServerError)?
+                        .ok_or_else(|| {
+                            warn!(path = %path, "Invalid API key");
+                            actix_web::error::ErrorUnauthorized(
+                                serde_json::json!({"error": "invalid api key"}).to_string(),
+                            )
+                        })?;
+                    trace!(user_id = %identity.user_id, key_id = %identity.key_id, path = %path, "API key validated");
+
+                    AuthenticatedUser {
+                        user_id: identity.user_id,
+                        role: identity.role,
+                        scopes: identity.scopes,
+                        api_key_id: Some(identity.key_id),
+                    }
+                }
+            };
+
+            debug!(user_id = %user.user_id, role = %user.role, path = %path, "User authenticated");
+
+            // Store the user in extensions BEFORE calling the service
+            req.extensions_mut().insert(user);
+
+            service.call(req).await
+        })
+    }
+}
 
-        // Store user_id in extensions BEFORE calling the service
-        req.extensions_mut().insert(AuthenticatedUser {
-            user_id: user_id.clone(),
-        });
+// Role guard for individual routes or scopes, e.g.
+// `web::get().to(handler).wrap(RequireRole::new(&[Role::Admin]))`.
+// Must run inside `JwtAuthMiddleware`, which provides the authenticated user. Closed to
+// scoped callers unless a `RequireScope` wrapped around it let them through.
+pub struct RequireRole {
+    roles: Rc<[Role]>,
+}
 
-        debug!(user_id = %user_id, path = %path, "User authenticated");
+impl RequireRole {
+    pub fn new(roles: &[Role]) -> Self {
+        Self {
+            roles: Rc::from(roles),
+        }
+    }
+}
 
-        let fut = service.call(req);
-        Box::pin(fut)
+impl<S, B> Transform<S, ServiceRequest> for RequireRole
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type InitError = ();
+    type Transform = RequireRoleService<S>;
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(RequireRoleService {
+            service: Rc::new(service),
+            roles: self.roles.clone(),
+        }))
+    }
+}
+
+pub struct RequireRoleService<S> {
+    service: Rc<S>,
+    roles: Rc<[Role]>,
+}
+
+impl<S, B> Service<ServiceRequest> for RequireRoleService<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Future = Pin<Box<dyn std::future::Future<Output = Result<Self::Response, Self::Error>>>>;
+
+    fn poll_ready(&self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.service.poll_ready(cx)
+    }
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        let user = req.extensions().get::<AuthenticatedUser>().cloned();
+        let path = req.path().to_string();
+
+        let Some(user) = user else {
+            warn!(path = %path, "Role check without authenticated user");
+            return Box::pin(async move {
+                Err(actix_web::error::ErrorUnauthorized(
+                    serde_json::json!({"error": "missing bearer"}).to_string(),
+                ))
+            });
+        };
+        if let Err(e) = check_scope_declared(req.request(), &user) {
+            return Box::pin(async move { Err(e) });
+        }
+
+        if !self.roles.contains(&user.role) {
+            warn!(user_id = %user.user_id, role = %user.role, path = %path, "Insufficient role");
+            return Box::pin(async move {
+                Err(actix_web::error::ErrorForbidden(
+                    serde_json::json!({"error": "forbidden"}).to_string(),
+                ))
+            });
+        }
+
+        trace!(user_id = %user.user_id, role = %user.role, path = %path, "Role check passed");
+        Box::pin(self.service.call(req))
+    }
+}
+
+// Set by `RequireScope` once the caller's scopes allow the route
+#[derive(Clone, Copy, Debug)]
+struct ScopeChecked;
+
+// Scoped callers only reach routes that declare the scope they need with `RequireScope`;
+// the `AuthenticatedUser` extractor and `RequireRole` refuse them everywhere else
+pub(crate) fn check_scope_declared(
+    req: &HttpRequest,
+    user: &AuthenticatedUser,
+) -> Result<(), Error> {
+    if user.scopes.is_none() || req.extensions().contains::<ScopeChecked>() {
+        return Ok(());
+    }
+    warn!(
+        user_id = %user.user_id,
+        path = %req.path(),
+        "Scoped caller on a route without a scope requirement"
+    );
+    Err(actix_web::error::ErrorForbidden(
+        serde_json::json!({"error": "insufficient_scope"}).to_string(),
+    ))
+}
+
+// Scope that scoped tokens and API keys need for a route, declared where the route is
+// registered, e.g. `web::post().to(transfer).wrap(RequireScope::new(Scope::PaymentsWrite))`.
+// Unscoped callers are not affected. Must run inside `JwtAuthMiddleware`; on routes that
+// also require a role, wrap it around `RequireRole`.
+pub struct RequireScope {
+    scope: Scope,
+}
+
+impl RequireScope {
+    pub fn new(scope: Scope) -> Self {
+        Self { scope }
+    }
+}
+
+impl<S, B> Transform<S, ServiceRequest> for RequireScope
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type InitError = ();
+    type Transform = RequireScopeService<S>;
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(RequireScopeService {
+            service: Rc::new(service),
+            scope: self.scope,
+        }))
+    }
+}
+
+pub struct RequireScopeService<S> {
+    service: Rc<S>,
+    scope: Scope,
+}
+
+impl<S, B> Service<ServiceRequest> for RequireScopeService<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Future = Pin<Box<dyn std::future::Future<Output = Result<Self::Response, Self::Error>>>>;
+
+    fn poll_ready(&self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.service.poll_ready(cx)
+    }
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        let user = req.extensions().get::<AuthenticatedUser>().cloned();
+        let path = req.path().to_string();
+
+        let Some(user) = user else {
+            warn!(path = %path, "Scope check without authenticated user");
+            return Box::pin(async move {
+                Err(actix_web::error::ErrorUnauthorized(
+                    serde_json::json!({"error": "missing bearer"}).to_string(),
+                ))
+            });
+        };
+
+        if let Some(scopes) = &user.scopes
+            && !scopes.contains(&self.scope)
+        {
+            warn!(
+                user_id = %user.user_id,
+                scopes = ?scopes,
+                required = %self.scope,
+                path = %path,
+                "Insufficient scope"
+            );
+            let body = serde_json::json!({
+                "error": "insufficient_scope",
+                "missing_scope": self.scope,
+            });
+            return Box::pin(
+                async move { Err(actix_web::error::ErrorForbidden(body.to_string())) },
+            );
+        }
+
+        trace!(user_id = %user.user_id, scope = %self.scope, path = %path, "Scope check passed");
+        req.extensions_mut().insert(ScopeChecked);
+        Box::pin(self.service.call(req))
     }
 }
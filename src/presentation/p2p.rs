@@ -0,0 +1,84 @@
+// Payments to other users by email
+use crate::domain::p2p::{ClaimTransferRequest, P2pPayment, PayByEmail};
+use crate::presentation::handlers::{AppState, BankError, two_factor_code};
+use crate::presentation::middleware::AuthenticatedUser;
+use actix_web::{HttpRequest, HttpResponse, web};
+use tracing::{error, info, instrument, warn};
+
+#[instrument(
+    skip(state, user, http_req, req),
+    fields(from_account_id = req.from_account_id, amount = req.amount.inner())
+)]
+pub async fn pay_by_email(
+    state: web::Data<AppState>,
+    user: AuthenticatedUser,
+    http_req: HttpRequest,
+    req: web::Json<PayByEmail>,
+) -> Result<HttpResponse, BankError> {
+    let req = req.into_inner();
+    info!("Processing payment by email");
+    state
+        .auth_service
+        .authorize_transfer(&user.user_id, req.amount, two_factor_code(&http_req))
+        .await?;
+    let payee = state.auth_service.find_payee(&req.email).await?;
+    let payment = state
+        .service
+        .pay_by_email(&user.user_id, req, payee.as_ref().map(|u| u.id.as_str()))
+        .await
+        .map_err(|e| {
+            error!(error = %e, "Failed to pay by email");
+            BankError::from(e)
+        })?;
+
+    match &payment {
+        P2pPayment::Completed { .. } => Ok(HttpResponse::Ok().json(payment)),
+        P2pPayment::Pending { transfer } => {
+            // The money is on its way either way; the recipient can still find it later
+            if let Err(e) = state.auth_service.notify_claimable_transfer(transfer).await {
+                warn!(error = %e, "Failed to notify the recipient of a claimable transfer");
+            }
+            Ok(HttpResponse::Accepted().json(payment))
+        }
+    }
+}
+
+#[instrument(skip(state, user))]
+pub async fn list_claimable_transfers(
+    state: web::Data<AppState>,
+    user: AuthenticatedUser,
+) -> Result<HttpResponse, BankError> {
+    let email = state.auth_service.verified_email(&user.user_id).await?;
+    let transfers = state
+        .service
+        .list_claimable_transfers(&user.user_id, email.as_deref())
+        .await?;
+    Ok(HttpResponse::Ok().json(transfers))
+}
+
+#[instrument(skip(state, user, req), fields(transfer_id = %*path))]
+pub async fn claim_transfer(
+    state: web::Data<AppState>,
+    user: AuthenticatedUser,
+    path: web::Path<String>,
+    req: web::Json<ClaimTransferRequest>,
+) -> Result<HttpResponse, BankError> {
+    let transfer_id = path.into_inner();
+    info!("Claiming transfer");
+    let email = state
+        .auth_service
+        .verified_email(&user.user_id)
+        .await?
+        .ok_or_else(|| {
+            BankError::Forbidden("Verify your email to claim money sent to it".to_string())
+        })?;
+    let transfer = state
+        .service
+        .claim_transfer(&user.user_id, &email, &transfer_id, req.account_id)
+        .await
+        .map_err(|e| {
+            error!(error = %e, "Failed to claim transfer");
+            BankError::from(e)
+        })?;
+    Ok(HttpResponse::Ok().json(transfer))
+}
@@ -0,0 +1,104 @@
+// Requests for money between users
+use crate::domain::payment_request::{CreatePaymentRequest, PayPaymentRequest};
+use crate::presentation::handlers::{AppState, BankError, two_factor_code};
+use crate::presentation::middleware::AuthenticatedUser;
+use actix_web::{HttpRequest, HttpResponse, web};
+use tracing::{error, info, instrument};
+
+#[instrument(
+    skip(state, user, req),
+    fields(to_account_id = req.to_account_id, amount = req.amount.inner())
+)]
+pub async fn create_payment_request(
+    state: web::Data<AppState>,
+    user: AuthenticatedUser,
+    req: web::Json<CreatePaymentRequest>,
+) -> Result<HttpResponse, BankError> {
+    let req = req.into_inner();
+    info!("Creating payment request");
+    let payer = state
+        .auth_service
+        .find_payee(&req.payer_email)
+        .await?
+        .ok_or_else(|| BankError::NotFound("No user with this email".to_string()))?;
+    let request = state
+        .service
+        .create_payment_request(&user.user_id, &payer.id, req)
+        .await
+        .map_err(|e| {
+            error!(error = %e, "Failed to create payment request");
+            BankError::from(e)
+        })?;
+    Ok(HttpResponse::Created().json(request))
+}
+
+#[instrument(skip(state, user))]
+pub async fn list_payment_requests(
+    state: web::Data<AppState>,
+    user: AuthenticatedUser,
+) -> Result<HttpResponse, BankError> {
+    let requests = state.service.list_payment_requests(&user.user_id).await?;
+    Ok(HttpResponse::Ok().json(requests))
+}
+
+#[instrument(skip(state, user), fields(payment_request_id = %*path))]
+pub async fn get_payment_request(
+    state: web::Data<AppState>,
+    user: AuthenticatedUser,
+    path: web::Path<String>,
+) -> Result<HttpResponse, BankError> {
+    let request = state
+        .service
+        .get_payment_request(&user.user_id, &path.into_inner())
+        .await?;
+    Ok(HttpResponse::Ok().json(request))
+}
+
+#[instrument(
+    skip(state, user, http_req, req),
+    fields(payment_request_id = %*path, from_account_id = req.from_account_id)
+)]
+pub async fn pay_payment_request(
+    state: web::Data<AppState>,
+    user: AuthenticatedUser,
+    http_req: HttpRequest,
+    path: web::Path<String>,
+    req: web::Json<PayPaymentRequest>,
+) -> Result<HttpResponse, BankError> {
+    let id = path.into_inner();
+    info!("Paying payment request");
+    let request = state
+        .service
+        .get_payment_request(&user.user_id, &id)
+        .await?;
+    state
+        .auth_service
+        .authorize_transfer(&user.user_id, request.amount, two_factor_code(&http_req))
+        .await?;
+    let request = state
+        .service
+        .pay_payment_request(&user.user_id, &id, req.from_account_id)
+        .await
+        .map_err(|e| {
+            error!(error = %e, "Failed to pay payment request");
+            BankError::from(e)
+        })?;
+    Ok(HttpResponse::Ok().json(request))
+}
+
+#[instrument(skip(state, user), fields(payment_request_id = %*path))]
+pub async fn decline_payment_request(
+    state: web::Data<AppState>,
+    user: AuthenticatedUser,
+    path: web::Path<String>,
+) -> Result<HttpResponse, BankError> {
+    let request = state
+        .service
+        .decline_payment_request(&user.user_id, &path.into_inner())
+        .await
+        .map_err(|e| {
+            error!(error = %e, "Failed to decline payment request");
+            BankError::from(e)
+        })?;
+    Ok(HttpResponse::Ok().json(request))
+}
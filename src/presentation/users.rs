@@ -0,0 +1,181 @@
+// Endpoints of the signed-in user about themselves
+use crate::domain::models::AccountExport;
+use crate::domain::user::{
+    DeleteUserRequest, KycStatus, Profile, Role, UpdateProfileRequest, User, UserExport, UserStatus,
+};
+use crate::presentation::handlers::{AppState, BankError};
+use crate::presentation::middleware::AuthenticatedUser;
+use actix_web::http::header::{ContentDisposition, DispositionParam, DispositionType};
+use actix_web::{HttpResponse, web};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use tracing::{error, info, instrument, warn};
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct MeResponse {
+    pub id: String,
+    pub email: String,
+    pub role: Role,
+    pub status: UserStatus,
+    pub kyc_status: KycStatus,
+    #[serde(flatten)]
+    pub profile: Profile,
+}
+
+impl From<User> for MeResponse {
+    fn from(user: User) -> Self {
+        Self {
+            id: user.id,
+            email: user.email,
+            role: user.role,
+            status: user.status,
+            kyc_status: user.kyc_status,
+            profile: user.profile,
+        }
+    }
+}
+
+// Bumped when fields are removed or change meaning
+const EXPORT_FORMAT_VERSION: u32 = 1;
+
+// The archive returned by the data export
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DataExport {
+    pub format_version: u32,
+    pub exported_at: DateTime<Utc>,
+    pub user: UserExport,
+    pub accounts: Vec<AccountExport>,
+}
+
+#[instrument(skip(state, user), fields(user_id = %user.user_id))]
+pub async fn get_me(
+    state: web::Data<AppState>,
+    user: AuthenticatedUser,
+) -> Result<HttpResponse, BankError> {
+    let me = state
+        .auth_service
+        .get_user(&user.user_id)
+        .await
+        .map_err(|e| {
+            error!(error = %e, "Failed to get own profile");
+            BankError::from(e)
+        })?;
+
+    Ok(HttpResponse::Ok().json(MeResponse::from(me)))
+}
+
+#[instrument(skip(state, user, req), fields(user_id = %user.user_id))]
+pub async fn update_me(
+    state: web::Data<AppState>,
+    user: AuthenticatedUser,
+    req: web::Json<UpdateProfileRequest>,
+) -> Result<HttpResponse, BankError> {
+    info!("Profile update request received");
+
+    let me = state
+        .auth_service
+        .update_profile(&user.user_id, req.into_inner())
+        .await
+        .map_err(|e| {
+            error!(error = %e, "Failed to update profile");
+            BankError::from(e)
+        })?;
+
+    Ok(HttpResponse::Ok().json(MeResponse::from(me)))
+}
+
+#[instrument(skip(state, user), fields(user_id = %user.user_id))]
+pub async fn export_me(
+    state: web::Data<AppState>,
+    user: AuthenticatedUser,
+) -> Result<HttpResponse, BankError> {
+    info!("Data export requested");
+
+    let user_data = state
+        .auth_service
+        .export_user_data(&user.user_id)
+        .await
+        .map_err(|e| {
+            error!(error = %e, "Failed to export user data");
+            BankError::from(e)
+        })?;
+    let accounts = state
+        .service
+        .export_accounts(&user.user_id)
+        .await
+        .map_err(|e| {
+            error!(error = %e, "Failed to export accounts");
+            BankError::from(e)
+        })?;
+
+    let export = DataExport {
+        format_version: EXPORT_FORMAT_VERSION,
+        exported_at: Utc::now(),
+        user: user_data,
+        accounts,
+    };
+    Ok(HttpResponse::Ok()
+        .insert_header(ContentDisposition {
+            disposition: DispositionType::Attachment,
+            parameters: vec![DispositionParam::Filename(format!(
+                "export-{}.json",
+                user.user_id
+            ))],
+        })
+        .json(export))
+}
+
+// Deletes the caller: their personal data is erased, their accounts and transactions are
+// kept for audit and closed. Accounts must be empty first.
+#[instrument(skip(state, user, req), fields(user_id = %user.user_id))]
+pub async fn delete_me(
+    state: web::Data<AppState>,
+    user: AuthenticatedUser,
+    req: web::Json<DeleteUserRequest>,
+) -> Result<HttpResponse, BankError> {
+    info!("User deletion requested");
+
+    state
+        .auth_service
+        .confirm_password(&user.user_id, &req.password)
+        .await
+        .map_err(|e| {
+            warn!(error = %e, "User deletion not confirmed");
+            BankError::from(e)
+        })?;
+
+    // Closed before the data is erased, so that no money arrives while the user is deleted
+    let closed = state
+        .service
+        .close_accounts(&user.user_id)
+        .await
+        .map_err(|e| {
+            error!(error = %e, "User cannot be deleted yet");
+            BankError::from(e)
+        })?;
+    let cancelled = match state.service.cancel_payment_requests(&user.user_id).await {
+        Ok(cancelled) => cancelled,
+        Err(e) => {
+            error!(error = %e, "Failed to cancel payment requests");
+            reopen_accounts(&state, &closed).await;
+            return Err(BankError::from(e));
+        }
+    };
+    if let Err(e) = state.auth_service.delete_user(&user.user_id).await {
+        error!(error = %e, "Failed to delete user");
+        if let Err(restore_error) = state.service.restore_payment_requests(cancelled).await {
+            error!(error = %restore_error, "Failed to restore payment requests");
+        }
+        reopen_accounts(&state, &closed).await;
+        return Err(BankError::from(e));
+    }
+
+    Ok(HttpResponse::NoContent().finish())
+}
+
+// Best effort: the original error is what the client needs to see
+async fn reopen_accounts(state: &AppState, closed: &[u32]) {
+    if let Err(e) = state.service.reopen_accounts(closed).await {
+        error!(error = %e, accounts = ?closed, "Failed to reopen accounts");
+    }
+}
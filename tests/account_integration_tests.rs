@@ -25,15 +25,21 @@ macro_rules! setup_account_test {
         // Register and login
         let create_user = CreateUser {
             email: "account@example.com".to_string(),
-            password: "test123".to_string(),
+            password: "Bank-Passw0rd-123".to_string(),
         };
         let _user = auth_service.register_user(create_user).await.unwrap();
 
         let login_req = LoginRequest {
             email: "account@example.com".to_string(),
-            password: "test123".to_string(),
+            password: "Bank-Passw0rd-123".to_string(),
         };
-        let token = auth_service.login(login_req).await.unwrap();
+        let token = auth_service
+            .login(login_req)
+            .await
+            .unwrap()
+            .into_tokens()
+            .unwrap()
+            .access_token;
 
         let state = web::Data::new(AppState {
             service,
@@ -80,6 +86,7 @@ async fn test_complex_transfer_scenario() {
         .insert_header(("Authorization", format!("Bearer {}", token)))
         .set_json(&Deposit {
             amount: Amount::new(1000),
+            details: Default::default(),
         })
         .to_request();
     test::call_service(&app, req).await;
@@ -92,6 +99,8 @@ async fn test_complex_transfer_scenario() {
             from_account_id: accounts[0].id,
             to_account_id: accounts[1].id,
             amount: Amount::new(300),
+            beneficiary_id: None,
+            details: Default::default(),
         })
         .to_request();
     test::call_service(&app, req).await;
@@ -104,6 +113,8 @@ async fn test_complex_transfer_scenario() {
             from_account_id: accounts[1].id,
             to_account_id: accounts[2].id,
             amount: Amount::new(100),
+            beneficiary_id: None,
+            details: Default::default(),
         })
         .to_request();
     test::call_service(&app, req).await;
@@ -152,6 +163,7 @@ async fn test_multiple_concurrent_deposits() {
             .insert_header(("Authorization", format!("Bearer {}", token)))
             .set_json(&Deposit {
                 amount: Amount::new(amount),
+                details: Default::default(),
             })
             .to_request();
         test::call_service(&app, req).await;
@@ -187,6 +199,7 @@ async fn test_account_balance_edge_cases() {
         .insert_header(("Authorization", format!("Bearer {}", token)))
         .set_json(&Deposit {
             amount: Amount::new(1_000_000_000),
+            details: Default::default(),
         })
         .to_request();
     let updated: Account = test::call_and_read_body_json(&app, req).await;
@@ -198,6 +211,7 @@ async fn test_account_balance_edge_cases() {
         .insert_header(("Authorization", format!("Bearer {}", token)))
         .set_json(&Withdraw {
             amount: Amount::new(1_000_000_000),
+            details: Default::default(),
         })
         .to_request();
     let updated: Account = test::call_and_read_body_json(&app, req).await;
@@ -233,6 +247,7 @@ async fn test_transfer_rollback_scenario() {
         .insert_header(("Authorization", format!("Bearer {}", token)))
         .set_json(&Deposit {
             amount: Amount::new(500),
+            details: Default::default(),
         })
         .to_request();
     test::call_service(&app, req).await;
@@ -245,6 +260,8 @@ async fn test_transfer_rollback_scenario() {
             from_account_id: source.id,
             to_account_id: dest.id,
             amount: Amount::new(200),
+            beneficiary_id: None,
+            details: Default::default(),
         })
         .to_request();
     test::call_service(&app, req).await;
@@ -272,6 +289,8 @@ async fn test_transfer_rollback_scenario() {
             from_account_id: dest.id,
             to_account_id: source.id,
             amount: Amount::new(300),
+            beneficiary_id: None,
+            details: Default::default(),
         })
         .to_request();
     let resp: actix_web::dev::ServiceResponse = test::call_service(&app, req).await;
@@ -303,6 +322,7 @@ async fn test_multiple_accounts_operations() {
             .insert_header(("Authorization", format!("Bearer {}", token)))
             .set_json(&Deposit {
                 amount: Amount::new((i + 1) as u64 * 100),
+                details: Default::default(),
             })
             .to_request();
         test::call_service(&app, req).await;
@@ -339,6 +359,7 @@ async fn test_sequential_operations() {
         .insert_header(("Authorization", format!("Bearer {}", token)))
         .set_json(&Deposit {
             amount: Amount::new(100),
+            details: Default::default(),
         })
         .to_request();
     test::call_service(&app, req).await;
@@ -349,6 +370,7 @@ async fn test_sequential_operations() {
         .insert_header(("Authorization", format!("Bearer {}", token)))
         .set_json(&Withdraw {
             amount: Amount::new(30),
+            details: Default::default(),
         })
         .to_request();
     test::call_service(&app, req).await;
@@ -359,6 +381,7 @@ async fn test_sequential_operations() {
         .insert_header(("Authorization", format!("Bearer {}", token)))
         .set_json(&Deposit {
             amount: Amount::new(50),
+            details: Default::default(),
         })
         .to_request();
     test::call_service(&app, req).await;
@@ -369,6 +392,7 @@ async fn test_sequential_operations() {
         .insert_header(("Authorization", format!("Bearer {}", token)))
         .set_json(&Withdraw {
             amount: Amount::new(20),
+            details: Default::default(),
         })
         .to_request();
     test::call_service(&app, req).await;
@@ -0,0 +1,286 @@
+use actix_web::http::StatusCode;
+use actix_web::{App, test, web};
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use std::sync::Arc;
+use yandex_bank_api::application::auth_service::AuthService;
+use yandex_bank_api::application::service::BankService;
+use yandex_bank_api::data::memory::InMemoryAccountRepository;
+use yandex_bank_api::data::user_repository::InMemoryUserRepository;
+use yandex_bank_api::domain::api_key::{ApiKeyAuthenticator, CreateApiKeyRequest};
+use yandex_bank_api::domain::models::{Amount, CreateAccount, Transfer};
+use yandex_bank_api::domain::token::Scope;
+use yandex_bank_api::domain::user::{CreateUser, LoginRequest};
+use yandex_bank_api::presentation::auth::{
+    GetTokenRequest, create_api_key, get_token, list_api_keys, login, register, revoke_api_key,
+};
+use yandex_bank_api::presentation::handlers::{AppState, create_account, get_account, transfer};
+use yandex_bank_api::presentation::middleware::{JwtAuthMiddleware, RequireScope};
+
+const PASSWORD: &str = "Passw0rd-Strong";
+
+macro_rules! setup_api_key_test {
+    () => {{
+        let repository = InMemoryAccountRepository::new();
+        let service = BankService::new(Arc::new(repository));
+
+        let jwt_secret = "test-secret-key-for-api-key-tests".to_string();
+        let auth_service = Arc::new(
+            AuthService::new(Arc::new(InMemoryUserRepository::new()), jwt_secret.clone())
+                .with_token_client("integration", "integration-secret"),
+        );
+        let authenticator: Arc<dyn ApiKeyAuthenticator> = auth_service.clone();
+
+        let state = web::Data::new(AppState {
+            service,
+            auth_service,
+        });
+
+        test::init_service(
+            App::new()
+                .app_data(state.clone())
+                .wrap(JwtAuthMiddleware::new(jwt_secret).with_api_key_authenticator(authenticator))
+                .service(
+                    web::scope("/api")
+                        .route("/auth/register", web::post().to(register))
+                        .route("/auth/login", web::post().to(login))
+                        .route("/auth/token", web::post().to(get_token))
+                        .route("/auth/api-keys", web::post().to(create_api_key))
+                        .route("/auth/api-keys", web::get().to(list_api_keys))
+                        .route("/auth/api-keys/{id}", web::delete().to(revoke_api_key))
+                        .route(
+                            "/accounts",
+                            web::post()
+                                .to(create_account)
+                                .wrap(RequireScope::new(Scope::AccountsWrite)),
+                        )
+                        .route(
+                            "/accounts/{id}",
+                            web::get()
+                                .to(get_account)
+                                .wrap(RequireScope::new(Scope::AccountsRead)),
+                        )
+                        .route(
+                            "/transfers",
+                            web::post()
+                                .to(transfer)
+                                .wrap(RequireScope::new(Scope::PaymentsWrite)),
+                        ),
+                ),
+        )
+        .await
+    }};
+}
+
+// Registers a user, logs in and creates an account with the session token.
+// Returns the user id, the session token and the account id.
+macro_rules! user_with_account {
+    ($app:expr, $email:expr) => {{
+        let req = test::TestRequest::post()
+            .uri("/api/auth/register")
+            .set_json(&CreateUser {
+                email: $email.to_string(),
+                password: PASSWORD.to_string(),
+            })
+            .to_request();
+        let user: serde_json::Value = test::call_and_read_body_json(&$app, req).await;
+
+        let req = test::TestRequest::post()
+            .uri("/api/auth/login")
+            .set_json(&LoginRequest {
+                email: $email.to_string(),
+                password: PASSWORD.to_string(),
+            })
+            .to_request();
+        let login: serde_json::Value = test::call_and_read_body_json(&$app, req).await;
+        let token = login["access_token"].as_str().unwrap().to_string();
+
+        let req = test::TestRequest::post()
+            .uri("/api/accounts")
+            .insert_header(("Authorization", format!("Bearer {}", token)))
+            .set_json(&CreateAccount {
+                name: "Main".to_string(),
+            })
+            .to_request();
+        let account: serde_json::Value = test::call_and_read_body_json(&$app, req).await;
+
+        (
+            user["id"].as_str().unwrap().to_string(),
+            token,
+            account["id"].as_u64().unwrap() as u32,
+        )
+    }};
+}
+
+// Creates an API key with the given authorization header and returns the response
+macro_rules! create_key {
+    ($app:expr, $authorization:expr, $scopes:expr) => {{
+        let req = test::TestRequest::post()
+            .uri("/api/auth/api-keys")
+            .insert_header(("Authorization", $authorization))
+            .set_json(&CreateApiKeyRequest {
+                name: "backend".to_string(),
+                expires_at: None,
+                scopes: $scopes,
+            })
+            .to_request();
+        test::call_service(&$app, req).await
+    }};
+}
+
+// Middleware rejections surface as service errors rather than responses
+fn status_of(result: Result<actix_web::dev::ServiceResponse, actix_web::Error>) -> StatusCode {
+    match result {
+        Ok(resp) => resp.status(),
+        Err(err) => err.as_response_error().status_code(),
+    }
+}
+
+macro_rules! get_account_status {
+    ($app:expr, $account_id:expr, $authorization:expr) => {{
+        let req = test::TestRequest::get()
+            .uri(&format!("/api/accounts/{}", $account_id))
+            .insert_header(("Authorization", $authorization))
+            .to_request();
+        status_of(test::try_call_service(&$app, req).await)
+    }};
+}
+
+#[actix_web::test]
+async fn test_api_key_lifecycle() {
+    let app = setup_api_key_test!();
+    let (_, token, account_id) = user_with_account!(app, "server@example.com");
+
+    let resp = create_key!(app, format!("Bearer {}", token), None);
+    assert_eq!(resp.status(), StatusCode::CREATED);
+    let created: serde_json::Value = test::read_body_json(resp).await;
+    let key = created["key"].as_str().unwrap().to_string();
+    let key_id = created["id"].as_str().unwrap().to_string();
+    assert!(key.starts_with(created["prefix"].as_str().unwrap()));
+    assert!(created.get("key_hash").is_none());
+
+    assert_eq!(
+        get_account_status!(app, account_id, format!("ApiKey {}", key)),
+        StatusCode::OK
+    );
+
+    // The key is shown once: listings carry the prefix only
+    let req = test::TestRequest::get()
+        .uri("/api/auth/api-keys")
+        .insert_header(("Authorization", format!("Bearer {}", token)))
+        .to_request();
+    let listed: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+    let listed = listed.as_array().unwrap();
+    assert_eq!(listed.len(), 1);
+    assert_eq!(listed[0]["id"], key_id);
+    assert_eq!(listed[0]["name"], "backend");
+    assert!(listed[0].get("key").is_none());
+    assert!(listed[0].get("key_hash").is_none());
+
+    let req = test::TestRequest::delete()
+        .uri(&format!("/api/auth/api-keys/{}", key_id))
+        .insert_header(("Authorization", format!("Bearer {}", token)))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::NO_CONTENT);
+
+    let req = test::TestRequest::get()
+        .uri(&format!("/api/accounts/{}", account_id))
+        .insert_header(("Authorization", format!("ApiKey {}", key)))
+        .to_request();
+    let err = test::try_call_service(&app, req).await.unwrap_err();
+    assert_eq!(
+        err.as_response_error().status_code(),
+        StatusCode::UNAUTHORIZED
+    );
+    assert_eq!(err.to_string(), r#"{"error":"invalid api key"}"#);
+}
+
+#[actix_web::test]
+async fn test_scoped_api_key_is_limited_to_its_scopes() {
+    let app = setup_api_key_test!();
+    let (_, token, account_id) = user_with_account!(app, "reporting@example.com");
+
+    let resp = create_key!(
+        app,
+        format!("Bearer {}", token),
+        Some(vec![Scope::AccountsRead])
+    );
+    let created: serde_json::Value = test::read_body_json(resp).await;
+    let key = created["key"].as_str().unwrap();
+    assert_eq!(created["scopes"], serde_json::json!(["accounts:read"]));
+
+    assert_eq!(
+        get_account_status!(app, account_id, format!("ApiKey {}", key)),
+        StatusCode::OK
+    );
+
+    let req = test::TestRequest::post()
+        .uri("/api/transfers")
+        .insert_header(("Authorization", format!("ApiKey {}", key)))
+        .set_json(&Transfer {
+            from_account_id: account_id,
+            to_account_id: account_id,
+            amount: Amount::new(1),
+            beneficiary_id: None,
+            details: Default::default(),
+        })
+        .to_request();
+    let err = test::try_call_service(&app, req).await.unwrap_err();
+    assert_eq!(err.as_response_error().status_code(), StatusCode::FORBIDDEN);
+    let body: serde_json::Value = serde_json::from_str(&err.to_string()).unwrap();
+    assert_eq!(body["missing_scope"], "payments:write");
+}
+
+#[actix_web::test]
+async fn test_api_keys_require_a_session_token_to_manage() {
+    let app = setup_api_key_test!();
+    let (user_id, token, _) = user_with_account!(app, "escalation@example.com");
+    let resp = create_key!(
+        app,
+        format!("Bearer {}", token),
+        Some(vec![Scope::AccountsRead])
+    );
+    let created: serde_json::Value = test::read_body_json(resp).await;
+    let key = created["key"].as_str().unwrap();
+
+    // A read-only key cannot mint itself an unrestricted one
+    let resp = create_key!(app, format!("ApiKey {}", key), None);
+    assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
+
+    // Neither can a scoped token
+    let req = test::TestRequest::post()
+        .uri("/api/auth/token")
+        .insert_header((
+            "Authorization",
+            format!(
+                "Basic {}",
+                STANDARD.encode("integration:integration-secret")
+            ),
+        ))
+        .set_json(&GetTokenRequest {
+            user_id,
+            scopes: Some(vec![Scope::AccountsRead]),
+        })
+        .to_request();
+    let resp: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+    let scoped_token = resp["access_token"].as_str().unwrap();
+
+    let resp = create_key!(app, format!("Bearer {}", scoped_token), None);
+    assert_eq!(resp.status(), StatusCode::FORBIDDEN);
+}
+
+#[actix_web::test]
+async fn test_unknown_api_keys_are_rejected() {
+    let app = setup_api_key_test!();
+    let (_, _, account_id) = user_with_account!(app, "unknown@example.com");
+
+    for key in ["ybk_unknown", "not-a-key", ""] {
+        assert_eq!(
+            get_account_status!(app, account_id, format!("ApiKey {}", key)),
+            StatusCode::UNAUTHORIZED,
+            "{}",
+            key
+        );
+    }
+}
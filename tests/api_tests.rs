@@ -25,16 +25,22 @@ macro_rules! setup_test {
         // Register a test user
         let create_user = CreateUser {
             email: "test@example.com".to_string(),
-            password: "test123".to_string(),
+            password: "Bank-Passw0rd-123".to_string(),
         };
         let _user = auth_service.register_user(create_user).await.unwrap();
 
         // Login to get token
         let login_req = LoginRequest {
             email: "test@example.com".to_string(),
-            password: "test123".to_string(),
+            password: "Bank-Passw0rd-123".to_string(),
         };
-        let token = auth_service.login(login_req).await.unwrap();
+        let token = auth_service
+            .login(login_req)
+            .await
+            .unwrap()
+            .into_tokens()
+            .unwrap()
+            .access_token;
 
         let state = web::Data::new(AppState {
             service,
@@ -94,6 +100,7 @@ async fn test_deposit_and_withdraw() {
         .insert_header(("Authorization", format!("Bearer {}", token)))
         .set_json(&Deposit {
             amount: Amount::new(100),
+            details: Default::default(),
         })
         .to_request();
     let updated_account: Account = test::call_and_read_body_json(&app, req).await;
@@ -105,6 +112,7 @@ async fn test_deposit_and_withdraw() {
         .insert_header(("Authorization", format!("Bearer {}", token)))
         .set_json(&Withdraw {
             amount: Amount::new(50),
+            details: Default::default(),
         })
         .to_request();
     let final_account: Account = test::call_and_read_body_json(&app, req).await;
@@ -141,6 +149,7 @@ async fn test_transfer() {
         .insert_header(("Authorization", format!("Bearer {}", token)))
         .set_json(&Deposit {
             amount: Amount::new(100),
+            details: Default::default(),
         })
         .to_request();
     test::call_service(&app, req).await;
@@ -153,6 +162,8 @@ async fn test_transfer() {
             from_account_id: alice.id,
             to_account_id: bob.id,
             amount: Amount::new(50),
+            beneficiary_id: None,
+            details: Default::default(),
         })
         .to_request();
     let resp: actix_web::dev::ServiceResponse = test::call_service(&app, req).await;
@@ -293,6 +304,7 @@ async fn test_withdraw_insufficient_funds() {
         .insert_header(("Authorization", format!("Bearer {}", token)))
         .set_json(&Deposit {
             amount: Amount::new(50),
+            details: Default::default(),
         })
         .to_request();
     test::call_service(&app, req).await;
@@ -303,6 +315,7 @@ async fn test_withdraw_insufficient_funds() {
         .insert_header(("Authorization", format!("Bearer {}", token)))
         .set_json(&Withdraw {
             amount: Amount::new(100),
+            details: Default::default(),
         })
         .to_request();
     let resp: actix_web::dev::ServiceResponse = test::call_service(&app, req).await;
@@ -338,6 +351,7 @@ async fn test_transfer_insufficient_funds() {
         .insert_header(("Authorization", format!("Bearer {}", token)))
         .set_json(&Deposit {
             amount: Amount::new(50),
+            details: Default::default(),
         })
         .to_request();
     test::call_service(&app, req).await;
@@ -350,6 +364,8 @@ async fn test_transfer_insufficient_funds() {
             from_account_id: from.id,
             to_account_id: to.id,
             amount: Amount::new(100),
+            beneficiary_id: None,
+            details: Default::default(),
         })
         .to_request();
     let resp: actix_web::dev::ServiceResponse = test::call_service(&app, req).await;
@@ -378,6 +394,8 @@ async fn test_transfer_same_account() {
             from_account_id: account.id,
             to_account_id: account.id,
             amount: Amount::new(50),
+            beneficiary_id: None,
+            details: Default::default(),
         })
         .to_request();
     let resp: actix_web::dev::ServiceResponse = test::call_service(&app, req).await;
@@ -393,6 +411,7 @@ async fn test_deposit_to_nonexistent_account() {
         .insert_header(("Authorization", format!("Bearer {}", token)))
         .set_json(&Deposit {
             amount: Amount::new(100),
+            details: Default::default(),
         })
         .to_request();
     let resp: actix_web::dev::ServiceResponse = test::call_service(&app, req).await;
@@ -408,6 +427,7 @@ async fn test_withdraw_from_nonexistent_account() {
         .insert_header(("Authorization", format!("Bearer {}", token)))
         .set_json(&Withdraw {
             amount: Amount::new(100),
+            details: Default::default(),
         })
         .to_request();
     let resp: actix_web::dev::ServiceResponse = test::call_service(&app, req).await;
@@ -434,6 +454,7 @@ async fn test_zero_deposit() {
         .insert_header(("Authorization", format!("Bearer {}", token)))
         .set_json(&Deposit {
             amount: Amount::new(0),
+            details: Default::default(),
         })
         .to_request();
     let updated: Account = test::call_and_read_body_json(&app, req).await;
@@ -459,6 +480,7 @@ async fn test_zero_withdraw() {
         .insert_header(("Authorization", format!("Bearer {}", token)))
         .set_json(&Deposit {
             amount: Amount::new(100),
+            details: Default::default(),
         })
         .to_request();
     test::call_service(&app, req).await;
@@ -469,6 +491,7 @@ async fn test_zero_withdraw() {
         .insert_header(("Authorization", format!("Bearer {}", token)))
         .set_json(&Withdraw {
             amount: Amount::new(0),
+            details: Default::default(),
         })
         .to_request();
     let updated: Account = test::call_and_read_body_json(&app, req).await;
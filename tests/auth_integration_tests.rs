@@ -1,22 +1,52 @@
 use actix_web::{App, test, web};
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
 use std::sync::Arc;
 use yandex_bank_api::application::auth_service::AuthService;
 use yandex_bank_api::application::service::BankService;
 use yandex_bank_api::data::memory::InMemoryAccountRepository;
+use yandex_bank_api::data::revocation_store::InMemoryRevocationStore;
 use yandex_bank_api::data::user_repository::InMemoryUserRepository;
+use yandex_bank_api::domain::models::CreateAccount;
+use yandex_bank_api::domain::notifier::Notification;
+use yandex_bank_api::domain::repository::RevocationStore;
 use yandex_bank_api::domain::user::{CreateUser, LoginRequest};
-use yandex_bank_api::presentation::auth::{get_token, login, register};
-use yandex_bank_api::presentation::handlers::AppState;
+use yandex_bank_api::infrastructure::notifier::InMemoryNotifier;
+use yandex_bank_api::infrastructure::totp::{totp_code, totp_step};
+use yandex_bank_api::presentation::auth::{
+    change_password, enroll_two_factor, get_token, login, login_two_factor, logout, logout_all,
+    refresh, register, request_password_reset, reset_password, verify_two_factor,
+};
+use yandex_bank_api::presentation::handlers::{AppState, create_account, deposit, transfer};
 use yandex_bank_api::presentation::middleware::JwtAuthMiddleware;
 
+const TOKEN_CLIENT_ID: &str = "backoffice";
+const TOKEN_CLIENT_SECRET: &str = "backoffice-secret";
+
+fn basic_auth(client_id: &str, client_secret: &str) -> String {
+    format!(
+        "Basic {}",
+        STANDARD.encode(format!("{}:{}", client_id, client_secret))
+    )
+}
+
 macro_rules! setup_auth_test {
     () => {{
+        let (app, _notifier) = setup_auth_test!(with_notifier);
+        app
+    }};
+    (with_notifier) => {{
         let repository = InMemoryAccountRepository::new();
         let service = BankService::new(Arc::new(repository));
 
         let user_repository = InMemoryUserRepository::new();
         let jwt_secret = "test-secret-key-for-auth-tests".to_string();
-        let auth_service = AuthService::new(Arc::new(user_repository), jwt_secret.clone());
+        let revocation_store: Arc<dyn RevocationStore> = Arc::new(InMemoryRevocationStore::new());
+        let notifier = InMemoryNotifier::new();
+        let auth_service = AuthService::new(Arc::new(user_repository), jwt_secret.clone())
+            .with_revocation_store(revocation_store.clone())
+            .with_notifier(Arc::new(notifier.clone()))
+            .with_token_client(TOKEN_CLIENT_ID, TOKEN_CLIENT_SECRET);
 
         let state = web::Data::new(AppState {
             service,
@@ -26,17 +56,55 @@ macro_rules! setup_auth_test {
         let app = test::init_service(
             App::new()
                 .app_data(state.clone())
-                .wrap(JwtAuthMiddleware::new(jwt_secret))
+                .wrap(JwtAuthMiddleware::new(jwt_secret).with_revocation_store(revocation_store))
                 .service(
                     web::scope("/api")
                         .route("/auth/register", web::post().to(register))
                         .route("/auth/login", web::post().to(login))
-                        .route("/auth/token", web::post().to(get_token)),
+                        .route("/auth/login/2fa", web::post().to(login_two_factor))
+                        .route("/auth/refresh", web::post().to(refresh))
+                        .route("/auth/logout", web::post().to(logout))
+                        .route("/auth/logout-all", web::post().to(logout_all))
+                        .route("/auth/password", web::post().to(change_password))
+                        .route(
+                            "/auth/password/forgot",
+                            web::post().to(request_password_reset),
+                        )
+                        .route("/auth/password/reset", web::post().to(reset_password))
+                        .route("/auth/token", web::post().to(get_token))
+                        .route("/auth/2fa/enroll", web::post().to(enroll_two_factor))
+                        .route("/auth/2fa/verify", web::post().to(verify_two_factor))
+                        .route("/accounts", web::post().to(create_account))
+                        .route("/accounts/{id}/deposit", web::post().to(deposit))
+                        .route("/transfers", web::post().to(transfer)),
                 ),
         )
         .await;
 
-        app
+        (app, notifier)
+    }};
+}
+
+macro_rules! register_and_login {
+    ($app:expr, $email:expr) => {{
+        let req = test::TestRequest::post()
+            .uri("/api/auth/register")
+            .set_json(&CreateUser {
+                email: $email.to_string(),
+                password: "Passw0rd-Strong".to_string(),
+            })
+            .to_request();
+        test::call_service(&$app, req).await;
+
+        let req = test::TestRequest::post()
+            .uri("/api/auth/login")
+            .set_json(&LoginRequest {
+                email: $email.to_string(),
+                password: "Passw0rd-Strong".to_string(),
+            })
+            .to_request();
+        let resp: serde_json::Value = test::call_and_read_body_json(&$app, req).await;
+        resp
     }};
 }
 
@@ -49,7 +117,7 @@ async fn test_full_registration_login_flow() {
         .uri("/api/auth/register")
         .set_json(&CreateUser {
             email: "flow@example.com".to_string(),
-            password: "password123".to_string(),
+            password: "Passw0rd-123-Full".to_string(),
         })
         .to_request();
 
@@ -65,7 +133,7 @@ async fn test_full_registration_login_flow() {
         .uri("/api/auth/login")
         .set_json(&LoginRequest {
             email: "flow@example.com".to_string(),
-            password: "password123".to_string(),
+            password: "Passw0rd-123-Full".to_string(),
         })
         .to_request();
 
@@ -75,9 +143,13 @@ async fn test_full_registration_login_flow() {
     assert!(resp.get("access_token").is_some());
     let token = resp["access_token"].as_str().unwrap();
 
-    // Get token using user_id
+    // Get token for user_id as a trusted client
     let req = test::TestRequest::post()
         .uri("/api/auth/token")
+        .insert_header((
+            "Authorization",
+            basic_auth(TOKEN_CLIENT_ID, TOKEN_CLIENT_SECRET),
+        ))
         .set_json(serde_json::json!({
             "user_id": user_id
         }))
@@ -106,7 +178,7 @@ async fn test_register_duplicate_email() {
         .uri("/api/auth/register")
         .set_json(&CreateUser {
             email: "duplicate@example.com".to_string(),
-            password: "pass1".to_string(),
+            password: "First-Passw0rd-1".to_string(),
         })
         .to_request();
     test::call_service(&app, req).await;
@@ -116,7 +188,7 @@ async fn test_register_duplicate_email() {
         .uri("/api/auth/register")
         .set_json(&CreateUser {
             email: "duplicate@example.com".to_string(),
-            password: "pass2".to_string(),
+            password: "Second-Passw0rd-2".to_string(),
         })
         .to_request();
     let resp: actix_web::dev::ServiceResponse = test::call_service(&app, req).await;
@@ -132,7 +204,7 @@ async fn test_login_wrong_password() {
         .uri("/api/auth/register")
         .set_json(&CreateUser {
             email: "wrongpass@example.com".to_string(),
-            password: "correct".to_string(),
+            password: "Correct-Passw0rd-1".to_string(),
         })
         .to_request();
     test::call_service(&app, req).await;
@@ -157,7 +229,7 @@ async fn test_login_nonexistent_user() {
         .uri("/api/auth/login")
         .set_json(&LoginRequest {
             email: "nonexistent@example.com".to_string(),
-            password: "password".to_string(),
+            password: "Passw0rd-Strong".to_string(),
         })
         .to_request();
     let resp: actix_web::dev::ServiceResponse = test::call_service(&app, req).await;
@@ -170,6 +242,10 @@ async fn test_get_token_nonexistent_user() {
 
     let req = test::TestRequest::post()
         .uri("/api/auth/token")
+        .insert_header((
+            "Authorization",
+            basic_auth(TOKEN_CLIENT_ID, TOKEN_CLIENT_SECRET),
+        ))
         .set_json(serde_json::json!({
             "user_id": "nonexistent-id"
         }))
@@ -178,6 +254,74 @@ async fn test_get_token_nonexistent_user() {
     assert_eq!(resp.status(), actix_web::http::StatusCode::NOT_FOUND);
 }
 
+macro_rules! register_user_id {
+    ($app:expr, $email:expr) => {{
+        let req = test::TestRequest::post()
+            .uri("/api/auth/register")
+            .set_json(&CreateUser {
+                email: $email.to_string(),
+                password: "Passw0rd-Strong".to_string(),
+            })
+            .to_request();
+        let resp: serde_json::Value = test::call_and_read_body_json(&$app, req).await;
+        resp["id"].as_str().unwrap().to_string()
+    }};
+}
+
+#[actix_web::test]
+async fn test_get_token_anonymous_caller_is_rejected() {
+    let app = setup_auth_test!();
+    let user_id = register_user_id!(app, "victim@example.com");
+
+    let req = test::TestRequest::post()
+        .uri("/api/auth/token")
+        .set_json(serde_json::json!({ "user_id": user_id }))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), actix_web::http::StatusCode::UNAUTHORIZED);
+    let body: serde_json::Value = test::read_body_json(resp).await;
+    assert!(body.get("access_token").is_none());
+}
+
+#[actix_web::test]
+async fn test_get_token_with_invalid_client_credentials_is_rejected() {
+    let app = setup_auth_test!();
+    let user_id = register_user_id!(app, "victim2@example.com");
+
+    for authorization in [
+        basic_auth(TOKEN_CLIENT_ID, "wrong-secret"),
+        basic_auth("unknown-client", TOKEN_CLIENT_SECRET),
+        "Basic not-base64!".to_string(),
+    ] {
+        let req = test::TestRequest::post()
+            .uri("/api/auth/token")
+            .insert_header(("Authorization", authorization))
+            .set_json(serde_json::json!({ "user_id": user_id }))
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), actix_web::http::StatusCode::UNAUTHORIZED);
+    }
+}
+
+#[actix_web::test]
+async fn test_get_token_with_user_access_token_is_rejected() {
+    let app = setup_auth_test!();
+    let login = register_and_login!(app, "attacker@example.com");
+    let victim_id = register_user_id!(app, "victim3@example.com");
+
+    // A regular user's bearer token is not a client credential
+    let req = test::TestRequest::post()
+        .uri("/api/auth/token")
+        .insert_header((
+            "Authorization",
+            format!("Bearer {}", login["access_token"].as_str().unwrap()),
+        ))
+        .set_json(serde_json::json!({ "user_id": victim_id }))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), actix_web::http::StatusCode::UNAUTHORIZED);
+}
+
 #[actix_web::test]
 async fn test_multiple_users_registration() {
     let app = setup_auth_test!();
@@ -188,7 +332,7 @@ async fn test_multiple_users_registration() {
             .uri("/api/auth/register")
             .set_json(&CreateUser {
                 email: format!("user{}@example.com", i),
-                password: format!("pass{}", i),
+                password: format!("Str0ng-Passw0rd-{}", i),
             })
             .to_request();
         let service_resp = test::call_service(&app, req).await;
@@ -207,23 +351,19 @@ async fn test_login_multiple_times_generates_different_tokens() {
         .uri("/api/auth/register")
         .set_json(&CreateUser {
             email: "multitoken@example.com".to_string(),
-            password: "password".to_string(),
+            password: "Passw0rd-Strong".to_string(),
         })
         .to_request();
     test::call_service(&app, req).await;
 
     // Login multiple times and verify all tokens are valid
     let mut tokens = Vec::new();
-    for i in 0..3 {
-        // Add a small delay to ensure different timestamps (JWT uses second precision)
-        if i > 0 {
-            tokio::time::sleep(tokio::time::Duration::from_millis(1100)).await;
-        }
+    for _ in 0..3 {
         let req = test::TestRequest::post()
             .uri("/api/auth/login")
             .set_json(&LoginRequest {
                 email: "multitoken@example.com".to_string(),
-                password: "password".to_string(),
+                password: "Passw0rd-Strong".to_string(),
             })
             .to_request();
         let service_resp = test::call_service(&app, req).await;
@@ -232,7 +372,7 @@ async fn test_login_multiple_times_generates_different_tokens() {
         tokens.push(resp["access_token"].as_str().unwrap().to_string());
     }
 
-    // With delays, all tokens should be different (different timestamps)
+    // Every token has its own jti, so they differ even within the same second
     assert_ne!(tokens[0], tokens[1]);
     assert_ne!(tokens[1], tokens[2]);
     assert_ne!(tokens[0], tokens[2]);
@@ -242,7 +382,7 @@ async fn test_login_multiple_times_generates_different_tokens() {
 async fn test_password_not_stored_in_plain_text() {
     let app = setup_auth_test!();
 
-    let password = "sensitive_password_123";
+    let password = "Sensitive-Passw0rd-123";
 
     // Register user
     let req = test::TestRequest::post()
@@ -273,3 +413,604 @@ async fn test_password_not_stored_in_plain_text() {
     let resp: serde_json::Value = test::read_body_json(service_resp).await;
     assert!(resp.get("access_token").is_some());
 }
+
+macro_rules! refresh_with {
+    ($app:expr, $refresh_token:expr) => {{
+        let req = test::TestRequest::post()
+            .uri("/api/auth/refresh")
+            .set_json(serde_json::json!({ "refresh_token": $refresh_token }))
+            .to_request();
+        test::call_service(&$app, req).await
+    }};
+}
+
+#[actix_web::test]
+async fn test_login_returns_refresh_token() {
+    let app = setup_auth_test!();
+
+    let resp = register_and_login!(app, "pair@example.com");
+
+    assert!(resp["access_token"].as_str().is_some());
+    assert!(resp["refresh_token"].as_str().is_some());
+    assert_eq!(resp["token_type"], "Bearer");
+    assert_eq!(resp["expires_in"], 3600);
+}
+
+#[actix_web::test]
+async fn test_refresh_rotates_tokens() {
+    let app = setup_auth_test!();
+    let login = register_and_login!(app, "rotate@example.com");
+    let refresh_token = login["refresh_token"].as_str().unwrap();
+
+    let resp = refresh_with!(app, refresh_token);
+    assert_eq!(resp.status(), actix_web::http::StatusCode::OK);
+    let rotated: serde_json::Value = test::read_body_json(resp).await;
+    assert!(rotated["access_token"].as_str().is_some());
+    assert_ne!(rotated["refresh_token"], login["refresh_token"]);
+
+    // The rotated refresh token is usable in turn
+    let resp = refresh_with!(app, rotated["refresh_token"].as_str().unwrap());
+    assert_eq!(resp.status(), actix_web::http::StatusCode::OK);
+}
+
+#[actix_web::test]
+async fn test_refresh_token_reuse_revokes_family() {
+    let app = setup_auth_test!();
+    let login = register_and_login!(app, "reuse@example.com");
+    let original = login["refresh_token"].as_str().unwrap();
+
+    let resp = refresh_with!(app, original);
+    let rotated: serde_json::Value = test::read_body_json(resp).await;
+
+    // Replaying the already used token is rejected...
+    let resp = refresh_with!(app, original);
+    assert_eq!(resp.status(), actix_web::http::StatusCode::UNAUTHORIZED);
+
+    // ...and kills the rest of the family, including the legitimate successor
+    let resp = refresh_with!(app, rotated["refresh_token"].as_str().unwrap());
+    assert_eq!(resp.status(), actix_web::http::StatusCode::UNAUTHORIZED);
+}
+
+#[actix_web::test]
+async fn test_refresh_with_invalid_token() {
+    let app = setup_auth_test!();
+
+    let resp = refresh_with!(app, "definitely-not-a-refresh-token");
+    assert_eq!(resp.status(), actix_web::http::StatusCode::UNAUTHORIZED);
+}
+
+macro_rules! create_account_with {
+    ($app:expr, $access_token:expr) => {{
+        let req = test::TestRequest::post()
+            .uri("/api/accounts")
+            .insert_header(("Authorization", format!("Bearer {}", $access_token)))
+            .set_json(&CreateAccount {
+                name: "Session check".to_string(),
+            })
+            .to_request();
+        test::try_call_service(&$app, req).await
+    }};
+}
+
+#[actix_web::test]
+async fn test_logout_revokes_access_and_refresh_token() {
+    let app = setup_auth_test!();
+    let login = register_and_login!(app, "logout@example.com");
+    let access_token = login["access_token"].as_str().unwrap();
+    let refresh_token = login["refresh_token"].as_str().unwrap();
+    assert!(create_account_with!(app, access_token).is_ok());
+
+    let req = test::TestRequest::post()
+        .uri("/api/auth/logout")
+        .insert_header(("Authorization", format!("Bearer {}", access_token)))
+        .set_json(serde_json::json!({ "refresh_token": refresh_token }))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), actix_web::http::StatusCode::NO_CONTENT);
+
+    // The middleware rejects the revoked access token...
+    let err = create_account_with!(app, access_token).unwrap_err();
+    assert_eq!(
+        err.as_response_error().status_code(),
+        actix_web::http::StatusCode::UNAUTHORIZED
+    );
+    // ...and the refresh token can no longer be exchanged
+    let resp = refresh_with!(app, refresh_token);
+    assert_eq!(resp.status(), actix_web::http::StatusCode::UNAUTHORIZED);
+}
+
+#[actix_web::test]
+async fn test_logout_requires_valid_token() {
+    let app = setup_auth_test!();
+    let login = register_and_login!(app, "logout-twice@example.com");
+    let access_token = login["access_token"].as_str().unwrap();
+
+    let req = test::TestRequest::post()
+        .uri("/api/auth/logout")
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), actix_web::http::StatusCode::UNAUTHORIZED);
+
+    for expected in [
+        actix_web::http::StatusCode::NO_CONTENT,
+        actix_web::http::StatusCode::UNAUTHORIZED,
+    ] {
+        let req = test::TestRequest::post()
+            .uri("/api/auth/logout")
+            .insert_header(("Authorization", format!("Bearer {}", access_token)))
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), expected);
+    }
+}
+
+#[actix_web::test]
+async fn test_logout_all_revokes_every_session() {
+    let app = setup_auth_test!();
+    let first = register_and_login!(app, "everywhere@example.com");
+    let req = test::TestRequest::post()
+        .uri("/api/auth/login")
+        .set_json(&LoginRequest {
+            email: "everywhere@example.com".to_string(),
+            password: "Passw0rd-Strong".to_string(),
+        })
+        .to_request();
+    let second: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+
+    let req = test::TestRequest::post()
+        .uri("/api/auth/logout-all")
+        .insert_header((
+            "Authorization",
+            format!("Bearer {}", first["access_token"].as_str().unwrap()),
+        ))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), actix_web::http::StatusCode::NO_CONTENT);
+
+    for session in [&first, &second] {
+        assert!(create_account_with!(app, session["access_token"].as_str().unwrap()).is_err());
+        let resp = refresh_with!(app, session["refresh_token"].as_str().unwrap());
+        assert_eq!(resp.status(), actix_web::http::StatusCode::UNAUTHORIZED);
+    }
+
+    // Tokens issued after the cutoff are accepted again, even within the same second
+    let req = test::TestRequest::post()
+        .uri("/api/auth/login")
+        .set_json(&LoginRequest {
+            email: "everywhere@example.com".to_string(),
+            password: "Passw0rd-Strong".to_string(),
+        })
+        .to_request();
+    let fresh: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+    assert!(create_account_with!(app, fresh["access_token"].as_str().unwrap()).is_ok());
+}
+
+#[actix_web::test]
+async fn test_login_right_after_logout_all_is_accepted() {
+    let app = setup_auth_test!();
+    let mut login = register_and_login!(app, "straight-back@example.com");
+
+    // Each new login lands in the same second as the logout it follows
+    for _ in 0..3 {
+        let req = test::TestRequest::post()
+            .uri("/api/auth/logout-all")
+            .insert_header((
+                "Authorization",
+                format!("Bearer {}", login["access_token"].as_str().unwrap()),
+            ))
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), actix_web::http::StatusCode::NO_CONTENT);
+
+        let req = test::TestRequest::post()
+            .uri("/api/auth/login")
+            .set_json(&LoginRequest {
+                email: "straight-back@example.com".to_string(),
+                password: "Passw0rd-Strong".to_string(),
+            })
+            .to_request();
+        login = test::call_and_read_body_json(&app, req).await;
+        assert!(create_account_with!(app, login["access_token"].as_str().unwrap()).is_ok());
+    }
+}
+
+macro_rules! login_status {
+    ($app:expr, $email:expr, $password:expr) => {{
+        let req = test::TestRequest::post()
+            .uri("/api/auth/login")
+            .set_json(&LoginRequest {
+                email: $email.to_string(),
+                password: $password.to_string(),
+            })
+            .to_request();
+        test::call_service(&$app, req).await.status()
+    }};
+}
+
+#[actix_web::test]
+async fn test_change_password_flow() {
+    let app = setup_auth_test!();
+    let login = register_and_login!(app, "change@example.com");
+    let access_token = login["access_token"].as_str().unwrap();
+
+    // Wrong current password is rejected
+    let req = test::TestRequest::post()
+        .uri("/api/auth/password")
+        .insert_header(("Authorization", format!("Bearer {}", access_token)))
+        .set_json(serde_json::json!({
+            "current_password": "wrong",
+            "new_password": "New-Passw0rd-5"
+        }))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), actix_web::http::StatusCode::UNAUTHORIZED);
+
+    let req = test::TestRequest::post()
+        .uri("/api/auth/password")
+        .insert_header(("Authorization", format!("Bearer {}", access_token)))
+        .set_json(serde_json::json!({
+            "current_password": "Passw0rd-Strong",
+            "new_password": "New-Passw0rd-5"
+        }))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), actix_web::http::StatusCode::NO_CONTENT);
+
+    // Existing sessions are invalidated
+    assert!(create_account_with!(app, access_token).is_err());
+    let resp = refresh_with!(app, login["refresh_token"].as_str().unwrap());
+    assert_eq!(resp.status(), actix_web::http::StatusCode::UNAUTHORIZED);
+
+    assert_eq!(
+        login_status!(app, "change@example.com", "Passw0rd-Strong"),
+        actix_web::http::StatusCode::UNAUTHORIZED
+    );
+    assert_eq!(
+        login_status!(app, "change@example.com", "New-Passw0rd-5"),
+        actix_web::http::StatusCode::OK
+    );
+}
+
+#[actix_web::test]
+async fn test_change_password_requires_authentication() {
+    let app = setup_auth_test!();
+
+    let req = test::TestRequest::post()
+        .uri("/api/auth/password")
+        .set_json(serde_json::json!({
+            "current_password": "Passw0rd-Strong",
+            "new_password": "New-Passw0rd-5"
+        }))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), actix_web::http::StatusCode::UNAUTHORIZED);
+}
+
+#[actix_web::test]
+async fn test_password_reset_flow() {
+    let (app, notifier) = setup_auth_test!(with_notifier);
+    let login = register_and_login!(app, "forgot@example.com");
+
+    let req = test::TestRequest::post()
+        .uri("/api/auth/password/forgot")
+        .set_json(serde_json::json!({ "email": "forgot@example.com" }))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), actix_web::http::StatusCode::ACCEPTED);
+
+    let token = notifier
+        .sent_to("forgot@example.com")
+        .await
+        .into_iter()
+        .find_map(|n| match n {
+            Notification::PasswordReset { token, .. } => Some(token),
+            _ => None,
+        })
+        .unwrap();
+
+    for expected in [
+        actix_web::http::StatusCode::NO_CONTENT,
+        actix_web::http::StatusCode::UNAUTHORIZED,
+    ] {
+        let req = test::TestRequest::post()
+            .uri("/api/auth/password/reset")
+            .set_json(serde_json::json!({
+                "token": token,
+                "new_password": "Reset-Passw0rd-6"
+            }))
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), expected);
+    }
+
+    let resp = refresh_with!(app, login["refresh_token"].as_str().unwrap());
+    assert_eq!(resp.status(), actix_web::http::StatusCode::UNAUTHORIZED);
+    assert_eq!(
+        login_status!(app, "forgot@example.com", "Reset-Passw0rd-6"),
+        actix_web::http::StatusCode::OK
+    );
+}
+
+#[actix_web::test]
+async fn test_password_reset_for_unknown_email_looks_the_same() {
+    let (app, notifier) = setup_auth_test!(with_notifier);
+
+    let req = test::TestRequest::post()
+        .uri("/api/auth/password/forgot")
+        .set_json(serde_json::json!({ "email": "ghost@example.com" }))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), actix_web::http::StatusCode::ACCEPTED);
+    assert!(notifier.sent().await.is_empty());
+
+    let req = test::TestRequest::post()
+        .uri("/api/auth/password/reset")
+        .set_json(serde_json::json!({
+            "token": "made-up-token",
+            "new_password": "Whatever-Passw0rd-8"
+        }))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), actix_web::http::StatusCode::UNAUTHORIZED);
+}
+
+#[actix_web::test]
+async fn test_register_with_invalid_input_lists_every_failed_rule() {
+    let app = setup_auth_test!();
+
+    let req = test::TestRequest::post()
+        .uri("/api/auth/register")
+        .set_json(&CreateUser {
+            email: "missing-at.example.com".to_string(),
+            password: "short".to_string(),
+        })
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), actix_web::http::StatusCode::BAD_REQUEST);
+
+    let body: serde_json::Value = test::read_body_json(resp).await;
+    let errors = body["details"]["errors"].as_array().unwrap();
+    let failed: Vec<(&str, &str)> = errors
+        .iter()
+        .map(|e| (e["field"].as_str().unwrap(), e["rule"].as_str().unwrap()))
+        .collect();
+    assert_eq!(
+        failed,
+        vec![
+            ("email", "format"),
+            ("password", "min_length"),
+            ("password", "uppercase"),
+            ("password", "digit"),
+        ]
+    );
+    assert!(errors.iter().all(|e| e["message"].is_string()));
+}
+
+#[actix_web::test]
+async fn test_register_with_common_password_is_rejected() {
+    let app = setup_auth_test!();
+
+    let req = test::TestRequest::post()
+        .uri("/api/auth/register")
+        .set_json(&CreateUser {
+            email: "common@example.com".to_string(),
+            password: "Password1234".to_string(),
+        })
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), actix_web::http::StatusCode::BAD_REQUEST);
+
+    let body: serde_json::Value = test::read_body_json(resp).await;
+    assert_eq!(body["details"]["errors"][0]["rule"], "common");
+}
+
+#[actix_web::test]
+async fn test_email_identity_is_case_insensitive() {
+    let app = setup_auth_test!();
+
+    let req = test::TestRequest::post()
+        .uri("/api/auth/register")
+        .set_json(&CreateUser {
+            email: "Mixed.Case@Example.COM".to_string(),
+            password: "Passw0rd-Strong".to_string(),
+        })
+        .to_request();
+    let resp: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(resp["email"], "Mixed.Case@example.com");
+
+    // Registering the same address in another case is a duplicate
+    let req = test::TestRequest::post()
+        .uri("/api/auth/register")
+        .set_json(&CreateUser {
+            email: "mixed.case@example.com".to_string(),
+            password: "Passw0rd-Strong".to_string(),
+        })
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), actix_web::http::StatusCode::BAD_REQUEST);
+
+    assert_eq!(
+        login_status!(app, "  MIXED.CASE@example.com", "Passw0rd-Strong"),
+        actix_web::http::StatusCode::OK
+    );
+}
+
+#[actix_web::test]
+async fn test_repeated_failed_logins_are_throttled() {
+    let app = setup_auth_test!();
+    register_and_login!(app, "locked@example.com");
+
+    for _ in 0..3 {
+        assert_eq!(
+            login_status!(app, "locked@example.com", "Wrong-Passw0rd-1"),
+            actix_web::http::StatusCode::UNAUTHORIZED
+        );
+    }
+
+    let req = test::TestRequest::post()
+        .uri("/api/auth/login")
+        .set_json(&LoginRequest {
+            email: "locked@example.com".to_string(),
+            password: "Passw0rd-Strong".to_string(),
+        })
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(
+        resp.status(),
+        actix_web::http::StatusCode::TOO_MANY_REQUESTS
+    );
+    assert!(resp.headers().contains_key("retry-after"));
+    let body: serde_json::Value = test::read_body_json(resp).await;
+    assert!(body["details"]["retry_after"].as_u64().unwrap() > 0);
+
+    // An unknown email is throttled the same way
+    for _ in 0..3 {
+        assert_eq!(
+            login_status!(app, "nobody@example.com", "Wrong-Passw0rd-1"),
+            actix_web::http::StatusCode::UNAUTHORIZED
+        );
+    }
+    assert_eq!(
+        login_status!(app, "nobody@example.com", "Wrong-Passw0rd-1"),
+        actix_web::http::StatusCode::TOO_MANY_REQUESTS
+    );
+}
+
+fn totp_now(secret: &str, offset: i64) -> String {
+    totp_code(secret, totp_step(chrono::Utc::now().timestamp()) + offset).unwrap()
+}
+
+macro_rules! transfer_status {
+    ($app:expr, $access_token:expr, $from:expr, $to:expr, $amount:expr, $code:expr) => {{
+        let mut req = test::TestRequest::post()
+            .uri("/api/transfers")
+            .insert_header(("Authorization", format!("Bearer {}", $access_token)))
+            .set_json(serde_json::json!({
+                "from_account_id": $from,
+                "to_account_id": $to,
+                "amount": $amount,
+            }));
+        if let Some(code) = $code {
+            req = req.insert_header(("X-Two-Factor-Code", code));
+        }
+        test::call_service(&$app, req.to_request()).await.status()
+    }};
+}
+
+#[actix_web::test]
+async fn test_two_factor_login_and_transfer_step_up() {
+    let app = setup_auth_test!();
+    let login = register_and_login!(app, "twofactor@example.com");
+    let access_token = login["access_token"].as_str().unwrap().to_string();
+
+    let mut account_ids = Vec::new();
+    for _ in 0..2 {
+        let resp = create_account_with!(app, access_token).unwrap();
+        let account: serde_json::Value = test::read_body_json(resp).await;
+        account_ids.push(account["id"].as_u64().unwrap());
+    }
+    let req = test::TestRequest::post()
+        .uri(&format!("/api/accounts/{}/deposit", account_ids[0]))
+        .insert_header(("Authorization", format!("Bearer {}", access_token)))
+        .set_json(serde_json::json!({ "amount": 3_000_000 }))
+        .to_request();
+    assert!(test::call_service(&app, req).await.status().is_success());
+
+    // Small transfers need no second factor, large ones need 2FA to be enabled
+    let ok = actix_web::http::StatusCode::OK;
+    let forbidden = actix_web::http::StatusCode::FORBIDDEN;
+    assert_eq!(
+        transfer_status!(
+            app,
+            access_token,
+            account_ids[0],
+            account_ids[1],
+            100,
+            None::<String>
+        ),
+        ok
+    );
+    assert_eq!(
+        transfer_status!(
+            app,
+            access_token,
+            account_ids[0],
+            account_ids[1],
+            1_500_000,
+            None::<String>
+        ),
+        forbidden
+    );
+
+    // Enroll and activate
+    let req = test::TestRequest::post()
+        .uri("/api/auth/2fa/enroll")
+        .insert_header(("Authorization", format!("Bearer {}", access_token)))
+        .to_request();
+    let enrollment: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+    let secret = enrollment["secret"].as_str().unwrap().to_string();
+    assert!(
+        enrollment["otpauth_uri"]
+            .as_str()
+            .unwrap()
+            .starts_with("otpauth://totp/")
+    );
+
+    let req = test::TestRequest::post()
+        .uri("/api/auth/2fa/verify")
+        .insert_header(("Authorization", format!("Bearer {}", access_token)))
+        .set_json(serde_json::json!({ "code": totp_now(&secret, 0) }))
+        .to_request();
+    let resp: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+    let recovery_codes = resp["recovery_codes"].as_array().unwrap();
+    assert_eq!(recovery_codes.len(), 10);
+
+    // Password login now returns a challenge instead of tokens
+    let req = test::TestRequest::post()
+        .uri("/api/auth/login")
+        .set_json(&LoginRequest {
+            email: "twofactor@example.com".to_string(),
+            password: "Passw0rd-Strong".to_string(),
+        })
+        .to_request();
+    let resp: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(resp["two_factor_required"], true);
+    assert!(resp.get("access_token").is_none());
+    let challenge_token = resp["challenge_token"].as_str().unwrap();
+
+    let req = test::TestRequest::post()
+        .uri("/api/auth/login/2fa")
+        .set_json(serde_json::json!({
+            "challenge_token": challenge_token,
+            "code": recovery_codes[0],
+        }))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), ok);
+    let tokens: serde_json::Value = test::read_body_json(resp).await;
+    let access_token = tokens["access_token"].as_str().unwrap();
+
+    // Large transfers go through with a current code
+    assert_eq!(
+        transfer_status!(
+            app,
+            access_token,
+            account_ids[0],
+            account_ids[1],
+            1_500_000,
+            None::<String>
+        ),
+        forbidden
+    );
+    assert_eq!(
+        transfer_status!(
+            app,
+            access_token,
+            account_ids[0],
+            account_ids[1],
+            1_500_000,
+            Some(totp_now(&secret, 1))
+        ),
+        ok
+    );
+}
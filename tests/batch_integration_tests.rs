@@ -0,0 +1,232 @@
+use actix_web::{App, test, web};
+use std::sync::Arc;
+use yandex_bank_api::application::auth_service::AuthService;
+use yandex_bank_api::application::service::BankService;
+use yandex_bank_api::data::memory::InMemoryAccountRepository;
+use yandex_bank_api::data::user_repository::InMemoryUserRepository;
+use yandex_bank_api::domain::models::{Account, Amount, CreateAccount, Deposit};
+use yandex_bank_api::domain::user::{CreateUser, LoginRequest};
+use yandex_bank_api::presentation::batch::batch_transfer;
+use yandex_bank_api::presentation::handlers::{AppState, create_account, deposit, get_account};
+use yandex_bank_api::presentation::middleware::JwtAuthMiddleware;
+
+macro_rules! setup_batch_test {
+    () => {{
+        let repository = InMemoryAccountRepository::new();
+        let service = BankService::new(Arc::new(repository));
+
+        let user_repository = InMemoryUserRepository::new();
+        let jwt_secret = "test-secret-key-for-batch-tests".to_string();
+        let auth_service = AuthService::new(Arc::new(user_repository), jwt_secret.clone())
+            .with_step_up_threshold(Some(Amount::new(1000)));
+
+        let create_user = CreateUser {
+            email: "payroll@example.com".to_string(),
+            password: "Bank-Passw0rd-123".to_string(),
+        };
+        let _user = auth_service.register_user(create_user).await.unwrap();
+
+        let login_req = LoginRequest {
+            email: "payroll@example.com".to_string(),
+            password: "Bank-Passw0rd-123".to_string(),
+        };
+        let token = auth_service
+            .login(login_req)
+            .await
+            .unwrap()
+            .into_tokens()
+            .unwrap()
+            .access_token;
+
+        let state = web::Data::new(AppState {
+            service,
+            auth_service: Arc::new(auth_service),
+        });
+
+        let app = test::init_service(
+            App::new()
+                .app_data(state.clone())
+                .wrap(JwtAuthMiddleware::new(jwt_secret))
+                .route("/accounts", web::post().to(create_account))
+                .route("/accounts/{id}", web::get().to(get_account))
+                .route("/accounts/{id}/deposit", web::post().to(deposit))
+                .route("/transfers/batch", web::post().to(batch_transfer)),
+        )
+        .await;
+
+        (app, token)
+    }};
+}
+
+macro_rules! create_funded_account {
+    ($app:expr, $token:expr, $name:expr, $amount:expr) => {{
+        let req = test::TestRequest::post()
+            .uri("/accounts")
+            .insert_header(("Authorization", format!("Bearer {}", $token)))
+            .set_json(&CreateAccount {
+                name: $name.to_string(),
+            })
+            .to_request();
+        let account: Account = test::call_and_read_body_json(&$app, req).await;
+
+        let req = test::TestRequest::post()
+            .uri(&format!("/accounts/{}/deposit", account.id))
+            .insert_header(("Authorization", format!("Bearer {}", $token)))
+            .set_json(&Deposit {
+                amount: Amount::new($amount),
+                details: Default::default(),
+            })
+            .to_request();
+        test::call_service(&$app, req).await;
+        account
+    }};
+}
+
+macro_rules! balance_of {
+    ($app:expr, $token:expr, $id:expr) => {{
+        let req = test::TestRequest::get()
+            .uri(&format!("/accounts/{}", $id))
+            .insert_header(("Authorization", format!("Bearer {}", $token)))
+            .to_request();
+        let account: Account = test::call_and_read_body_json(&$app, req).await;
+        account.balance.inner()
+    }};
+}
+
+#[actix_web::test]
+async fn test_csv_batch_all_or_nothing_success() {
+    let (app, token) = setup_batch_test!();
+    let employer = create_funded_account!(app, token, "Employer", 1000);
+    let alice = create_funded_account!(app, token, "Alice", 0);
+    let bob = create_funded_account!(app, token, "Bob", 0);
+
+    let csv = format!(
+        "from_account_id,to_account_id,amount\n{},{},300\n{},{},200\n",
+        employer.id, alice.id, employer.id, bob.id
+    );
+    let req = test::TestRequest::post()
+        .uri("/transfers/batch")
+        .insert_header(("Authorization", format!("Bearer {}", token)))
+        .insert_header(("Content-Type", "text/csv"))
+        .set_payload(csv)
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), actix_web::http::StatusCode::OK);
+    let report: serde_json::Value = test::read_body_json(resp).await;
+    assert_eq!(report["succeeded"], 2);
+    assert_eq!(report["results"][0]["line"], 2);
+    assert_eq!(report["results"][1]["line"], 3);
+    assert_eq!(report["results"][1]["status"], "completed");
+
+    assert_eq!(balance_of!(app, token, employer.id), 500);
+    assert_eq!(balance_of!(app, token, alice.id), 300);
+    assert_eq!(balance_of!(app, token, bob.id), 200);
+}
+
+#[actix_web::test]
+async fn test_csv_batch_all_or_nothing_rejects_on_invalid_row() {
+    let (app, token) = setup_batch_test!();
+    let employer = create_funded_account!(app, token, "Employer", 400);
+    let alice = create_funded_account!(app, token, "Alice", 0);
+
+    let csv = format!(
+        "from_account_id,to_account_id,amount\n{},{},300\n{},{},200\n",
+        employer.id, alice.id, employer.id, alice.id
+    );
+    let req = test::TestRequest::post()
+        .uri("/transfers/batch?mode=all_or_nothing")
+        .insert_header(("Authorization", format!("Bearer {}", token)))
+        .insert_header(("Content-Type", "text/csv"))
+        .set_payload(csv)
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(
+        resp.status(),
+        actix_web::http::StatusCode::UNPROCESSABLE_ENTITY
+    );
+    let report: serde_json::Value = test::read_body_json(resp).await;
+    assert_eq!(report["succeeded"], 0);
+    assert_eq!(report["results"][0]["status"], "rejected");
+    assert_eq!(report["results"][1]["status"], "failed");
+    assert_eq!(report["results"][1]["line"], 3);
+    assert_eq!(report["results"][1]["reason"], "Insufficient funds");
+
+    assert_eq!(balance_of!(app, token, employer.id), 400);
+    assert_eq!(balance_of!(app, token, alice.id), 0);
+}
+
+#[actix_web::test]
+async fn test_json_batch_best_effort_executes_valid_rows() {
+    let (app, token) = setup_batch_test!();
+    let employer = create_funded_account!(app, token, "Employer", 400);
+    let alice = create_funded_account!(app, token, "Alice", 0);
+
+    let req = test::TestRequest::post()
+        .uri("/transfers/batch?mode=best_effort")
+        .insert_header(("Authorization", format!("Bearer {}", token)))
+        .set_json(serde_json::json!([
+            { "from_account_id": employer.id, "to_account_id": alice.id, "amount": 100 },
+            { "from_account_id": employer.id, "to_account_id": 99999, "amount": 100 },
+            { "from_account_id": employer.id, "to_account_id": alice.id, "amount": 50 }
+        ]))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), actix_web::http::StatusCode::OK);
+    let report: serde_json::Value = test::read_body_json(resp).await;
+    assert_eq!(report["succeeded"], 2);
+    assert_eq!(report["failed"], 1);
+    assert_eq!(report["results"][1]["line"], 2);
+    assert_eq!(report["results"][1]["reason"], "Account not found");
+
+    assert_eq!(balance_of!(app, token, employer.id), 250);
+    assert_eq!(balance_of!(app, token, alice.id), 150);
+}
+
+#[actix_web::test]
+async fn test_batch_steps_up_on_total_amount() {
+    let (app, token) = setup_batch_test!();
+    let employer = create_funded_account!(app, token, "Employer", 1500);
+    let alice = create_funded_account!(app, token, "Alice", 0);
+
+    // Every row is below the step-up threshold, but together they exceed it
+    let req = test::TestRequest::post()
+        .uri("/transfers/batch?mode=best_effort")
+        .insert_header(("Authorization", format!("Bearer {}", token)))
+        .set_json(serde_json::json!([
+            { "from_account_id": employer.id, "to_account_id": alice.id, "amount": 400 },
+            { "from_account_id": employer.id, "to_account_id": alice.id, "amount": 400 },
+            { "from_account_id": employer.id, "to_account_id": alice.id, "amount": 400 }
+        ]))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), actix_web::http::StatusCode::FORBIDDEN);
+
+    assert_eq!(balance_of!(app, token, employer.id), 1500);
+    assert_eq!(balance_of!(app, token, alice.id), 0);
+}
+
+#[actix_web::test]
+async fn test_malformed_csv_batch_is_rejected() {
+    let (app, token) = setup_batch_test!();
+
+    let req = test::TestRequest::post()
+        .uri("/transfers/batch")
+        .insert_header(("Authorization", format!("Bearer {}", token)))
+        .insert_header(("Content-Type", "text/csv"))
+        .set_payload("from_account_id,to_account_id,amount\n1,2,-5\n")
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), actix_web::http::StatusCode::BAD_REQUEST);
+}
+
+#[actix_web::test]
+async fn test_batch_requires_authentication() {
+    let (app, _token) = setup_batch_test!();
+
+    let req = test::TestRequest::post()
+        .uri("/transfers/batch")
+        .set_json(serde_json::json!([]))
+        .to_request();
+    let resp = test::try_call_service(&app, req).await;
+    assert!(resp.is_err());
+}
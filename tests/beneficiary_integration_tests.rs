@@ -0,0 +1,251 @@
+use actix_web::http::StatusCode;
+use actix_web::{App, test, web};
+use chrono::Duration;
+use std::sync::Arc;
+use yandex_bank_api::application::auth_service::AuthService;
+use yandex_bank_api::application::service::BankService;
+use yandex_bank_api::data::memory::InMemoryAccountRepository;
+use yandex_bank_api::data::user_repository::InMemoryUserRepository;
+use yandex_bank_api::domain::beneficiary::CreateBeneficiary;
+use yandex_bank_api::domain::models::{Amount, CreateAccount, Deposit, Transfer};
+use yandex_bank_api::domain::user::{CreateUser, LoginRequest};
+use yandex_bank_api::presentation::auth::{login, register};
+use yandex_bank_api::presentation::beneficiaries::{
+    add_beneficiary, delete_beneficiary, get_beneficiary, list_beneficiaries, update_beneficiary,
+};
+use yandex_bank_api::presentation::handlers::{AppState, create_account, deposit, transfer};
+use yandex_bank_api::presentation::middleware::JwtAuthMiddleware;
+
+const PASSWORD: &str = "Passw0rd-Strong";
+
+macro_rules! setup_beneficiary_test {
+    ($cooling_off:expr) => {{
+        let jwt_secret = "test-secret-key-for-beneficiary-tests".to_string();
+        let service = BankService::new(Arc::new(InMemoryAccountRepository::new()))
+            .with_payee_cooling_off($cooling_off)
+            .with_payee_cooling_off_threshold(Amount::new(1000));
+        let state = web::Data::new(AppState {
+            service,
+            auth_service: Arc::new(AuthService::new(
+                Arc::new(InMemoryUserRepository::new()),
+                jwt_secret.clone(),
+            )),
+        });
+
+        let app = test::init_service(
+            App::new()
+                .app_data(state.clone())
+                .wrap(JwtAuthMiddleware::new(jwt_secret))
+                .service(
+                    web::scope("/api")
+                        .route("/auth/register", web::post().to(register))
+                        .route("/auth/login", web::post().to(login))
+                        .route("/accounts", web::post().to(create_account))
+                        .route("/accounts/{id}/deposit", web::post().to(deposit))
+                        .route("/transfers", web::post().to(transfer))
+                        .route("/beneficiaries", web::get().to(list_beneficiaries))
+                        .route("/beneficiaries", web::post().to(add_beneficiary))
+                        .route("/beneficiaries/{id}", web::get().to(get_beneficiary))
+                        .route("/beneficiaries/{id}", web::patch().to(update_beneficiary))
+                        .route("/beneficiaries/{id}", web::delete().to(delete_beneficiary)),
+                ),
+        )
+        .await;
+        (app, state)
+    }};
+}
+
+// Registers, logs in and opens an account with `$balance`; returns (bearer header, account id)
+macro_rules! customer_with_account {
+    ($app:expr, $email:expr, $balance:expr) => {{
+        let req = test::TestRequest::post()
+            .uri("/api/auth/register")
+            .set_json(&CreateUser {
+                email: $email.to_string(),
+                password: PASSWORD.to_string(),
+            })
+            .to_request();
+        test::call_service(&$app, req).await;
+        let req = test::TestRequest::post()
+            .uri("/api/auth/login")
+            .set_json(&LoginRequest {
+                email: $email.to_string(),
+                password: PASSWORD.to_string(),
+            })
+            .to_request();
+        let login: serde_json::Value = test::call_and_read_body_json(&$app, req).await;
+        let auth = (
+            "Authorization",
+            format!("Bearer {}", login["access_token"].as_str().unwrap()),
+        );
+
+        let req = test::TestRequest::post()
+            .uri("/api/accounts")
+            .insert_header(auth.clone())
+            .set_json(&CreateAccount {
+                name: "Main".to_string(),
+            })
+            .to_request();
+        let account: serde_json::Value = test::call_and_read_body_json(&$app, req).await;
+        let account_id = account["id"].as_u64().unwrap() as u32;
+        let req = test::TestRequest::post()
+            .uri(&format!("/api/accounts/{}/deposit", account_id))
+            .insert_header(auth.clone())
+            .set_json(&Deposit {
+                amount: Amount::new($balance),
+                details: Default::default(),
+            })
+            .to_request();
+        test::call_service(&$app, req).await;
+        (auth, account_id)
+    }};
+}
+
+macro_rules! pay_beneficiary {
+    ($app:expr, $auth:expr, $from:expr, $beneficiary_id:expr, $amount:expr) => {{
+        let req = test::TestRequest::post()
+            .uri("/api/transfers")
+            .insert_header($auth.clone())
+            .set_json(serde_json::json!({
+                "from_account_id": $from,
+                "beneficiary_id": $beneficiary_id,
+                "amount": $amount,
+            }))
+            .to_request();
+        test::call_service(&$app, req).await.status()
+    }};
+}
+
+#[actix_web::test]
+async fn test_beneficiary_crud_and_transfer() {
+    let (app, state) = setup_beneficiary_test!(Duration::hours(24));
+    let (alice, alice_account) = customer_with_account!(app, "alice@example.com", 5000);
+    let (bob, bob_account) = customer_with_account!(app, "bob@example.com", 0);
+
+    let req = test::TestRequest::post()
+        .uri("/api/beneficiaries")
+        .insert_header(alice.clone())
+        .set_json(&CreateBeneficiary {
+            name: "Bob Smith".to_string(),
+            account_id: bob_account,
+            nickname: Some("Flatmate".to_string()),
+        })
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::CREATED);
+    let beneficiary: serde_json::Value = test::read_body_json(resp).await;
+    let beneficiary_id = beneficiary["id"].as_str().unwrap().to_string();
+    assert!(beneficiary.get("user_id").is_none());
+
+    // Saving the same account twice, or an account that does not exist, fails
+    for account_id in [bob_account, bob_account.wrapping_add(1)] {
+        let req = test::TestRequest::post()
+            .uri("/api/beneficiaries")
+            .insert_header(alice.clone())
+            .set_json(&CreateBeneficiary {
+                name: "Again".to_string(),
+                account_id,
+                nickname: None,
+            })
+            .to_request();
+        assert!(
+            test::call_service(&app, req)
+                .await
+                .status()
+                .is_client_error()
+        );
+    }
+
+    let req = test::TestRequest::patch()
+        .uri(&format!("/api/beneficiaries/{}", beneficiary_id))
+        .insert_header(alice.clone())
+        .set_json(serde_json::json!({"nickname": ""}))
+        .to_request();
+    let updated: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(updated["name"], "Bob Smith");
+    assert!(updated.get("nickname").is_none());
+
+    assert_eq!(
+        pay_beneficiary!(app, alice, alice_account, beneficiary_id, 1000),
+        StatusCode::OK
+    );
+    assert_eq!(
+        state
+            .service
+            .get_any_account(bob_account)
+            .await
+            .unwrap()
+            .balance
+            .inner(),
+        1000
+    );
+    // Large amounts wait for the cooling-off period; paying the account directly is unaffected
+    assert_eq!(
+        pay_beneficiary!(app, alice, alice_account, beneficiary_id, 1001),
+        StatusCode::FORBIDDEN
+    );
+    let req = test::TestRequest::post()
+        .uri("/api/transfers")
+        .insert_header(alice.clone())
+        .set_json(&Transfer {
+            from_account_id: alice_account,
+            to_account_id: bob_account,
+            amount: Amount::new(1001),
+            beneficiary_id: None,
+            details: Default::default(),
+        })
+        .to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), StatusCode::OK);
+
+    // Beneficiaries are private
+    let req = test::TestRequest::get()
+        .uri(&format!("/api/beneficiaries/{}", beneficiary_id))
+        .insert_header(bob.clone())
+        .to_request();
+    assert_eq!(
+        test::call_service(&app, req).await.status(),
+        StatusCode::NOT_FOUND
+    );
+    assert_eq!(
+        pay_beneficiary!(app, bob, bob_account, beneficiary_id, 1),
+        StatusCode::NOT_FOUND
+    );
+
+    let req = test::TestRequest::delete()
+        .uri(&format!("/api/beneficiaries/{}", beneficiary_id))
+        .insert_header(alice.clone())
+        .to_request();
+    assert_eq!(
+        test::call_service(&app, req).await.status(),
+        StatusCode::NO_CONTENT
+    );
+    let req = test::TestRequest::get()
+        .uri("/api/beneficiaries")
+        .insert_header(alice.clone())
+        .to_request();
+    let listed: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+    assert!(listed.as_array().unwrap().is_empty());
+}
+
+#[actix_web::test]
+async fn test_large_transfer_allowed_after_cooling_off() {
+    let (app, _) = setup_beneficiary_test!(Duration::zero());
+    let (alice, alice_account) = customer_with_account!(app, "alice@example.com", 5000);
+    let (_, bob_account) = customer_with_account!(app, "bob@example.com", 0);
+
+    let req = test::TestRequest::post()
+        .uri("/api/beneficiaries")
+        .insert_header(alice.clone())
+        .set_json(&CreateBeneficiary {
+            name: "Bob".to_string(),
+            account_id: bob_account,
+            nickname: None,
+        })
+        .to_request();
+    let beneficiary: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+
+    assert_eq!(
+        pay_beneficiary!(app, alice, alice_account, beneficiary["id"], 4000),
+        StatusCode::OK
+    );
+}
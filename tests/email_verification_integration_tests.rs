@@ -0,0 +1,140 @@
+use actix_web::http::StatusCode;
+use actix_web::{App, test, web};
+use std::sync::Arc;
+use yandex_bank_api::application::auth_service::AuthService;
+use yandex_bank_api::application::service::BankService;
+use yandex_bank_api::data::memory::InMemoryAccountRepository;
+use yandex_bank_api::data::user_repository::InMemoryUserRepository;
+use yandex_bank_api::domain::models::CreateAccount;
+use yandex_bank_api::domain::notifier::Notification;
+use yandex_bank_api::domain::user::{CreateUser, LoginRequest, VerifyEmailRequest};
+use yandex_bank_api::infrastructure::notifier::InMemoryNotifier;
+use yandex_bank_api::presentation::auth::{
+    login, register, resend_verification_email, verify_email,
+};
+use yandex_bank_api::presentation::handlers::{AppState, create_account};
+use yandex_bank_api::presentation::middleware::JwtAuthMiddleware;
+
+const EMAIL: &str = "newcomer@example.com";
+const PASSWORD: &str = "Passw0rd-Strong";
+
+macro_rules! setup_verification_test {
+    () => {{
+        let jwt_secret = "test-secret-key-for-verification-tests".to_string();
+        let notifier = InMemoryNotifier::new();
+        let auth_service = Arc::new(
+            AuthService::new(Arc::new(InMemoryUserRepository::new()), jwt_secret.clone())
+                .with_notifier(Arc::new(notifier.clone())),
+        );
+        let service = BankService::new(Arc::new(InMemoryAccountRepository::new()))
+            .with_customer_policy(auth_service.clone());
+
+        let state = web::Data::new(AppState {
+            service,
+            auth_service,
+        });
+
+        let app = test::init_service(
+            App::new()
+                .app_data(state.clone())
+                .wrap(JwtAuthMiddleware::new(jwt_secret))
+                .service(
+                    web::scope("/api")
+                        .route("/auth/register", web::post().to(register))
+                        .route("/auth/login", web::post().to(login))
+                        .route("/auth/verify-email", web::post().to(verify_email))
+                        .route(
+                            "/auth/verify-email/resend",
+                            web::post().to(resend_verification_email),
+                        )
+                        .route("/accounts", web::post().to(create_account)),
+                ),
+        )
+        .await;
+        (app, notifier)
+    }};
+}
+
+macro_rules! create_account_status {
+    ($app:expr, $token:expr) => {{
+        let req = test::TestRequest::post()
+            .uri("/api/accounts")
+            .insert_header(("Authorization", format!("Bearer {}", $token)))
+            .set_json(&CreateAccount {
+                name: "Savings".to_string(),
+            })
+            .to_request();
+        test::call_service(&$app, req).await.status()
+    }};
+}
+
+#[actix_web::test]
+async fn test_accounts_can_be_opened_once_email_is_verified() {
+    let (app, notifier) = setup_verification_test!();
+
+    let req = test::TestRequest::post()
+        .uri("/api/auth/register")
+        .set_json(&CreateUser {
+            email: EMAIL.to_string(),
+            password: PASSWORD.to_string(),
+        })
+        .to_request();
+    let user: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(user["status"], "pending_verification");
+
+    // Pending users can sign in, but not open accounts
+    let req = test::TestRequest::post()
+        .uri("/api/auth/login")
+        .set_json(&LoginRequest {
+            email: EMAIL.to_string(),
+            password: PASSWORD.to_string(),
+        })
+        .to_request();
+    let login: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+    let token = login["access_token"].as_str().unwrap();
+    assert_eq!(create_account_status!(app, token), StatusCode::FORBIDDEN);
+
+    // The email from registration is too recent to ask for another one
+    let req = test::TestRequest::post()
+        .uri("/api/auth/verify-email/resend")
+        .insert_header(("Authorization", format!("Bearer {}", token)))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::TOO_MANY_REQUESTS);
+    assert!(resp.headers().contains_key("Retry-After"));
+
+    let verification_token = notifier
+        .sent_to(EMAIL)
+        .await
+        .into_iter()
+        .find_map(|n| match n {
+            Notification::EmailVerification { token, .. } => Some(token),
+            _ => None,
+        })
+        .expect("No verification email sent");
+    let req = test::TestRequest::post()
+        .uri("/api/auth/verify-email")
+        .set_json(&VerifyEmailRequest {
+            token: verification_token.clone(),
+        })
+        .to_request();
+    assert_eq!(
+        test::call_service(&app, req).await.status(),
+        StatusCode::NO_CONTENT
+    );
+
+    // The same access token works now, no new login needed
+    assert_eq!(create_account_status!(app, token), StatusCode::CREATED);
+
+    // Tokens are single use
+    let req = test::TestRequest::post()
+        .uri("/api/auth/verify-email")
+        .set_json(&VerifyEmailRequest {
+            token: verification_token,
+        })
+        .to_request();
+    assert_eq!(
+        test::call_service(&app, req).await.status(),
+        StatusCode::UNAUTHORIZED
+    );
+}
@@ -0,0 +1,257 @@
+use actix_web::{App, test, web};
+use std::sync::Arc;
+use yandex_bank_api::application::auth_service::AuthService;
+use yandex_bank_api::application::service::BankService;
+use yandex_bank_api::data::memory::InMemoryAccountRepository;
+use yandex_bank_api::data::user_repository::InMemoryUserRepository;
+use yandex_bank_api::domain::models::{
+    Account, Amount, CreateAccount, Deposit, TransactionKind, Transfer,
+};
+use yandex_bank_api::domain::user::{CreateUser, LoginRequest};
+use yandex_bank_api::infrastructure::iso20022::{
+    parse_camt053, parse_pain001, write_camt053, write_pain001,
+};
+use yandex_bank_api::presentation::batch::batch_transfer;
+use yandex_bank_api::presentation::handlers::{
+    AppState, create_account, deposit, get_account, get_statement,
+};
+use yandex_bank_api::presentation::middleware::JwtAuthMiddleware;
+
+const PAIN001_SAMPLE: &str = include_str!("fixtures/pain.001.sample.xml");
+const CAMT053_SAMPLE: &str = include_str!("fixtures/camt.053.sample.xml");
+
+macro_rules! setup_iso_test {
+    () => {{
+        let repository = InMemoryAccountRepository::new();
+        let service = BankService::new(Arc::new(repository));
+
+        let user_repository = InMemoryUserRepository::new();
+        let jwt_secret = "test-secret-key-for-iso-tests".to_string();
+        let auth_service = AuthService::new(Arc::new(user_repository), jwt_secret.clone());
+
+        let create_user = CreateUser {
+            email: "iso@example.com".to_string(),
+            password: "Bank-Passw0rd-123".to_string(),
+        };
+        let _user = auth_service.register_user(create_user).await.unwrap();
+
+        let login_req = LoginRequest {
+            email: "iso@example.com".to_string(),
+            password: "Bank-Passw0rd-123".to_string(),
+        };
+        let token = auth_service
+            .login(login_req)
+            .await
+            .unwrap()
+            .into_tokens()
+            .unwrap()
+            .access_token;
+
+        let state = web::Data::new(AppState {
+            service,
+            auth_service: Arc::new(auth_service),
+        });
+
+        let app = test::init_service(
+            App::new()
+                .app_data(state.clone())
+                .wrap(JwtAuthMiddleware::new(jwt_secret))
+                .route("/accounts", web::post().to(create_account))
+                .route("/accounts/{id}", web::get().to(get_account))
+                .route("/accounts/{id}/deposit", web::post().to(deposit))
+                .route("/accounts/{id}/statement", web::get().to(get_statement))
+                .route("/transfers/batch", web::post().to(batch_transfer)),
+        )
+        .await;
+
+        (app, token)
+    }};
+}
+
+#[actix_web::test]
+async fn test_pain001_sample_is_parsed() {
+    let rows = parse_pain001(PAIN001_SAMPLE).unwrap();
+
+    assert_eq!(rows.len(), 3);
+    assert_eq!(rows[0].line, 1);
+    assert_eq!(rows[0].transfer.from_account_id, 1001);
+    assert_eq!(rows[0].transfer.to_account_id, 2001);
+    assert_eq!(rows[0].transfer.amount.inner(), 100_000);
+    assert_eq!(rows[1].transfer.amount.inner(), 70_050);
+    assert_eq!(rows[2].transfer.from_account_id, 1002);
+    assert_eq!(rows[2].transfer.amount.inner(), 5_000);
+}
+
+#[actix_web::test]
+async fn test_pain001_sample_round_trip() {
+    let rows = parse_pain001(PAIN001_SAMPLE).unwrap();
+    let transfers: Vec<_> = rows.iter().map(|r| r.transfer.clone()).collect();
+
+    let xml = write_pain001("PAYROLL-2024-03", &transfers).unwrap();
+    let reparsed = parse_pain001(&xml).unwrap();
+
+    assert_eq!(reparsed.len(), rows.len());
+    for (original, round_tripped) in rows.iter().zip(reparsed.iter()) {
+        assert_eq!(original.line, round_tripped.line);
+        assert_eq!(
+            original.transfer.from_account_id,
+            round_tripped.transfer.from_account_id
+        );
+        assert_eq!(
+            original.transfer.to_account_id,
+            round_tripped.transfer.to_account_id
+        );
+        assert_eq!(original.transfer.amount, round_tripped.transfer.amount);
+    }
+}
+
+#[actix_web::test]
+async fn test_pain001_with_wrong_control_sum_is_rejected() {
+    let xml = PAIN001_SAMPLE.replace("<CtrlSum>1750.50</CtrlSum>", "<CtrlSum>1.00</CtrlSum>");
+    assert!(parse_pain001(&xml).is_err());
+}
+
+#[actix_web::test]
+async fn test_pain001_with_foreign_currency_is_rejected() {
+    let xml = PAIN001_SAMPLE.replace("Ccy=\"RUB\">700.50", "Ccy=\"EUR\">700.50");
+    let error = parse_pain001(&xml).unwrap_err();
+    assert!(error.to_string().contains("transaction 2"));
+}
+
+#[actix_web::test]
+async fn test_camt053_sample_is_parsed() {
+    let statement = parse_camt053(CAMT053_SAMPLE).unwrap();
+
+    assert_eq!(statement.account_id, 2001);
+    assert_eq!(statement.account_name, "Alice Salary");
+    assert_eq!(statement.opening_balance.inner(), 25_000);
+    assert_eq!(statement.closing_balance.inner(), 110_000);
+    assert!(statement.from.is_some());
+    assert_eq!(statement.transactions.len(), 2);
+    assert_eq!(statement.transactions[0].kind, TransactionKind::TransferIn);
+    assert_eq!(
+        statement.transactions[0].counterparty_account_id,
+        Some(1001)
+    );
+    assert_eq!(statement.transactions[0].balance_after.inner(), 125_000);
+    assert_eq!(statement.transactions[1].kind, TransactionKind::Withdrawal);
+    assert_eq!(statement.transactions[1].counterparty_account_id, None);
+}
+
+#[actix_web::test]
+async fn test_camt053_sample_round_trip() {
+    let statement = parse_camt053(CAMT053_SAMPLE).unwrap();
+
+    let xml = write_camt053(&statement).unwrap();
+    let reparsed = parse_camt053(&xml).unwrap();
+
+    assert_eq!(reparsed, statement);
+}
+
+#[actix_web::test]
+async fn test_camt053_with_inconsistent_closing_balance_is_rejected() {
+    let xml = CAMT053_SAMPLE.replace(
+        "<Amt Ccy=\"RUB\">1100.00</Amt>",
+        "<Amt Ccy=\"RUB\">1200.00</Amt>",
+    );
+    assert!(parse_camt053(&xml).is_err());
+}
+
+#[actix_web::test]
+async fn test_pain001_batch_import_and_camt053_export() {
+    let (app, token) = setup_iso_test!();
+
+    let mut accounts = Vec::new();
+    for name in ["Employer", "Alice", "Bob"] {
+        let req = test::TestRequest::post()
+            .uri("/accounts")
+            .insert_header(("Authorization", format!("Bearer {}", token)))
+            .set_json(&CreateAccount {
+                name: name.to_string(),
+            })
+            .to_request();
+        let account: Account = test::call_and_read_body_json(&app, req).await;
+        accounts.push(account);
+    }
+    let req = test::TestRequest::post()
+        .uri(&format!("/accounts/{}/deposit", accounts[0].id))
+        .insert_header(("Authorization", format!("Bearer {}", token)))
+        .set_json(&Deposit {
+            amount: Amount::new(100_000),
+            details: Default::default(),
+        })
+        .to_request();
+    test::call_service(&app, req).await;
+
+    // Import a generated pain.001 as a batch
+    let transfers = vec![
+        Transfer {
+            from_account_id: accounts[0].id,
+            to_account_id: accounts[1].id,
+            amount: Amount::new(60_000),
+            beneficiary_id: None,
+            details: Default::default(),
+        },
+        Transfer {
+            from_account_id: accounts[0].id,
+            to_account_id: accounts[2].id,
+            amount: Amount::new(15_050),
+            beneficiary_id: None,
+            details: Default::default(),
+        },
+    ];
+    let xml = write_pain001("PAYROLL-TEST", &transfers).unwrap();
+    let req = test::TestRequest::post()
+        .uri("/transfers/batch")
+        .insert_header(("Authorization", format!("Bearer {}", token)))
+        .insert_header(("Content-Type", "application/xml"))
+        .set_payload(xml)
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), actix_web::http::StatusCode::OK);
+    let report: serde_json::Value = test::read_body_json(resp).await;
+    assert_eq!(report["succeeded"], 2);
+
+    // Export the employer statement as camt.053
+    let req = test::TestRequest::get()
+        .uri(&format!("/accounts/{}/statement", accounts[0].id))
+        .insert_header(("Authorization", format!("Bearer {}", token)))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), actix_web::http::StatusCode::OK);
+    assert_eq!(
+        resp.headers().get("content-type").unwrap(),
+        "application/xml"
+    );
+    let body = test::read_body(resp).await;
+    let statement = parse_camt053(std::str::from_utf8(&body).unwrap()).unwrap();
+
+    assert_eq!(statement.account_id, accounts[0].id);
+    assert_eq!(statement.opening_balance.inner(), 0);
+    assert_eq!(statement.closing_balance.inner(), 24_950);
+    let kinds: Vec<_> = statement.transactions.iter().map(|t| t.kind).collect();
+    assert_eq!(
+        kinds,
+        vec![
+            TransactionKind::Deposit,
+            TransactionKind::TransferOut,
+            TransactionKind::TransferOut
+        ]
+    );
+    assert_eq!(
+        statement.transactions[2].counterparty_account_id,
+        Some(accounts[2].id)
+    );
+}
+
+#[actix_web::test]
+async fn test_statement_for_unknown_account_returns_not_found() {
+    let (app, token) = setup_iso_test!();
+
+    let req = test::TestRequest::get()
+        .uri("/accounts/99999/statement")
+        .insert_header(("Authorization", format!("Bearer {}", token)))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), actix_web::http::StatusCode::NOT_FOUND);
+}
@@ -0,0 +1,236 @@
+use actix_web::http::StatusCode;
+use actix_web::{App, test, web};
+use std::sync::Arc;
+use yandex_bank_api::application::auth_service::AuthService;
+use yandex_bank_api::application::service::BankService;
+use yandex_bank_api::data::memory::InMemoryAccountRepository;
+use yandex_bank_api::data::user_repository::InMemoryUserRepository;
+use yandex_bank_api::domain::models::{Amount, CreateAccount, Deposit, Withdraw};
+use yandex_bank_api::domain::user::{CreateUser, LoginRequest};
+use yandex_bank_api::presentation::auth::{login, register};
+use yandex_bank_api::presentation::handlers::{
+    AppState, create_account, deposit, get_account, withdraw,
+};
+use yandex_bank_api::presentation::holders::{add_holder, list_holders, remove_holder};
+use yandex_bank_api::presentation::middleware::JwtAuthMiddleware;
+
+const PASSWORD: &str = "Passw0rd-Strong";
+
+macro_rules! setup_joint_account_test {
+    () => {{
+        let jwt_secret = "test-secret-key-for-joint-account-tests".to_string();
+        let state = web::Data::new(AppState {
+            service: BankService::new(Arc::new(InMemoryAccountRepository::new())),
+            auth_service: Arc::new(AuthService::new(
+                Arc::new(InMemoryUserRepository::new()),
+                jwt_secret.clone(),
+            )),
+        });
+
+        test::init_service(
+            App::new()
+                .app_data(state)
+                .wrap(JwtAuthMiddleware::new(jwt_secret))
+                .service(
+                    web::scope("/api")
+                        .route("/auth/register", web::post().to(register))
+                        .route("/auth/login", web::post().to(login))
+                        .route("/accounts", web::post().to(create_account))
+                        .route("/accounts/{id}", web::get().to(get_account))
+                        .route("/accounts/{id}/deposit", web::post().to(deposit))
+                        .route("/accounts/{id}/withdraw", web::post().to(withdraw))
+                        .route("/accounts/{id}/holders", web::get().to(list_holders))
+                        .route("/accounts/{id}/holders", web::post().to(add_holder))
+                        .route(
+                            "/accounts/{id}/holders/{user_id}",
+                            web::delete().to(remove_holder),
+                        ),
+                ),
+        )
+        .await
+    }};
+}
+
+// Registers and logs in; returns (user id, bearer header)
+macro_rules! sign_up {
+    ($app:expr, $email:expr) => {{
+        let req = test::TestRequest::post()
+            .uri("/api/auth/register")
+            .set_json(&CreateUser {
+                email: $email.to_string(),
+                password: PASSWORD.to_string(),
+            })
+            .to_request();
+        let user: serde_json::Value = test::call_and_read_body_json(&$app, req).await;
+        let req = test::TestRequest::post()
+            .uri("/api/auth/login")
+            .set_json(&LoginRequest {
+                email: $email.to_string(),
+                password: PASSWORD.to_string(),
+            })
+            .to_request();
+        let login: serde_json::Value = test::call_and_read_body_json(&$app, req).await;
+        (
+            user["id"].as_str().unwrap().to_string(),
+            (
+                "Authorization",
+                format!("Bearer {}", login["access_token"].as_str().unwrap()),
+            ),
+        )
+    }};
+}
+
+macro_rules! withdraw_status {
+    ($app:expr, $auth:expr, $account_id:expr, $amount:expr) => {{
+        let req = test::TestRequest::post()
+            .uri(&format!("/api/accounts/{}/withdraw", $account_id))
+            .insert_header($auth.clone())
+            .set_json(&Withdraw {
+                amount: Amount::new($amount),
+                details: Default::default(),
+            })
+            .to_request();
+        test::call_service(&$app, req).await.status()
+    }};
+}
+
+#[actix_web::test]
+async fn test_viewer_reads_balance_but_cannot_withdraw() {
+    let app = setup_joint_account_test!();
+    let (_, owner) = sign_up!(app, "owner@example.com");
+    let (viewer_id, viewer) = sign_up!(app, "viewer@example.com");
+
+    let req = test::TestRequest::post()
+        .uri("/api/accounts")
+        .insert_header(owner.clone())
+        .set_json(&CreateAccount {
+            name: "Household".to_string(),
+        })
+        .to_request();
+    let account: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+    let account_id = account["id"].as_u64().unwrap();
+    let req = test::TestRequest::post()
+        .uri(&format!("/api/accounts/{}/deposit", account_id))
+        .insert_header(owner.clone())
+        .set_json(&Deposit {
+            amount: Amount::new(1000),
+            details: Default::default(),
+        })
+        .to_request();
+    test::call_service(&app, req).await;
+
+    // Not a holder yet: the account does not exist as far as they can tell
+    let req = test::TestRequest::get()
+        .uri(&format!("/api/accounts/{}", account_id))
+        .insert_header(viewer.clone())
+        .to_request();
+    assert_eq!(
+        test::call_service(&app, req).await.status(),
+        StatusCode::NOT_FOUND
+    );
+
+    let req = test::TestRequest::post()
+        .uri(&format!("/api/accounts/{}/holders", account_id))
+        .insert_header(owner.clone())
+        .set_json(serde_json::json!({"email": "viewer@example.com", "permission": "viewer"}))
+        .to_request();
+    let holders: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(holders[0]["permission"], "owner");
+    assert_eq!(holders[1]["user_id"], viewer_id.as_str());
+    assert_eq!(holders[1]["permission"], "viewer");
+
+    let req = test::TestRequest::get()
+        .uri(&format!("/api/accounts/{}", account_id))
+        .insert_header(viewer.clone())
+        .to_request();
+    let seen: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(seen["balance"], 1000);
+    assert_eq!(
+        withdraw_status!(app, viewer, account_id, 100),
+        StatusCode::FORBIDDEN
+    );
+
+    // Viewers cannot invite anyone either
+    let req = test::TestRequest::post()
+        .uri(&format!("/api/accounts/{}/holders", account_id))
+        .insert_header(viewer.clone())
+        .set_json(serde_json::json!({"email": "owner@example.com", "permission": "co_owner"}))
+        .to_request();
+    assert_eq!(
+        test::call_service(&app, req).await.status(),
+        StatusCode::FORBIDDEN
+    );
+
+    let req = test::TestRequest::delete()
+        .uri(&format!(
+            "/api/accounts/{}/holders/{}",
+            account_id, viewer_id
+        ))
+        .insert_header(owner.clone())
+        .to_request();
+    assert_eq!(
+        test::call_service(&app, req).await.status(),
+        StatusCode::NO_CONTENT
+    );
+    let req = test::TestRequest::get()
+        .uri(&format!("/api/accounts/{}", account_id))
+        .insert_header(viewer.clone())
+        .to_request();
+    assert_eq!(
+        test::call_service(&app, req).await.status(),
+        StatusCode::NOT_FOUND
+    );
+}
+
+#[actix_web::test]
+async fn test_limited_holder_withdraws_up_to_limit() {
+    let app = setup_joint_account_test!();
+    let (_, owner) = sign_up!(app, "parent@example.com");
+    let (_, child) = sign_up!(app, "child@example.com");
+
+    let req = test::TestRequest::post()
+        .uri("/api/accounts")
+        .insert_header(owner.clone())
+        .set_json(&CreateAccount {
+            name: "Pocket money".to_string(),
+        })
+        .to_request();
+    let account: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+    let account_id = account["id"].as_u64().unwrap();
+    let req = test::TestRequest::post()
+        .uri(&format!("/api/accounts/{}/deposit", account_id))
+        .insert_header(owner.clone())
+        .set_json(&Deposit {
+            amount: Amount::new(1000),
+            details: Default::default(),
+        })
+        .to_request();
+    test::call_service(&app, req).await;
+
+    let req = test::TestRequest::post()
+        .uri(&format!("/api/accounts/{}/holders", account_id))
+        .insert_header(owner.clone())
+        .set_json(serde_json::json!({
+            "email": "child@example.com",
+            "permission": "transfer_up_to_limit",
+            "transfer_limit": 200
+        }))
+        .to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), StatusCode::OK);
+
+    assert_eq!(
+        withdraw_status!(app, child, account_id, 201),
+        StatusCode::FORBIDDEN
+    );
+    assert_eq!(
+        withdraw_status!(app, child, account_id, 200),
+        StatusCode::OK
+    );
+
+    let req = test::TestRequest::get()
+        .uri(&format!("/api/accounts/{}/holders", account_id))
+        .insert_header(child.clone())
+        .to_request();
+    let holders: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(holders[1]["transfer_limit"], 200);
+}
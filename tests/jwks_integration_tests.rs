@@ -0,0 +1,185 @@
+use actix_web::http::StatusCode;
+use actix_web::{App, test, web};
+use jsonwebtoken::jwk::JwkSet;
+use jsonwebtoken::{DecodingKey, Validation, decode, decode_header};
+use std::sync::Arc;
+use yandex_bank_api::application::auth_service::AuthService;
+use yandex_bank_api::application::service::BankService;
+use yandex_bank_api::data::memory::InMemoryAccountRepository;
+use yandex_bank_api::data::user_repository::InMemoryUserRepository;
+use yandex_bank_api::domain::models::CreateAccount;
+use yandex_bank_api::domain::user::{CreateUser, LoginRequest, Role};
+use yandex_bank_api::infrastructure::jwt_keys::{JwtKey, JwtKeySet};
+use yandex_bank_api::infrastructure::security::{generate_token, sign_access_token};
+use yandex_bank_api::presentation::auth::{jwks, login, register};
+use yandex_bank_api::presentation::handlers::{AppState, create_account};
+use yandex_bank_api::presentation::middleware::JwtAuthMiddleware;
+
+const PASSWORD: &str = "Passw0rd-Strong";
+const JWT_SECRET: &str = "test-secret-key-for-jwks-tests";
+const RSA_PEM: &str = include_str!("fixtures/jwt_rs256_private.pem");
+
+// The RSA key was rotated out: it still verifies, the Ed25519 key signs
+fn rotated_keys(old: &JwtKey) -> Arc<JwtKeySet> {
+    Arc::new(
+        JwtKeySet::new(JwtKey::generate_ed25519("ed-2"))
+            .with_verification_key(old.clone())
+            .with_verification_key(JwtKey::from_secret(JWT_SECRET)),
+    )
+}
+
+macro_rules! setup_jwks_test {
+    ($keys:expr) => {{
+        let keys: Arc<JwtKeySet> = $keys;
+        let repository = InMemoryAccountRepository::new();
+        let service = BankService::new(Arc::new(repository));
+        let auth_service = AuthService::new(
+            Arc::new(InMemoryUserRepository::new()),
+            JWT_SECRET.to_string(),
+        )
+        .with_jwt_keys(keys.clone());
+
+        let state = web::Data::new(AppState {
+            service,
+            auth_service: Arc::new(auth_service),
+        });
+
+        test::init_service(
+            App::new()
+                .app_data(state.clone())
+                .wrap(JwtAuthMiddleware::new(JWT_SECRET.to_string()).with_jwt_keys(keys))
+                .route("/.well-known/jwks.json", web::get().to(jwks))
+                .service(
+                    web::scope("/api")
+                        .route("/auth/register", web::post().to(register))
+                        .route("/auth/login", web::post().to(login))
+                        .route("/accounts", web::post().to(create_account)),
+                ),
+        )
+        .await
+    }};
+}
+
+// Returns the user id and an access token
+macro_rules! register_and_login {
+    ($app:expr, $email:expr) => {{
+        let req = test::TestRequest::post()
+            .uri("/api/auth/register")
+            .set_json(&CreateUser {
+                email: $email.to_string(),
+                password: PASSWORD.to_string(),
+            })
+            .to_request();
+        let user: serde_json::Value = test::call_and_read_body_json(&$app, req).await;
+
+        let req = test::TestRequest::post()
+            .uri("/api/auth/login")
+            .set_json(&LoginRequest {
+                email: $email.to_string(),
+                password: PASSWORD.to_string(),
+            })
+            .to_request();
+        let resp: serde_json::Value = test::call_and_read_body_json(&$app, req).await;
+        (
+            user["id"].as_str().unwrap().to_string(),
+            resp["access_token"].as_str().unwrap().to_string(),
+        )
+    }};
+}
+
+macro_rules! create_account_status {
+    ($app:expr, $token:expr) => {{
+        let req = test::TestRequest::post()
+            .uri("/api/accounts")
+            .insert_header(("Authorization", format!("Bearer {}", $token)))
+            .set_json(&CreateAccount {
+                name: "Main".to_string(),
+            })
+            .to_request();
+        match test::try_call_service(&$app, req).await {
+            Ok(resp) => resp.status(),
+            Err(err) => err.as_response_error().status_code(),
+        }
+    }};
+}
+
+#[actix_web::test]
+async fn test_downstream_service_verifies_token_with_published_jwks() {
+    let old = JwtKey::from_pem("rsa-1", RSA_PEM).unwrap();
+    let app = setup_jwks_test!(rotated_keys(&old));
+    let (_, token) = register_and_login!(app, "jwks@example.com");
+
+    let req = test::TestRequest::get()
+        .uri("/.well-known/jwks.json")
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::OK);
+    let jwks: JwkSet = test::read_body_json(resp).await;
+
+    // Both asymmetric keys are published, the shared secret is not
+    let kids: Vec<_> = jwks
+        .keys
+        .iter()
+        .map(|key| key.common.key_id.clone().unwrap())
+        .collect();
+    assert_eq!(kids, vec!["ed-2", "rsa-1"]);
+
+    // What a downstream service does: pick the key by kid and verify, no secret involved
+    let kid = decode_header(&token).unwrap().kid.unwrap();
+    assert_eq!(kid, "ed-2");
+    let jwk = jwks.find(&kid).unwrap();
+    let algorithm = jwk
+        .common
+        .key_algorithm
+        .unwrap()
+        .to_string()
+        .parse()
+        .unwrap();
+    let claims = decode::<serde_json::Value>(
+        &token,
+        &DecodingKey::from_jwk(jwk).unwrap(),
+        &Validation::new(algorithm),
+    )
+    .unwrap()
+    .claims;
+    assert_eq!(claims["role"], "customer");
+}
+
+#[actix_web::test]
+async fn test_tokens_of_rotated_out_keys_stay_valid() {
+    let old = JwtKey::from_pem("rsa-1", RSA_PEM).unwrap();
+    let app = setup_jwks_test!(rotated_keys(&old));
+    let (user_id, token) = register_and_login!(app, "rotation@example.com");
+
+    // Issued before the rotation, by the RSA key and by the shared secret
+    let rsa_token =
+        sign_access_token(&JwtKeySet::new(old), &user_id, Role::Customer, None).unwrap();
+    let hs256_token = generate_token(&user_id, Role::Customer, JWT_SECRET).unwrap();
+
+    assert_eq!(create_account_status!(app, token), StatusCode::CREATED);
+    assert_eq!(create_account_status!(app, rsa_token), StatusCode::CREATED);
+    assert_eq!(
+        create_account_status!(app, hs256_token),
+        StatusCode::CREATED
+    );
+}
+
+#[actix_web::test]
+async fn test_tokens_of_unknown_keys_are_rejected() {
+    let app = setup_jwks_test!(Arc::new(JwtKeySet::new(JwtKey::generate_ed25519("ed-1"))));
+    let foreign = JwtKeySet::new(JwtKey::generate_ed25519("ed-1"));
+
+    // Same kid, different key
+    let forged = sign_access_token(&foreign, "someone", Role::Admin, None).unwrap();
+    // The shared secret is no longer accepted once it was dropped from the set
+    let legacy = generate_token("someone", Role::Customer, JWT_SECRET).unwrap();
+
+    assert_eq!(
+        create_account_status!(app, forged),
+        StatusCode::UNAUTHORIZED
+    );
+    assert_eq!(
+        create_account_status!(app, legacy),
+        StatusCode::UNAUTHORIZED
+    );
+}
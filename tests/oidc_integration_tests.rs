@@ -0,0 +1,271 @@
+mod support;
+
+use actix_web::cookie::Cookie;
+use actix_web::http::StatusCode;
+use actix_web::{App, test, web};
+use std::sync::Arc;
+use support::mock_idp::{MockIdp, MockUser};
+use yandex_bank_api::application::auth_service::AuthService;
+use yandex_bank_api::application::service::BankService;
+use yandex_bank_api::data::memory::InMemoryAccountRepository;
+use yandex_bank_api::data::user_repository::InMemoryUserRepository;
+use yandex_bank_api::domain::models::CreateAccount;
+use yandex_bank_api::domain::user::{CreateUser, LoginRequest};
+use yandex_bank_api::infrastructure::oidc::{OidcClient, OidcConfig};
+use yandex_bank_api::infrastructure::security::decode_access_token;
+use yandex_bank_api::presentation::auth::{login, oidc_authorize, oidc_callback, register};
+use yandex_bank_api::presentation::handlers::{AppState, create_account};
+use yandex_bank_api::presentation::middleware::JwtAuthMiddleware;
+
+const PASSWORD: &str = "Passw0rd-Strong";
+const JWT_SECRET: &str = "test-secret-key-for-oidc-tests";
+const CLIENT_ID: &str = "yandex-bank";
+
+macro_rules! setup_oidc_test {
+    ($idp:expr) => {{
+        let provider = OidcClient::discover(OidcConfig {
+            issuer: $idp.issuer.clone(),
+            client_id: CLIENT_ID.to_string(),
+            client_secret: None,
+            redirect_uri: "http://localhost:8080/api/auth/oidc/callback".to_string(),
+        })
+        .await
+        .unwrap();
+
+        let repository = InMemoryAccountRepository::new();
+        let service = BankService::new(Arc::new(repository));
+        let auth_service = AuthService::new(
+            Arc::new(InMemoryUserRepository::new()),
+            JWT_SECRET.to_string(),
+        )
+        .with_identity_provider(Arc::new(provider));
+
+        let state = web::Data::new(AppState {
+            service,
+            auth_service: Arc::new(auth_service),
+        });
+
+        test::init_service(
+            App::new()
+                .app_data(state.clone())
+                .wrap(JwtAuthMiddleware::new(JWT_SECRET.to_string()))
+                .service(
+                    web::scope("/api")
+                        .route("/auth/register", web::post().to(register))
+                        .route("/auth/login", web::post().to(login))
+                        .route("/auth/oidc/authorize", web::post().to(oidc_authorize))
+                        .route("/auth/oidc/callback", web::get().to(oidc_callback))
+                        .route("/accounts", web::post().to(create_account)),
+                ),
+        )
+        .await
+    }};
+}
+
+// Starts a login like a browser would; `$bearer` links the identity to a signed-in user.
+// Returns the provider's code and state, and the binding cookie set for the browser.
+macro_rules! oidc_start {
+    ($app:expr, $idp:expr, $bearer:expr) => {{
+        let mut req = test::TestRequest::post().uri("/api/auth/oidc/authorize");
+        if let Some(token) = $bearer {
+            req = req.insert_header(("Authorization", format!("Bearer {}", token)));
+        }
+        let resp = test::call_service(&$app, req.to_request()).await;
+        let binding = resp
+            .response()
+            .cookies()
+            .find(|c| c.name() == "oidc_binding")
+            .map(|c| c.into_owned())
+            .unwrap();
+        assert!(binding.http_only().unwrap_or(false));
+        let start: serde_json::Value = test::read_body_json(resp).await;
+        assert!(start.get("binding").is_none());
+
+        let (code, state) = $idp
+            .authorize(start["authorization_url"].as_str().unwrap())
+            .await;
+        assert_eq!(state, start["state"].as_str().unwrap());
+        (code, state, binding)
+    }};
+}
+
+fn callback(code: &str, state: &str, binding: Option<&Cookie<'static>>) -> test::TestRequest {
+    let mut req = test::TestRequest::get().uri(&format!(
+        "/api/auth/oidc/callback?code={}&state={}",
+        code, state
+    ));
+    if let Some(binding) = binding {
+        req = req.cookie(binding.clone());
+    }
+    req
+}
+
+// Runs the whole browser round trip. Returns the callback response.
+macro_rules! oidc_login {
+    ($app:expr, $idp:expr, $bearer:expr) => {{
+        let (code, state, binding) = oidc_start!($app, $idp, $bearer);
+        test::call_service(&$app, callback(&code, &state, Some(&binding)).to_request()).await
+    }};
+}
+
+macro_rules! user_id_of {
+    ($resp:expr) => {{
+        assert_eq!($resp.status(), StatusCode::OK);
+        let body: serde_json::Value = test::read_body_json($resp).await;
+        let token = body["access_token"].as_str().unwrap();
+        decode_access_token(token, JWT_SECRET).unwrap().user_id
+    }};
+}
+
+#[actix_web::test]
+async fn test_oidc_login_creates_user_and_reuses_the_link() {
+    let idp = MockIdp::start(CLIENT_ID).await;
+    let app = setup_oidc_test!(idp);
+
+    let resp = oidc_login!(app, idp, None::<String>);
+    assert_eq!(resp.status(), StatusCode::OK);
+    let body: serde_json::Value = test::read_body_json(resp).await;
+    assert!(body["refresh_token"].is_string());
+    let token = body["access_token"].as_str().unwrap();
+    let user_id = decode_access_token(token, JWT_SECRET).unwrap().user_id;
+
+    // The session works like a password login
+    let req = test::TestRequest::post()
+        .uri("/api/accounts")
+        .insert_header(("Authorization", format!("Bearer {}", token)))
+        .set_json(&CreateAccount {
+            name: "Payroll".to_string(),
+        })
+        .to_request();
+    assert_eq!(
+        test::call_service(&app, req).await.status(),
+        StatusCode::CREATED
+    );
+
+    // The subject identifies the user, even after the email changed at the provider
+    idp.sign_in_as(MockUser::new("employee-1", "renamed@corp.example.com"));
+    let resp = oidc_login!(app, idp, None::<String>);
+    assert_eq!(user_id_of!(resp), user_id);
+
+    idp.stop().await;
+}
+
+#[actix_web::test]
+async fn test_signed_in_user_links_external_identity() {
+    let idp = MockIdp::start(CLIENT_ID).await;
+    let app = setup_oidc_test!(idp);
+
+    let req = test::TestRequest::post()
+        .uri("/api/auth/register")
+        .set_json(&CreateUser {
+            email: "alice@example.com".to_string(),
+            password: PASSWORD.to_string(),
+        })
+        .to_request();
+    let user: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+    let req = test::TestRequest::post()
+        .uri("/api/auth/login")
+        .set_json(&LoginRequest {
+            email: "alice@example.com".to_string(),
+            password: PASSWORD.to_string(),
+        })
+        .to_request();
+    let login: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+    let token = login["access_token"].as_str().unwrap().to_string();
+
+    // A corporate identity with an unrelated email
+    idp.sign_in_as(MockUser::new("employee-7", "a.smith@corp.example.com"));
+    let resp = oidc_login!(app, idp, Some(token));
+    assert_eq!(user_id_of!(resp), user["id"].as_str().unwrap());
+
+    // From now on signing in at the provider is enough
+    let resp = oidc_login!(app, idp, None::<String>);
+    assert_eq!(user_id_of!(resp), user["id"].as_str().unwrap());
+
+    idp.stop().await;
+}
+
+#[actix_web::test]
+async fn test_provider_email_does_not_take_over_existing_user() {
+    let idp = MockIdp::start(CLIENT_ID).await;
+    let app = setup_oidc_test!(idp);
+
+    let req = test::TestRequest::post()
+        .uri("/api/auth/register")
+        .set_json(&CreateUser {
+            email: "victim@example.com".to_string(),
+            password: PASSWORD.to_string(),
+        })
+        .to_request();
+    test::call_service(&app, req).await;
+
+    // Even a verified email would bypass the password and any second factor
+    for email_verified in [false, true] {
+        idp.sign_in_as(MockUser {
+            email_verified,
+            ..MockUser::new("attacker", "victim@example.com")
+        });
+        let resp = oidc_login!(app, idp, None::<String>);
+        assert_eq!(resp.status(), StatusCode::FORBIDDEN);
+    }
+
+    idp.stop().await;
+}
+
+#[actix_web::test]
+async fn test_callback_state_is_single_use() {
+    let idp = MockIdp::start(CLIENT_ID).await;
+    let app = setup_oidc_test!(idp);
+
+    let (code, state, binding) = oidc_start!(app, idp, None::<String>);
+
+    // A state we never issued
+    let resp =
+        test::call_service(&app, callback(&code, "forged", Some(&binding)).to_request()).await;
+    assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
+
+    let resp = test::call_service(&app, callback(&code, &state, Some(&binding)).to_request()).await;
+    assert_eq!(resp.status(), StatusCode::OK);
+    let resp = test::call_service(&app, callback(&code, &state, Some(&binding)).to_request()).await;
+    assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
+
+    idp.stop().await;
+}
+
+#[actix_web::test]
+async fn test_provider_key_rotation_is_picked_up() {
+    let idp = MockIdp::start(CLIENT_ID).await;
+    let app = setup_oidc_test!(idp);
+    let resp = oidc_login!(app, idp, None::<String>);
+    let user_id = user_id_of!(resp);
+
+    // The new key was not published at discovery time
+    idp.rotate_key("idp-2");
+    let resp = oidc_login!(app, idp, None::<String>);
+    assert_eq!(user_id_of!(resp), user_id);
+
+    idp.stop().await;
+}
+
+#[actix_web::test]
+async fn test_callback_only_completes_in_the_starting_browser() {
+    let idp = MockIdp::start(CLIENT_ID).await;
+    let app = setup_oidc_test!(idp);
+
+    // Someone else starts a login and sends the callback link to the victim
+    let (code, state, _) = oidc_start!(app, idp, None::<String>);
+    let resp = test::call_service(&app, callback(&code, &state, None).to_request()).await;
+    assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
+
+    // The victim's own pending login does not help either
+    let (code, state, _) = oidc_start!(app, idp, None::<String>);
+    let (_, _, victim_binding) = oidc_start!(app, idp, None::<String>);
+    let resp = test::call_service(
+        &app,
+        callback(&code, &state, Some(&victim_binding)).to_request(),
+    )
+    .await;
+    assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
+
+    idp.stop().await;
+}
@@ -0,0 +1,301 @@
+use actix_web::http::StatusCode;
+use actix_web::{App, test, web};
+use chrono::{Duration, Utc};
+use std::sync::Arc;
+use yandex_bank_api::application::auth_service::AuthService;
+use yandex_bank_api::application::service::BankService;
+use yandex_bank_api::data::memory::InMemoryAccountRepository;
+use yandex_bank_api::data::user_repository::InMemoryUserRepository;
+use yandex_bank_api::domain::models::{Amount, CreateAccount, Deposit};
+use yandex_bank_api::domain::notifier::Notification;
+use yandex_bank_api::domain::p2p::PayByEmail;
+use yandex_bank_api::domain::user::{CreateUser, LoginRequest, VerifyEmailRequest};
+use yandex_bank_api::infrastructure::notifier::InMemoryNotifier;
+use yandex_bank_api::presentation::auth::{login, register, verify_email};
+use yandex_bank_api::presentation::handlers::{AppState, create_account, deposit};
+use yandex_bank_api::presentation::middleware::JwtAuthMiddleware;
+use yandex_bank_api::presentation::p2p::{claim_transfer, list_claimable_transfers, pay_by_email};
+
+const PASSWORD: &str = "Passw0rd-Strong";
+
+macro_rules! setup_p2p_test {
+    ($claim_ttl:expr) => {{
+        let jwt_secret = "test-secret-key-for-p2p-tests".to_string();
+        let notifier = InMemoryNotifier::new();
+        let auth_service = Arc::new(
+            AuthService::new(Arc::new(InMemoryUserRepository::new()), jwt_secret.clone())
+                .with_notifier(Arc::new(notifier.clone())),
+        );
+        let service = BankService::new(Arc::new(InMemoryAccountRepository::new()))
+            .with_customer_policy(auth_service.clone())
+            .with_claim_ttl($claim_ttl);
+        let state = web::Data::new(AppState {
+            service,
+            auth_service,
+        });
+
+        let app = test::init_service(
+            App::new()
+                .app_data(state.clone())
+                .wrap(JwtAuthMiddleware::new(jwt_secret))
+                .service(
+                    web::scope("/api")
+                        .route("/auth/register", web::post().to(register))
+                        .route("/auth/login", web::post().to(login))
+                        .route("/auth/verify-email", web::post().to(verify_email))
+                        .route("/accounts", web::post().to(create_account))
+                        .route("/accounts/{id}/deposit", web::post().to(deposit))
+                        .route("/transfers/email", web::post().to(pay_by_email))
+                        .route("/transfers/claims", web::get().to(list_claimable_transfers))
+                        .route(
+                            "/transfers/claims/{id}/claim",
+                            web::post().to(claim_transfer),
+                        ),
+                ),
+        )
+        .await;
+        (app, state, notifier)
+    }};
+}
+
+// Registers and logs in; returns the bearer header
+macro_rules! sign_up {
+    ($app:expr, $email:expr) => {{
+        let req = test::TestRequest::post()
+            .uri("/api/auth/register")
+            .set_json(&CreateUser {
+                email: $email.to_string(),
+                password: PASSWORD.to_string(),
+            })
+            .to_request();
+        test::call_service(&$app, req).await;
+        let req = test::TestRequest::post()
+            .uri("/api/auth/login")
+            .set_json(&LoginRequest {
+                email: $email.to_string(),
+                password: PASSWORD.to_string(),
+            })
+            .to_request();
+        let login: serde_json::Value = test::call_and_read_body_json(&$app, req).await;
+        (
+            "Authorization",
+            format!("Bearer {}", login["access_token"].as_str().unwrap()),
+        )
+    }};
+}
+
+macro_rules! verify {
+    ($app:expr, $notifier:expr, $email:expr) => {{
+        let token = $notifier
+            .sent_to($email)
+            .await
+            .into_iter()
+            .find_map(|n| match n {
+                Notification::EmailVerification { token, .. } => Some(token),
+                _ => None,
+            })
+            .expect("No verification email sent");
+        let req = test::TestRequest::post()
+            .uri("/api/auth/verify-email")
+            .set_json(&VerifyEmailRequest { token })
+            .to_request();
+        assert_eq!(
+            test::call_service(&$app, req).await.status(),
+            StatusCode::NO_CONTENT
+        );
+    }};
+}
+
+// Opens an account with `$balance` on it and returns its id
+macro_rules! open_account {
+    ($app:expr, $auth:expr, $balance:expr) => {{
+        let req = test::TestRequest::post()
+            .uri("/api/accounts")
+            .insert_header($auth.clone())
+            .set_json(&CreateAccount {
+                name: "Main".to_string(),
+            })
+            .to_request();
+        let account: serde_json::Value = test::call_and_read_body_json(&$app, req).await;
+        let account_id = account["id"].as_u64().unwrap() as u32;
+        if $balance > 0 {
+            let req = test::TestRequest::post()
+                .uri(&format!("/api/accounts/{}/deposit", account_id))
+                .insert_header($auth.clone())
+                .set_json(&Deposit {
+                    amount: Amount::new($balance),
+                    details: Default::default(),
+                })
+                .to_request();
+            test::call_service(&$app, req).await;
+        }
+        account_id
+    }};
+}
+
+macro_rules! pay {
+    ($app:expr, $auth:expr, $from:expr, $email:expr, $amount:expr) => {{
+        let req = test::TestRequest::post()
+            .uri("/api/transfers/email")
+            .insert_header($auth.clone())
+            .set_json(&PayByEmail {
+                from_account_id: $from,
+                email: $email.to_string(),
+                amount: Amount::new($amount),
+            })
+            .to_request();
+        let resp = test::call_service(&$app, req).await;
+        let status = resp.status();
+        let body: serde_json::Value = test::read_body_json(resp).await;
+        (status, body)
+    }};
+}
+
+macro_rules! balance {
+    ($state:expr, $account_id:expr) => {
+        $state
+            .service
+            .get_any_account($account_id)
+            .await
+            .unwrap()
+            .balance
+            .inner()
+    };
+}
+
+#[actix_web::test]
+async fn test_payment_to_user_with_account_is_credited() {
+    let (app, state, notifier) = setup_p2p_test!(Duration::days(14));
+    let alice = sign_up!(app, "alice@example.com");
+    verify!(app, notifier, "alice@example.com");
+    let bob = sign_up!(app, "bob@example.com");
+    verify!(app, notifier, "bob@example.com");
+    let alice_account = open_account!(app, alice, 1000);
+    let bob_account = open_account!(app, bob, 0);
+
+    // The email is matched however it is typed
+    let (status, body) = pay!(app, alice, alice_account, " Bob@EXAMPLE.com", 300);
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["status"], "completed");
+    assert_eq!(balance!(state, alice_account), 700);
+    assert_eq!(balance!(state, bob_account), 300);
+
+    let (status, _) = pay!(app, alice, alice_account, "bob@example.com", 701);
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    let (status, _) = pay!(app, alice, alice_account, "not-an-email", 1);
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+}
+
+#[actix_web::test]
+async fn test_payment_to_newcomer_is_held_until_claimed() {
+    let (app, state, notifier) = setup_p2p_test!(Duration::days(14));
+    let alice = sign_up!(app, "alice@example.com");
+    verify!(app, notifier, "alice@example.com");
+    let alice_account = open_account!(app, alice, 1000);
+
+    let (status, body) = pay!(app, alice, alice_account, "carol@example.com", 400);
+    assert_eq!(status, StatusCode::ACCEPTED);
+    assert_eq!(body["status"], "pending");
+    let transfer_id = body["id"].as_str().unwrap().to_string();
+    assert_eq!(balance!(state, alice_account), 600);
+    assert!(notifier.sent_to("carol@example.com").await.iter().any(
+        |n| matches!(n, Notification::TransferToClaim { transfer_id: id, .. } if *id == transfer_id)
+    ));
+
+    // Registering with the email is not enough, it has to be verified
+    let carol = sign_up!(app, "carol@example.com");
+    let claim = |auth: (&'static str, String)| {
+        test::TestRequest::post()
+            .uri(&format!("/api/transfers/claims/{}/claim", transfer_id))
+            .insert_header(auth)
+            .set_json(serde_json::json!({}))
+            .to_request()
+    };
+    assert_eq!(
+        test::call_service(&app, claim(carol.clone()))
+            .await
+            .status(),
+        StatusCode::FORBIDDEN
+    );
+    let req = test::TestRequest::get()
+        .uri("/api/transfers/claims")
+        .insert_header(carol.clone())
+        .to_request();
+    let listed: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(listed.as_array().unwrap().len(), 0);
+
+    verify!(app, notifier, "carol@example.com");
+    let req = test::TestRequest::get()
+        .uri("/api/transfers/claims")
+        .insert_header(carol.clone())
+        .to_request();
+    let listed: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(listed[0]["id"], transfer_id.as_str());
+    assert_eq!(listed[0]["status"], "pending");
+
+    // The money needs an account to go to
+    assert_eq!(
+        test::call_service(&app, claim(carol.clone()))
+            .await
+            .status(),
+        StatusCode::BAD_REQUEST
+    );
+    let carol_account = open_account!(app, carol, 0);
+    let resp = test::call_service(&app, claim(carol.clone())).await;
+    assert_eq!(resp.status(), StatusCode::OK);
+    let claimed: serde_json::Value = test::read_body_json(resp).await;
+    assert_eq!(claimed["status"], "claimed");
+    assert_eq!(balance!(state, carol_account), 400);
+
+    assert_eq!(
+        test::call_service(&app, claim(carol.clone()))
+            .await
+            .status(),
+        StatusCode::BAD_REQUEST
+    );
+    // Nobody else can claim it
+    assert_eq!(
+        test::call_service(&app, claim(alice.clone()))
+            .await
+            .status(),
+        StatusCode::NOT_FOUND
+    );
+    assert_eq!(balance!(state, carol_account), 400);
+}
+
+#[actix_web::test]
+async fn test_unclaimed_payment_is_refunded_after_expiry() {
+    let (app, state, notifier) = setup_p2p_test!(Duration::zero());
+    let alice = sign_up!(app, "alice@example.com");
+    verify!(app, notifier, "alice@example.com");
+    let alice_account = open_account!(app, alice, 1000);
+
+    let (status, _) = pay!(app, alice, alice_account, "dave@example.com", 250);
+    assert_eq!(status, StatusCode::ACCEPTED);
+    assert_eq!(balance!(state, alice_account), 750);
+
+    assert_eq!(
+        state
+            .service
+            .refund_expired_transfers(Utc::now())
+            .await
+            .unwrap(),
+        1
+    );
+    assert_eq!(balance!(state, alice_account), 1000);
+    assert_eq!(
+        state
+            .service
+            .refund_expired_transfers(Utc::now())
+            .await
+            .unwrap(),
+        0
+    );
+
+    let req = test::TestRequest::get()
+        .uri("/api/transfers/claims")
+        .insert_header(alice.clone())
+        .to_request();
+    let listed: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(listed[0]["status"], "refunded");
+}
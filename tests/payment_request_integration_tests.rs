@@ -0,0 +1,291 @@
+use actix_web::http::StatusCode;
+use actix_web::{App, test, web};
+use chrono::{Duration, Utc};
+use std::sync::Arc;
+use yandex_bank_api::application::auth_service::AuthService;
+use yandex_bank_api::application::service::BankService;
+use yandex_bank_api::data::memory::InMemoryAccountRepository;
+use yandex_bank_api::data::user_repository::InMemoryUserRepository;
+use yandex_bank_api::domain::models::{Amount, CreateAccount, Deposit};
+use yandex_bank_api::domain::payment_request::PayPaymentRequest;
+use yandex_bank_api::domain::user::{CreateUser, DeleteUserRequest, LoginRequest};
+use yandex_bank_api::presentation::auth::{login, register};
+use yandex_bank_api::presentation::handlers::{AppState, create_account, deposit, get_account};
+use yandex_bank_api::presentation::middleware::JwtAuthMiddleware;
+use yandex_bank_api::presentation::payment_requests::{
+    create_payment_request, decline_payment_request, get_payment_request, list_payment_requests,
+    pay_payment_request,
+};
+use yandex_bank_api::presentation::users::delete_me;
+
+const PASSWORD: &str = "Passw0rd-Strong";
+
+macro_rules! setup_payment_request_test {
+    () => {{
+        let jwt_secret = "test-secret-key-for-payment-request-tests".to_string();
+        let state = web::Data::new(AppState {
+            service: BankService::new(Arc::new(InMemoryAccountRepository::new())),
+            auth_service: Arc::new(AuthService::new(
+                Arc::new(InMemoryUserRepository::new()),
+                jwt_secret.clone(),
+            )),
+        });
+
+        test::init_service(
+            App::new()
+                .app_data(state)
+                .wrap(JwtAuthMiddleware::new(jwt_secret))
+                .service(
+                    web::scope("/api")
+                        .route("/auth/register", web::post().to(register))
+                        .route("/auth/login", web::post().to(login))
+                        .route("/users/me", web::delete().to(delete_me))
+                        .route("/accounts", web::post().to(create_account))
+                        .route("/accounts/{id}", web::get().to(get_account))
+                        .route("/accounts/{id}/deposit", web::post().to(deposit))
+                        .route("/payment-requests", web::get().to(list_payment_requests))
+                        .route("/payment-requests", web::post().to(create_payment_request))
+                        .route("/payment-requests/{id}", web::get().to(get_payment_request))
+                        .route(
+                            "/payment-requests/{id}/pay",
+                            web::post().to(pay_payment_request),
+                        )
+                        .route(
+                            "/payment-requests/{id}/decline",
+                            web::post().to(decline_payment_request),
+                        ),
+                ),
+        )
+        .await
+    }};
+}
+
+// Registers, logs in and opens an account with `$balance`; returns (bearer header, account id)
+macro_rules! customer_with_account {
+    ($app:expr, $email:expr, $balance:expr) => {{
+        let req = test::TestRequest::post()
+            .uri("/api/auth/register")
+            .set_json(&CreateUser {
+                email: $email.to_string(),
+                password: PASSWORD.to_string(),
+            })
+            .to_request();
+        test::call_service(&$app, req).await;
+        let req = test::TestRequest::post()
+            .uri("/api/auth/login")
+            .set_json(&LoginRequest {
+                email: $email.to_string(),
+                password: PASSWORD.to_string(),
+            })
+            .to_request();
+        let login: serde_json::Value = test::call_and_read_body_json(&$app, req).await;
+        let auth = (
+            "Authorization",
+            format!("Bearer {}", login["access_token"].as_str().unwrap()),
+        );
+
+        let req = test::TestRequest::post()
+            .uri("/api/accounts")
+            .insert_header(auth.clone())
+            .set_json(&CreateAccount {
+                name: "Main".to_string(),
+            })
+            .to_request();
+        let account: serde_json::Value = test::call_and_read_body_json(&$app, req).await;
+        let account_id = account["id"].as_u64().unwrap() as u32;
+        let req = test::TestRequest::post()
+            .uri(&format!("/api/accounts/{}/deposit", account_id))
+            .insert_header(auth.clone())
+            .set_json(&Deposit {
+                amount: Amount::new($balance),
+                details: Default::default(),
+            })
+            .to_request();
+        test::call_service(&$app, req).await;
+        (auth, account_id)
+    }};
+}
+
+macro_rules! request_payment {
+    ($app:expr, $auth:expr, $payer_email:expr, $to:expr, $amount:expr) => {{
+        let req = test::TestRequest::post()
+            .uri("/api/payment-requests")
+            .insert_header($auth.clone())
+            .set_json(serde_json::json!({
+                "payer_email": $payer_email,
+                "to_account_id": $to,
+                "amount": $amount,
+                "description": "Invoice #42",
+                "due_date": (Utc::now() + Duration::days(7)).date_naive(),
+            }))
+            .to_request();
+        test::call_service(&$app, req).await
+    }};
+}
+
+macro_rules! balance_of {
+    ($app:expr, $auth:expr, $id:expr) => {{
+        let req = test::TestRequest::get()
+            .uri(&format!("/api/accounts/{}", $id))
+            .insert_header($auth.clone())
+            .to_request();
+        let account: serde_json::Value = test::call_and_read_body_json(&$app, req).await;
+        account["balance"].as_u64().unwrap()
+    }};
+}
+
+#[actix_web::test]
+async fn test_payer_pays_request_once() {
+    let app = setup_payment_request_test!();
+    let (alice, alice_account) = customer_with_account!(app, "alice@example.com", 10);
+    let (bob, bob_account) = customer_with_account!(app, "bob@example.com", 500);
+
+    let resp = request_payment!(app, alice, "Bob@Example.com", alice_account, 200);
+    assert_eq!(resp.status(), StatusCode::CREATED);
+    let request: serde_json::Value = test::read_body_json(resp).await;
+    assert_eq!(request["status"], "pending");
+    let id = request["id"].as_str().unwrap().to_string();
+
+    // Both parties see the request
+    for auth in [&alice, &bob] {
+        let req = test::TestRequest::get()
+            .uri("/api/payment-requests")
+            .insert_header(auth.clone())
+            .to_request();
+        let listed: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+        assert_eq!(listed.as_array().unwrap().len(), 1);
+    }
+
+    // Only the payer can pay, from an account they may debit
+    let pay = |auth: &(&'static str, String), from| {
+        test::TestRequest::post()
+            .uri(&format!("/api/payment-requests/{}/pay", id))
+            .insert_header(auth.clone())
+            .set_json(&PayPaymentRequest {
+                from_account_id: from,
+            })
+            .to_request()
+    };
+    let resp = test::call_service(&app, pay(&alice, alice_account)).await;
+    assert_eq!(resp.status(), StatusCode::FORBIDDEN);
+    let resp = test::call_service(&app, pay(&bob, alice_account)).await;
+    assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+
+    let resp = test::call_service(&app, pay(&bob, bob_account)).await;
+    assert_eq!(resp.status(), StatusCode::OK);
+    let paid: serde_json::Value = test::read_body_json(resp).await;
+    assert_eq!(paid["status"], "paid");
+    assert_eq!(paid["from_account_id"], bob_account);
+    assert_eq!(balance_of!(app, alice, alice_account), 210);
+    assert_eq!(balance_of!(app, bob, bob_account), 300);
+
+    let resp = test::call_service(&app, pay(&bob, bob_account)).await;
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+    assert_eq!(balance_of!(app, bob, bob_account), 300);
+}
+
+#[actix_web::test]
+async fn test_payer_declines_request_and_outsiders_cannot_see_it() {
+    let app = setup_payment_request_test!();
+    let (alice, alice_account) = customer_with_account!(app, "alice@example.com", 10);
+    let (bob, _) = customer_with_account!(app, "bob@example.com", 10);
+    let (carol, _) = customer_with_account!(app, "carol@example.com", 10);
+
+    let resp = request_payment!(app, alice, "nobody@example.com", alice_account, 50);
+    assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+    let resp = request_payment!(app, alice, "alice@example.com", alice_account, 50);
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+
+    let resp = request_payment!(app, alice, "bob@example.com", alice_account, 50);
+    let request: serde_json::Value = test::read_body_json(resp).await;
+    let id = request["id"].as_str().unwrap();
+
+    let req = test::TestRequest::get()
+        .uri(&format!("/api/payment-requests/{}", id))
+        .insert_header(carol.clone())
+        .to_request();
+    assert_eq!(
+        test::call_service(&app, req).await.status(),
+        StatusCode::NOT_FOUND
+    );
+
+    let req = test::TestRequest::post()
+        .uri(&format!("/api/payment-requests/{}/decline", id))
+        .insert_header(bob.clone())
+        .to_request();
+    let declined: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(declined["status"], "declined");
+
+    let req = test::TestRequest::get()
+        .uri(&format!("/api/payment-requests/{}", id))
+        .insert_header(alice.clone())
+        .to_request();
+    let seen: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(seen["status"], "declined");
+}
+
+#[actix_web::test]
+async fn test_request_with_past_due_date_is_rejected() {
+    let app = setup_payment_request_test!();
+    let (alice, alice_account) = customer_with_account!(app, "alice@example.com", 10);
+    customer_with_account!(app, "bob@example.com", 10);
+
+    let req = test::TestRequest::post()
+        .uri("/api/payment-requests")
+        .insert_header(alice.clone())
+        .set_json(serde_json::json!({
+            "payer_email": "bob@example.com",
+            "to_account_id": alice_account,
+            "amount": 0,
+            "description": " ",
+            "due_date": (Utc::now() - Duration::days(2)).date_naive(),
+        }))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+    let body: serde_json::Value = test::read_body_json(resp).await;
+    let body = body.to_string();
+    for field in ["amount", "description", "due_date"] {
+        assert!(body.contains(field), "{} not reported in {}", field, body);
+    }
+}
+
+#[actix_web::test]
+async fn test_requests_of_deleted_user_are_cancelled() {
+    let app = setup_payment_request_test!();
+    let (alice, alice_account) = customer_with_account!(app, "alice@example.com", 0);
+    let (bob, bob_account) = customer_with_account!(app, "bob@example.com", 500);
+
+    let resp = request_payment!(app, alice, "bob@example.com", alice_account, 100);
+    assert_eq!(resp.status(), StatusCode::CREATED);
+    let request: serde_json::Value = test::read_body_json(resp).await;
+    let id = request["id"].as_str().unwrap();
+
+    let req = test::TestRequest::delete()
+        .uri("/api/users/me")
+        .insert_header(alice.clone())
+        .set_json(&DeleteUserRequest {
+            password: PASSWORD.to_string(),
+        })
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::NO_CONTENT);
+
+    let req = test::TestRequest::get()
+        .uri(&format!("/api/payment-requests/{}", id))
+        .insert_header(bob.clone())
+        .to_request();
+    let seen: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(seen["status"], "cancelled");
+
+    let req = test::TestRequest::post()
+        .uri(&format!("/api/payment-requests/{}/pay", id))
+        .insert_header(bob.clone())
+        .set_json(&PayPaymentRequest {
+            from_account_id: bob_account,
+        })
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+    assert_eq!(balance_of!(app, bob, bob_account), 500);
+}
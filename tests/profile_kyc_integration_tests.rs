@@ -0,0 +1,173 @@
+use actix_web::http::StatusCode;
+use actix_web::{App, test, web};
+use std::sync::Arc;
+use yandex_bank_api::application::auth_service::AuthService;
+use yandex_bank_api::application::service::BankService;
+use yandex_bank_api::data::memory::InMemoryAccountRepository;
+use yandex_bank_api::data::user_repository::InMemoryUserRepository;
+use yandex_bank_api::domain::user::{
+    CreateUser, KycStatus, LoginRequest, Role, UpdateKycStatusRequest,
+};
+use yandex_bank_api::presentation::admin::{kyc_history, update_kyc_status};
+use yandex_bank_api::presentation::auth::{login, register};
+use yandex_bank_api::presentation::handlers::AppState;
+use yandex_bank_api::presentation::middleware::{JwtAuthMiddleware, RequireRole};
+use yandex_bank_api::presentation::users::{get_me, update_me};
+
+const PASSWORD: &str = "Passw0rd-Strong";
+const OPERATOR_EMAIL: &str = "kyc-desk@example.com";
+
+macro_rules! setup_kyc_test {
+    () => {{
+        let jwt_secret = "test-secret-key-for-kyc-tests".to_string();
+        let auth_service = Arc::new(AuthService::new(
+            Arc::new(InMemoryUserRepository::new()),
+            jwt_secret.clone(),
+        ));
+        auth_service
+            .register_user_with_role(
+                CreateUser {
+                    email: OPERATOR_EMAIL.to_string(),
+                    password: PASSWORD.to_string(),
+                },
+                Role::Operator,
+            )
+            .await
+            .unwrap();
+        let service = BankService::new(Arc::new(InMemoryAccountRepository::new()))
+            .with_customer_policy(auth_service.clone());
+
+        let state = web::Data::new(AppState {
+            service,
+            auth_service,
+        });
+
+        test::init_service(
+            App::new()
+                .app_data(state.clone())
+                .wrap(JwtAuthMiddleware::new(jwt_secret))
+                .service(
+                    web::scope("/api")
+                        .route("/auth/register", web::post().to(register))
+                        .route("/auth/login", web::post().to(login))
+                        .route("/users/me", web::get().to(get_me))
+                        .route("/users/me", web::patch().to(update_me))
+                        .route(
+                            "/admin/users/{id}/kyc",
+                            web::put()
+                                .to(update_kyc_status)
+                                .wrap(RequireRole::new(&[Role::Operator, Role::Admin])),
+                        )
+                        .route(
+                            "/admin/users/{id}/kyc",
+                            web::get()
+                                .to(kyc_history)
+                                .wrap(RequireRole::new(&[Role::Operator, Role::Admin])),
+                        ),
+                ),
+        )
+        .await
+    }};
+}
+
+macro_rules! login_token {
+    ($app:expr, $email:expr) => {{
+        let req = test::TestRequest::post()
+            .uri("/api/auth/login")
+            .set_json(&LoginRequest {
+                email: $email.to_string(),
+                password: PASSWORD.to_string(),
+            })
+            .to_request();
+        let resp: serde_json::Value = test::call_and_read_body_json(&$app, req).await;
+        resp["access_token"].as_str().unwrap().to_string()
+    }};
+}
+
+macro_rules! set_kyc {
+    ($app:expr, $user_id:expr, $status:expr, $token:expr) => {{
+        let req = test::TestRequest::put()
+            .uri(&format!("/api/admin/users/{}/kyc", $user_id))
+            .insert_header(("Authorization", format!("Bearer {}", $token)))
+            .set_json(&UpdateKycStatusRequest {
+                status: $status,
+                reason: "Passport and proof of address checked".to_string(),
+            })
+            .to_request();
+        test::try_call_service(&$app, req).await
+    }};
+}
+
+#[actix_web::test]
+async fn test_profile_is_filled_in_and_reviewed_by_operator() {
+    let app = setup_kyc_test!();
+
+    let req = test::TestRequest::post()
+        .uri("/api/auth/register")
+        .set_json(&CreateUser {
+            email: "alice@example.com".to_string(),
+            password: PASSWORD.to_string(),
+        })
+        .to_request();
+    let user: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+    let user_id = user["id"].as_str().unwrap();
+    let token = login_token!(app, "alice@example.com");
+
+    let req = test::TestRequest::patch()
+        .uri("/api/users/me")
+        .insert_header(("Authorization", format!("Bearer {}", token)))
+        .set_json(serde_json::json!({
+            "full_name": "Alice Smith",
+            "date_of_birth": "1990-05-17",
+            "phone": "+79161234567",
+            "address": {
+                "line1": "Tverskaya 1",
+                "city": "Moscow",
+                "postal_code": "125009",
+                "country": "RU"
+            }
+        }))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::OK);
+
+    let req = test::TestRequest::get()
+        .uri("/api/users/me")
+        .insert_header(("Authorization", format!("Bearer {}", token)))
+        .to_request();
+    let me: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(me["full_name"], "Alice Smith");
+    assert_eq!(me["address"]["city"], "Moscow");
+    assert_eq!(me["kyc_status"], "unverified");
+
+    // Customers cannot verify themselves
+    let err = set_kyc!(app, user_id, KycStatus::Verified, token).unwrap_err();
+    assert_eq!(err.as_response_error().status_code(), StatusCode::FORBIDDEN);
+
+    let operator_token = login_token!(app, OPERATOR_EMAIL);
+    for status in [KycStatus::Pending, KycStatus::Verified] {
+        let resp = set_kyc!(app, user_id, status, operator_token).unwrap();
+        assert_eq!(resp.status(), StatusCode::OK);
+    }
+
+    // The verified name is locked
+    let req = test::TestRequest::patch()
+        .uri("/api/users/me")
+        .insert_header(("Authorization", format!("Bearer {}", token)))
+        .set_json(serde_json::json!({"full_name": "Mallory"}))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+
+    let req = test::TestRequest::get()
+        .uri(&format!("/api/admin/users/{}/kyc", user_id))
+        .insert_header(("Authorization", format!("Bearer {}", operator_token)))
+        .to_request();
+    let history: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(history.as_array().unwrap().len(), 2);
+    assert_eq!(history[1]["to"], "verified");
+    assert_eq!(
+        history[1]["reason"],
+        "Passport and proof of address checked"
+    );
+}
@@ -0,0 +1,319 @@
+use actix_web::http::StatusCode;
+use actix_web::{App, test, web};
+use std::sync::Arc;
+use yandex_bank_api::application::auth_service::AuthService;
+use yandex_bank_api::application::service::BankService;
+use yandex_bank_api::data::memory::InMemoryAccountRepository;
+use yandex_bank_api::data::revocation_store::InMemoryRevocationStore;
+use yandex_bank_api::data::user_repository::InMemoryUserRepository;
+use yandex_bank_api::domain::models::CreateAccount;
+use yandex_bank_api::domain::repository::RevocationStore;
+use yandex_bank_api::domain::user::{CreateUser, LoginRequest, Role, UpdateRoleRequest};
+use yandex_bank_api::presentation::admin::{
+    find_user, get_any_account, get_user, unlock_login, update_user_role,
+};
+use yandex_bank_api::presentation::auth::{login, register};
+use yandex_bank_api::presentation::handlers::{AppState, create_account, get_account};
+use yandex_bank_api::presentation::middleware::{JwtAuthMiddleware, RequireRole};
+
+const PASSWORD: &str = "Passw0rd-Strong";
+const ADMIN_EMAIL: &str = "root@example.com";
+
+macro_rules! setup_rbac_test {
+    () => {{
+        let repository = InMemoryAccountRepository::new();
+        let service = BankService::new(Arc::new(repository));
+
+        let user_repository = InMemoryUserRepository::new();
+        let jwt_secret = "test-secret-key-for-rbac-tests".to_string();
+        let revocation_store: Arc<dyn RevocationStore> = Arc::new(InMemoryRevocationStore::new());
+        let auth_service = AuthService::new(Arc::new(user_repository), jwt_secret.clone())
+            .with_revocation_store(revocation_store.clone());
+        auth_service
+            .register_user_with_role(
+                CreateUser {
+                    email: ADMIN_EMAIL.to_string(),
+                    password: PASSWORD.to_string(),
+                },
+                Role::Admin,
+            )
+            .await
+            .unwrap();
+
+        let state = web::Data::new(AppState {
+            service,
+            auth_service: Arc::new(auth_service),
+        });
+
+        test::init_service(
+            App::new()
+                .app_data(state.clone())
+                .wrap(JwtAuthMiddleware::new(jwt_secret).with_revocation_store(revocation_store))
+                .service(
+                    web::scope("/api")
+                        .route("/auth/register", web::post().to(register))
+                        .route("/auth/login", web::post().to(login))
+                        .route("/accounts", web::post().to(create_account))
+                        .route("/accounts/{id}", web::get().to(get_account))
+                        .route(
+                            "/admin/users",
+                            web::get()
+                                .to(find_user)
+                                .wrap(RequireRole::new(&[Role::Admin])),
+                        )
+                        .route(
+                            "/admin/users/{id}",
+                            web::get()
+                                .to(get_user)
+                                .wrap(RequireRole::new(&[Role::Admin])),
+                        )
+                        .route(
+                            "/admin/users/{id}/role",
+                            web::put()
+                                .to(update_user_role)
+                                .wrap(RequireRole::new(&[Role::Admin])),
+                        )
+                        .route(
+                            "/admin/unlock",
+                            web::post()
+                                .to(unlock_login)
+                                .wrap(RequireRole::new(&[Role::Admin])),
+                        )
+                        .route(
+                            "/admin/accounts/{id}",
+                            web::get()
+                                .to(get_any_account)
+                                .wrap(RequireRole::new(&[Role::Admin])),
+                        ),
+                ),
+        )
+        .await
+    }};
+}
+
+macro_rules! login_token {
+    ($app:expr, $email:expr) => {{
+        let req = test::TestRequest::post()
+            .uri("/api/auth/login")
+            .set_json(&LoginRequest {
+                email: $email.to_string(),
+                password: PASSWORD.to_string(),
+            })
+            .to_request();
+        let resp: serde_json::Value = test::call_and_read_body_json(&$app, req).await;
+        resp["access_token"].as_str().unwrap().to_string()
+    }};
+}
+
+// Registers a customer and returns their user id and access token
+macro_rules! register_customer {
+    ($app:expr, $email:expr) => {{
+        let req = test::TestRequest::post()
+            .uri("/api/auth/register")
+            .set_json(&CreateUser {
+                email: $email.to_string(),
+                password: PASSWORD.to_string(),
+            })
+            .to_request();
+        let resp: serde_json::Value = test::call_and_read_body_json(&$app, req).await;
+        let user_id = resp["id"].as_str().unwrap().to_string();
+        (user_id, login_token!($app, $email))
+    }};
+}
+
+// Middleware rejections surface as service errors rather than responses
+fn status_of(result: Result<actix_web::dev::ServiceResponse, actix_web::Error>) -> StatusCode {
+    match result {
+        Ok(resp) => resp.status(),
+        Err(err) => err.as_response_error().status_code(),
+    }
+}
+
+macro_rules! get_status {
+    ($app:expr, $uri:expr, $token:expr) => {{
+        let req = test::TestRequest::get()
+            .uri($uri)
+            .insert_header(("Authorization", format!("Bearer {}", $token)))
+            .to_request();
+        status_of(test::try_call_service(&$app, req).await)
+    }};
+}
+
+macro_rules! set_role {
+    ($app:expr, $user_id:expr, $role:expr, $token:expr) => {{
+        let req = test::TestRequest::put()
+            .uri(&format!("/api/admin/users/{}/role", $user_id))
+            .insert_header(("Authorization", format!("Bearer {}", $token)))
+            .set_json(&UpdateRoleRequest { role: $role })
+            .to_request();
+        test::try_call_service(&$app, req).await
+    }};
+}
+
+#[actix_web::test]
+async fn test_customer_gets_forbidden_on_admin_routes() {
+    let app = setup_rbac_test!();
+    let (user_id, token) = register_customer!(app, "customer@example.com");
+
+    for uri in [
+        format!("/api/admin/users/{}", user_id),
+        "/api/admin/users?email=customer@example.com".to_string(),
+        "/api/admin/accounts/1".to_string(),
+    ] {
+        assert_eq!(
+            get_status!(app, &uri, token),
+            StatusCode::FORBIDDEN,
+            "{}",
+            uri
+        );
+    }
+
+    let err = set_role!(app, user_id, Role::Admin, token).unwrap_err();
+    assert_eq!(err.as_response_error().status_code(), StatusCode::FORBIDDEN);
+    assert_eq!(err.to_string(), r#"{"error":"forbidden"}"#);
+}
+
+#[actix_web::test]
+async fn test_admin_routes_require_authentication() {
+    let app = setup_rbac_test!();
+
+    let req = test::TestRequest::get()
+        .uri("/api/admin/accounts/1")
+        .to_request();
+
+    assert_eq!(
+        status_of(test::try_call_service(&app, req).await),
+        StatusCode::UNAUTHORIZED
+    );
+}
+
+#[actix_web::test]
+async fn test_admin_can_look_up_any_user_and_account() {
+    let app = setup_rbac_test!();
+    let (user_id, customer_token) = register_customer!(app, "owner@example.com");
+    let admin_token = login_token!(app, ADMIN_EMAIL);
+
+    let req = test::TestRequest::post()
+        .uri("/api/accounts")
+        .insert_header(("Authorization", format!("Bearer {}", customer_token)))
+        .set_json(&CreateAccount {
+            name: "Savings".to_string(),
+        })
+        .to_request();
+    let account: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+    let account_id = account["id"].as_u64().unwrap();
+
+    let req = test::TestRequest::get()
+        .uri(&format!("/api/admin/accounts/{}", account_id))
+        .insert_header(("Authorization", format!("Bearer {}", admin_token)))
+        .to_request();
+    let resp: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(resp["id"], account_id);
+    assert_eq!(resp["name"], "Savings");
+
+    let req = test::TestRequest::get()
+        .uri(&format!("/api/admin/users/{}", user_id))
+        .insert_header(("Authorization", format!("Bearer {}", admin_token)))
+        .to_request();
+    let resp: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(resp["email"], "owner@example.com");
+    assert_eq!(resp["role"], "customer");
+    assert!(resp.get("password_hash").is_none());
+
+    let req = test::TestRequest::get()
+        .uri("/api/admin/users?email=OWNER@example.com")
+        .insert_header(("Authorization", format!("Bearer {}", admin_token)))
+        .to_request();
+    let resp: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(resp["id"], user_id);
+
+    assert_eq!(
+        get_status!(app, "/api/admin/users/missing", admin_token),
+        StatusCode::NOT_FOUND
+    );
+}
+
+#[actix_web::test]
+async fn test_role_change_revokes_tokens_and_operator_is_not_admin() {
+    let app = setup_rbac_test!();
+    let (user_id, token) = register_customer!(app, "staff@example.com");
+    let admin_token = login_token!(app, ADMIN_EMAIL);
+
+    let resp = set_role!(app, user_id, Role::Operator, admin_token).unwrap();
+    assert_eq!(resp.status(), StatusCode::OK);
+    let body: serde_json::Value = test::read_body_json(resp).await;
+    assert_eq!(body["role"], "operator");
+
+    // The old token still says "customer" and is revoked with the role change
+    assert_eq!(
+        get_status!(app, "/api/accounts/1", token),
+        StatusCode::UNAUTHORIZED
+    );
+
+    let operator_token = login_token!(app, "staff@example.com");
+    // Operators are not admins
+    assert_eq!(
+        get_status!(app, "/api/admin/accounts/1", operator_token),
+        StatusCode::FORBIDDEN
+    );
+}
+
+#[actix_web::test]
+async fn test_admin_cannot_demote_themselves() {
+    let app = setup_rbac_test!();
+    let admin_token = login_token!(app, ADMIN_EMAIL);
+    let req = test::TestRequest::get()
+        .uri(&format!("/api/admin/users?email={}", ADMIN_EMAIL))
+        .insert_header(("Authorization", format!("Bearer {}", admin_token)))
+        .to_request();
+    let admin: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+
+    let resp = set_role!(
+        app,
+        admin["id"].as_str().unwrap(),
+        Role::Customer,
+        admin_token
+    );
+
+    assert_eq!(status_of(resp), StatusCode::BAD_REQUEST);
+}
+
+#[actix_web::test]
+async fn test_only_admins_unlock_logins() {
+    let app = setup_rbac_test!();
+    let (_, customer_token) = register_customer!(app, "customer@example.com");
+    let admin_token = login_token!(app, ADMIN_EMAIL);
+
+    let login = |password: &str| {
+        test::TestRequest::post()
+            .uri("/api/auth/login")
+            .set_json(&LoginRequest {
+                email: "locked@example.com".to_string(),
+                password: password.to_string(),
+            })
+            .to_request()
+    };
+    register_customer!(app, "locked@example.com");
+    for _ in 0..3 {
+        test::call_service(&app, login("Wrong-Passw0rd-1")).await;
+    }
+    let resp = test::call_service(&app, login(PASSWORD)).await;
+    assert_eq!(resp.status(), StatusCode::TOO_MANY_REQUESTS);
+
+    let unlock = |token: &str| {
+        test::TestRequest::post()
+            .uri("/api/admin/unlock")
+            .insert_header(("Authorization", format!("Bearer {}", token)))
+            .set_json(serde_json::json!({ "email": "Locked@Example.com" }))
+            .to_request()
+    };
+    assert_eq!(
+        status_of(test::try_call_service(&app, unlock(&customer_token)).await),
+        StatusCode::FORBIDDEN
+    );
+    let resp = test::call_service(&app, unlock(&admin_token)).await;
+    assert_eq!(resp.status(), StatusCode::NO_CONTENT);
+
+    let resp = test::call_service(&app, login(PASSWORD)).await;
+    assert_eq!(resp.status(), StatusCode::OK);
+}
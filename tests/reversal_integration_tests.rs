@@ -0,0 +1,271 @@
+use actix_web::http::StatusCode;
+use actix_web::{App, test, web};
+use std::sync::Arc;
+use yandex_bank_api::application::auth_service::AuthService;
+use yandex_bank_api::application::service::BankService;
+use yandex_bank_api::data::memory::InMemoryAccountRepository;
+use yandex_bank_api::data::user_repository::InMemoryUserRepository;
+use yandex_bank_api::domain::models::CreateAccount;
+use yandex_bank_api::domain::reversal::ShortfallPolicy;
+use yandex_bank_api::domain::user::{CreateUser, LoginRequest, Role};
+use yandex_bank_api::presentation::admin::reverse_transfer;
+use yandex_bank_api::presentation::auth::{login, register};
+use yandex_bank_api::presentation::handlers::{
+    AppState, create_account, deposit, get_account, transfer, withdraw,
+};
+use yandex_bank_api::presentation::middleware::{JwtAuthMiddleware, RequireRole};
+
+const PASSWORD: &str = "Passw0rd-Strong";
+const OPERATOR_EMAIL: &str = "back-office@example.com";
+
+macro_rules! setup_reversal_test {
+    ($policy:expr) => {{
+        let jwt_secret = "test-secret-key-for-reversal-tests".to_string();
+        let auth_service = Arc::new(AuthService::new(
+            Arc::new(InMemoryUserRepository::new()),
+            jwt_secret.clone(),
+        ));
+        auth_service
+            .register_user_with_role(
+                CreateUser {
+                    email: OPERATOR_EMAIL.to_string(),
+                    password: PASSWORD.to_string(),
+                },
+                Role::Operator,
+            )
+            .await
+            .unwrap();
+        let state = web::Data::new(AppState {
+            service: BankService::new(Arc::new(InMemoryAccountRepository::new()))
+                .with_shortfall_policy($policy),
+            auth_service,
+        });
+
+        test::init_service(
+            App::new()
+                .app_data(state)
+                .wrap(JwtAuthMiddleware::new(jwt_secret))
+                .service(
+                    web::scope("/api")
+                        .route("/auth/register", web::post().to(register))
+                        .route("/auth/login", web::post().to(login))
+                        .route("/accounts", web::post().to(create_account))
+                        .route("/accounts/{id}", web::get().to(get_account))
+                        .route("/accounts/{id}/deposit", web::post().to(deposit))
+                        .route("/accounts/{id}/withdraw", web::post().to(withdraw))
+                        .route("/transfers", web::post().to(transfer))
+                        .route(
+                            "/transfers/{id}/reverse",
+                            web::post()
+                                .to(reverse_transfer)
+                                .wrap(RequireRole::new(&[Role::Operator, Role::Admin])),
+                        ),
+                ),
+        )
+        .await
+    }};
+}
+
+// Registers (unless it exists) and logs in; returns the bearer header
+macro_rules! bearer {
+    ($app:expr, $email:expr) => {{
+        let req = test::TestRequest::post()
+            .uri("/api/auth/register")
+            .set_json(&CreateUser {
+                email: $email.to_string(),
+                password: PASSWORD.to_string(),
+            })
+            .to_request();
+        test::call_service(&$app, req).await;
+        let req = test::TestRequest::post()
+            .uri("/api/auth/login")
+            .set_json(&LoginRequest {
+                email: $email.to_string(),
+                password: PASSWORD.to_string(),
+            })
+            .to_request();
+        let login: serde_json::Value = test::call_and_read_body_json(&$app, req).await;
+        (
+            "Authorization",
+            format!("Bearer {}", login["access_token"].as_str().unwrap()),
+        )
+    }};
+}
+
+macro_rules! open_account {
+    ($app:expr, $auth:expr, $balance:expr) => {{
+        let req = test::TestRequest::post()
+            .uri("/api/accounts")
+            .insert_header($auth.clone())
+            .set_json(&CreateAccount {
+                name: "Main".to_string(),
+            })
+            .to_request();
+        let account: serde_json::Value = test::call_and_read_body_json(&$app, req).await;
+        let id = account["id"].as_u64().unwrap();
+        let req = test::TestRequest::post()
+            .uri(&format!("/api/accounts/{}/deposit", id))
+            .insert_header($auth.clone())
+            .set_json(serde_json::json!({ "amount": $balance }))
+            .to_request();
+        test::call_service(&$app, req).await;
+        id
+    }};
+}
+
+macro_rules! pay {
+    ($app:expr, $auth:expr, $from:expr, $to:expr, $amount:expr) => {{
+        let req = test::TestRequest::post()
+            .uri("/api/transfers")
+            .insert_header($auth.clone())
+            .set_json(serde_json::json!({
+                "from_account_id": $from,
+                "to_account_id": $to,
+                "amount": $amount,
+                "reference": "INV-7",
+            }))
+            .to_request();
+        let receipt: serde_json::Value = test::call_and_read_body_json(&$app, req).await;
+        receipt["transfer_id"].as_str().unwrap().to_string()
+    }};
+}
+
+macro_rules! reverse {
+    ($app:expr, $auth:expr, $transfer_id:expr, $body:expr) => {{
+        let req = test::TestRequest::post()
+            .uri(&format!("/api/transfers/{}/reverse", $transfer_id))
+            .insert_header($auth.clone())
+            .set_json($body)
+            .to_request();
+        test::try_call_service(&$app, req).await
+    }};
+}
+
+macro_rules! balance_of {
+    ($app:expr, $auth:expr, $id:expr) => {{
+        let req = test::TestRequest::get()
+            .uri(&format!("/api/accounts/{}", $id))
+            .insert_header($auth.clone())
+            .to_request();
+        let account: serde_json::Value = test::call_and_read_body_json(&$app, req).await;
+        account["balance"].as_u64().unwrap()
+    }};
+}
+
+#[actix_web::test]
+async fn test_operator_reverses_transfer_in_parts() {
+    let app = setup_reversal_test!(ShortfallPolicy::Reject);
+    let alice = bearer!(app, "alice@example.com");
+    let bob = bearer!(app, "bob@example.com");
+    let operator = bearer!(app, OPERATOR_EMAIL);
+    let alices = open_account!(app, alice, 500);
+    let bobs = open_account!(app, bob, 0);
+    let transfer_id = pay!(app, alice, alices, bobs, 300);
+
+    // Customers cannot undo their own payments
+    let err = reverse!(
+        app,
+        alice,
+        transfer_id,
+        serde_json::json!({"reason": "Oops"})
+    )
+    .unwrap_err();
+    assert_eq!(err.as_response_error().status_code(), StatusCode::FORBIDDEN);
+
+    let resp = reverse!(
+        app,
+        operator,
+        transfer_id,
+        serde_json::json!({"amount": 100, "reason": "Partly refunded at the customer's request"})
+    )
+    .unwrap();
+    assert_eq!(resp.status(), StatusCode::CREATED);
+    let reversal: serde_json::Value = test::read_body_json(resp).await;
+    assert_eq!(reversal["transfer_id"], transfer_id.as_str());
+    assert_eq!(reversal["amount"], 100);
+    assert_eq!(reversal["from_account_id"], bobs);
+    assert_eq!(reversal["to_account_id"], alices);
+    assert_eq!(balance_of!(app, alice, alices), 300);
+    assert_eq!(balance_of!(app, bob, bobs), 200);
+
+    let resp = reverse!(
+        app,
+        operator,
+        transfer_id,
+        serde_json::json!({"amount": 201, "reason": "Too much"})
+    )
+    .unwrap();
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+    let resp = reverse!(
+        app,
+        operator,
+        transfer_id,
+        serde_json::json!({"reason": "The rest"})
+    )
+    .unwrap();
+    assert_eq!(resp.status(), StatusCode::CREATED);
+    assert_eq!(balance_of!(app, alice, alices), 500);
+    assert_eq!(balance_of!(app, bob, bobs), 0);
+
+    let resp = reverse!(
+        app,
+        operator,
+        transfer_id,
+        serde_json::json!({"reason": "Again"})
+    )
+    .unwrap();
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+    let resp = reverse!(
+        app,
+        operator,
+        "no-such-transfer",
+        serde_json::json!({"reason": "?"})
+    )
+    .unwrap();
+    assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+    let resp = reverse!(
+        app,
+        operator,
+        transfer_id,
+        serde_json::json!({"reason": " "})
+    )
+    .unwrap();
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+}
+
+#[actix_web::test]
+async fn test_shortfall_policy_decides_when_recipient_spent_the_money() {
+    for (policy, expected_status, bobs_balance) in [
+        (ShortfallPolicy::Reject, StatusCode::BAD_REQUEST, 50),
+        (ShortfallPolicy::Partial, StatusCode::CREATED, 0),
+    ] {
+        let app = setup_reversal_test!(policy);
+        let alice = bearer!(app, "alice@example.com");
+        let bob = bearer!(app, "bob@example.com");
+        let operator = bearer!(app, OPERATOR_EMAIL);
+        let alices = open_account!(app, alice, 500);
+        let bobs = open_account!(app, bob, 0);
+        let transfer_id = pay!(app, alice, alices, bobs, 300);
+        let req = test::TestRequest::post()
+            .uri(&format!("/api/accounts/{}/withdraw", bobs))
+            .insert_header(bob.clone())
+            .set_json(serde_json::json!({"amount": 250}))
+            .to_request();
+        test::call_service(&app, req).await;
+
+        let resp = reverse!(
+            app,
+            operator,
+            transfer_id,
+            serde_json::json!({"reason": "Fraud"})
+        )
+        .unwrap();
+        assert_eq!(resp.status(), expected_status);
+        assert_eq!(balance_of!(app, bob, bobs), bobs_balance);
+        if policy == ShortfallPolicy::Partial {
+            let reversal: serde_json::Value = test::read_body_json(resp).await;
+            assert_eq!(reversal["amount"], 50);
+            assert_eq!(reversal["requested_amount"], 300);
+        }
+    }
+}
@@ -0,0 +1,290 @@
+use actix_web::http::StatusCode;
+use actix_web::{App, test, web};
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use std::sync::Arc;
+use yandex_bank_api::application::auth_service::AuthService;
+use yandex_bank_api::application::service::BankService;
+use yandex_bank_api::data::memory::InMemoryAccountRepository;
+use yandex_bank_api::data::user_repository::InMemoryUserRepository;
+use yandex_bank_api::domain::models::{Amount, CreateAccount, Deposit, Transfer};
+use yandex_bank_api::domain::token::Scope;
+use yandex_bank_api::domain::user::{ChangePasswordRequest, CreateUser, LoginRequest};
+use yandex_bank_api::presentation::auth::{
+    GetTokenRequest, change_password, enroll_two_factor, get_token, login, logout, logout_all,
+    register,
+};
+use yandex_bank_api::presentation::handlers::{
+    AppState, create_account, deposit, get_account, transfer,
+};
+use yandex_bank_api::presentation::middleware::{JwtAuthMiddleware, RequireScope};
+
+const PASSWORD: &str = "Passw0rd-Strong";
+
+macro_rules! setup_scope_test {
+    () => {{
+        let repository = InMemoryAccountRepository::new();
+        let service = BankService::new(Arc::new(repository));
+
+        let user_repository = InMemoryUserRepository::new();
+        let jwt_secret = "test-secret-key-for-scope-tests".to_string();
+        let auth_service = AuthService::new(Arc::new(user_repository), jwt_secret.clone())
+            .with_token_client("integration", "integration-secret");
+
+        let state = web::Data::new(AppState {
+            service,
+            auth_service: Arc::new(auth_service),
+        });
+
+        test::init_service(
+            App::new()
+                .app_data(state.clone())
+                .wrap(JwtAuthMiddleware::new(jwt_secret))
+                .service(
+                    web::scope("/api")
+                        .route("/auth/register", web::post().to(register))
+                        .route("/auth/login", web::post().to(login))
+                        .route("/auth/token", web::post().to(get_token))
+                        .route("/auth/logout", web::post().to(logout))
+                        .route("/auth/logout-all", web::post().to(logout_all))
+                        .route("/auth/password", web::post().to(change_password))
+                        .route("/auth/2fa/enroll", web::post().to(enroll_two_factor))
+                        .route(
+                            "/accounts",
+                            web::post()
+                                .to(create_account)
+                                .wrap(RequireScope::new(Scope::AccountsWrite)),
+                        )
+                        .route(
+                            "/accounts/{id}",
+                            web::get()
+                                .to(get_account)
+                                .wrap(RequireScope::new(Scope::AccountsRead)),
+                        )
+                        .route(
+                            "/transfers",
+                            web::post()
+                                .to(transfer)
+                                .wrap(RequireScope::new(Scope::PaymentsWrite)),
+                        )
+                        // Deliberately without a scope requirement
+                        .route("/accounts/{id}/deposit", web::post().to(deposit)),
+                ),
+        )
+        .await
+    }};
+}
+
+// Registers a user, logs in and creates an account with the session token.
+// Returns the user id, the session token and the account id.
+macro_rules! user_with_account {
+    ($app:expr, $email:expr) => {{
+        let req = test::TestRequest::post()
+            .uri("/api/auth/register")
+            .set_json(&CreateUser {
+                email: $email.to_string(),
+                password: PASSWORD.to_string(),
+            })
+            .to_request();
+        let user: serde_json::Value = test::call_and_read_body_json(&$app, req).await;
+
+        let req = test::TestRequest::post()
+            .uri("/api/auth/login")
+            .set_json(&LoginRequest {
+                email: $email.to_string(),
+                password: PASSWORD.to_string(),
+            })
+            .to_request();
+        let login: serde_json::Value = test::call_and_read_body_json(&$app, req).await;
+        let token = login["access_token"].as_str().unwrap().to_string();
+
+        let req = test::TestRequest::post()
+            .uri("/api/accounts")
+            .insert_header(("Authorization", format!("Bearer {}", token)))
+            .set_json(&CreateAccount {
+                name: "Main".to_string(),
+            })
+            .to_request();
+        let account: serde_json::Value = test::call_and_read_body_json(&$app, req).await;
+
+        (
+            user["id"].as_str().unwrap().to_string(),
+            token,
+            account["id"].as_u64().unwrap() as u32,
+        )
+    }};
+}
+
+macro_rules! scoped_token {
+    ($app:expr, $user_id:expr, $scopes:expr) => {{
+        let req = test::TestRequest::post()
+            .uri("/api/auth/token")
+            .insert_header((
+                "Authorization",
+                format!(
+                    "Basic {}",
+                    STANDARD.encode("integration:integration-secret")
+                ),
+            ))
+            .set_json(&GetTokenRequest {
+                user_id: $user_id.to_string(),
+                scopes: $scopes,
+            })
+            .to_request();
+        test::call_service(&$app, req).await
+    }};
+}
+
+macro_rules! token_from {
+    ($resp:expr) => {{
+        assert_eq!($resp.status(), StatusCode::OK);
+        let body: serde_json::Value = test::read_body_json($resp).await;
+        body["access_token"].as_str().unwrap().to_string()
+    }};
+}
+
+macro_rules! transfer_with {
+    ($app:expr, $token:expr, $from:expr, $to:expr) => {{
+        let req = test::TestRequest::post()
+            .uri("/api/transfers")
+            .insert_header(("Authorization", format!("Bearer {}", $token)))
+            .set_json(&Transfer {
+                from_account_id: $from,
+                to_account_id: $to,
+                amount: Amount::new(0),
+                beneficiary_id: None,
+                details: Default::default(),
+            })
+            .to_request();
+        test::try_call_service(&$app, req).await
+    }};
+}
+
+#[actix_web::test]
+async fn test_read_scope_allows_reads_but_not_payments() {
+    let app = setup_scope_test!();
+    let (user_id, _, account_id) = user_with_account!(app, "reader@example.com");
+    let resp = scoped_token!(app, user_id, Some(vec![Scope::AccountsRead]));
+    let token = token_from!(resp);
+
+    let req = test::TestRequest::get()
+        .uri(&format!("/api/accounts/{}", account_id))
+        .insert_header(("Authorization", format!("Bearer {}", token)))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::OK);
+
+    let err = transfer_with!(app, token, account_id, account_id).unwrap_err();
+    assert_eq!(err.as_response_error().status_code(), StatusCode::FORBIDDEN);
+    let body: serde_json::Value = serde_json::from_str(&err.to_string()).unwrap();
+    assert_eq!(body["error"], "insufficient_scope");
+    assert_eq!(body["missing_scope"], "payments:write");
+}
+
+#[actix_web::test]
+async fn test_scoped_token_cannot_call_routes_without_requirement() {
+    let app = setup_scope_test!();
+    let (user_id, session_token, account_id) = user_with_account!(app, "closed@example.com");
+    let token = token_from!(scoped_token!(app, user_id, Some(Scope::ALL.to_vec())));
+
+    let deposit = |token: &str| {
+        test::TestRequest::post()
+            .uri(&format!("/api/accounts/{}/deposit", account_id))
+            .insert_header(("Authorization", format!("Bearer {}", token)))
+            .set_json(&Deposit {
+                amount: Amount::new(10),
+                details: Default::default(),
+            })
+            .to_request()
+    };
+
+    let resp = test::call_service(&app, deposit(&token)).await;
+    assert_eq!(resp.status(), StatusCode::FORBIDDEN);
+    let body: serde_json::Value = serde_json::from_slice(&test::read_body(resp).await).unwrap();
+    assert_eq!(body["error"], "insufficient_scope");
+    assert!(body.get("missing_scope").is_none());
+
+    // Session tokens are not scoped and keep full access
+    let resp = test::call_service(&app, deposit(&session_token)).await;
+    assert!(resp.status().is_success());
+}
+
+#[actix_web::test]
+async fn test_unscoped_client_token_keeps_full_access() {
+    let app = setup_scope_test!();
+    let (user_id, _, account_id) = user_with_account!(app, "full@example.com");
+    let token = token_from!(scoped_token!(app, user_id, None));
+
+    let result = transfer_with!(app, token, account_id, account_id);
+
+    // Passes the scope check; the handler itself rejects the zero amount
+    let resp = result.unwrap();
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+}
+
+#[actix_web::test]
+async fn test_token_request_rejects_empty_and_unknown_scopes() {
+    let app = setup_scope_test!();
+    let (user_id, _, _) = user_with_account!(app, "invalid@example.com");
+
+    let resp = scoped_token!(app, user_id, Some(vec![]));
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+
+    let req = test::TestRequest::post()
+        .uri("/api/auth/token")
+        .insert_header((
+            "Authorization",
+            format!(
+                "Basic {}",
+                STANDARD.encode("integration:integration-secret")
+            ),
+        ))
+        .set_json(serde_json::json!({ "user_id": user_id, "scopes": ["accounts:delete"] }))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+}
+
+#[actix_web::test]
+async fn test_scoped_token_cannot_manage_the_login() {
+    let app = setup_scope_test!();
+    let (user_id, session_token, account_id) = user_with_account!(app, "integration@example.com");
+    let token = token_from!(scoped_token!(app, user_id, Some(vec![Scope::AccountsRead])));
+
+    for uri in [
+        "/api/auth/2fa/enroll",
+        "/api/auth/logout",
+        "/api/auth/logout-all",
+        "/api/auth/password",
+    ] {
+        let req = test::TestRequest::post()
+            .uri(uri)
+            .insert_header(("Authorization", format!("Bearer {}", token)))
+            .set_json(&ChangePasswordRequest {
+                current_password: PASSWORD.to_string(),
+                new_password: "Taken-Over-Passw0rd-1".to_string(),
+            })
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::FORBIDDEN, "{}", uri);
+    }
+
+    // The password is unchanged
+    let req = test::TestRequest::post()
+        .uri("/api/auth/login")
+        .set_json(&LoginRequest {
+            email: "integration@example.com".to_string(),
+            password: PASSWORD.to_string(),
+        })
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::OK);
+
+    // The user's session survived
+    let req = test::TestRequest::get()
+        .uri(&format!("/api/accounts/{}", account_id))
+        .insert_header(("Authorization", format!("Bearer {}", session_token)))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::OK);
+}
@@ -0,0 +1,185 @@
+use actix_web::http::StatusCode;
+use actix_web::{App, test, web};
+use std::sync::Arc;
+use yandex_bank_api::application::auth_service::AuthService;
+use yandex_bank_api::application::service::BankService;
+use yandex_bank_api::data::memory::InMemoryAccountRepository;
+use yandex_bank_api::data::revocation_store::InMemoryRevocationStore;
+use yandex_bank_api::data::user_repository::InMemoryUserRepository;
+use yandex_bank_api::domain::models::CreateAccount;
+use yandex_bank_api::domain::repository::RevocationStore;
+use yandex_bank_api::domain::user::{CreateUser, LoginRequest};
+use yandex_bank_api::presentation::auth::{
+    list_sessions, login, refresh, register, revoke_session,
+};
+use yandex_bank_api::presentation::handlers::{AppState, create_account};
+use yandex_bank_api::presentation::middleware::JwtAuthMiddleware;
+
+const EMAIL: &str = "sessions@example.com";
+const PASSWORD: &str = "Passw0rd-Strong";
+
+macro_rules! setup_session_test {
+    () => {{
+        let repository = InMemoryAccountRepository::new();
+        let service = BankService::new(Arc::new(repository));
+
+        let jwt_secret = "test-secret-key-for-session-tests".to_string();
+        let revocation_store: Arc<dyn RevocationStore> = Arc::new(InMemoryRevocationStore::new());
+        let auth_service =
+            AuthService::new(Arc::new(InMemoryUserRepository::new()), jwt_secret.clone())
+                .with_revocation_store(revocation_store.clone());
+
+        let state = web::Data::new(AppState {
+            service,
+            auth_service: Arc::new(auth_service),
+        });
+
+        test::init_service(
+            App::new()
+                .app_data(state.clone())
+                .wrap(JwtAuthMiddleware::new(jwt_secret).with_revocation_store(revocation_store))
+                .service(
+                    web::scope("/api")
+                        .route("/auth/register", web::post().to(register))
+                        .route("/auth/login", web::post().to(login))
+                        .route("/auth/refresh", web::post().to(refresh))
+                        .route("/auth/sessions", web::get().to(list_sessions))
+                        .route("/auth/sessions/{id}", web::delete().to(revoke_session))
+                        .route("/accounts", web::post().to(create_account)),
+                ),
+        )
+        .await
+    }};
+}
+
+// Logs in from the given device and address, returns the login response
+macro_rules! login_from {
+    ($app:expr, $user_agent:expr, $addr:expr) => {{
+        let req = test::TestRequest::post()
+            .uri("/api/auth/login")
+            .insert_header(("User-Agent", $user_agent))
+            .peer_addr($addr.parse().unwrap())
+            .set_json(&LoginRequest {
+                email: EMAIL.to_string(),
+                password: PASSWORD.to_string(),
+            })
+            .to_request();
+        let resp: serde_json::Value = test::call_and_read_body_json(&$app, req).await;
+        resp
+    }};
+}
+
+macro_rules! list_sessions {
+    ($app:expr, $token:expr) => {{
+        let req = test::TestRequest::get()
+            .uri("/api/auth/sessions")
+            .insert_header(("Authorization", format!("Bearer {}", $token)))
+            .to_request();
+        let sessions: Vec<serde_json::Value> = test::call_and_read_body_json(&$app, req).await;
+        sessions
+    }};
+}
+
+#[actix_web::test]
+async fn test_sessions_are_listed_and_revoked_individually() {
+    let app = setup_session_test!();
+    let req = test::TestRequest::post()
+        .uri("/api/auth/register")
+        .set_json(&CreateUser {
+            email: EMAIL.to_string(),
+            password: PASSWORD.to_string(),
+        })
+        .to_request();
+    test::call_service(&app, req).await;
+
+    let laptop = login_from!(app, "Firefox/128.0", "192.0.2.10:50000");
+    let phone = login_from!(app, "YandexBank-iOS/4.2", "198.51.100.7:41000");
+    let laptop_token = laptop["access_token"].as_str().unwrap();
+    let phone_token = phone["access_token"].as_str().unwrap();
+
+    let sessions = list_sessions!(app, laptop_token);
+    assert_eq!(sessions.len(), 2);
+    assert_eq!(sessions[0]["user_agent"], "Firefox/128.0");
+    assert_eq!(sessions[0]["ip"], "192.0.2.10");
+    assert_eq!(sessions[0]["current"], true);
+    assert_eq!(sessions[1]["user_agent"], "YandexBank-iOS/4.2");
+    assert_eq!(sessions[1]["ip"], "198.51.100.7");
+    assert_eq!(sessions[1]["current"], false);
+    assert!(sessions[1]["last_seen_at"].is_string());
+
+    // The phone was lost: sign it out from the laptop
+    let phone_session = sessions[1]["id"].as_str().unwrap();
+    let req = test::TestRequest::delete()
+        .uri(&format!("/api/auth/sessions/{}", phone_session))
+        .insert_header(("Authorization", format!("Bearer {}", laptop_token)))
+        .to_request();
+    assert_eq!(
+        test::call_service(&app, req).await.status(),
+        StatusCode::NO_CONTENT
+    );
+
+    // Its access token is rejected before reaching any handler
+    let req = test::TestRequest::post()
+        .uri("/api/accounts")
+        .insert_header(("Authorization", format!("Bearer {}", phone_token)))
+        .set_json(&CreateAccount {
+            name: "Savings".to_string(),
+        })
+        .to_request();
+    let err = test::try_call_service(&app, req).await.unwrap_err();
+    assert_eq!(
+        err.as_response_error().status_code(),
+        StatusCode::UNAUTHORIZED
+    );
+    // ...and it cannot get new tokens either
+    let req = test::TestRequest::post()
+        .uri("/api/auth/refresh")
+        .set_json(serde_json::json!({"refresh_token": phone["refresh_token"]}))
+        .to_request();
+    assert_eq!(
+        test::call_service(&app, req).await.status(),
+        StatusCode::UNAUTHORIZED
+    );
+
+    // The laptop is still signed in
+    let req = test::TestRequest::post()
+        .uri("/api/accounts")
+        .insert_header(("Authorization", format!("Bearer {}", laptop_token)))
+        .set_json(&CreateAccount {
+            name: "Savings".to_string(),
+        })
+        .to_request();
+    assert_eq!(
+        test::call_service(&app, req).await.status(),
+        StatusCode::CREATED
+    );
+    let sessions = list_sessions!(app, laptop_token);
+    assert_eq!(sessions.len(), 1);
+    assert_eq!(sessions[0]["current"], true);
+}
+
+#[actix_web::test]
+async fn test_revoking_unknown_session_is_not_found() {
+    let app = setup_session_test!();
+    let req = test::TestRequest::post()
+        .uri("/api/auth/register")
+        .set_json(&CreateUser {
+            email: EMAIL.to_string(),
+            password: PASSWORD.to_string(),
+        })
+        .to_request();
+    test::call_service(&app, req).await;
+    let login = login_from!(app, "curl/8.5.0", "192.0.2.10:50000");
+
+    let req = test::TestRequest::delete()
+        .uri("/api/auth/sessions/not-a-session")
+        .insert_header((
+            "Authorization",
+            format!("Bearer {}", login["access_token"].as_str().unwrap()),
+        ))
+        .to_request();
+    assert_eq!(
+        test::call_service(&app, req).await.status(),
+        StatusCode::NOT_FOUND
+    );
+}
@@ -0,0 +1,227 @@
+// A minimal OpenID Connect provider on a local port, so that OIDC login is tested offline.
+// It signs in whoever `sign_in_as` names without asking, and checks PKCE on code exchange.
+use actix_web::dev::ServerHandle;
+use actix_web::{App, HttpResponse, HttpServer, web};
+use base64::Engine;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use jsonwebtoken::{Header, encode};
+use reqwest::Url;
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use yandex_bank_api::infrastructure::jwt_keys::{JwtKey, JwtKeySet};
+
+#[derive(Debug, Clone)]
+pub struct MockUser {
+    pub subject: String,
+    pub email: String,
+    pub email_verified: bool,
+}
+
+impl MockUser {
+    pub fn new(subject: &str, email: &str) -> Self {
+        Self {
+            subject: subject.to_string(),
+            email: email.to_string(),
+            email_verified: true,
+        }
+    }
+}
+
+struct IssuedCode {
+    redirect_uri: String,
+    code_challenge: String,
+    nonce: String,
+    user: MockUser,
+}
+
+struct MockState {
+    issuer: String,
+    client_id: String,
+    // Signing key first; older keys stay published
+    keys: Mutex<Vec<JwtKey>>,
+    user: Mutex<MockUser>,
+    codes: Mutex<HashMap<String, IssuedCode>>,
+}
+
+pub struct MockIdp {
+    pub issuer: String,
+    state: Arc<MockState>,
+    handle: ServerHandle,
+}
+
+#[derive(Deserialize)]
+struct AuthorizeQuery {
+    client_id: String,
+    redirect_uri: String,
+    state: String,
+    nonce: String,
+    code_challenge: String,
+    code_challenge_method: String,
+}
+
+#[derive(Deserialize)]
+struct TokenForm {
+    grant_type: String,
+    code: String,
+    redirect_uri: String,
+    client_id: String,
+    code_verifier: String,
+}
+
+impl MockIdp {
+    pub async fn start(client_id: &str) -> Self {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let issuer = format!("http://{}", listener.local_addr().unwrap());
+        let state = Arc::new(MockState {
+            issuer: issuer.clone(),
+            client_id: client_id.to_string(),
+            keys: Mutex::new(vec![JwtKey::generate_ed25519("idp-1")]),
+            user: Mutex::new(MockUser::new("employee-1", "employee@corp.example.com")),
+            codes: Mutex::new(HashMap::new()),
+        });
+
+        let data = web::Data::from(state.clone());
+        let server = HttpServer::new(move || {
+            App::new()
+                .app_data(data.clone())
+                .route(
+                    "/.well-known/openid-configuration",
+                    web::get().to(discovery),
+                )
+                .route("/authorize", web::get().to(authorize))
+                .route("/token", web::post().to(token))
+                .route("/jwks", web::get().to(jwks))
+        })
+        .workers(1)
+        .listen(listener)
+        .unwrap()
+        .run();
+        let handle = server.handle();
+        actix_web::rt::spawn(server);
+
+        Self {
+            issuer,
+            state,
+            handle,
+        }
+    }
+
+    pub fn sign_in_as(&self, user: MockUser) {
+        *self.state.user.lock().unwrap() = user;
+    }
+
+    // Signs with a new key from now on; the previous one stays published
+    pub fn rotate_key(&self, kid: &str) {
+        self.state
+            .keys
+            .lock()
+            .unwrap()
+            .insert(0, JwtKey::generate_ed25519(kid));
+    }
+
+    // What the browser does: follows the authorization URL and returns the `code` and
+    // `state` the provider redirects back with
+    pub async fn authorize(&self, authorization_url: &str) -> (String, String) {
+        let client = reqwest::Client::builder()
+            .redirect(reqwest::redirect::Policy::none())
+            .build()
+            .unwrap();
+        let resp = client.get(authorization_url).send().await.unwrap();
+        assert_eq!(resp.status(), reqwest::StatusCode::FOUND);
+        let location = resp.headers()["Location"].to_str().unwrap();
+        let params: HashMap<_, _> = Url::parse(location)
+            .unwrap()
+            .query_pairs()
+            .into_owned()
+            .collect();
+        (params["code"].clone(), params["state"].clone())
+    }
+
+    pub async fn stop(self) {
+        self.handle.stop(false).await;
+    }
+}
+
+async fn discovery(state: web::Data<MockState>) -> HttpResponse {
+    HttpResponse::Ok().json(serde_json::json!({
+        "issuer": state.issuer,
+        "authorization_endpoint": format!("{}/authorize", state.issuer),
+        "token_endpoint": format!("{}/token", state.issuer),
+        "jwks_uri": format!("{}/jwks", state.issuer),
+    }))
+}
+
+async fn authorize(state: web::Data<MockState>, query: web::Query<AuthorizeQuery>) -> HttpResponse {
+    if query.client_id != state.client_id || query.code_challenge_method != "S256" {
+        return HttpResponse::BadRequest().finish();
+    }
+    let code = uuid::Uuid::new_v4().to_string();
+    state.codes.lock().unwrap().insert(
+        code.clone(),
+        IssuedCode {
+            redirect_uri: query.redirect_uri.clone(),
+            code_challenge: query.code_challenge.clone(),
+            nonce: query.nonce.clone(),
+            user: state.user.lock().unwrap().clone(),
+        },
+    );
+    let location = Url::parse_with_params(
+        &query.redirect_uri,
+        &[("code", code.as_str()), ("state", query.state.as_str())],
+    )
+    .unwrap();
+    HttpResponse::Found()
+        .insert_header(("Location", location.as_str()))
+        .finish()
+}
+
+async fn token(state: web::Data<MockState>, form: web::Form<TokenForm>) -> HttpResponse {
+    let invalid_grant =
+        || HttpResponse::BadRequest().json(serde_json::json!({"error": "invalid_grant"}));
+    // Codes are single use
+    let Some(issued) = state.codes.lock().unwrap().remove(&form.code) else {
+        return invalid_grant();
+    };
+    let challenge = URL_SAFE_NO_PAD.encode(Sha256::digest(form.code_verifier.as_bytes()));
+    if form.grant_type != "authorization_code"
+        || form.client_id != state.client_id
+        || form.redirect_uri != issued.redirect_uri
+        || challenge != issued.code_challenge
+    {
+        return invalid_grant();
+    }
+
+    let keys = state.keys.lock().unwrap();
+    let key = &keys[0];
+    let mut header = Header::new(key.algorithm());
+    header.kid = key.kid().map(str::to_string);
+    let now = chrono::Utc::now().timestamp();
+    let claims = serde_json::json!({
+        "iss": state.issuer,
+        "aud": state.client_id,
+        "sub": issued.user.subject,
+        "email": issued.user.email,
+        "email_verified": issued.user.email_verified,
+        "nonce": issued.nonce,
+        "iat": now,
+        "exp": now + 300,
+    });
+    let id_token = encode(&header, &claims, key.encoding_key()).unwrap();
+    HttpResponse::Ok().json(serde_json::json!({
+        "access_token": "mock-access-token",
+        "token_type": "Bearer",
+        "id_token": id_token,
+    }))
+}
+
+async fn jwks(state: web::Data<MockState>) -> HttpResponse {
+    let keys = state.keys.lock().unwrap();
+    let set = keys[1..]
+        .iter()
+        .fold(JwtKeySet::new(keys[0].clone()), |set, key| {
+            set.with_verification_key(key.clone())
+        });
+    HttpResponse::Ok().json(set.jwks())
+}
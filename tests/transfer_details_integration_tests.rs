@@ -0,0 +1,205 @@
+use actix_web::http::StatusCode;
+use actix_web::{App, test, web};
+use std::sync::Arc;
+use yandex_bank_api::application::auth_service::AuthService;
+use yandex_bank_api::application::service::BankService;
+use yandex_bank_api::data::memory::InMemoryAccountRepository;
+use yandex_bank_api::data::user_repository::InMemoryUserRepository;
+use yandex_bank_api::domain::models::CreateAccount;
+use yandex_bank_api::domain::user::{CreateUser, LoginRequest};
+use yandex_bank_api::infrastructure::iso20022::parse_camt053;
+use yandex_bank_api::presentation::auth::{login, register};
+use yandex_bank_api::presentation::handlers::{
+    AppState, create_account, deposit, get_statement, transfer, withdraw,
+};
+use yandex_bank_api::presentation::middleware::JwtAuthMiddleware;
+
+const PASSWORD: &str = "Passw0rd-Strong";
+
+macro_rules! setup_transfer_details_test {
+    () => {{
+        let jwt_secret = "test-secret-key-for-transfer-details-tests".to_string();
+        let state = web::Data::new(AppState {
+            service: BankService::new(Arc::new(InMemoryAccountRepository::new())),
+            auth_service: Arc::new(AuthService::new(
+                Arc::new(InMemoryUserRepository::new()),
+                jwt_secret.clone(),
+            )),
+        });
+
+        test::init_service(
+            App::new()
+                .app_data(state)
+                .wrap(JwtAuthMiddleware::new(jwt_secret))
+                .service(
+                    web::scope("/api")
+                        .route("/auth/register", web::post().to(register))
+                        .route("/auth/login", web::post().to(login))
+                        .route("/accounts", web::post().to(create_account))
+                        .route("/accounts/{id}/deposit", web::post().to(deposit))
+                        .route("/accounts/{id}/withdraw", web::post().to(withdraw))
+                        .route("/accounts/{id}/statement", web::get().to(get_statement))
+                        .route("/transfers", web::post().to(transfer)),
+                ),
+        )
+        .await
+    }};
+}
+
+// Registers and logs in; returns the bearer header
+macro_rules! customer {
+    ($app:expr, $email:expr) => {{
+        let req = test::TestRequest::post()
+            .uri("/api/auth/register")
+            .set_json(&CreateUser {
+                email: $email.to_string(),
+                password: PASSWORD.to_string(),
+            })
+            .to_request();
+        test::call_service(&$app, req).await;
+        let req = test::TestRequest::post()
+            .uri("/api/auth/login")
+            .set_json(&LoginRequest {
+                email: $email.to_string(),
+                password: PASSWORD.to_string(),
+            })
+            .to_request();
+        let login: serde_json::Value = test::call_and_read_body_json(&$app, req).await;
+        (
+            "Authorization",
+            format!("Bearer {}", login["access_token"].as_str().unwrap()),
+        )
+    }};
+}
+
+macro_rules! open_account {
+    ($app:expr, $auth:expr) => {{
+        let req = test::TestRequest::post()
+            .uri("/api/accounts")
+            .insert_header($auth.clone())
+            .set_json(&CreateAccount {
+                name: "Main".to_string(),
+            })
+            .to_request();
+        let account: serde_json::Value = test::call_and_read_body_json(&$app, req).await;
+        account["id"].as_u64().unwrap()
+    }};
+}
+
+macro_rules! post_json {
+    ($app:expr, $auth:expr, $uri:expr, $body:expr) => {{
+        let req = test::TestRequest::post()
+            .uri(&$uri)
+            .insert_header($auth.clone())
+            .set_json($body)
+            .to_request();
+        test::call_service(&$app, req).await
+    }};
+}
+
+#[actix_web::test]
+async fn test_transfer_returns_receipt_with_details() {
+    let app = setup_transfer_details_test!();
+    let alice = customer!(app, "alice@example.com");
+    let bob = customer!(app, "bob@example.com");
+    let checking = open_account!(app, alice);
+    let savings = open_account!(app, alice);
+    let bobs = open_account!(app, bob);
+
+    let resp = post_json!(
+        app,
+        alice,
+        format!("/api/accounts/{}/deposit", checking),
+        serde_json::json!({"amount": 1000, "description": "Salary"})
+    );
+    assert_eq!(resp.status(), StatusCode::OK);
+
+    let resp = post_json!(
+        app,
+        alice,
+        "/api/transfers",
+        serde_json::json!({
+            "from_account_id": checking,
+            "to_account_id": savings,
+            "amount": 300,
+            "description": "Holiday fund",
+            "reference": "HOL-2024",
+            "metadata": {"goal": "summer"},
+        })
+    );
+    assert_eq!(resp.status(), StatusCode::OK);
+    let receipt: serde_json::Value = test::read_body_json(resp).await;
+    assert!(!receipt["transfer_id"].as_str().unwrap().is_empty());
+    assert!(receipt["created_at"].is_string());
+    assert_eq!(receipt["description"], "Holiday fund");
+    assert_eq!(receipt["reference"], "HOL-2024");
+    assert_eq!(receipt["metadata"]["goal"], "summer");
+    assert_eq!(receipt["from_balance"], 700);
+    assert_eq!(receipt["to_balance"], 300);
+
+    // Bob's balance is not Alice's business
+    let resp = post_json!(
+        app,
+        alice,
+        "/api/transfers",
+        serde_json::json!({"from_account_id": checking, "to_account_id": bobs, "amount": 100})
+    );
+    let receipt: serde_json::Value = test::read_body_json(resp).await;
+    assert_eq!(receipt["from_balance"], 600);
+    assert!(receipt.get("to_balance").is_none());
+    assert!(receipt.get("description").is_none());
+
+    // The statement keeps what the customer wrote
+    let req = test::TestRequest::get()
+        .uri(&format!("/api/accounts/{}/statement", savings))
+        .insert_header(alice.clone())
+        .to_request();
+    let body = test::call_and_read_body(&app, req).await;
+    let statement = parse_camt053(std::str::from_utf8(&body).unwrap()).unwrap();
+    assert_eq!(
+        statement.transactions[0].details.description.as_deref(),
+        Some("Holiday fund")
+    );
+}
+
+#[actix_web::test]
+async fn test_oversized_details_are_rejected() {
+    let app = setup_transfer_details_test!();
+    let alice = customer!(app, "alice@example.com");
+    let checking = open_account!(app, alice);
+    let savings = open_account!(app, alice);
+    post_json!(
+        app,
+        alice,
+        format!("/api/accounts/{}/deposit", checking),
+        serde_json::json!({"amount": 1000})
+    );
+
+    let metadata: serde_json::Map<_, _> = (0..21)
+        .map(|i| (format!("key-{}", i), serde_json::json!("value")))
+        .collect();
+    let resp = post_json!(
+        app,
+        alice,
+        "/api/transfers",
+        serde_json::json!({
+            "from_account_id": checking,
+            "to_account_id": savings,
+            "amount": 10,
+            "description": "x".repeat(141),
+            "metadata": metadata,
+        })
+    );
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+    let body: serde_json::Value = test::read_body_json(resp).await;
+    let body = body.to_string();
+    assert!(body.contains("description") && body.contains("metadata"));
+
+    let resp = post_json!(
+        app,
+        alice,
+        format!("/api/accounts/{}/withdraw", checking),
+        serde_json::json!({"amount": 10, "reference": "R".repeat(36)})
+    );
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+}
@@ -0,0 +1,284 @@
+use actix_web::http::StatusCode;
+use actix_web::{App, test, web};
+use std::sync::Arc;
+use yandex_bank_api::application::auth_service::AuthService;
+use yandex_bank_api::application::service::BankService;
+use yandex_bank_api::data::memory::InMemoryAccountRepository;
+use yandex_bank_api::data::revocation_store::InMemoryRevocationStore;
+use yandex_bank_api::data::user_repository::InMemoryUserRepository;
+use yandex_bank_api::domain::models::{Amount, CreateAccount, Deposit, Transfer, Withdraw};
+use yandex_bank_api::domain::repository::RevocationStore;
+use yandex_bank_api::domain::user::{CreateUser, DeleteUserRequest, LoginRequest};
+use yandex_bank_api::presentation::auth::{login, register};
+use yandex_bank_api::presentation::handlers::{
+    AppState, create_account, deposit, transfer, withdraw,
+};
+use yandex_bank_api::presentation::middleware::JwtAuthMiddleware;
+use yandex_bank_api::presentation::users::{delete_me, export_me, update_me};
+
+const EMAIL: &str = "leaving@example.com";
+const PASSWORD: &str = "Passw0rd-Strong";
+
+macro_rules! setup_user_data_test {
+    () => {{
+        let jwt_secret = "test-secret-key-for-user-data-tests".to_string();
+        let revocation_store: Arc<dyn RevocationStore> = Arc::new(InMemoryRevocationStore::new());
+        let auth_service =
+            AuthService::new(Arc::new(InMemoryUserRepository::new()), jwt_secret.clone())
+                .with_revocation_store(revocation_store.clone());
+        let state = web::Data::new(AppState {
+            service: BankService::new(Arc::new(InMemoryAccountRepository::new())),
+            auth_service: Arc::new(auth_service),
+        });
+
+        let app = test::init_service(
+            App::new()
+                .app_data(state.clone())
+                .wrap(JwtAuthMiddleware::new(jwt_secret).with_revocation_store(revocation_store))
+                .service(
+                    web::scope("/api")
+                        .route("/auth/register", web::post().to(register))
+                        .route("/auth/login", web::post().to(login))
+                        .route("/users/me", web::patch().to(update_me))
+                        .route("/users/me", web::delete().to(delete_me))
+                        .route("/users/me/export", web::get().to(export_me))
+                        .route("/accounts", web::post().to(create_account))
+                        .route("/accounts/{id}/deposit", web::post().to(deposit))
+                        .route("/accounts/{id}/withdraw", web::post().to(withdraw))
+                        .route("/transfers", web::post().to(transfer)),
+                ),
+        )
+        .await;
+        (app, state)
+    }};
+}
+
+macro_rules! login_status_and_body {
+    ($app:expr) => {{
+        let req = test::TestRequest::post()
+            .uri("/api/auth/login")
+            .set_json(&LoginRequest {
+                email: EMAIL.to_string(),
+                password: PASSWORD.to_string(),
+            })
+            .to_request();
+        let resp = test::call_service(&$app, req).await;
+        let status = resp.status();
+        let body: serde_json::Value = test::read_body_json(resp).await;
+        (status, body)
+    }};
+}
+
+macro_rules! delete_me {
+    ($app:expr, $token:expr, $password:expr) => {{
+        let req = test::TestRequest::delete()
+            .uri("/api/users/me")
+            .insert_header(("Authorization", format!("Bearer {}", $token)))
+            .set_json(&DeleteUserRequest {
+                password: $password.to_string(),
+            })
+            .to_request();
+        test::call_service(&$app, req).await.status()
+    }};
+}
+
+#[actix_web::test]
+async fn test_export_then_delete_keeps_financial_records() {
+    let (app, state) = setup_user_data_test!();
+
+    let req = test::TestRequest::post()
+        .uri("/api/auth/register")
+        .set_json(&CreateUser {
+            email: EMAIL.to_string(),
+            password: PASSWORD.to_string(),
+        })
+        .to_request();
+    let user: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+    let user_id = user["id"].as_str().unwrap().to_string();
+    let (_, login) = login_status_and_body!(app);
+    let token = login["access_token"].as_str().unwrap().to_string();
+    let auth = ("Authorization", format!("Bearer {}", token));
+
+    let req = test::TestRequest::patch()
+        .uri("/api/users/me")
+        .insert_header(auth.clone())
+        .set_json(serde_json::json!({"full_name": "Alice Smith"}))
+        .to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), StatusCode::OK);
+    let req = test::TestRequest::post()
+        .uri("/api/accounts")
+        .insert_header(auth.clone())
+        .set_json(&CreateAccount {
+            name: "Savings".to_string(),
+        })
+        .to_request();
+    let account: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+    let account_id = account["id"].as_u64().unwrap() as u32;
+    let req = test::TestRequest::post()
+        .uri(&format!("/api/accounts/{}/deposit", account_id))
+        .insert_header(auth.clone())
+        .set_json(&Deposit {
+            amount: Amount::new(500),
+            details: Default::default(),
+        })
+        .to_request();
+    test::call_service(&app, req).await;
+
+    let req = test::TestRequest::get()
+        .uri("/api/users/me/export")
+        .insert_header(auth.clone())
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::OK);
+    assert!(
+        resp.headers()
+            .get("Content-Disposition")
+            .unwrap()
+            .to_str()
+            .unwrap()
+            .starts_with("attachment")
+    );
+    let export: serde_json::Value = test::read_body_json(resp).await;
+    assert_eq!(export["user"]["email"], EMAIL);
+    assert_eq!(export["user"]["profile"]["full_name"], "Alice Smith");
+    assert_eq!(export["accounts"][0]["account"]["balance"], 500);
+    assert_eq!(export["accounts"][0]["transactions"][0]["kind"], "deposit");
+
+    // Money left on an account blocks the deletion
+    assert_eq!(delete_me!(app, token, PASSWORD), StatusCode::BAD_REQUEST);
+    let req = test::TestRequest::post()
+        .uri(&format!("/api/accounts/{}/withdraw", account_id))
+        .insert_header(auth.clone())
+        .set_json(&Withdraw {
+            amount: Amount::new(500),
+            details: Default::default(),
+        })
+        .to_request();
+    test::call_service(&app, req).await;
+    assert_eq!(
+        delete_me!(app, token, "Wrong-Passw0rd"),
+        StatusCode::UNAUTHORIZED
+    );
+    assert_eq!(delete_me!(app, token, PASSWORD), StatusCode::NO_CONTENT);
+
+    // Signed out everywhere, and the email no longer signs in
+    let req = test::TestRequest::get()
+        .uri("/api/users/me/export")
+        .insert_header(auth)
+        .to_request();
+    assert!(test::try_call_service(&app, req).await.is_err());
+    let (status, _) = login_status_and_body!(app);
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+
+    // The account and its history stay for audit, without personal data on the user
+    let account = state.service.get_any_account(account_id).await.unwrap();
+    assert_eq!(account.owner_id, user_id);
+    assert_eq!(
+        state.service.export_accounts(&user_id).await.unwrap()[0]
+            .transactions
+            .len(),
+        2
+    );
+    let user = state.auth_service.get_user(&user_id).await.unwrap();
+    assert_ne!(user.email, EMAIL);
+    assert!(user.profile.full_name.is_none());
+}
+
+// Registers and logs in `$email`, opens an account and deposits `$balance`; returns the
+// bearer token and the account id
+macro_rules! customer_with_account {
+    ($app:expr, $email:expr, $balance:expr) => {{
+        let req = test::TestRequest::post()
+            .uri("/api/auth/register")
+            .set_json(&CreateUser {
+                email: $email.to_string(),
+                password: PASSWORD.to_string(),
+            })
+            .to_request();
+        test::call_service(&$app, req).await;
+        let req = test::TestRequest::post()
+            .uri("/api/auth/login")
+            .set_json(&LoginRequest {
+                email: $email.to_string(),
+                password: PASSWORD.to_string(),
+            })
+            .to_request();
+        let login: serde_json::Value = test::call_and_read_body_json(&$app, req).await;
+        let token = login["access_token"].as_str().unwrap().to_string();
+        let req = test::TestRequest::post()
+            .uri("/api/accounts")
+            .insert_header(("Authorization", format!("Bearer {}", token)))
+            .set_json(&CreateAccount {
+                name: "Main".to_string(),
+            })
+            .to_request();
+        let account: serde_json::Value = test::call_and_read_body_json(&$app, req).await;
+        let account_id = account["id"].as_u64().unwrap() as u32;
+        if $balance > 0 {
+            let req = test::TestRequest::post()
+                .uri(&format!("/api/accounts/{}/deposit", account_id))
+                .insert_header(("Authorization", format!("Bearer {}", token)))
+                .set_json(&Deposit {
+                    amount: Amount::new($balance),
+                    details: Default::default(),
+                })
+                .to_request();
+            test::call_service(&$app, req).await;
+        }
+        (token, account_id)
+    }};
+}
+
+macro_rules! transfer_status {
+    ($app:expr, $token:expr, $from:expr, $to:expr) => {{
+        let req = test::TestRequest::post()
+            .uri("/api/transfers")
+            .insert_header(("Authorization", format!("Bearer {}", $token)))
+            .set_json(&Transfer {
+                from_account_id: $from,
+                to_account_id: $to,
+                amount: Amount::new(50),
+                beneficiary_id: None,
+                details: Default::default(),
+            })
+            .to_request();
+        test::call_service(&$app, req).await.status()
+    }};
+}
+
+#[actix_web::test]
+async fn test_accounts_of_deleted_user_take_no_money() {
+    let (app, state) = setup_user_data_test!();
+    let (token, account_id) = customer_with_account!(app, EMAIL, 0);
+    let (sender, sender_account) = customer_with_account!(app, "sender@example.com", 500);
+
+    // A deletion that does not go through leaves the account open
+    assert_eq!(
+        delete_me!(app, token, "Wrong-Passw0rd"),
+        StatusCode::UNAUTHORIZED
+    );
+    assert_eq!(
+        transfer_status!(app, sender, sender_account, account_id),
+        StatusCode::OK
+    );
+    let req = test::TestRequest::post()
+        .uri(&format!("/api/accounts/{}/withdraw", account_id))
+        .insert_header(("Authorization", format!("Bearer {}", token)))
+        .set_json(&Withdraw {
+            amount: Amount::new(50),
+            details: Default::default(),
+        })
+        .to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), StatusCode::OK);
+
+    assert_eq!(delete_me!(app, token, PASSWORD), StatusCode::NO_CONTENT);
+    assert_eq!(
+        transfer_status!(app, sender, sender_account, account_id),
+        StatusCode::BAD_REQUEST
+    );
+    let account = state.service.get_any_account(account_id).await.unwrap();
+    assert!(account.closed);
+    assert_eq!(account.balance.inner(), 0);
+    let sender_balance = state.service.get_any_account(sender_account).await.unwrap();
+    assert_eq!(sender_balance.balance.inner(), 450);
+}